@@ -29,6 +29,13 @@ fn get_all_benchmarks() -> Vec<(String, String)> {
         utils::get_target()
       ),
     ),
+    (
+      "wry_custom_protocol_cancellation".into(),
+      format!(
+        "tests/target/{}/release/bench_custom_protocol_cancellation",
+        utils::get_target()
+      ),
+    ),
     (
       "wry_cpu_intensive".into(),
       format!(
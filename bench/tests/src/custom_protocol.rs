@@ -16,8 +16,12 @@ const INDEX_HTML: &[u8] = br#"
 <body>
     <h1>Welcome to WRY!</h1>
     <script>
-    document.addEventListener('DOMContentLoaded', () => {
-        ipc.postMessage('dom-loaded')
+    // many small requests, to measure the cost of each custom protocol request
+    document.addEventListener('DOMContentLoaded', async () => {
+        const requests = Array.from({ length: 1000 }, (_, i) => fetch(`/data/${i}`)
+            .then((response) => response.text()))
+        await Promise.all(requests)
+        ipc.postMessage('requests-done')
     })
     </script>
 </body>
@@ -44,40 +48,46 @@ fn main() -> wry::Result<()> {
   let window = WindowBuilder::new().build(&event_loop).unwrap();
 
   let handler = |req: Request<String>| {
-    if req.body() == "dom-loaded" {
+    if req.body() == "requests-done" {
       exit(0);
     }
   };
+
+  let builder = WebViewBuilder::new()
+    .with_ipc_handler(handler)
+    .with_custom_protocol("wrybench".into(), move |_webview_id, request| {
+      if let Some(index) = request.uri().path().strip_prefix("/data/") {
+        return Response::builder()
+          .header(CONTENT_TYPE, "text/plain")
+          .body(index.as_bytes().to_vec().into())
+          .unwrap();
+      }
+      Response::builder()
+        .header(CONTENT_TYPE, "text/html")
+        .body(INDEX_HTML.into())
+        .unwrap()
+    })
+    .with_url("wrybench://localhost");
+
   #[cfg(any(
     target_os = "windows",
     target_os = "macos",
     target_os = "ios",
     target_os = "android"
   ))]
-  let builder = WebViewBuilder::new(&window);
-
+  let _webview = builder.build(&window)?;
   #[cfg(not(any(
     target_os = "windows",
     target_os = "macos",
     target_os = "ios",
     target_os = "android"
   )))]
-  let builder = {
+  let _webview = {
     use tao::platform::unix::WindowExtUnix;
     use wry::WebViewBuilderExtUnix;
     let vbox = window.default_vbox().unwrap();
-    WebViewBuilder::new_gtk(vbox)
+    builder.build_gtk(vbox)?
   };
-  let _webview = builder
-    .with_ipc_handler(handler)
-    .with_custom_protocol("wrybench".into(), move |_request| {
-      Response::builder()
-        .header(CONTENT_TYPE, "text/html")
-        .body(INDEX_HTML.into())
-        .unwrap()
-    })
-    .with_url("wrybench://localhost")
-    .build()?;
 
   event_loop.run(move |event, _, control_flow| {
     *control_flow = ControlFlow::Wait;
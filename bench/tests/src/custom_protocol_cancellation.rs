@@ -0,0 +1,126 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  process::exit,
+  sync::atomic::{AtomicUsize, Ordering},
+  thread,
+  time::Duration,
+};
+
+/// The number of responses refused because the page navigated away from their request.
+static CANCELLED: AtomicUsize = AtomicUsize::new(0);
+
+const INDEX_HTML: &[u8] = br#"
+<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8" />
+</head>
+<body>
+    <h1>Welcome to WRY!</h1>
+    <script>
+    // the page navigates away while the custom protocol handlers are responding
+    document.addEventListener('DOMContentLoaded', () => {
+        for (let i = 0; i < 200; i++) fetch(`/slow/${i}`).catch(() => {})
+        setTimeout(() => location.href = '/done', 50)
+    })
+    </script>
+</body>
+</html>"#;
+
+const DONE_HTML: &[u8] = br#"
+<!DOCTYPE html>
+<html lang="en">
+<body>
+    <script>
+    document.addEventListener('DOMContentLoaded', () => {
+        ipc.postMessage('done')
+    })
+    </script>
+</body>
+</html>"#;
+
+fn main() -> wry::Result<()> {
+  use tao::{
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowBuilder,
+  };
+  use wry::http::Request;
+  use wry::{
+    http::{header::CONTENT_TYPE, Response},
+    WebViewBuilder,
+  };
+
+  let event_loop = EventLoop::new();
+  let window = WindowBuilder::new().build(&event_loop).unwrap();
+
+  let handler = |req: Request<String>| {
+    if req.body() == "done" {
+      // the responses of the cancelled requests must not crash the app
+      thread::spawn(|| {
+        thread::sleep(Duration::from_millis(500));
+        eprintln!("cancelled responses: {}", CANCELLED.load(Ordering::SeqCst));
+        exit(0);
+      });
+    }
+  };
+
+  let builder = WebViewBuilder::new()
+    .with_ipc_handler(handler)
+    .with_asynchronous_custom_protocol("wrybench".into(), move |_webview_id, request, responder| {
+      let path = request.uri().path().to_string();
+      let (delay, body) = match path.as_str() {
+        "/done" => (Duration::ZERO, DONE_HTML),
+        path if path.starts_with("/slow/") => (Duration::from_millis(100), path.as_bytes()),
+        _ => (Duration::ZERO, INDEX_HTML),
+      };
+      let body = body.to_vec();
+      thread::spawn(move || {
+        thread::sleep(delay);
+        // the responders of the requests cancelled by the navigation return an error
+        let response = Response::builder()
+          .header(CONTENT_TYPE, "text/html")
+          .body(body)
+          .unwrap();
+        if responder.respond(response).is_err() {
+          CANCELLED.fetch_add(1, Ordering::SeqCst);
+        }
+      });
+    })
+    .with_url("wrybench://localhost");
+
+  #[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "android"
+  ))]
+  let _webview = builder.build(&window)?;
+  #[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "android"
+  )))]
+  let _webview = {
+    use tao::platform::unix::WindowExtUnix;
+    use wry::WebViewBuilderExtUnix;
+    let vbox = window.default_vbox().unwrap();
+    builder.build_gtk(vbox)?
+  };
+
+  event_loop.run(move |event, _, control_flow| {
+    *control_flow = ControlFlow::Wait;
+
+    match event {
+      Event::WindowEvent {
+        event: WindowEvent::CloseRequested,
+        ..
+      } => *control_flow = ControlFlow::Exit,
+      _ => {}
+    }
+  })
+}
@@ -39,41 +39,24 @@ fn main() {
       for file in kotlin_files {
         let file = file.unwrap();
 
-        let class_extension_env = format!(
-          "WRY_{}_CLASS_EXTENSION",
-          file
-            .path()
-            .file_stem()
-            .unwrap()
-            .to_string_lossy()
-            .to_uppercase()
-        );
-        let class_init_env = format!(
-          "WRY_{}_CLASS_INIT",
-          file
-            .path()
-            .file_stem()
-            .unwrap()
-            .to_string_lossy()
-            .to_uppercase()
-        );
-
-        println!("cargo:rerun-if-env-changed={class_extension_env}");
-        println!("cargo:rerun-if-env-changed={class_init_env}");
+        let file_stem = file
+          .path()
+          .file_stem()
+          .unwrap()
+          .to_string_lossy()
+          .to_uppercase();
+        let class_extension_env = format!("WRY_{file_stem}_CLASS_EXTENSION");
+        let class_init_env = format!("WRY_{file_stem}_CLASS_INIT");
+        let imports_env = format!("WRY_{file_stem}_IMPORTS");
 
         let content = fs::read_to_string(file.path())
           .expect("failed to read kotlin file as string")
           .replace("{{package}}", &package)
           .replace("{{package-unescaped}}", &package.replace('`', ""))
           .replace("{{library}}", &library)
-          .replace(
-            "{{class-extension}}",
-            &std::env::var(&class_extension_env).unwrap_or_default(),
-          )
-          .replace(
-            "{{class-init}}",
-            &std::env::var(&class_init_env).unwrap_or_default(),
-          );
+          .replace("{{class-extension}}", &kotlin_snippet(&class_extension_env))
+          .replace("{{class-init}}", &kotlin_snippet(&class_init_env))
+          .replace("{{imports}}", &kotlin_snippet(&imports_env));
 
         let auto_generated_comment = match file
           .path()
@@ -111,6 +94,26 @@ fn main() {
   alias("gtk", cfg!(feature = "os-webview") && linux);
 }
 
+/// Reads a kotlin snippet from the `var` environment variable,
+/// or from the file pointed by the `{var}_PATH` environment variable.
+fn kotlin_snippet(var: &str) -> String {
+  let path_var = format!("{var}_PATH");
+  println!("cargo:rerun-if-env-changed={var}");
+  println!("cargo:rerun-if-env-changed={path_var}");
+
+  if let Ok(snippet) = std::env::var(var) {
+    return snippet;
+  }
+
+  if let Ok(path) = std::env::var(&path_var) {
+    println!("cargo:rerun-if-changed={path}");
+    return std::fs::read_to_string(&path)
+      .unwrap_or_else(|_| panic!("Failed to read `{path_var}` file {path}"));
+  }
+
+  String::new()
+}
+
 fn alias(alias: &str, condition: bool) {
   if condition {
     println!("cargo:rustc-cfg={alias}");
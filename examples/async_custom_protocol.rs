@@ -20,16 +20,15 @@ fn main() -> wry::Result<()> {
 
   let builder = WebViewBuilder::new()
     .with_asynchronous_custom_protocol("wry".into(), move |_webview_id, request, responder| {
-      match get_wry_response(request) {
-        Ok(http_response) => responder.respond(http_response),
-        Err(e) => responder.respond(
-          http::Response::builder()
-            .header(CONTENT_TYPE, "text/plain")
-            .status(500)
-            .body(e.to_string().as_bytes().to_vec())
-            .unwrap(),
-        ),
-      }
+      let response = get_wry_response(request).unwrap_or_else(|e| {
+        http::Response::builder()
+          .header(CONTENT_TYPE, "text/plain")
+          .status(500)
+          .body(e.to_string().as_bytes().to_vec())
+          .unwrap()
+      });
+      // the page may have navigated away in the meantime
+      let _ = responder.respond(response);
     })
     // tell the webview to load the custom protocol
     .with_url("wry://localhost");
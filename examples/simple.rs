@@ -17,11 +17,15 @@ fn main() -> wry::Result<()> {
     .with_url("http://tauri.app")
     .with_drag_drop_handler(|e| {
       match e {
-        wry::DragDropEvent::Enter { paths, position } => {
+        wry::DragDropEvent::Enter {
+          paths, position, ..
+        } => {
           println!("DragEnter: {position:?} {paths:?} ")
         }
         wry::DragDropEvent::Over { position } => println!("DragOver: {position:?} "),
-        wry::DragDropEvent::Drop { paths, position } => {
+        wry::DragDropEvent::Drop {
+          paths, position, ..
+        } => {
           println!("DragDrop: {position:?} {paths:?} ")
         }
         wry::DragDropEvent::Leave => println!("DragLeave"),
@@ -0,0 +1,67 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+// The C API of wry, exported by a `cdylib` or `staticlib` crate depending on wry with the `ffi`
+// feature. All the functions must be called on the thread running the event loop of the window,
+// and the strings are NUL-terminated UTF-8.
+
+#ifndef WRY_H
+#define WRY_H
+
+#include <stdbool.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// A webview builder, created with `wry_builder_new`.
+typedef struct WryBuilder WryBuilder;
+
+// A webview, created with `wry_builder_build`.
+typedef struct WryWebView WryWebView;
+
+// The callback receiving the IPC messages, with the message and the user data given to
+// `wry_builder_set_ipc_callback`.
+typedef void (*WryIpcCallback)(const char *message, void *user_data);
+
+// Create a webview builder, to be consumed by `wry_builder_build` or freed by
+// `wry_builder_destroy`.
+WryBuilder *wry_builder_new(void);
+
+// Load `url` when the webview is created.
+bool wry_builder_set_url(WryBuilder *builder, const char *url);
+
+// Load the `html` string when the webview is created.
+bool wry_builder_set_html(WryBuilder *builder, const char *html);
+
+// Call `callback` with the messages sent by the pages with `window.ipc.postMessage`. `user_data`
+// must be valid for as long as the webview lives.
+bool wry_builder_set_ipc_callback(WryBuilder *builder, WryIpcCallback callback, void *user_data);
+
+// Create the webview in `window` and free the builder, returning `NULL` on failure.
+//
+// `window` is a `HWND` on Windows, a `NSView*` on macOS, a `UIView*` on iOS and a `GtkContainer*`
+// on Linux. Android is unsupported.
+WryWebView *wry_builder_build(WryBuilder *builder, void *window);
+
+// Free a builder that wasn't consumed by `wry_builder_build`.
+void wry_builder_destroy(WryBuilder *builder);
+
+// Navigate the webview to `url`.
+bool wry_webview_load_url(WryWebView *webview, const char *url);
+
+// Load the `html` string in the webview.
+bool wry_webview_load_html(WryWebView *webview, const char *html);
+
+// Evaluate the `js` script in the webview.
+bool wry_webview_evaluate_script(WryWebView *webview, const char *js);
+
+// Destroy the webview.
+void wry_webview_destroy(WryWebView *webview);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // WRY_H
@@ -10,17 +10,19 @@ use jni::errors::Result as JniResult;
 pub use jni::{
   self,
   objects::{GlobalRef, JClass, JMap, JObject, JString},
-  sys::{jboolean, jint, jobject, jstring},
+  sys::{jboolean, jfloat, jint, jobject, jstring},
   JNIEnv,
 };
 pub use ndk;
 
+use std::sync::Arc;
+
 use super::{
-  ASSET_LOADER_DOMAIN, EVAL_CALLBACKS, IPC, ON_LOAD_HANDLER, REQUEST_HANDLER, TITLE_CHANGE_HANDLER,
-  URL_LOADING_OVERRIDE, WITH_ASSET_LOADER,
+  webview_handlers, WebViewHandlers, ASSET_LOADER_DOMAIN, EVAL_CALLBACKS, IPC,
+  JAVASCRIPT_INTERFACES, REQUEST_HANDLER, TITLE_CHANGE_HANDLER, WITH_ASSET_LOADER,
 };
 
-use crate::PageLoadEvent;
+use crate::{PageLoadEvent, ProcessFailure, ProcessFailureReason, TextInputEvent};
 
 #[macro_export]
 macro_rules! android_binding {
@@ -61,6 +63,14 @@ macro_rules! android_binding {
       $package,
       RustWebViewClient,
       shouldOverride,
+      [JString, JString],
+      jboolean
+    );
+    android_fn!(
+      $domain,
+      $package,
+      RustWebViewClient,
+      openExternalUrls,
       [JString],
       jboolean
     );
@@ -69,25 +79,63 @@ macro_rules! android_binding {
       $package,
       RustWebView,
       shouldOverride,
-      [JString],
+      [JString, JString],
       jboolean
     );
     android_fn!($domain, $package, RustWebView, onEval, [jint, JString]);
+    android_fn!(
+      $domain,
+      $package,
+      RustWebView,
+      hasTextInputHandler,
+      [JString],
+      jboolean
+    );
+    android_fn!(
+      $domain,
+      $package,
+      RustWebView,
+      handleVirtualKeyboard,
+      [JString, jboolean, jfloat]
+    );
     android_fn!(
       $domain,
       $package,
       RustWebViewClient,
       onPageLoading,
-      [JString]
+      [JString, JString]
     );
     android_fn!(
       $domain,
       $package,
       RustWebViewClient,
       onPageLoaded,
-      [JString]
+      [JString, JString]
+    );
+    android_fn!(
+      $domain,
+      $package,
+      RustWebViewClient,
+      hasProcessFailedHandler,
+      [JString],
+      jboolean
+    );
+    android_fn!(
+      $domain,
+      $package,
+      RustWebViewClient,
+      handleRenderProcessGone,
+      [JString, jboolean]
     );
     android_fn!($domain, $package, Ipc, ipc, [JString, JString]);
+    android_fn!(
+      $domain,
+      $package,
+      JsInterface,
+      handleInvoke,
+      [JString, JString, JString, JString],
+      jstring
+    );
     android_fn!(
       $domain,
       $package,
@@ -95,6 +143,29 @@ macro_rules! android_binding {
       handleReceivedTitle,
       [JObject, JString],
     );
+    android_fn!(
+      $domain,
+      $package,
+      RustWebChromeClient,
+      hasBeforeUnloadHandler,
+      [JString],
+      jboolean
+    );
+    android_fn!(
+      $domain,
+      $package,
+      RustWebChromeClient,
+      handleBeforeUnload,
+      [JString, JString, JString],
+      jboolean
+    );
+    android_fn!(
+      $domain,
+      $package,
+      RustWebChromeClient,
+      handleCloseWindow,
+      [JString]
+    );
   }};
 }
 
@@ -142,11 +213,16 @@ fn handle_request(
       .l()?;
     let request_headers = JMap::from_env(env, &request_headers)?;
     let mut iter = request_headers.iter(env)?;
+    let mut raw_headers = Vec::new();
     while let Some((header, value)) = iter.next(env)? {
       let header = JString::from(header);
       let value = JString::from(value);
       let header = env.get_string(&header)?;
       let value = env.get_string(&value)?;
+      raw_headers.push((
+        header.to_string_lossy().to_string(),
+        value.to_string_lossy().to_string(),
+      ));
       if let (Ok(header), Ok(value)) = (
         HeaderName::from_bytes(header.to_bytes()),
         HeaderValue::from_bytes(value.to_bytes()),
@@ -154,6 +230,7 @@ fn handle_request(
         request_builder = request_builder.header(header, value);
       }
     }
+    request_builder = request_builder.extension(crate::RawHeaders(raw_headers));
 
     let final_request = match request_builder.body(Vec::new()) {
       Ok(req) => req,
@@ -281,18 +358,37 @@ pub unsafe fn handleRequest(
   }
 }
 
+/// The handlers of the webview with the id passed by the Java classes.
+fn handlers(env: &mut JNIEnv, webview_id: &JString) -> Option<Arc<WebViewHandlers>> {
+  match env.get_string(webview_id) {
+    Ok(webview_id) => webview_handlers(&webview_id.to_string_lossy()),
+    Err(e) => {
+      #[cfg(feature = "tracing")]
+      tracing::warn!("Failed to parse JString: {}", e);
+      None
+    }
+  }
+}
+
 #[allow(non_snake_case)]
-pub unsafe fn shouldOverride(mut env: JNIEnv, _: JClass, url: JString) -> jboolean {
+pub unsafe fn shouldOverride(
+  mut env: JNIEnv,
+  _: JClass,
+  webview_id: JString,
+  url: JString,
+) -> jboolean {
+  let handlers = handlers(&mut env, &webview_id);
   match env.get_string(&url) {
     Ok(url) => {
       let url = url.to_string_lossy().to_string();
-      URL_LOADING_OVERRIDE
-        .get()
+      handlers
+        .as_ref()
+        .and_then(|handlers| handlers.url_loading_override.as_ref())
         // We negate the result of the function because the logic for the android
         // client is different from how the navigation_handler is defined.
         //
         // https://developer.android.com/reference/android/webkit/WebViewClient#shouldOverrideUrlLoading(android.webkit.WebView,%20android.webkit.WebResourceRequest)
-        .map(|f| !(f.handler)(url))
+        .map(|handler| !handler(url))
         .unwrap_or(false)
     }
     Err(e) => {
@@ -343,6 +439,49 @@ pub unsafe fn ipc(mut env: JNIEnv, _: JClass, url: JString, body: JString) {
   }
 }
 
+#[allow(non_snake_case)]
+pub unsafe fn handleInvoke(
+  mut env: JNIEnv,
+  _: JClass,
+  webview_id: JString,
+  name: JString,
+  method: JString,
+  payload: JString,
+) -> jstring {
+  match (
+    env.get_string(&webview_id),
+    env.get_string(&name),
+    env.get_string(&method),
+    env.get_string(&payload),
+  ) {
+    (Ok(webview_id), Ok(name), Ok(method), Ok(payload)) => {
+      let webview_id = webview_id.to_string_lossy().to_string();
+      let name = name.to_string_lossy().to_string();
+      let method = method.to_string_lossy().to_string();
+      let payload = payload.to_string_lossy().to_string();
+      // not locked while the handler runs, it can be called again from another frame
+      let interfaces = JAVASCRIPT_INTERFACES
+        .lock()
+        .unwrap()
+        .get(&webview_id)
+        .cloned();
+      let response = interfaces
+        .as_ref()
+        .and_then(|interfaces| interfaces.get(&name))
+        .and_then(|handler| handler(&method, payload));
+      match response.map(|response| env.new_string(response)) {
+        Some(Ok(response)) => response.as_raw(),
+        _ => std::ptr::null_mut(),
+      }
+    }
+    (Err(e), _, _, _) | (_, Err(e), _, _) | (_, _, Err(e), _) | (_, _, _, Err(e)) => {
+      #[cfg(feature = "tracing")]
+      tracing::warn!("Failed to parse JString: {}", e);
+      std::ptr::null_mut()
+    }
+  }
+}
+
 #[allow(non_snake_case)]
 pub unsafe fn handleReceivedTitle(mut env: JNIEnv, _: JClass, _webview: JObject, title: JString) {
   match env.get_string(&title) {
@@ -359,11 +498,127 @@ pub unsafe fn handleReceivedTitle(mut env: JNIEnv, _: JClass, _webview: JObject,
   }
 }
 
+#[allow(non_snake_case)]
+pub unsafe fn hasBeforeUnloadHandler(mut env: JNIEnv, _: JClass, webview_id: JString) -> jboolean {
+  handlers(&mut env, &webview_id)
+    .is_some_and(|handlers| handlers.before_unload.is_some())
+    .into()
+}
+
+#[allow(non_snake_case)]
+pub unsafe fn handleBeforeUnload(
+  mut env: JNIEnv,
+  _: JClass,
+  webview_id: JString,
+  url: JString,
+  message: JString,
+) -> jboolean {
+  let handlers = handlers(&mut env, &webview_id);
+  match (env.get_string(&url), env.get_string(&message)) {
+    (Ok(url), Ok(message)) => {
+      let url = url.to_string_lossy().to_string();
+      let message = message.to_string_lossy().to_string();
+      handlers
+        .as_ref()
+        .and_then(|handlers| handlers.before_unload.as_ref())
+        .map(|handler| handler(url, message))
+        .unwrap_or(true)
+    }
+    (Err(e), _) | (_, Err(e)) => {
+      #[cfg(feature = "tracing")]
+      tracing::warn!("Failed to parse JString: {}", e);
+      true
+    }
+  }
+  .into()
+}
+
+#[allow(non_snake_case)]
+pub unsafe fn handleCloseWindow(mut env: JNIEnv, _: JClass, webview_id: JString) {
+  let handlers = handlers(&mut env, &webview_id);
+  if let Some(close_requested_handler) = handlers
+    .as_ref()
+    .and_then(|handlers| handlers.close_requested.as_ref())
+  {
+    close_requested_handler();
+  }
+}
+
+#[allow(non_snake_case)]
+pub unsafe fn hasProcessFailedHandler(mut env: JNIEnv, _: JClass, webview_id: JString) -> jboolean {
+  handlers(&mut env, &webview_id)
+    .is_some_and(|handlers| handlers.process_failed.is_some())
+    .into()
+}
+
+#[allow(non_snake_case)]
+pub unsafe fn handleRenderProcessGone(
+  mut env: JNIEnv,
+  _: JClass,
+  webview_id: JString,
+  did_crash: jboolean,
+) {
+  let handlers = handlers(&mut env, &webview_id);
+  if let Some(process_failed_handler) = handlers
+    .as_ref()
+    .and_then(|handlers| handlers.process_failed.as_ref())
+  {
+    process_failed_handler(ProcessFailure {
+      reason: if did_crash != 0 {
+        ProcessFailureReason::Crashed
+      } else {
+        // the system killed the renderer to reclaim memory
+        ProcessFailureReason::Terminated
+      },
+      exit_code: None,
+      description: None,
+      crash_dumps_dir: None,
+    });
+  }
+}
+
+#[allow(non_snake_case)]
+pub unsafe fn hasTextInputHandler(mut env: JNIEnv, _: JClass, webview_id: JString) -> jboolean {
+  handlers(&mut env, &webview_id)
+    .is_some_and(|handlers| handlers.text_input.is_some())
+    .into()
+}
+
+#[allow(non_snake_case)]
+pub unsafe fn handleVirtualKeyboard(
+  mut env: JNIEnv,
+  _: JClass,
+  webview_id: JString,
+  visible: jboolean,
+  height: jfloat,
+) {
+  let handlers = handlers(&mut env, &webview_id);
+  if let Some(text_input_handler) = handlers
+    .as_ref()
+    .and_then(|handlers| handlers.text_input.as_ref())
+  {
+    text_input_handler(if visible != 0 {
+      TextInputEvent::VirtualKeyboardShown {
+        height: height as f64,
+      }
+    } else {
+      TextInputEvent::VirtualKeyboardHidden
+    });
+  }
+}
+
 #[allow(non_snake_case)]
 pub unsafe fn withAssetLoader(_: JNIEnv, _: JClass) -> jboolean {
   (*WITH_ASSET_LOADER.get().unwrap_or(&false)).into()
 }
 
+#[allow(non_snake_case)]
+pub unsafe fn openExternalUrls(mut env: JNIEnv, _: JClass, webview_id: JString) -> jboolean {
+  handlers(&mut env, &webview_id)
+    .is_some_and(|handlers| handlers.open_external_urls)
+    .into()
+}
+
 #[allow(non_snake_case)]
 pub unsafe fn assetLoaderDomain(env: JNIEnv, _: JClass) -> jstring {
   if let Some(domain) = ASSET_LOADER_DOMAIN.get() {
@@ -374,12 +629,19 @@ pub unsafe fn assetLoaderDomain(env: JNIEnv, _: JClass) -> jstring {
 }
 
 #[allow(non_snake_case)]
-pub unsafe fn onPageLoading(mut env: JNIEnv, _: JClass, url: JString) {
+pub unsafe fn onPageLoading(mut env: JNIEnv, _: JClass, webview_id: JString, url: JString) {
+  let handlers = handlers(&mut env, &webview_id);
+  if let Some(handlers) = &handlers {
+    handlers.navigation_state.start(None);
+  }
   match env.get_string(&url) {
     Ok(url) => {
       let url = url.to_string_lossy().to_string();
-      if let Some(on_load) = ON_LOAD_HANDLER.get() {
-        (on_load.handler)(PageLoadEvent::Started, url)
+      if let Some(on_load) = handlers
+        .as_ref()
+        .and_then(|handlers| handlers.on_page_load.as_ref())
+      {
+        on_load(PageLoadEvent::Started, url)
       }
     }
     Err(e) => {
@@ -390,12 +652,19 @@ pub unsafe fn onPageLoading(mut env: JNIEnv, _: JClass, url: JString) {
 }
 
 #[allow(non_snake_case)]
-pub unsafe fn onPageLoaded(mut env: JNIEnv, _: JClass, url: JString) {
+pub unsafe fn onPageLoaded(mut env: JNIEnv, _: JClass, webview_id: JString, url: JString) {
+  let handlers = handlers(&mut env, &webview_id);
+  if let Some(handlers) = &handlers {
+    handlers.navigation_state.finish(None);
+  }
   match env.get_string(&url) {
     Ok(url) => {
       let url = url.to_string_lossy().to_string();
-      if let Some(on_load) = ON_LOAD_HANDLER.get() {
-        (on_load.handler)(PageLoadEvent::Finished, url)
+      if let Some(on_load) = handlers
+        .as_ref()
+        .and_then(|handlers| handlers.on_page_load.as_ref())
+      {
+        on_load(PageLoadEvent::Finished, url)
       }
     }
     Err(e) => {
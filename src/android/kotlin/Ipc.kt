@@ -7,6 +7,7 @@
 package {{package}}
 
 import android.webkit.*
+{{imports}}
 
 class Ipc(val webViewClient: RustWebViewClient) {
     @JavascriptInterface
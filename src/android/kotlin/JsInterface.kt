@@ -0,0 +1,27 @@
+// Copyright 2020-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+@file:Suppress("unused")
+
+package {{package}}
+
+import android.webkit.*
+{{imports}}
+
+class JsInterface(val webviewId: String, val name: String) {
+    @JavascriptInterface
+    fun invoke(method: String, payload: String?): String? {
+        return handleInvoke(webviewId, name, method, payload ?: "")
+    }
+
+    companion object {
+        init {
+            System.loadLibrary("{{library}}")
+        }
+    }
+
+    private external fun handleInvoke(webviewId: String, name: String, method: String, payload: String): String?
+
+    {{class-extension}}
+}
@@ -30,6 +30,7 @@ import java.io.File
 import java.io.IOException
 import java.text.SimpleDateFormat
 import java.util.*
+{{imports}}
 
 class RustWebChromeClient(appActivity: WryActivity) : WebChromeClient() {
   private interface PermissionListener {
@@ -45,6 +46,9 @@ class RustWebChromeClient(appActivity: WryActivity) : WebChromeClient() {
   private var activityLauncher: ActivityResultLauncher<Intent>
   private var permissionListener: PermissionListener? = null
   private var activityListener: ActivityResultListener? = null
+  private var dialogOkLabel = "OK"
+  private var dialogCancelLabel = "Cancel"
+  private var dialogLayoutDirection = View.LAYOUT_DIRECTION_INHERIT
 
   init {
     activity = appActivity
@@ -118,6 +122,27 @@ class RustWebChromeClient(appActivity: WryActivity) : WebChromeClient() {
     }
   }
 
+  /**
+   * Set the labels of the dialog buttons, keeping the default ones when null, and the layout
+   * direction of the dialogs
+   * @param okLabel
+   * @param cancelLabel
+   * @param layoutDirection one of View.LAYOUT_DIRECTION_LTR, LAYOUT_DIRECTION_RTL or LAYOUT_DIRECTION_INHERIT
+   */
+  fun setDialogOptions(okLabel: String?, cancelLabel: String?, layoutDirection: Int) {
+    dialogOkLabel = okLabel ?: "OK"
+    dialogCancelLabel = cancelLabel ?: "Cancel"
+    dialogLayoutDirection = layoutDirection
+  }
+
+  private fun showDialog(builder: AlertDialog.Builder) {
+    val dialog = builder.create()
+    if (dialogLayoutDirection != View.LAYOUT_DIRECTION_INHERIT) {
+      dialog.window?.decorView?.layoutDirection = dialogLayoutDirection
+    }
+    dialog.show()
+  }
+
   /**
    * Show the browser alert modal
    * @param view
@@ -134,7 +159,7 @@ class RustWebChromeClient(appActivity: WryActivity) : WebChromeClient() {
     builder
       .setMessage(message)
       .setPositiveButton(
-        "OK"
+        dialogOkLabel
       ) { dialog: DialogInterface, _: Int ->
         dialog.dismiss()
         result.confirm()
@@ -143,8 +168,7 @@ class RustWebChromeClient(appActivity: WryActivity) : WebChromeClient() {
         dialog.dismiss()
         result.cancel()
       }
-    val dialog = builder.create()
-    dialog.show()
+    showDialog(builder)
     return true
   }
 
@@ -164,13 +188,13 @@ class RustWebChromeClient(appActivity: WryActivity) : WebChromeClient() {
     builder
       .setMessage(message)
       .setPositiveButton(
-        "OK"
+        dialogOkLabel
       ) { dialog: DialogInterface, _: Int ->
         dialog.dismiss()
         result.confirm()
       }
       .setNegativeButton(
-        "Cancel"
+        dialogCancelLabel
       ) { dialog: DialogInterface, _: Int ->
         dialog.dismiss()
         result.cancel()
@@ -179,8 +203,28 @@ class RustWebChromeClient(appActivity: WryActivity) : WebChromeClient() {
         dialog.dismiss()
         result.cancel()
       }
-    val dialog = builder.create()
-    dialog.show()
+    showDialog(builder)
+    return true
+  }
+
+  /**
+   * Ask the before unload handler whether the page can be unloaded
+   * @param view
+   * @param url
+   * @param message
+   * @param result
+   * @return
+   */
+  override fun onJsBeforeUnload(view: WebView, url: String, message: String, result: JsResult): Boolean {
+    val webviewId = (view as RustWebView).id
+    if (!hasBeforeUnloadHandler(webviewId)) {
+      return super.onJsBeforeUnload(view, url, message, result)
+    }
+    if (handleBeforeUnload(webviewId, url, message)) {
+      result.confirm()
+    } else {
+      result.cancel()
+    }
     return true
   }
 
@@ -209,14 +253,14 @@ class RustWebChromeClient(appActivity: WryActivity) : WebChromeClient() {
       .setMessage(message)
       .setView(input)
       .setPositiveButton(
-        "OK"
+        dialogOkLabel
       ) { dialog: DialogInterface, _: Int ->
         dialog.dismiss()
         val inputText1 = input.text.toString().trim { it <= ' ' }
         result.confirm(inputText1)
       }
       .setNegativeButton(
-        "Cancel"
+        dialogCancelLabel
       ) { dialog: DialogInterface, _: Int ->
         dialog.dismiss()
         result.cancel()
@@ -225,8 +269,7 @@ class RustWebChromeClient(appActivity: WryActivity) : WebChromeClient() {
         dialog.dismiss()
         result.cancel()
       }
-    val dialog = builder.create()
-    dialog.show()
+    showDialog(builder)
     return true
   }
 
@@ -489,5 +532,14 @@ class RustWebChromeClient(appActivity: WryActivity) : WebChromeClient() {
     handleReceivedTitle(view, title)
   }
 
+  override fun onCloseWindow(window: WebView) {
+    handleCloseWindow((window as RustWebView).id)
+  }
+
   private external fun handleReceivedTitle(webview: WebView, title: String)
+  private external fun handleCloseWindow(webviewId: String)
+  private external fun hasBeforeUnloadHandler(webviewId: String): Boolean
+  private external fun handleBeforeUnload(webviewId: String, url: String, message: String): Boolean
+
+  {{class-extension}}
 }
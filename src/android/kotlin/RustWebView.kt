@@ -7,16 +7,24 @@
 package {{package}}
 
 import android.annotation.SuppressLint
+import android.app.Activity
 import android.webkit.*
 import android.content.Context
+import android.view.WindowManager
+import android.widget.FrameLayout
+import androidx.core.view.ViewCompat
+import androidx.core.view.WindowInsetsCompat
+import androidx.webkit.WebSettingsCompat
 import androidx.webkit.WebViewCompat
 import androidx.webkit.WebViewFeature
 import kotlin.collections.Map
+{{imports}}
 
 @SuppressLint("RestrictedApi")
 class RustWebView(context: Context, val initScripts: Array<String>, val id: String): WebView(context) {
     val isDocumentStartScriptEnabled: Boolean
-  
+    private var virtualKeyboardHeight = 0f
+
     init {
         settings.javaScriptEnabled = true
         settings.domStorageEnabled = true
@@ -34,6 +42,21 @@ class RustWebView(context: Context, val initScripts: Array<String>, val id: Stri
           isDocumentStartScriptEnabled = false
         }
 
+        ViewCompat.setOnApplyWindowInsetsListener(this) { view, insets ->
+            if (hasTextInputHandler(id)) {
+                val height = if (insets.isVisible(WindowInsetsCompat.Type.ime())) {
+                    insets.getInsets(WindowInsetsCompat.Type.ime()).bottom / resources.displayMetrics.density
+                } else {
+                    0f
+                }
+                if (height != virtualKeyboardHeight) {
+                    virtualKeyboardHeight = height
+                    handleVirtualKeyboard(id, height > 0f, height)
+                }
+            }
+            ViewCompat.onApplyWindowInsets(view, insets)
+        }
+
         {{class-init}}
     }
 
@@ -50,13 +73,13 @@ class RustWebView(context: Context, val initScripts: Array<String>, val id: Stri
     }
 
     override fun loadUrl(url: String) {
-        if (!shouldOverride(url)) {
+        if (!shouldOverride(id, url)) {
             super.loadUrl(url);
         }
     }
 
     override fun loadUrl(url: String, additionalHttpHeaders: Map<String, String>) {
-        if (!shouldOverride(url)) {
+        if (!shouldOverride(id, url)) {
             super.loadUrl(url, additionalHttpHeaders);
         }
     }
@@ -87,11 +110,87 @@ class RustWebView(context: Context, val initScripts: Array<String>, val id: Stri
         }
     }
 
+    fun clearHttpCache() {
+        super.clearCache(true)
+    }
+
+    // mode is one of WebSettings.LOAD_DEFAULT, LOAD_NO_CACHE or LOAD_CACHE_ONLY
+    fun setCacheMode(mode: Int) {
+        val settings = super.getSettings()
+        settings.cacheMode = mode
+    }
+
+    fun setFileAccess(allow: Boolean, fromFileUrls: Boolean, universal: Boolean) {
+        val settings = super.getSettings()
+        settings.allowFileAccess = allow
+        settings.allowFileAccessFromFileURLs = fromFileUrls
+        settings.allowUniversalAccessFromFileURLs = universal
+    }
+
     fun setAutoPlay(enable: Boolean) {
         val settings = super.getSettings()
         settings.mediaPlaybackRequiresUserGesture = !enable
     }
 
+    fun setJavaScriptEnabled(enable: Boolean) {
+        val settings = super.getSettings()
+        settings.javaScriptEnabled = enable
+    }
+
+    fun setImagesEnabled(enable: Boolean) {
+        val settings = super.getSettings()
+        settings.loadsImagesAutomatically = enable
+    }
+
+    fun setIncognito(enable: Boolean) {
+        val settings = super.getSettings()
+        settings.domStorageEnabled = !enable
+        settings.databaseEnabled = !enable
+        settings.cacheMode = if (enable) WebSettings.LOAD_NO_CACHE else WebSettings.LOAD_DEFAULT
+        CookieManager.getInstance().setAcceptCookie(!enable)
+        CookieManager.getInstance().setAcceptThirdPartyCookies(this, !enable)
+        if (enable) {
+            super.clearCache(true)
+        }
+    }
+
+    fun setSafeBrowsingEnabled(enable: Boolean) {
+        if (WebViewFeature.isFeatureSupported(WebViewFeature.SAFE_BROWSING_ENABLE)) {
+            WebSettingsCompat.setSafeBrowsingEnabled(settings, enable)
+        }
+    }
+
+    // mode is one of WebSettingsCompat.FORCE_DARK_OFF, FORCE_DARK_AUTO or FORCE_DARK_ON
+    @Suppress("DEPRECATION")
+    fun setForceDark(mode: Int) {
+        if (WebViewFeature.isFeatureSupported(WebViewFeature.ALGORITHMIC_DARKENING)) {
+            WebSettingsCompat.setAlgorithmicDarkeningAllowed(settings, mode != WebSettingsCompat.FORCE_DARK_OFF)
+        } else if (WebViewFeature.isFeatureSupported(WebViewFeature.FORCE_DARK)) {
+            WebSettingsCompat.setForceDark(settings, mode)
+        }
+    }
+
+    fun setVirtualKeyboardOverlay(overlay: Boolean) {
+        val mode = if (overlay) {
+            WindowManager.LayoutParams.SOFT_INPUT_ADJUST_NOTHING
+        } else {
+            @Suppress("DEPRECATION")
+            WindowManager.LayoutParams.SOFT_INPUT_ADJUST_RESIZE
+        }
+        (context as? Activity)?.window?.setSoftInputMode(mode)
+    }
+
+    fun setBounds(x: Int, y: Int, width: Int, height: Int) {
+        val params = FrameLayout.LayoutParams(width, height)
+        params.leftMargin = x
+        params.topMargin = y
+        layoutParams = params
+    }
+
+    fun getScaleFactor(): Float {
+        return resources.displayMetrics.density
+    }
+
     fun setUserAgent(ua: String) {
         val settings = super.getSettings()
         settings.userAgentString = ua
@@ -102,8 +201,10 @@ class RustWebView(context: Context, val initScripts: Array<String>, val id: Stri
         return cookieManager.getCookie(url)
     }
 
-    private external fun shouldOverride(url: String): Boolean
+    private external fun shouldOverride(webviewId: String, url: String): Boolean
     private external fun onEval(id: Int, result: String)
+    private external fun hasTextInputHandler(webviewId: String): Boolean
+    private external fun handleVirtualKeyboard(webviewId: String, visible: Boolean, height: Float)
 
     {{class-extension}}
 }
@@ -6,11 +6,14 @@ package {{package}}
 
 import android.net.Uri
 import android.webkit.*
+import android.content.ActivityNotFoundException
 import android.content.Context
+import android.content.Intent
 import android.graphics.Bitmap
 import android.os.Handler
 import android.os.Looper
 import androidx.webkit.WebViewAssetLoader
+{{imports}}
 
 class RustWebViewClient(context: Context): WebViewClient() {
     private val interceptedState = mutableMapOf<String, Boolean>()
@@ -50,7 +53,49 @@ class RustWebViewClient(context: Context): WebViewClient() {
         view: WebView,
         request: WebResourceRequest
     ): Boolean {
-        return shouldOverride(request.url.toString())
+        if (shouldOverride((view as RustWebView).id, request.url.toString())) {
+            return true
+        }
+        return openExternalUrl(view, request.url)
+    }
+
+    // Opens URLs that the webview cannot handle (e.g. tel:, mailto:, market:// or intent://)
+    // with the system, if enabled with `WebViewBuilderExtAndroid::with_open_external_urls`
+    private fun openExternalUrl(view: WebView, url: Uri): Boolean {
+        val scheme = url.scheme?.lowercase() ?: return false
+        if (scheme in WEBVIEW_SCHEMES || !openExternalUrls((view as RustWebView).id)) {
+            return false
+        }
+
+        val intent = if (scheme == "intent") {
+            try {
+                Intent.parseUri(url.toString(), Intent.URI_INTENT_SCHEME)
+            } catch (ex: Exception) {
+                Logger.warn("Unable to parse intent URL '$url': " + ex.message)
+                return true
+            }
+        } else {
+            Intent(Intent.ACTION_VIEW, url)
+        }
+        // only let the page open the activities a browser could open, and never a given component
+        // such as the non-exported activities of the app
+        intent.addCategory(Intent.CATEGORY_BROWSABLE)
+        intent.component = null
+        intent.selector = null
+        intent.addFlags(Intent.FLAG_ACTIVITY_NEW_TASK)
+
+        try {
+            view.context.startActivity(intent)
+        } catch (ex: ActivityNotFoundException) {
+            val fallbackUrl = intent.getStringExtra("browser_fallback_url")
+            val fallbackScheme = fallbackUrl?.let { Uri.parse(it).scheme?.lowercase() }
+            if (fallbackUrl != null && (fallbackScheme == "http" || fallbackScheme == "https")) {
+                view.loadUrl(fallbackUrl)
+            } else {
+                Logger.warn("No activity found to open '$url'")
+            }
+        }
+        return true
     }
 
     override fun onPageStarted(view: WebView, url: String, favicon: Bitmap?) {
@@ -61,11 +106,11 @@ class RustWebViewClient(context: Context): WebViewClient() {
                 view.evaluateJavascript(script, null)
             }
         }
-        return onPageLoading(url)
+        return onPageLoading((view as RustWebView).id, url)
     }
 
     override fun onPageFinished(view: WebView, url: String) {
-        onPageLoaded(url)
+        onPageLoaded((view as RustWebView).id, url)
     }
 
     override fun onReceivedError(
@@ -88,7 +133,19 @@ class RustWebViewClient(context: Context): WebViewClient() {
         }
     }
 
+    override fun onRenderProcessGone(view: WebView, detail: RenderProcessGoneDetail): Boolean {
+        val webviewId = (view as RustWebView).id
+        if (!hasProcessFailedHandler(webviewId)) {
+            return super.onRenderProcessGone(view, detail)
+        }
+        // keep the app running, the webview must be recreated by the handler
+        handleRenderProcessGone(webviewId, detail.didCrash())
+        return true
+    }
+
     companion object {
+        private val WEBVIEW_SCHEMES = setOf("http", "https", "file", "about", "data", "blob", "javascript")
+
         init {
             System.loadLibrary("{{library}}")
         }
@@ -97,9 +154,12 @@ class RustWebViewClient(context: Context): WebViewClient() {
     private external fun assetLoaderDomain(): String
     private external fun withAssetLoader(): Boolean
     private external fun handleRequest(webviewId: String, request: WebResourceRequest, isDocumentStartScriptEnabled: Boolean): WebResourceResponse?
-    private external fun shouldOverride(url: String): Boolean
-    private external fun onPageLoading(url: String)
-    private external fun onPageLoaded(url: String)
+    private external fun shouldOverride(webviewId: String, url: String): Boolean
+    private external fun openExternalUrls(webviewId: String): Boolean
+    private external fun onPageLoading(webviewId: String, url: String)
+    private external fun onPageLoaded(webviewId: String, url: String)
+    private external fun hasProcessFailedHandler(webviewId: String): Boolean
+    private external fun handleRenderProcessGone(webviewId: String, didCrash: Boolean)
 
     {{class-extension}}
 }
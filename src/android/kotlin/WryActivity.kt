@@ -11,6 +11,7 @@ import android.os.Bundle
 import android.webkit.WebView
 import android.view.KeyEvent
 import androidx.appcompat.app.AppCompatActivity
+{{imports}}
 
 abstract class WryActivity : AppCompatActivity() {
     private lateinit var mWebView: RustWebView
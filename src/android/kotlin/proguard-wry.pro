@@ -20,16 +20,40 @@
   @android.webkit.JavascriptInterface public <methods>;
 }
 
+-keep class {{package-unescaped}}.JsInterface {
+  public <init>(...);
+
+  @android.webkit.JavascriptInterface public <methods>;
+}
+
 -keep class {{package-unescaped}}.RustWebView {
   public <init>(...);
 
   void loadUrlMainThread(...);
   void loadHTMLMainThread(...);
   void setAutoPlay(...);
+  void setIncognito(...);
+  void setSafeBrowsingEnabled(...);
+  void setForceDark(...);
+  void setBounds(...);
+  float getScaleFactor();
   void setUserAgent(...);
   void evalScript(...);
 }
 
 -keep class {{package-unescaped}}.RustWebChromeClient,{{package-unescaped}}.RustWebViewClient {
   public <init>(...);
-}
\ No newline at end of file
+}
+
+-keepclassmembers class {{package-unescaped}}.RustWebView {
+  void setJavaScriptEnabled(boolean);
+  void setImagesEnabled(boolean);
+  void setCacheMode(int);
+  void clearHttpCache();
+  void setFileAccess(boolean, boolean, boolean);
+  void setVirtualKeyboardOverlay(boolean);
+}
+
+-keepclassmembers class {{package-unescaped}}.RustWebChromeClient {
+  void setDialogOptions(java.lang.String, java.lang.String, int);
+}
@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use crate::{Error, RGBA};
+use crate::{
+  CacheMode, Error, FileAccessPolicy, Rect, Theme, UiLayoutDirection, UiString,
+  VirtualKeyboardPolicy, RGBA,
+};
 use crossbeam_channel::*;
 use jni::{
   errors::Result as JniResult,
@@ -10,21 +13,27 @@ use jni::{
   JNIEnv,
 };
 use once_cell::sync::Lazy;
-use std::{os::unix::prelude::*, str::FromStr, sync::atomic::Ordering};
+use std::{collections::HashMap, os::unix::prelude::*, str::FromStr, sync::atomic::Ordering};
 
 use super::{find_class, EvalCallback, EVAL_CALLBACKS, EVAL_ID_GENERATOR, PACKAGE};
 
 static CHANNEL: Lazy<(Sender<WebViewMessage>, Receiver<WebViewMessage>)> = Lazy::new(|| bounded(8));
-pub static MAIN_PIPE: Lazy<[OwnedFd; 2]> = Lazy::new(|| {
+pub static MAIN_PIPE: Lazy<[OwnedFd; 2]> = Lazy::new(pipe);
+
+/// Creates a pipe, returning its read and write ends.
+pub(crate) fn pipe() -> [OwnedFd; 2] {
   let mut pipe: [RawFd; 2] = Default::default();
   unsafe { libc::pipe(pipe.as_mut_ptr()) };
   unsafe { pipe.map(|fd| OwnedFd::from_raw_fd(fd)) }
-});
+}
 
 pub struct MainPipe<'a> {
   pub env: JNIEnv<'a>,
   pub activity: GlobalRef,
-  pub webview: Option<GlobalRef>,
+  /// The webviews by id, removed when their [`InnerWebView`](super::InnerWebView) is dropped.
+  pub webviews: HashMap<String, GlobalRef>,
+  /// The id of the last created webview, given to the closures of [`super::dispatch`].
+  pub last_webview_id: Option<String>,
   pub webchrome_client: GlobalRef,
 }
 
@@ -57,9 +66,23 @@ impl<'a> MainPipe<'a> {
             headers,
             on_webview_created,
             autoplay,
+            javascript_enabled,
+            images_enabled,
+            cache_mode,
+            file_access,
             user_agent,
             initialization_scripts,
             id,
+            incognito,
+            focused,
+            visible,
+            bounds,
+            safe_browsing,
+            force_dark,
+            javascript_interfaces,
+            virtual_keyboard_policy,
+            ui_strings,
+            ui_layout_direction,
             ..
           } = attrs;
 
@@ -77,6 +100,7 @@ impl<'a> MainPipe<'a> {
             )?;
           }
 
+          let webview_id = id.clone();
           let id = self.env.new_string(id)?;
 
           // Create webview
@@ -100,6 +124,59 @@ impl<'a> MainPipe<'a> {
             .env
             .call_method(&webview, "setAutoPlay", "(Z)V", &[autoplay.into()])?;
 
+          if !javascript_enabled {
+            set_javascript_enabled(&mut self.env, &webview, javascript_enabled)?;
+          }
+
+          if !images_enabled {
+            set_images_enabled(&mut self.env, &webview, images_enabled)?;
+          }
+
+          // set incognito mode
+          if incognito {
+            self
+              .env
+              .call_method(&webview, "setIncognito", "(Z)V", &[incognito.into()])?;
+          }
+
+          if cache_mode != CacheMode::Default {
+            set_cache_mode(&mut self.env, &webview, cache_mode)?;
+          }
+
+          if file_access != FileAccessPolicy::Default {
+            set_file_access(&mut self.env, &webview, file_access)?;
+          }
+
+          if let Some(safe_browsing) = safe_browsing {
+            self.env.call_method(
+              &webview,
+              "setSafeBrowsingEnabled",
+              "(Z)V",
+              &[safe_browsing.into()],
+            )?;
+          }
+
+          if let Some(force_dark) = force_dark {
+            // WebSettingsCompat.FORCE_DARK_OFF, FORCE_DARK_AUTO and FORCE_DARK_ON
+            let mode = match force_dark {
+              Theme::Light => 0,
+              Theme::Auto => 1,
+              Theme::Dark => 2,
+            };
+            self
+              .env
+              .call_method(&webview, "setForceDark", "(I)V", &[mode.into()])?;
+          }
+
+          if virtual_keyboard_policy == VirtualKeyboardPolicy::Overlay {
+            self.env.call_method(
+              &webview,
+              "setVirtualKeyboardOverlay",
+              "(Z)V",
+              &[true.into()],
+            )?;
+          }
+
           // set user-agent
           if let Some(user_agent) = user_agent {
             let user_agent = self.env.new_string(user_agent)?;
@@ -160,6 +237,22 @@ impl<'a> MainPipe<'a> {
             &[(&webview_client).into()],
           )?;
 
+          // translate and mirror the dialogs of the webchrome client
+          let ok = ui_string(&mut self.env, &ui_strings, UiString::DialogOk)?;
+          let cancel = ui_string(&mut self.env, &ui_strings, UiString::DialogCancel)?;
+          // View.LAYOUT_DIRECTION_LTR, LAYOUT_DIRECTION_RTL and LAYOUT_DIRECTION_INHERIT
+          let layout_direction = match ui_layout_direction {
+            UiLayoutDirection::LeftToRight => 0,
+            UiLayoutDirection::RightToLeft => 1,
+            UiLayoutDirection::Auto => 2,
+          };
+          self.env.call_method(
+            self.webchrome_client.as_obj(),
+            "setDialogOptions",
+            "(Ljava/lang/String;Ljava/lang/String;I)V",
+            &[(&ok).into(), (&cancel).into(), layout_direction.into()],
+          )?;
+
           // set webchrome client
           self.env.call_method(
             &webview,
@@ -187,13 +280,64 @@ impl<'a> MainPipe<'a> {
             &[(&ipc).into(), (&ipc_str).into()],
           )?;
 
-          // Set content view
-          self.env.call_method(
-            activity,
-            "setContentView",
-            "(Landroid/view/View;)V",
-            &[(&webview).into()],
-          )?;
+          // Add additional javascript interfaces
+          if !javascript_interfaces.is_empty() {
+            let js_interface_class = find_class(
+              &mut self.env,
+              activity,
+              format!("{}/JsInterface", PACKAGE.get().unwrap()),
+            )?;
+            for name in javascript_interfaces {
+              let name = self.env.new_string(name)?;
+              let js_interface = self.env.new_object(
+                &js_interface_class,
+                "(Ljava/lang/String;Ljava/lang/String;)V",
+                &[(&id).into(), (&name).into()],
+              )?;
+              self.env.call_method(
+                &webview,
+                "addJavascriptInterface",
+                "(Ljava/lang/Object;Ljava/lang/String;)V",
+                &[(&js_interface).into(), (&name).into()],
+              )?;
+            }
+          }
+
+          if let Some(bounds) = bounds {
+            // Add the webview as a child of the activity content, positioned using its bounds
+            set_bounds(&mut self.env, &webview, bounds)?;
+            let layout_params = self
+              .env
+              .call_method(
+                &webview,
+                "getLayoutParams",
+                "()Landroid/view/ViewGroup$LayoutParams;",
+                &[],
+              )?
+              .l()?;
+            self.env.call_method(
+              activity,
+              "addContentView",
+              "(Landroid/view/View;Landroid/view/ViewGroup$LayoutParams;)V",
+              &[(&webview).into(), (&layout_params).into()],
+            )?;
+          } else {
+            // Set content view
+            self.env.call_method(
+              activity,
+              "setContentView",
+              "(Landroid/view/View;)V",
+              &[(&webview).into()],
+            )?;
+          }
+
+          if !visible {
+            set_visible(&mut self.env, &webview, visible)?;
+          }
+
+          if focused {
+            self.env.call_method(&webview, "requestFocus", "()Z", &[])?;
+          }
 
           if let Some(on_webview_created) = on_webview_created {
             if let Err(e) = on_webview_created(super::Context {
@@ -208,11 +352,15 @@ impl<'a> MainPipe<'a> {
 
           let webview = self.env.new_global_ref(webview)?;
 
-          self.webview = Some(webview);
+          self.webviews.insert(webview_id.clone(), webview);
+          self.last_webview_id = Some(webview_id);
+        }
+        WebViewMessage::RemoveWebView(id) => {
+          self.webviews.remove(&id);
         }
-        WebViewMessage::Eval(script, callback) => {
-          if let Some(webview) = &self.webview {
-            let id = EVAL_ID_GENERATOR.next() as i32;
+        WebViewMessage::Eval(id, script, callback) => {
+          if let Some(webview) = self.webviews.get(&id) {
+            let eval_id = EVAL_ID_GENERATOR.next() as i32;
 
             #[cfg(feature = "tracing")]
             let span = std::sync::Mutex::new(Some(SendEnteredSpan(
@@ -224,7 +372,7 @@ impl<'a> MainPipe<'a> {
               .lock()
               .unwrap()
               .insert(
-                id,
+                eval_id,
                 Box::new(move |result| {
                   #[cfg(feature = "tracing")]
                   span.lock().unwrap().take();
@@ -240,12 +388,12 @@ impl<'a> MainPipe<'a> {
               webview.as_obj(),
               "evalScript",
               "(ILjava/lang/String;)V",
-              &[id.into(), (&s).into()],
+              &[eval_id.into(), (&s).into()],
             )?;
           }
         }
-        WebViewMessage::SetBackgroundColor(background_color) => {
-          if let Some(webview) = &self.webview {
+        WebViewMessage::SetBackgroundColor(id, background_color) => {
+          if let Some(webview) = self.webviews.get(&id) {
             set_background_color(&mut self.env, webview.as_obj(), background_color)?;
           }
         }
@@ -267,8 +415,8 @@ impl<'a> MainPipe<'a> {
             Err(e) => tx.send(Err(e.into())).unwrap(),
           }
         }
-        WebViewMessage::GetUrl(tx) => {
-          if let Some(webview) = &self.webview {
+        WebViewMessage::GetUrl(id, tx) => {
+          if let Some(webview) = self.webviews.get(&id) {
             let url = self
               .env
               .call_method(webview.as_obj(), "getUrl", "()Ljava/lang/String;", &[])
@@ -286,33 +434,92 @@ impl<'a> MainPipe<'a> {
           }
         }
         WebViewMessage::Jni(f) => {
-          if let Some(w) = &self.webview {
+          let webview = self
+            .last_webview_id
+            .as_ref()
+            .and_then(|id| self.webviews.get(id));
+          if let Some(w) = webview {
             f(&mut self.env, activity, w.as_obj());
           } else {
             f(&mut self.env, activity, &JObject::null());
           }
         }
-        WebViewMessage::LoadUrl(url, headers) => {
-          if let Some(webview) = &self.webview {
+        WebViewMessage::LoadUrl(id, url, headers) => {
+          if let Some(webview) = self.webviews.get(&id) {
             let url = self.env.new_string(url)?;
             load_url(&mut self.env, webview.as_obj(), &url, headers, false)?;
           }
         }
-        WebViewMessage::ClearAllBrowsingData => {
-          if let Some(webview) = &self.webview {
+        WebViewMessage::ClearAllBrowsingData(id) => {
+          if let Some(webview) = self.webviews.get(&id) {
             self
               .env
               .call_method(webview, "clearAllBrowsingData", "()V", &[])?;
           }
         }
-        WebViewMessage::LoadHtml(html) => {
-          if let Some(webview) = &self.webview {
+        WebViewMessage::ClearHttpCache(id) => {
+          if let Some(webview) = self.webviews.get(&id) {
+            self
+              .env
+              .call_method(webview, "clearHttpCache", "()V", &[])?;
+          }
+        }
+        WebViewMessage::SetBounds(id, bounds) => {
+          if let Some(webview) = self.webviews.get(&id) {
+            set_bounds(&mut self.env, webview.as_obj(), bounds)?;
+          }
+        }
+        WebViewMessage::GetBounds(id, tx) => {
+          if let Some(webview) = self.webviews.get(&id) {
+            let webview = webview.as_obj();
+            let x = self.env.call_method(webview, "getLeft", "()I", &[])?.i()?;
+            let y = self.env.call_method(webview, "getTop", "()I", &[])?.i()?;
+            let width = self.env.call_method(webview, "getWidth", "()I", &[])?.i()?;
+            let height = self
+              .env
+              .call_method(webview, "getHeight", "()I", &[])?
+              .i()?;
+
+            tx.send(Rect {
+              position: dpi::PhysicalPosition::new(x, y).into(),
+              size: dpi::PhysicalSize::new(width as u32, height as u32).into(),
+            })
+            .unwrap();
+          }
+        }
+        WebViewMessage::SetVisible(id, visible) => {
+          if let Some(webview) = self.webviews.get(&id) {
+            set_visible(&mut self.env, webview.as_obj(), visible)?;
+          }
+        }
+        WebViewMessage::SetJavaScriptEnabled(id, enabled) => {
+          if let Some(webview) = self.webviews.get(&id) {
+            set_javascript_enabled(&mut self.env, webview.as_obj(), enabled)?;
+          }
+        }
+        WebViewMessage::SetImagesEnabled(id, enabled) => {
+          if let Some(webview) = self.webviews.get(&id) {
+            set_images_enabled(&mut self.env, webview.as_obj(), enabled)?;
+          }
+        }
+        WebViewMessage::Focus(id) => {
+          if let Some(webview) = self.webviews.get(&id) {
+            self.env.call_method(webview, "requestFocus", "()Z", &[])?;
+          }
+        }
+        WebViewMessage::FocusParent(id) => {
+          if let Some(webview) = self.webviews.get(&id) {
+            self.env.call_method(webview, "clearFocus", "()V", &[])?;
+          }
+        }
+        WebViewMessage::LoadHtml(id, html) => {
+          if let Some(webview) = self.webviews.get(&id) {
             let html = self.env.new_string(html)?;
             load_html(&mut self.env, webview.as_obj(), &html)?;
           }
         }
-        WebViewMessage::GetCookies(tx, url) => {
-          if let Some(webview) = &self.webview {
+        WebViewMessage::GetCookies(id, tx, url) => {
+          if let Some(webview) = self.webviews.get(&id) {
             let url = self.env.new_string(url)?;
             let cookies = self
               .env
@@ -402,17 +609,103 @@ fn set_background_color<'a>(
   Ok(())
 }
 
+fn set_bounds<'a>(env: &mut JNIEnv<'a>, webview: &JObject<'a>, bounds: Rect) -> JniResult<()> {
+  let scale_factor = env
+    .call_method(webview, "getScaleFactor", "()F", &[])?
+    .f()? as f64;
+  let (x, y): (i32, i32) = bounds.position.to_physical::<i32>(scale_factor).into();
+  let (width, height): (i32, i32) = bounds.size.to_physical::<i32>(scale_factor).into();
+  env.call_method(
+    webview,
+    "setBounds",
+    "(IIII)V",
+    &[x.into(), y.into(), width.into(), height.into()],
+  )?;
+  Ok(())
+}
+
+fn set_visible<'a>(env: &mut JNIEnv<'a>, webview: &JObject<'a>, visible: bool) -> JniResult<()> {
+  // android.view.View.VISIBLE and android.view.View.INVISIBLE
+  let visibility = if visible { 0 } else { 4 };
+  env.call_method(webview, "setVisibility", "(I)V", &[visibility.into()])?;
+  Ok(())
+}
+
+fn set_javascript_enabled<'a>(
+  env: &mut JNIEnv<'a>,
+  webview: &JObject<'a>,
+  enabled: bool,
+) -> JniResult<()> {
+  env.call_method(webview, "setJavaScriptEnabled", "(Z)V", &[enabled.into()])?;
+  Ok(())
+}
+
+fn set_images_enabled<'a>(
+  env: &mut JNIEnv<'a>,
+  webview: &JObject<'a>,
+  enabled: bool,
+) -> JniResult<()> {
+  env.call_method(webview, "setImagesEnabled", "(Z)V", &[enabled.into()])?;
+  Ok(())
+}
+
+fn set_file_access<'a>(
+  env: &mut JNIEnv<'a>,
+  webview: &JObject<'a>,
+  policy: FileAccessPolicy,
+) -> JniResult<()> {
+  let allow = policy != FileAccessPolicy::Deny;
+  let from_file_urls = matches!(
+    policy,
+    FileAccessPolicy::AllowFiles | FileAccessPolicy::Universal
+  );
+  let universal = policy == FileAccessPolicy::Universal;
+  env.call_method(
+    webview,
+    "setFileAccess",
+    "(ZZZ)V",
+    &[allow.into(), from_file_urls.into(), universal.into()],
+  )?;
+  Ok(())
+}
+
+fn set_cache_mode<'a>(
+  env: &mut JNIEnv<'a>,
+  webview: &JObject<'a>,
+  cache_mode: CacheMode,
+) -> JniResult<()> {
+  // android.webkit.WebSettings.LOAD_DEFAULT, LOAD_NO_CACHE and LOAD_CACHE_ONLY
+  let mode = match cache_mode {
+    CacheMode::NoCache => 2,
+    CacheMode::CacheOnly => 3,
+    CacheMode::Default => -1,
+  };
+  env.call_method(webview, "setCacheMode", "(I)V", &[mode.into()])?;
+  Ok(())
+}
+
+/// A message to the thread of [`super::android_setup`], most of them target the webview with the
+/// id given as their first field.
 pub(crate) enum WebViewMessage {
   CreateWebView(CreateWebViewAttributes),
-  Eval(String, Option<EvalCallback>),
-  SetBackgroundColor(RGBA),
+  RemoveWebView(String),
+  Eval(String, String, Option<EvalCallback>),
+  SetBackgroundColor(String, RGBA),
   GetWebViewVersion(Sender<Result<String, Error>>),
-  GetUrl(Sender<String>),
-  GetCookies(Sender<Vec<cookie::Cookie<'static>>>, String),
+  GetUrl(String, Sender<String>),
+  GetCookies(String, Sender<Vec<cookie::Cookie<'static>>>, String),
   Jni(Box<dyn FnOnce(&mut JNIEnv, &JObject, &JObject) + Send>),
-  LoadUrl(String, Option<http::HeaderMap>),
-  LoadHtml(String),
-  ClearAllBrowsingData,
+  LoadUrl(String, String, Option<http::HeaderMap>),
+  LoadHtml(String, String),
+  ClearAllBrowsingData(String),
+  ClearHttpCache(String),
+  SetBounds(String, Rect),
+  GetBounds(String, Sender<Rect>),
+  SetVisible(String, bool),
+  SetJavaScriptEnabled(String, bool),
+  SetImagesEnabled(String, bool),
+  Focus(String),
+  FocusParent(String),
 }
 
 pub(crate) struct CreateWebViewAttributes {
@@ -425,9 +718,35 @@ pub(crate) struct CreateWebViewAttributes {
   pub background_color: Option<RGBA>,
   pub headers: Option<http::HeaderMap>,
   pub autoplay: bool,
+  pub javascript_enabled: bool,
+  pub images_enabled: bool,
+  pub cache_mode: CacheMode,
+  pub file_access: FileAccessPolicy,
   pub on_webview_created: Option<Box<dyn Fn(super::Context) -> JniResult<()> + Send>>,
   pub user_agent: Option<String>,
   pub initialization_scripts: Vec<String>,
+  pub incognito: bool,
+  pub focused: bool,
+  pub visible: bool,
+  pub bounds: Option<Rect>,
+  pub safe_browsing: Option<bool>,
+  pub force_dark: Option<Theme>,
+  pub javascript_interfaces: Vec<String>,
+  pub virtual_keyboard_policy: VirtualKeyboardPolicy,
+  pub ui_strings: HashMap<UiString, String>,
+  pub ui_layout_direction: UiLayoutDirection,
+}
+
+/// The string of `key` in `ui_strings`, or `null` to use the default string.
+fn ui_string<'a>(
+  env: &mut JNIEnv<'a>,
+  ui_strings: &HashMap<UiString, String>,
+  key: UiString,
+) -> JniResult<JObject<'a>> {
+  match ui_strings.get(&key) {
+    Some(string) => Ok(env.new_string(string)?.into()),
+    None => Ok(JObject::null()),
+  }
 }
 
 // SAFETY: only use this when you are sure the span will be dropped on the same thread it was entered
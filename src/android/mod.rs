@@ -2,8 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use super::{PageLoadEvent, WebViewAttributes, RGBA};
-use crate::{RequestAsyncResponder, Result};
+use super::{
+  Engine, EngineInfo, PageLoadEvent, ProcessFailure, TextInputEvent, WebViewAttributes, RGBA,
+};
+use crate::{
+  remove_user_style_sheet_script, user_style_sheet_script, NavigationState, PersistentHeaders,
+  RequestAsyncResponder, ResponderFn, Result,
+};
 use base64::{engine::general_purpose, Engine};
 use crossbeam_channel::*;
 use html5ever::{interface::QualName, namespace_url, ns, tendril::TendrilSink, LocalName};
@@ -18,14 +23,15 @@ use jni::{
 };
 use kuchiki::NodeRef;
 use ndk::looper::{FdEvent, ThreadLooper};
-use once_cell::sync::OnceCell;
+use once_cell::sync::{Lazy, OnceCell};
 use raw_window_handle::HasWindowHandle;
 use sha2::{Digest, Sha256};
 use std::{
   borrow::Cow,
   collections::HashMap,
-  os::fd::{AsFd as _, AsRawFd as _},
-  sync::{mpsc::channel, Mutex},
+  os::fd::{AsFd as _, AsRawFd as _, OwnedFd},
+  path::Path,
+  sync::{mpsc::channel, Arc, Mutex},
 };
 
 pub(crate) mod binding;
@@ -64,10 +70,43 @@ define_static_handlers! {
   IPC =  UnsafeIpc { handler: Box<dyn Fn(Request<String>)> };
   REQUEST_HANDLER = UnsafeRequestHandler { handler:  Box<dyn Fn(&str, Request<Vec<u8>>, bool) -> Option<HttpResponse<Cow<'static, [u8]>>>> };
   TITLE_CHANGE_HANDLER = UnsafeTitleHandler { handler: Box<dyn Fn(String)> };
-  URL_LOADING_OVERRIDE = UnsafeUrlLoadingOverride { handler: Box<dyn Fn(String) -> bool> };
-  ON_LOAD_HANDLER = UnsafeOnPageLoadHandler { handler: Box<dyn Fn(PageLoadEvent, String)> };
 }
 
+/// The handlers of a webview, found with the id the Java classes pass to the bindings.
+#[derive(Default)]
+pub(crate) struct WebViewHandlers {
+  pub url_loading_override: Option<Box<dyn Fn(String) -> bool>>,
+  pub on_page_load: Option<Box<dyn Fn(PageLoadEvent, String)>>,
+  pub before_unload: Option<Box<dyn Fn(String, String) -> bool>>,
+  pub close_requested: Option<Box<dyn Fn() -> bool>>,
+  pub process_failed: Option<Box<dyn Fn(ProcessFailure)>>,
+  pub text_input: Option<Box<dyn Fn(TextInputEvent)>>,
+  /// Whether the URLs the webview can't load are opened with the system.
+  pub open_external_urls: bool,
+  /// Updated by the `onPageLoading` and `onPageLoaded` callbacks of the `RustWebViewClient`.
+  pub navigation_state: Arc<NavigationState>,
+}
+
+// Called on the main thread of the activity, like the handlers of `define_static_handlers`.
+unsafe impl Send for WebViewHandlers {}
+unsafe impl Sync for WebViewHandlers {}
+
+/// The handlers of the webviews by id, removed when their webview is dropped.
+static WEBVIEW_HANDLERS: Lazy<Mutex<HashMap<String, Arc<WebViewHandlers>>>> =
+  Lazy::new(Default::default);
+
+/// The handlers of the webview with `id`, if it wasn't dropped.
+pub(crate) fn webview_handlers(id: &str) -> Option<Arc<WebViewHandlers>> {
+  WEBVIEW_HANDLERS.lock().unwrap().get(id).cloned()
+}
+
+pub(crate) type JavascriptInterface = Box<dyn Fn(&str, String) -> Option<String> + Send + Sync>;
+
+/// The javascript interfaces of the webviews by id and name, called on the JavaBridge thread.
+pub(crate) static JAVASCRIPT_INTERFACES: Lazy<
+  Mutex<HashMap<String, Arc<HashMap<String, JavascriptInterface>>>>,
+> = Lazy::new(Default::default);
+
 pub static WITH_ASSET_LOADER: OnceCell<bool> = OnceCell::new();
 pub static ASSET_LOADER_DOMAIN: OnceCell<String> = OnceCell::new();
 
@@ -111,7 +150,8 @@ pub unsafe fn android_setup(
   let mut main_pipe = MainPipe {
     env,
     activity,
-    webview: None,
+    webviews: HashMap::new(),
+    last_webview_id: None,
     webchrome_client,
   };
 
@@ -130,6 +170,17 @@ pub unsafe fn android_setup(
 
 pub(crate) struct InnerWebView {
   id: String,
+  is_child: bool,
+  persistent_headers: Option<Arc<PersistentHeaders>>,
+  navigation_state: Arc<NavigationState>,
+}
+
+impl Drop for InnerWebView {
+  fn drop(&mut self) {
+    WEBVIEW_HANDLERS.lock().unwrap().remove(&self.id);
+    JAVASCRIPT_INTERFACES.lock().unwrap().remove(&self.id);
+    MainPipe::send(WebViewMessage::RemoveWebView(self.id.clone()));
+  }
 }
 
 impl InnerWebView {
@@ -138,18 +189,27 @@ impl InnerWebView {
     attributes: WebViewAttributes,
     pl_attrs: super::PlatformSpecificWebViewAttributes,
   ) -> Result<Self> {
-    Self::new(_window, attributes, pl_attrs)
+    Self::new_inner(attributes, pl_attrs, true)
   }
 
   pub fn new(
     _window: &impl HasWindowHandle,
     attributes: WebViewAttributes,
     pl_attrs: super::PlatformSpecificWebViewAttributes,
+  ) -> Result<Self> {
+    Self::new_inner(attributes, pl_attrs, false)
+  }
+
+  fn new_inner(
+    attributes: WebViewAttributes,
+    pl_attrs: super::PlatformSpecificWebViewAttributes,
+    is_child: bool,
   ) -> Result<Self> {
     let WebViewAttributes {
       url,
       html,
-      initialization_scripts,
+      mut initialization_scripts,
+      user_style_sheets,
       ipc_handler,
       #[cfg(any(debug_assertions, feature = "devtools"))]
       devtools,
@@ -158,7 +218,19 @@ impl InnerWebView {
       transparent,
       headers,
       autoplay,
+      javascript_enabled,
+      images_enabled,
+      forced_dark_content,
+      cache_mode,
+      file_access,
       user_agent,
+      incognito,
+      focused,
+      visible,
+      bounds,
+      virtual_keyboard_policy,
+      ui_strings,
+      ui_layout_direction,
       ..
     } = attributes;
 
@@ -167,10 +239,20 @@ impl InnerWebView {
       with_asset_loader,
       asset_loader_domain,
       https_scheme,
+      open_external_urls,
+      safe_browsing,
+      force_dark,
+      javascript_interfaces,
     } = pl_attrs;
 
     let scheme = if https_scheme { "https" } else { "http" };
 
+    initialization_scripts.extend(
+      user_style_sheets
+        .iter()
+        .map(|(css, main_frame_only)| user_style_sheet_script(css, *main_frame_only)),
+    );
+
     let url = if let Some(mut url) = url {
       if let Some(pos) = url.find("://") {
         let name = &url[..pos];
@@ -190,6 +272,55 @@ impl InnerWebView {
       .map(|id| id.to_string())
       .unwrap_or_else(|| COUNTER.next().to_string());
 
+    let persistent_headers = attributes
+      .persistent_headers
+      .then(|| Arc::new(PersistentHeaders::new(url.as_deref(), headers.clone())));
+
+    let url_loading_override = match persistent_headers.clone() {
+      Some(persistent_headers) => {
+        let id = id.clone();
+        let navigation_handler = attributes.navigation_handler;
+        Some(Box::new(move |url: String| {
+          let allow = match &navigation_handler {
+            Some(handler) => handler(url.clone()),
+            None => true,
+          };
+          if let Some(headers) = persistent_headers.get(&url).filter(|_| allow) {
+            // The client doesn't know whether the request has the headers, but navigations
+            // started with `loadUrl` don't reach it so they can always be started again.
+            MainPipe::send(WebViewMessage::LoadUrl(id.clone(), url, Some(headers)));
+            return false;
+          }
+          allow
+        }) as Box<dyn Fn(String) -> bool>)
+      }
+      None => attributes.navigation_handler,
+    };
+
+    // registered before the webview is created, so its first events reach them
+    let navigation_state = Arc::new(NavigationState::new());
+    WEBVIEW_HANDLERS.lock().unwrap().insert(
+      id.clone(),
+      Arc::new(WebViewHandlers {
+        url_loading_override,
+        on_page_load: attributes.on_page_load_handler,
+        before_unload: attributes.before_unload_handler,
+        close_requested: attributes.close_requested_handler,
+        process_failed: attributes.process_failed_handler,
+        text_input: attributes.text_input_handler,
+        open_external_urls,
+        navigation_state: navigation_state.clone(),
+      }),
+    );
+
+    let interface_names = javascript_interfaces.keys().cloned().collect();
+    if !javascript_interfaces.is_empty() {
+      JAVASCRIPT_INTERFACES
+        .lock()
+        .unwrap()
+        .insert(id.clone(), Arc::new(javascript_interfaces));
+    }
+
     MainPipe::send(WebViewMessage::CreateWebView(CreateWebViewAttributes {
       id: id.clone(),
       url,
@@ -201,8 +332,22 @@ impl InnerWebView {
       headers,
       on_webview_created,
       autoplay,
+      javascript_enabled,
+      images_enabled,
+      cache_mode,
+      file_access,
       user_agent,
       initialization_scripts: initialization_scripts.clone(),
+      incognito,
+      focused,
+      visible,
+      bounds: if is_child { bounds } else { None },
+      safe_browsing,
+      force_dark: force_dark.or(forced_dark_content.then_some(crate::Theme::Dark)),
+      javascript_interfaces: interface_names,
+      virtual_keyboard_policy,
+      ui_strings,
+      ui_layout_direction,
     }));
 
     WITH_ASSET_LOADER.get_or_init(move || with_asset_loader);
@@ -230,7 +375,7 @@ impl InnerWebView {
 
             let (tx, rx) = channel();
             let initialization_scripts = initialization_scripts.clone();
-            let responder: Box<dyn FnOnce(HttpResponse<Cow<'static, [u8]>>)> =
+            let responder: ResponderFn =
               Box::new(move |mut response| {
                 if !is_document_start_script_enabled {
                   #[cfg(feature = "tracing")]
@@ -283,11 +428,20 @@ impl InnerWebView {
                   }
                 }
 
-                tx.send(response).unwrap();
+                tx.send(response)
+                  .map_err(|_| crate::Error::CustomProtocolTaskInvalid)
               });
 
-            (custom_protocol.1)(webview_id, request, RequestAsyncResponder { responder });
-            return Some(rx.recv().unwrap());
+            (custom_protocol.1)(
+              webview_id,
+              request,
+              RequestAsyncResponder {
+                responder,
+                cancellation: Default::default(),
+              },
+            );
+            // the responder is dropped without responding when the request is cancelled
+            return rx.recv().ok();
           }
           None
         },
@@ -302,18 +456,59 @@ impl InnerWebView {
       TITLE_CHANGE_HANDLER.get_or_init(move || UnsafeTitleHandler::new(i));
     }
 
-    if let Some(i) = attributes.navigation_handler {
-      URL_LOADING_OVERRIDE.get_or_init(move || UnsafeUrlLoadingOverride::new(i));
-    }
+    Ok(Self {
+      id,
+      is_child,
+      persistent_headers,
+      navigation_state,
+    })
+  }
+
+  pub fn print(&self) -> crate::Result<()> {
+    Ok(())
+  }
 
-    if let Some(h) = attributes.on_page_load_handler {
-      ON_LOAD_HANDLER.get_or_init(move || UnsafeOnPageLoadHandler::new(h));
+  pub fn print_with_options(&self, _options: &crate::PrintOptions) -> crate::Result<()> {
+    Ok(())
+  }
+
+  pub fn print_capabilities(&self) -> crate::PrintCapabilities {
+    crate::PrintCapabilities::default()
+  }
+
+  pub fn api_capabilities(&self) -> crate::ApiCapabilities {
+    crate::ApiCapabilities {
+      gamepad: true,
+      ..Default::default()
     }
+  }
 
-    Ok(Self { id })
+  pub fn download(&self, _url: &str, _destination: &Path) -> crate::Result<()> {
+    Ok(())
   }
 
-  pub fn print(&self) -> crate::Result<()> {
+  pub fn storage_usage(&self) -> crate::Result<Vec<crate::OriginStorageUsage>> {
+    Ok(Vec::new())
+  }
+
+  pub fn renderer_pid(&self) -> crate::Result<Option<u32>> {
+    Ok(None)
+  }
+
+  pub fn engine_info(&self) -> crate::Result<EngineInfo> {
+    platform_engine_info()
+  }
+
+  pub fn history_length(&self) -> crate::Result<Option<usize>> {
+    Ok(None)
+  }
+
+  pub fn memory_usage(&self, handler: impl FnOnce(Option<u64>) + 'static) -> crate::Result<()> {
+    handler(None);
+    Ok(())
+  }
+
+  pub fn remove_origin_data(&self, _origin: &str) -> crate::Result<()> {
     Ok(())
   }
 
@@ -323,12 +518,21 @@ impl InnerWebView {
 
   pub fn url(&self) -> crate::Result<String> {
     let (tx, rx) = bounded(1);
-    MainPipe::send(WebViewMessage::GetUrl(tx));
+    MainPipe::send(WebViewMessage::GetUrl(self.id.clone(), tx));
     rx.recv().map_err(Into::into)
   }
 
+  pub fn platform_handle(&self) -> crate::PlatformWebViewHandle {
+    crate::PlatformWebViewHandle::Android(JniHandle)
+  }
+
+  pub fn navigation_state(&self) -> &NavigationState {
+    &self.navigation_state
+  }
+
   pub fn eval(&self, js: &str, callback: Option<impl Fn(String) + Send + 'static>) -> Result<()> {
     MainPipe::send(WebViewMessage::Eval(
+      self.id.clone(),
       js.into(),
       callback.map(|c| Box::new(c) as Box<dyn Fn(String) + Send + 'static>),
     ));
@@ -351,33 +555,59 @@ impl InnerWebView {
   }
 
   pub fn set_background_color(&self, background_color: RGBA) -> Result<()> {
-    MainPipe::send(WebViewMessage::SetBackgroundColor(background_color));
+    MainPipe::send(WebViewMessage::SetBackgroundColor(
+      self.id.clone(),
+      background_color,
+    ));
     Ok(())
   }
 
   pub fn load_url(&self, url: &str) -> Result<()> {
-    MainPipe::send(WebViewMessage::LoadUrl(url.to_string(), None));
+    if let Some(persistent_headers) = &self.persistent_headers {
+      persistent_headers.set(url, None);
+    }
+    MainPipe::send(WebViewMessage::LoadUrl(
+      self.id.clone(),
+      url.to_string(),
+      None,
+    ));
     Ok(())
   }
 
   pub fn load_url_with_headers(&self, url: &str, headers: http::HeaderMap) -> Result<()> {
-    MainPipe::send(WebViewMessage::LoadUrl(url.to_string(), Some(headers)));
+    if let Some(persistent_headers) = &self.persistent_headers {
+      persistent_headers.set(url, Some(headers.clone()));
+    }
+    MainPipe::send(WebViewMessage::LoadUrl(
+      self.id.clone(),
+      url.to_string(),
+      Some(headers),
+    ));
     Ok(())
   }
 
   pub fn load_html(&self, html: &str) -> Result<()> {
-    MainPipe::send(WebViewMessage::LoadHtml(html.to_string()));
+    MainPipe::send(WebViewMessage::LoadHtml(self.id.clone(), html.to_string()));
     Ok(())
   }
 
   pub fn clear_all_browsing_data(&self) -> Result<()> {
-    MainPipe::send(WebViewMessage::ClearAllBrowsingData);
+    MainPipe::send(WebViewMessage::ClearAllBrowsingData(self.id.clone()));
+    Ok(())
+  }
+
+  pub fn clear_http_cache(&self) -> Result<()> {
+    MainPipe::send(WebViewMessage::ClearHttpCache(self.id.clone()));
     Ok(())
   }
 
   pub fn cookies_for_url(&self, url: &str) -> Result<Vec<cookie::Cookie<'static>>> {
     let (tx, rx) = bounded(1);
-    MainPipe::send(WebViewMessage::GetCookies(tx, url.to_string()));
+    MainPipe::send(WebViewMessage::GetCookies(
+      self.id.clone(),
+      tx,
+      url.to_string(),
+    ));
     rx.recv().map_err(Into::into)
   }
 
@@ -386,28 +616,124 @@ impl InnerWebView {
   }
 
   pub fn bounds(&self) -> Result<crate::Rect> {
-    Ok(crate::Rect::default())
+    let (tx, rx) = bounded(1);
+    MainPipe::send(WebViewMessage::GetBounds(self.id.clone(), tx));
+    rx.recv().map_err(Into::into)
+  }
+
+  pub fn set_bounds(&self, bounds: crate::Rect) -> Result<()> {
+    if self.is_child {
+      MainPipe::send(WebViewMessage::SetBounds(self.id.clone(), bounds));
+    }
+    Ok(())
+  }
+
+  pub fn set_hit_test_ignore_regions(&self, _regions: Vec<crate::Rect>) -> Result<()> {
+    Ok(())
+  }
+
+  pub fn set_visible(&self, visible: bool) -> Result<()> {
+    MainPipe::send(WebViewMessage::SetVisible(self.id.clone(), visible));
+    Ok(())
+  }
+
+  pub fn add_css(&self, css: &str, main_frame_only: bool) -> Result<()> {
+    self.eval(
+      &user_style_sheet_script(css, main_frame_only),
+      None::<Box<dyn Fn(String) + Send + 'static>>,
+    )
   }
 
-  pub fn set_bounds(&self, _bounds: crate::Rect) -> Result<()> {
-    // Unsupported
+  pub fn remove_css(&self, css: &str) -> Result<()> {
+    self.eval(
+      &remove_user_style_sheet_script(css),
+      None::<Box<dyn Fn(String) + Send + 'static>>,
+    )
+  }
+
+  pub fn set_javascript_enabled(&self, enabled: bool) -> Result<()> {
+    MainPipe::send(WebViewMessage::SetJavaScriptEnabled(
+      self.id.clone(),
+      enabled,
+    ));
     Ok(())
   }
 
-  pub fn set_visible(&self, _visible: bool) -> Result<()> {
-    // Unsupported
+  pub fn set_images_enabled(&self, enabled: bool) -> Result<()> {
+    MainPipe::send(WebViewMessage::SetImagesEnabled(self.id.clone(), enabled));
     Ok(())
   }
 
   pub fn focus(&self) -> Result<()> {
-    // Unsupported
+    MainPipe::send(WebViewMessage::Focus(self.id.clone()));
     Ok(())
   }
 
   pub fn focus_parent(&self) -> Result<()> {
-    // Unsupported
+    MainPipe::send(WebViewMessage::FocusParent(self.id.clone()));
     Ok(())
   }
+
+  pub fn dispatcher(&self) -> MainThreadDispatcher {
+    MainThreadDispatcher::current()
+  }
+}
+
+type DispatchedFn = Box<dyn FnOnce() + Send>;
+
+thread_local! {
+  /// The dispatcher of the webviews created on the current thread.
+  static DISPATCHER: MainThreadDispatcher = MainThreadDispatcher::new();
+}
+
+/// A [`Send`] handle to run closures on the thread of a webview.
+///
+/// The webviews live on the thread that created them, not on the thread of [`android_setup`],
+/// so the closures are run by the looper of the creating thread, which is polled by the event loop.
+#[derive(Clone)]
+pub(crate) struct MainThreadDispatcher {
+  sender: Sender<DispatchedFn>,
+  /// The pipe waking the looper up, its read end is kept open by the looper callback.
+  pipe: Arc<[OwnedFd; 2]>,
+}
+
+impl MainThreadDispatcher {
+  /// The dispatcher of the webviews created on the current thread.
+  pub fn current() -> Self {
+    DISPATCHER.with(Clone::clone)
+  }
+
+  fn new() -> Self {
+    let (sender, receiver) = unbounded::<DispatchedFn>();
+    let pipe = Arc::new(main_pipe::pipe());
+    let read_end = pipe.clone();
+    ThreadLooper::prepare()
+      .add_fd_with_callback(pipe[0].as_fd(), FdEvent::INPUT, move |fd, _event| {
+        let _ = &read_end;
+        let size = std::mem::size_of::<bool>();
+        let mut wake = false;
+        unsafe { libc::read(fd.as_raw_fd(), &mut wake as *mut _ as *mut _, size) };
+        for f in receiver.try_iter() {
+          f();
+        }
+        true
+      })
+      .unwrap();
+    Self { sender, pipe }
+  }
+
+  pub fn dispatch(&self, f: DispatchedFn) {
+    let size = std::mem::size_of::<bool>();
+    if let Ok(()) = self.sender.send(f) {
+      unsafe {
+        libc::write(
+          self.pipe[1].as_raw_fd(),
+          &true as *const _ as *const _,
+          size,
+        )
+      };
+    }
+  }
 }
 
 #[derive(Clone, Copy)]
@@ -422,6 +748,17 @@ impl JniHandle {
   {
     MainPipe::send(WebViewMessage::Jni(Box::new(func)));
   }
+
+  /// Execute jni code on the thread of the webview and wait for its result.
+  ///
+  /// See [`dispatch_with_result`] for more information.
+  pub fn exec_with_result<F, R>(&self, func: F) -> Result<R>
+  where
+    F: FnOnce(&mut JNIEnv, &JObject, &JObject) -> JniResult<R> + Send + 'static,
+    R: Send + 'static,
+  {
+    dispatch_with_result(func)
+  }
 }
 
 pub fn platform_webview_version() -> Result<String> {
@@ -430,6 +767,16 @@ pub fn platform_webview_version() -> Result<String> {
   rx.recv().unwrap()
 }
 
+pub fn platform_engine_info() -> Result<EngineInfo> {
+  Ok(EngineInfo {
+    engine: Engine::AndroidWebView,
+    version: platform_webview_version()?,
+    runtime_channel: None,
+    runtime_path: None,
+    user_data_dir: None,
+  })
+}
+
 fn with_html_head<F: FnOnce(&NodeRef)>(document: &mut NodeRef, f: F) {
   if let Ok(ref node) = document.select_first("head") {
     f(node.as_node())
@@ -470,10 +817,92 @@ pub fn find_class<'a>(
 
 /// Dispatch a closure to run on the Android context.
 ///
-/// The closure takes the JNI env, the Android activity instance and the possibly null webview.
+/// The closure takes the JNI env, the Android activity instance and the last created webview, or
+/// null if there is none.
 pub fn dispatch<F>(func: F)
 where
   F: FnOnce(&mut JNIEnv, &JObject, &JObject) + Send + 'static,
 {
   MainPipe::send(WebViewMessage::Jni(Box::new(func)));
 }
+
+/// Dispatch a closure to run on the Android context and wait for its result.
+///
+/// The closure takes the JNI env, the Android activity instance and the last created webview, or
+/// null if there is none.
+///
+/// If the closure fails because a Java exception was thrown, the exception is cleared and
+/// returned as [`Error::JavaException`](crate::Error::JavaException).
+/// Other JNI failures are returned as [`Error::JniError`](crate::Error::JniError).
+///
+/// **Note:** This blocks the current thread until the closure runs,
+/// so it must not be called from the Android main thread.
+pub fn dispatch_with_result<F, R>(func: F) -> Result<R>
+where
+  F: FnOnce(&mut JNIEnv, &JObject, &JObject) -> JniResult<R> + Send + 'static,
+  R: Send + 'static,
+{
+  let (tx, rx) = bounded(1);
+  MainPipe::send(WebViewMessage::Jni(Box::new(
+    move |env, activity, webview| {
+      let result = func(env, activity, webview).map_err(|e| take_java_exception(env, e));
+      let _ = tx.send(result);
+    },
+  )));
+  rx.recv()?
+}
+
+/// Converts a pending Java exception into [`crate::Error::JavaException`] and clears it.
+fn take_java_exception(env: &mut JNIEnv, error: jni::errors::Error) -> crate::Error {
+  if !matches!(error, jni::errors::Error::JavaException) {
+    return error.into();
+  }
+
+  let exception = match env.exception_occurred() {
+    Ok(exception) if !exception.is_null() => exception,
+    _ => return error.into(),
+  };
+  let _ = env.exception_clear();
+
+  let message = env
+    .call_method(&exception, "toString", "()Ljava/lang/String;", &[])
+    .and_then(|v| v.l())
+    .and_then(|s| {
+      let s = jni::objects::JString::from(s);
+      env.get_string(&s).map(|v| v.to_string_lossy().to_string())
+    })
+    .unwrap_or_default();
+
+  crate::Error::JavaException(message)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+  };
+
+  #[test]
+  fn dispatched_closures_run_on_the_creating_thread() {
+    let dispatcher = MainThreadDispatcher::current();
+    let looper = ThreadLooper::for_thread().unwrap();
+    let ran = Arc::new(AtomicBool::new(false));
+
+    let ran_ = ran.clone();
+    let thread = std::thread::current().id();
+    std::thread::spawn(move || {
+      dispatcher.dispatch(Box::new(move || {
+        assert_eq!(std::thread::current().id(), thread);
+        ran_.store(true, Ordering::SeqCst);
+      }))
+    })
+    .join()
+    .unwrap();
+    assert!(!ran.load(Ordering::SeqCst));
+
+    looper.poll_once_timeout(Duration::from_secs(1)).unwrap();
+    assert!(ran.load(Ordering::SeqCst));
+  }
+}
@@ -0,0 +1,185 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Downloads of `blob:` URLs created in JavaScript.
+//!
+//! WebKit doesn't reliably route these downloads to its download APIs, so an initialization script
+//! intercepts clicks on `<a download>` elements pointing to a `blob:` URL, reads the blob and sends
+//! its content to a script message handler. The content is then saved through the download
+//! handlers of the webview, using the `blob:` URL as the download URL.
+
+use std::path::{Component, Path, PathBuf};
+
+use base64::{engine::general_purpose, Engine};
+
+/// Name of the script message handler receiving the content of the blobs.
+pub(crate) const BLOB_DOWNLOAD_MESSAGE_HANDLER_NAME: &str = "wryBlobDownload";
+
+/// Script intercepting the downloads of `blob:` URLs.
+///
+/// Sends `{url}\n{filename}\n{base64 content}` to [`BLOB_DOWNLOAD_MESSAGE_HANDLER_NAME`].
+pub(crate) const BLOB_DOWNLOAD_SCRIPT: &str = r#"
+(function () {
+  function intercept(anchor) {
+    if (!anchor.hasAttribute('download') || !anchor.href.startsWith('blob:')) {
+      return false;
+    }
+    const url = anchor.href;
+    const filename = anchor.getAttribute('download').replace(/[\r\n]/g, '');
+    fetch(url)
+      .then(function (response) { return response.blob(); })
+      .then(function (blob) {
+        const reader = new FileReader();
+        reader.onload = function () {
+          const data = reader.result.substring(reader.result.indexOf(',') + 1);
+          window.webkit.messageHandlers.wryBlobDownload.postMessage(url + '\n' + filename + '\n' + data);
+        };
+        reader.readAsDataURL(blob);
+      });
+    return true;
+  }
+
+  document.addEventListener('click', function (event) {
+    const anchor = event.target instanceof Element ? event.target.closest('a') : null;
+    if (anchor && intercept(anchor)) {
+      event.preventDefault();
+    }
+  }, true);
+
+  // anchors that are not in the document don't dispatch the click event to it
+  const click = HTMLAnchorElement.prototype.click;
+  HTMLAnchorElement.prototype.click = function () {
+    if (!this.isConnected && intercept(this)) {
+      return;
+    }
+    return click.call(this);
+  };
+})();
+"#;
+
+/// The default destination of a blob download: the file name of `filename` in `download_dir`.
+///
+/// The name comes from the page, so only its last component is kept and names that aren't a plain
+/// file name are rejected. A ` (n)` suffix is added to the name if the file already exists.
+fn blob_download_path(filename: &str, download_dir: &Path) -> Option<PathBuf> {
+  let filename = if filename.is_empty() {
+    "download"
+  } else {
+    filename
+  };
+
+  let path = Path::new(filename);
+  if path.is_absolute() || path.has_root() {
+    return None;
+  }
+  let name = match path.components().next_back()? {
+    Component::Normal(name) => Path::new(name),
+    _ => return None,
+  };
+
+  let mut destination = download_dir.join(name);
+  let stem = name.file_stem()?.to_string_lossy().into_owned();
+  let extension = name.extension().map(|e| e.to_string_lossy().into_owned());
+  let mut index = 1;
+  while destination.exists() {
+    destination = download_dir.join(match &extension {
+      Some(extension) => format!("{stem} ({index}).{extension}"),
+      None => format!("{stem} ({index})"),
+    });
+    index += 1;
+  }
+
+  Some(destination)
+}
+
+/// Save a blob sent by [`BLOB_DOWNLOAD_SCRIPT`] and run the download handlers.
+///
+/// The blob is saved in `download_dir` unless the download started handler changes the path.
+pub(crate) fn save_blob_download(
+  message: &str,
+  download_dir: Option<&Path>,
+  download_started_handler: &mut dyn FnMut(String, &mut PathBuf) -> bool,
+  download_completed_handler: Option<&dyn Fn(String, Option<PathBuf>, bool)>,
+) {
+  let mut parts = message.splitn(3, '\n');
+  let (Some(url), Some(filename), Some(data)) = (parts.next(), parts.next(), parts.next()) else {
+    #[cfg(feature = "tracing")]
+    tracing::warn!("WebView received an invalid blob download message.");
+    return;
+  };
+
+  let Some(mut path) = download_dir.and_then(|dir| blob_download_path(filename, dir)) else {
+    #[cfg(feature = "tracing")]
+    tracing::warn!("WebView received a blob download with an invalid file name: {filename}");
+    if let Some(download_completed_handler) = download_completed_handler {
+      download_completed_handler(url.to_string(), None, false);
+    }
+    return;
+  };
+
+  if !download_started_handler(url.to_string(), &mut path) {
+    return;
+  }
+
+  let result = general_purpose::STANDARD
+    .decode(data)
+    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    .and_then(|data| std::fs::write(&path, data));
+
+  #[cfg(feature = "tracing")]
+  if let Err(error) = &result {
+    tracing::warn!("Failed to save blob download: {error}");
+  }
+
+  if let Some(download_completed_handler) = download_completed_handler {
+    let success = result.is_ok();
+    download_completed_handler(url.to_string(), success.then_some(path), success);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn blob_downloads_stay_in_the_download_dir() {
+    let dir = std::env::temp_dir().join(format!("wry-blob-download-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    assert_eq!(
+      blob_download_path("../../.bashrc", &dir),
+      Some(dir.join(".bashrc"))
+    );
+    assert_eq!(
+      blob_download_path("a/b/report.pdf", &dir),
+      Some(dir.join("report.pdf"))
+    );
+    assert_eq!(blob_download_path("", &dir), Some(dir.join("download")));
+    assert_eq!(blob_download_path("/etc/passwd", &dir), None);
+    assert_eq!(blob_download_path("..", &dir), None);
+    assert_eq!(blob_download_path(".", &dir), None);
+    assert_eq!(blob_download_path("a/..", &dir), None);
+
+    std::fs::write(dir.join("report.pdf"), b"").unwrap();
+    assert_eq!(
+      blob_download_path("report.pdf", &dir),
+      Some(dir.join("report (1).pdf"))
+    );
+
+    let mut destination = None;
+    save_blob_download(
+      "blob:wry://localhost/1\n../../escaped.txt\nd3J5",
+      Some(&dir),
+      &mut |_, path| {
+        destination = Some(path.clone());
+        true
+      },
+      None,
+    );
+    assert_eq!(destination, Some(dir.join("escaped.txt")));
+    assert_eq!(std::fs::read(dir.join("escaped.txt")).unwrap(), b"wry");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+}
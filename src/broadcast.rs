@@ -0,0 +1,130 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The messages broadcast between the webviews of a [`WebContext`], see
+//! [`WebContext::broadcast`].
+
+use std::{
+  cell::RefCell,
+  rc::{Rc, Weak},
+};
+
+use crate::{escape_js_string, Result, RuntimeHandlers, UrlPattern, WebView};
+#[cfg(doc)]
+use crate::{WebContext, WebViewAttributes};
+
+/// A webview of a [`WebContext`] receiving the messages of [`WebContext::broadcast`].
+#[derive(Clone)]
+pub(crate) struct BroadcastReceiver {
+  pub(crate) handlers: Weak<RuntimeHandlers>,
+  /// See [`WebViewAttributes::broadcast_origins`].
+  pub(crate) origins: Rc<[UrlPattern]>,
+}
+
+/// Send `message` to the listeners of [`BROADCAST_SCRIPT`] in the webviews of `webviews` created on
+/// the current thread, except `sender`, whose page has one of their allowed origins.
+pub(crate) fn broadcast(
+  webviews: &RefCell<Vec<BroadcastReceiver>>,
+  message: &str,
+  sender: Option<&Weak<RuntimeHandlers>>,
+) -> Result<()> {
+  webviews
+    .borrow_mut()
+    .retain(|receiver| receiver.handlers.strong_count() > 0);
+  // Cloned so the listeners can create webviews or broadcast again
+  let webviews = webviews.borrow().clone();
+
+  let js = format!(
+    "window.wry && window.wry.__receiveBroadcast('{}')",
+    escape_js_string(message)
+  );
+  let mut result = Ok(());
+  for receiver in webviews {
+    if sender.is_some_and(|sender| sender.ptr_eq(&receiver.handlers)) {
+      continue;
+    }
+    let Some(webview) = receiver
+      .handlers
+      .upgrade()
+      .and_then(|handlers| WebView::inner_from_handlers(&handlers))
+    else {
+      continue;
+    };
+    let allowed = match webview.url() {
+      Ok(url) => receiver.origins.iter().any(|pattern| pattern.matches(&url)),
+      Err(_) => false,
+    };
+    if !allowed {
+      continue;
+    }
+    if let Err(e) = webview.eval(&js, None::<Box<dyn Fn(String) + Send + 'static>>) {
+      result = Err(e);
+    }
+  }
+  result
+}
+
+/// The `window.wry.broadcast` and `window.wry.onBroadcast` API of [`WebContext::broadcast`].
+pub(crate) const BROADCAST_SCRIPT: &str = r#"
+(function () {
+  if (window !== window.top) return;
+  const listeners = [];
+  window.wry = Object.assign(window.wry || {}, {
+    broadcast: (message) => send(String(message)),
+    onBroadcast: (listener) => {
+      listeners.push(listener);
+    },
+    __receiveBroadcast: (message) => listeners.forEach((listener) => listener(message)),
+  });
+})();
+"#;
+
+/// A `BroadcastChannel` sending its messages to the same origin in the other webviews of the
+/// context through [`BROADCAST_SCRIPT`], see [`WebViewAttributes::broadcast_channel_polyfill`].
+pub(crate) const BROADCAST_CHANNEL_POLYFILL_SCRIPT: &str = r#"
+(function () {
+  if (window !== window.top || !window.wry) return;
+  const prefix = '__WRY_BROADCAST_CHANNEL__:';
+  const channels = new Set();
+  const deliver = (name, origin, data, source) => {
+    if (origin !== window.location.origin) return;
+    for (const channel of channels) {
+      if (channel !== source && channel.name === name) {
+        setTimeout(() => channel.dispatchEvent(new MessageEvent('message', { data, origin })), 0);
+      }
+    }
+  };
+
+  class BroadcastChannel extends EventTarget {
+    constructor(name) {
+      super();
+      this.name = String(name);
+      this.onmessage = null;
+      this.addEventListener('message', (event) => this.onmessage && this.onmessage(event));
+      channels.add(this);
+    }
+
+    postMessage(data) {
+      if (!channels.has(this)) {
+        throw new DOMException('BroadcastChannel is closed', 'InvalidStateError');
+      }
+      const origin = window.location.origin;
+      deliver(this.name, origin, data, this);
+      window.wry.broadcast(prefix + JSON.stringify({ name: this.name, origin, data }));
+    }
+
+    close() {
+      channels.delete(this);
+    }
+  }
+
+  const receive = window.wry.__receiveBroadcast;
+  window.wry.__receiveBroadcast = (message) => {
+    if (!message.startsWith(prefix)) return receive(message);
+    const { name, origin, data } = JSON.parse(message.slice(prefix.length));
+    deliver(name, origin, data, null);
+  };
+  window.BroadcastChannel = BroadcastChannel;
+})();
+"#;
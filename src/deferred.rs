@@ -0,0 +1,149 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use raw_window_handle::HasWindowHandle;
+
+#[cfg(any(
+  target_os = "linux",
+  target_os = "dragonfly",
+  target_os = "freebsd",
+  target_os = "netbsd",
+  target_os = "openbsd",
+))]
+use crate::WebViewBuilderExtUnix;
+use crate::{Result, WebView, WebViewBuilder, RGBA};
+
+/// A webview that is only created the first time it is needed.
+///
+/// The [`WebViewBuilder`] is kept until [`DeferredWebView::ensure_created`] is called, so apps
+/// with many tabs don't pay the creation cost of the webviews the user never opens.
+///
+/// wry doesn't create a placeholder surface: nothing is drawn in the webview area until then, so
+/// the app paints it itself, e.g. with [`DeferredWebView::background_color`], which is also the
+/// background color of the created webview so the switch is seamless.
+///
+/// ```no_run
+/// # use wry::{DeferredWebView, WebViewBuilder};
+/// # use winit::{window::WindowBuilder, event_loop::EventLoop};
+/// let event_loop = EventLoop::new().unwrap();
+/// let window = WindowBuilder::new().build(&event_loop).unwrap();
+///
+/// let mut tab = DeferredWebView::new_as_child(WebViewBuilder::new().with_url("https://tauri.app"))
+///   .with_background_color((255, 255, 255, 255));
+///
+/// // later, when the tab is selected
+/// let webview = tab.ensure_created(&window).unwrap();
+/// webview.focus().unwrap();
+/// ```
+pub struct DeferredWebView<'a> {
+  builder: Option<WebViewBuilder<'a>>,
+  webview: Option<WebView>,
+  as_child: bool,
+  background_color: Option<RGBA>,
+}
+
+impl<'a> DeferredWebView<'a> {
+  /// Defer the creation of the webview built with [`WebViewBuilder::build`].
+  pub fn new(builder: WebViewBuilder<'a>) -> Self {
+    Self {
+      builder: Some(builder),
+      webview: None,
+      as_child: false,
+      background_color: None,
+    }
+  }
+
+  /// Defer the creation of the webview built with [`WebViewBuilder::build_as_child`].
+  pub fn new_as_child(builder: WebViewBuilder<'a>) -> Self {
+    Self {
+      as_child: true,
+      ..Self::new(builder)
+    }
+  }
+
+  /// Set the background color of the webview, see [`WebViewBuilder::with_background_color`].
+  ///
+  /// The color uses the RGBA format. It replaces the background color set on the builder, if any.
+  pub fn with_background_color(mut self, color: RGBA) -> Self {
+    self.background_color = Some(color);
+    self
+  }
+
+  /// The background color of the webview, to paint the webview area with until it is created.
+  ///
+  /// This is the color set with [`DeferredWebView::with_background_color`], or else the one set
+  /// on the builder, if any.
+  pub fn background_color(&self) -> Option<RGBA> {
+    self.background_color.or_else(|| {
+      let parts = self.builder.as_ref()?.inner.as_ref().ok()?;
+      parts.attrs.background_color
+    })
+  }
+
+  /// Whether the webview has been created.
+  pub fn is_created(&self) -> bool {
+    self.webview.is_some()
+  }
+
+  /// The webview, if it has been created.
+  pub fn webview(&self) -> Option<&WebView> {
+    self.webview.as_ref()
+  }
+
+  /// Create the webview in `window` if it isn't created yet, and return it.
+  ///
+  /// If the creation fails, the error is returned and the builder is dropped, so the following
+  /// calls return [`crate::Error::DeferredWebViewFailed`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux**: Only X11 is supported, like [`WebViewBuilder::build`]. Use
+  ///   [`DeferredWebView::ensure_created_gtk`] on Wayland.
+  pub fn ensure_created<W: HasWindowHandle>(&mut self, window: &W) -> Result<&WebView> {
+    let as_child = self.as_child;
+    self.create_with(|builder| match as_child {
+      true => builder.build_as_child(window),
+      false => builder.build(window),
+    })
+  }
+
+  /// Create the webview in the GTK container `widget` if it isn't created yet, and return it,
+  /// see [`WebViewBuilderExtUnix::build_gtk`] and [`DeferredWebView::ensure_created`].
+  #[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+  ))]
+  pub fn ensure_created_gtk<W>(&mut self, widget: &W) -> Result<&WebView>
+  where
+    W: gtk::prelude::IsA<gtk::Container>,
+  {
+    self.create_with(|builder| builder.build_gtk(widget))
+  }
+
+  fn create_with(
+    &mut self,
+    build: impl FnOnce(WebViewBuilder<'a>) -> Result<WebView>,
+  ) -> Result<&WebView> {
+    if self.webview.is_none() {
+      let mut builder = self
+        .builder
+        .take()
+        .ok_or(crate::Error::DeferredWebViewFailed)?;
+      if let Some(color) = self.background_color {
+        builder = builder.with_background_color(color);
+      }
+      self.webview = Some(build(builder)?);
+    }
+
+    Ok(self.webview.as_ref().unwrap())
+  }
+
+  /// Return the webview, dropping the builder if it wasn't created.
+  pub fn into_webview(self) -> Option<WebView> {
+    self.webview
+  }
+}
@@ -0,0 +1,193 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! A custom protocol forwarding its requests to a local development server, see
+//! [`WebViewBuilder::with_dev_url_proxy`](crate::WebViewBuilder::with_dev_url_proxy).
+
+use std::{
+  io::{self, Read, Write},
+  net::TcpStream,
+};
+
+use http::{header, Request, Response, StatusCode, Uri};
+
+use crate::{loopback, Error, RequestAsyncResponder, Result, WebViewId};
+
+/// The headers handled by the proxy instead of being forwarded.
+const HOP_BY_HOP_HEADERS: &[header::HeaderName] = &[
+  header::HOST,
+  header::CONNECTION,
+  header::CONTENT_LENGTH,
+  header::TRANSFER_ENCODING,
+  // the webviews don't decode the compressed responses of the custom protocols
+  header::ACCEPT_ENCODING,
+];
+
+/// The address of a development server, parsed from its URL.
+#[derive(Clone)]
+pub(crate) struct DevServerUrl {
+  authority: String,
+  host: String,
+  port: u16,
+  base_path: String,
+}
+
+impl DevServerUrl {
+  pub(crate) fn parse(url: &str) -> Result<Self> {
+    let invalid = || Error::InvalidDevUrl(url.to_string());
+    let uri: Uri = url.parse().map_err(|_| invalid())?;
+    if uri.scheme_str() != Some("http") {
+      return Err(invalid());
+    }
+    let authority = uri.authority().ok_or_else(invalid)?;
+
+    Ok(Self {
+      authority: authority.to_string(),
+      host: authority.host().to_string(),
+      port: authority.port_u16().unwrap_or(80),
+      base_path: uri.path().trim_end_matches('/').to_string(),
+    })
+  }
+
+  /// The custom protocol handler forwarding the requests to the server.
+  pub(crate) fn handler(self) -> impl Fn(WebViewId, Request<Vec<u8>>, RequestAsyncResponder) {
+    move |_, request, responder| {
+      let server = self.clone();
+      std::thread::spawn(move || {
+        let response = server.forward(request).unwrap_or_else(|e| {
+          Response::builder()
+            .status(StatusCode::BAD_GATEWAY)
+            .body(e.to_string().into_bytes())
+            .unwrap()
+        });
+        let _ = responder.respond(response);
+      });
+    }
+  }
+
+  /// Redirect the WebSockets of the pages of the custom protocol, e.g. for hot module replacement,
+  /// to the server.
+  pub(crate) fn websocket_script(&self, scheme: &str) -> String {
+    loopback::websocket_script(scheme, &self.authority, None)
+  }
+
+  fn forward(&self, request: Request<Vec<u8>>) -> io::Result<Response<Vec<u8>>> {
+    let stream = TcpStream::connect((self.host.as_str(), self.port))?;
+    exchange(stream, &request, &self.base_path, &self.authority)
+  }
+}
+
+/// Send `request` to the server at the other end of `stream` as an HTTP/1.1 request of
+/// `base_path` with `Connection: close`, and read its response until the server closes the
+/// connection.
+pub(crate) fn exchange<S: Read + Write>(
+  mut stream: S,
+  request: &Request<Vec<u8>>,
+  base_path: &str,
+  host: &str,
+) -> io::Result<Response<Vec<u8>>> {
+  let path = request
+    .uri()
+    .path_and_query()
+    .map_or("/", |path| path.as_str());
+
+  let mut head = format!(
+    "{} {}{} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+    request.method(),
+    base_path,
+    path,
+    host,
+    request.body().len()
+  );
+  for (name, value) in request.headers() {
+    if HOP_BY_HOP_HEADERS.contains(name) {
+      continue;
+    }
+    if let Ok(value) = value.to_str() {
+      head.push_str(&format!("{name}: {value}\r\n"));
+    }
+  }
+  head.push_str("\r\n");
+
+  stream.write_all(head.as_bytes())?;
+  stream.write_all(request.body())?;
+  let mut data = Vec::new();
+  stream.read_to_end(&mut data)?;
+
+  parse_response(&data)
+    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid HTTP response"))
+}
+
+fn parse_response(data: &[u8]) -> Option<Response<Vec<u8>>> {
+  let head_end = find(data, b"\r\n\r\n")?;
+  let head = std::str::from_utf8(&data[..head_end]).ok()?;
+  let body = &data[head_end + 4..];
+
+  let mut lines = head.split("\r\n");
+  let status: u16 = lines.next()?.split(' ').nth(1)?.parse().ok()?;
+
+  let mut response = Response::builder().status(status);
+  let mut chunked = false;
+  for line in lines {
+    let (name, value) = line.split_once(':')?;
+    let value = value.trim();
+    if name.eq_ignore_ascii_case("transfer-encoding") {
+      chunked = value.eq_ignore_ascii_case("chunked");
+      continue;
+    }
+    if name.eq_ignore_ascii_case("connection") || name.eq_ignore_ascii_case("content-length") {
+      continue;
+    }
+    response = response.header(name, value);
+  }
+
+  let body = if chunked {
+    dechunk(body)
+  } else {
+    body.to_vec()
+  };
+  response.body(body).ok()
+}
+
+/// Decode a body with the `chunked` transfer encoding.
+fn dechunk(mut data: &[u8]) -> Vec<u8> {
+  let mut body = Vec::new();
+  while let Some(line_end) = find(data, b"\r\n") {
+    let size = std::str::from_utf8(&data[..line_end])
+      .ok()
+      .and_then(|line| line.split(';').next())
+      .and_then(|size| usize::from_str_radix(size.trim(), 16).ok())
+      .unwrap_or_default();
+    data = &data[line_end + 2..];
+    if size == 0 || data.len() < size {
+      break;
+    }
+    body.extend_from_slice(&data[..size]);
+    data = data.get(size + 2..).unwrap_or_default();
+  }
+  body
+}
+
+fn find(data: &[u8], needle: &[u8]) -> Option<usize> {
+  data
+    .windows(needle.len())
+    .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn dev_server_websockets_are_redirected() {
+    let url = DevServerUrl::parse("http://localhost:5173/app/").unwrap();
+    let script = url.websocket_script("wry");
+    assert!(script.contains("const scheme = 'wry';"));
+    assert!(
+      script.contains("const target = new URL(url.pathname + url.search, 'ws://localhost:5173');")
+    );
+    assert!(script.contains("args[0] = target.href;"));
+    assert!(!script.contains("url.protocol = 'ws:'"));
+  }
+}
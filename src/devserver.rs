@@ -0,0 +1,217 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! A live-reload loop for the apps serving their frontend from a directory.
+
+use std::{
+  collections::HashMap,
+  fs, io,
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+  },
+  thread,
+  time::{Duration, SystemTime},
+};
+
+use crate::{escape_js_string, WebView, WebViewProxy};
+
+#[derive(Default)]
+struct DevServerState {
+  webviews: Vec<WebViewProxy>,
+  scripts: Vec<PathBuf>,
+}
+
+/// Watches a directory and reloads the webviews added to it when its files change.
+///
+/// When the changed files are:
+/// - stylesheets, the `<link rel="stylesheet">` loading them are reloaded without reloading the
+///   page,
+/// - scripts registered with [`DevServer::script`], they are evaluated again in the pages,
+/// - anything else, the pages are reloaded.
+///
+/// The directory is polled from a background thread, which stops when the [`DevServer`] is
+/// dropped.
+///
+/// ```no_run
+/// # use wry::{DevServer, WebViewBuilder};
+/// # use winit::{window::WindowBuilder, event_loop::EventLoop};
+/// let event_loop = EventLoop::new().unwrap();
+/// let window = WindowBuilder::new().build(&event_loop).unwrap();
+///
+/// let devserver = DevServer::watch("./dist");
+/// let webview = WebViewBuilder::new()
+///   .with_initialization_script(&devserver.script("./dist/preload.js").unwrap())
+///   .with_url("http://localhost:8080")
+///   .build(&window)
+///   .unwrap();
+/// devserver.add_webview(&webview);
+/// ```
+pub struct DevServer {
+  dir: PathBuf,
+  state: Arc<Mutex<DevServerState>>,
+  stopped: Arc<AtomicBool>,
+}
+
+impl DevServer {
+  /// Watch the files of `dir`, polling them every 500 milliseconds.
+  pub fn watch(dir: impl Into<PathBuf>) -> Self {
+    Self::watch_with_interval(dir, Duration::from_millis(500))
+  }
+
+  /// Watch the files of `dir`, polling them every `interval`.
+  pub fn watch_with_interval(dir: impl Into<PathBuf>, interval: Duration) -> Self {
+    let dir = dir.into();
+    let state = Arc::new(Mutex::new(DevServerState::default()));
+    let stopped = Arc::new(AtomicBool::new(false));
+
+    let dir_ = normalize(&dir);
+    let state_ = state.clone();
+    let stopped_ = stopped.clone();
+    thread::spawn(move || {
+      let mut files = scan(&dir_);
+      while !stopped_.load(Ordering::Relaxed) {
+        thread::sleep(interval);
+
+        let new_files = scan(&dir_);
+        let changed: Vec<PathBuf> = new_files
+          .iter()
+          .filter(|(path, modified)| files.get(*path) != Some(modified))
+          .map(|(path, _)| path.clone())
+          .chain(
+            files
+              .keys()
+              .filter(|path| !new_files.contains_key(*path))
+              .cloned(),
+          )
+          .collect();
+        files = new_files;
+
+        if !changed.is_empty() {
+          state_.lock().unwrap().reload(&dir_, &changed);
+        }
+      }
+    });
+
+    Self {
+      dir,
+      state,
+      stopped,
+    }
+  }
+
+  /// The watched directory.
+  pub fn dir(&self) -> &Path {
+    &self.dir
+  }
+
+  /// Reload `webview` when the files of the directory change.
+  pub fn add_webview(&self, webview: &WebView) {
+    self.state.lock().unwrap().webviews.push(webview.proxy());
+  }
+
+  /// Read the script at `path`, to pass to [`WebViewBuilder::with_initialization_script`], and
+  /// evaluate it again in the pages of the webviews when it changes instead of reloading them.
+  ///
+  /// The initialization scripts can't be replaced once the webview is created, so the pages loaded
+  /// afterwards run the script the webview was created with.
+  ///
+  /// [`WebViewBuilder::with_initialization_script`]: crate::WebViewBuilder::with_initialization_script
+  pub fn script(&self, path: impl AsRef<Path>) -> io::Result<String> {
+    let path = path.as_ref();
+    let script = fs::read_to_string(path)?;
+    self.state.lock().unwrap().scripts.push(normalize(path));
+    Ok(script)
+  }
+}
+
+impl Drop for DevServer {
+  fn drop(&mut self) {
+    self.stopped.store(true, Ordering::Relaxed);
+  }
+}
+
+impl DevServerState {
+  fn reload(&self, dir: &Path, changed: &[PathBuf]) {
+    let mut stylesheets = Vec::new();
+    let mut scripts = Vec::new();
+    let mut reload_page = false;
+    for path in changed {
+      if self.scripts.contains(path) {
+        scripts.extend(fs::read_to_string(path));
+      } else if path.extension().is_some_and(|extension| extension == "css") {
+        let relative = path.strip_prefix(dir).unwrap_or(path);
+        stylesheets.push(relative.to_string_lossy().replace('\\', "/"));
+      } else {
+        reload_page = true;
+      }
+    }
+
+    for webview in &self.webviews {
+      if reload_page {
+        webview.evaluate_script("window.location.reload()");
+        continue;
+      }
+      if !stylesheets.is_empty() {
+        webview.evaluate_script(&stylesheets_reload_script(&stylesheets));
+      }
+      for script in &scripts {
+        webview.evaluate_script(script);
+      }
+    }
+  }
+}
+
+/// The files of `dir` and its subdirectories, except the hidden ones, with their last modification.
+fn scan(dir: &Path) -> HashMap<PathBuf, SystemTime> {
+  let mut files = HashMap::new();
+  let mut dirs = vec![dir.to_path_buf()];
+  while let Some(dir) = dirs.pop() {
+    let Ok(entries) = fs::read_dir(&dir) else {
+      continue;
+    };
+    for entry in entries.flatten() {
+      if entry.file_name().to_string_lossy().starts_with('.') {
+        continue;
+      }
+      let Ok(metadata) = entry.metadata() else {
+        continue;
+      };
+      if metadata.is_dir() {
+        dirs.push(entry.path());
+      } else if let Ok(modified) = metadata.modified() {
+        files.insert(entry.path(), modified);
+      }
+    }
+  }
+  files
+}
+
+fn normalize(path: &Path) -> PathBuf {
+  fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Reload the stylesheets of the page whose path ends with one of `stylesheets`.
+fn stylesheets_reload_script(stylesheets: &[String]) -> String {
+  let stylesheets = stylesheets
+    .iter()
+    .map(|stylesheet| format!("'/{}'", escape_js_string(stylesheet)))
+    .collect::<Vec<_>>()
+    .join(", ");
+
+  format!(
+    r#"
+(function (stylesheets) {{
+  for (const link of document.querySelectorAll('link[rel="stylesheet"]')) {{
+    const url = new URL(link.href, window.location.href);
+    if (stylesheets.some((stylesheet) => url.pathname.endsWith(stylesheet))) {{
+      url.searchParams.set('wry-reload', Date.now());
+      link.href = url.href;
+    }}
+  }}
+}})([{stylesheets}]);
+"#
+  )
+}
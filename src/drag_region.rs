@@ -0,0 +1,63 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The drag regions of undecorated windows, see
+//! [`WebViewBuilder::with_drag_region_support`].
+
+#[cfg(doc)]
+use crate::WebViewBuilder;
+
+/// Sends the `data-wry-drag-region` attribute of the element pressed with the primary button,
+/// or of its closest ancestor with the attribute, as an internal message.
+///
+/// The events dispatched by the page scripts are ignored, so only the user can drag the window.
+pub(crate) const DRAG_REGION_SCRIPT: &str = r#"
+document.addEventListener('mousedown', (event) => {
+  if (!event.isTrusted || event.button !== 0 || event.detail > 1) return;
+  if (!(event.target instanceof Element)) return;
+  const region = event.target.closest('[data-wry-drag-region]');
+  if (!region) return;
+  const value = region.getAttribute('data-wry-drag-region');
+  if (value === 'false') return;
+  event.preventDefault();
+  send(value);
+});
+"#;
+
+/// What pressing a drag region does, see [`WebViewBuilder::with_drag_region_support`].
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+pub(crate) enum DragRegion {
+  Move,
+  Resize(ResizeEdge),
+}
+
+/// The edge or corner of the window resized by a drag region.
+pub(crate) enum ResizeEdge {
+  North,
+  NorthEast,
+  East,
+  SouthEast,
+  South,
+  SouthWest,
+  West,
+  NorthWest,
+}
+
+impl DragRegion {
+  pub(crate) fn parse(value: &str) -> Option<Self> {
+    let edge = match value {
+      "" | "move" => return Some(Self::Move),
+      "n" => ResizeEdge::North,
+      "ne" => ResizeEdge::NorthEast,
+      "e" => ResizeEdge::East,
+      "se" => ResizeEdge::SouthEast,
+      "s" => ResizeEdge::South,
+      "sw" => ResizeEdge::SouthWest,
+      "w" => ResizeEdge::West,
+      "nw" => ResizeEdge::NorthWest,
+      _ => return None,
+    };
+    Some(Self::Resize(edge))
+  }
+}
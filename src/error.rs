@@ -22,6 +22,8 @@ pub enum Error {
   XlibError(#[from] x11_dl::error::OpenError),
   #[error("Failed to initialize the script")]
   InitScriptError,
+  #[error("The initialization script is too large for the webview")]
+  InitScriptTooLarge,
   #[error("Bad RPC request: {0} ((1))")]
   RpcScriptError(String, String),
   #[error(transparent)]
@@ -44,6 +46,9 @@ pub enum Error {
   #[cfg(target_os = "android")]
   #[error(transparent)]
   JniError(#[from] jni::errors::Error),
+  #[cfg(target_os = "android")]
+  #[error("Java exception thrown: {0}")]
+  JavaException(String),
   #[error("Failed to create proxy endpoint")]
   ProxyEndpointCreationFailed,
   #[error(transparent)]
@@ -61,10 +66,47 @@ pub enum Error {
   CustomProtocolTaskInvalid,
   #[error("Failed to register URL scheme: {0}, could be due to invalid URL scheme or the scheme is already registered.")]
   UrlSchemeRegisterError(String),
+  #[error("Invalid custom protocol scheme `{0}`: {1}")]
+  InvalidScheme(String, &'static str),
   #[error("Duplicate custom protocol registered on Linux: {0}")]
   DuplicateCustomProtocol(String),
   #[error("Duplicate custom protocol registered on the same web context on Linux: {0}")]
   ContextDuplicateCustomProtocol(String),
+  #[error(
+    "Invalid profile name `{0}`, it must be 1 to 64 lowercase ASCII letters, digits, `-` or `_`"
+  )]
+  InvalidProfileName(String),
+  #[error("Duplicate profile created on the same web context: {0}")]
+  DuplicateProfile(String),
+  #[error("Options unsupported on this platform: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+  UnsupportedOptions(Vec<crate::UnsupportedOption>),
+  #[error("The deferred webview failed to be created")]
+  DeferredWebViewFailed,
+  #[error("The {0} handler can only be replaced if one is set when the webview is built")]
+  HandlerNotInstalled(&'static str),
+  #[error("The fetch request failed: {0}")]
+  FetchFailed(String),
+  #[cfg(feature = "reader")]
+  #[error("The article extraction failed: {0}")]
+  ArticleExtractionFailed(String),
+  #[error("Invalid development server URL, it must be an http URL: {0}")]
+  InvalidDevUrl(String),
+  #[error(
+    "The webview engine version {version} is older than the minimum version {minimum}, {hint}"
+  )]
+  EngineTooOld {
+    version: String,
+    minimum: String,
+    hint: &'static str,
+  },
+  #[error("Invalid engine version, it must be numbers separated by dots: {0}")]
+  InvalidEngineVersion(String),
+  #[cfg(target_os = "windows")]
+  #[error("The WebView2 Runtime is not installed")]
+  WebView2RuntimeMissing,
+  #[cfg(target_os = "windows")]
+  #[error("Failed to install the WebView2 Runtime: {0}")]
+  WebView2RuntimeInstallFailed(String),
   #[error(transparent)]
   #[cfg(any(target_os = "macos", target_os = "ios"))]
   UrlPrase(#[from] url::ParseError),
@@ -0,0 +1,256 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! HTTP requests made by the page of a webview for [`WebView::fetch`](crate::WebView::fetch).
+//!
+//! The request is run with the `fetch` function in an isolated world of the page, so it uses the
+//! network stack and the cookies of the webview while the page scripts can't tamper with it or read
+//! its response. Each request has a random id, so the responses can't be forged either.
+//!
+//! Android has no isolated worlds, so the request runs in the page world there and its response is
+//! sent back through the internal IPC messages.
+
+use std::{
+  cell::RefCell,
+  collections::HashMap,
+  future::Future,
+  pin::Pin,
+  sync::{Arc, Mutex},
+  task::{Context, Poll, Waker},
+};
+
+use base64::{engine::general_purpose, Engine};
+use http::{Request, Response};
+
+use crate::{escape_js_string, util::random_token, Error, Result};
+
+#[derive(Default)]
+struct FetchState {
+  response: Option<Result<Response<Vec<u8>>>>,
+  waker: Option<Waker>,
+}
+
+/// The response of a request made with [`WebView::fetch`](crate::WebView::fetch).
+pub struct FetchFuture {
+  state: Arc<Mutex<FetchState>>,
+}
+
+impl Future for FetchFuture {
+  type Output = Result<Response<Vec<u8>>>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let mut state = self.state.lock().unwrap();
+    match state.response.take() {
+      Some(response) => Poll::Ready(response),
+      None => {
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+      }
+    }
+  }
+}
+
+/// The requests of a webview waiting for their response.
+#[derive(Default)]
+pub(crate) struct PendingFetches {
+  requests: RefCell<HashMap<String, Arc<Mutex<FetchState>>>>,
+  /// The prefix of the internal IPC messages sent by the scripts of [`PendingFetches::script`].
+  #[cfg(target_os = "android")]
+  message_prefix: RefCell<String>,
+}
+
+impl PendingFetches {
+  /// Send the responses with the internal IPC messages of `prefix`.
+  #[cfg(target_os = "android")]
+  pub(crate) fn set_message_prefix(&self, prefix: String) {
+    *self.message_prefix.borrow_mut() = prefix;
+  }
+
+  /// Register `request`, returning its id, its response and the body of the async function making
+  /// it, which returns the response to pass to [`parse_response`].
+  pub(crate) fn start(&self, request: &Request<Vec<u8>>) -> (String, FetchFuture, String) {
+    let id = random_token();
+    let state = Arc::new(Mutex::new(FetchState::default()));
+    self.requests.borrow_mut().insert(id.clone(), state.clone());
+
+    let headers = request
+      .headers()
+      .iter()
+      .filter_map(|(name, value)| {
+        let value = value.to_str().ok()?;
+        Some(format!(
+          "['{}', '{}']",
+          escape_js_string(name.as_str()),
+          escape_js_string(value)
+        ))
+      })
+      .collect::<Vec<_>>()
+      .join(", ");
+
+    // Returns `{status}\n{name}: {value}\n...\n\n{base64 body}`
+    let body = format!(
+      r#"
+const body = '{body}';
+const response = await fetch('{url}', {{
+  method: '{method}',
+  headers: [{headers}],
+  body: body ? Uint8Array.from(atob(body), (c) => c.charCodeAt(0)) : undefined,
+  credentials: 'include',
+}});
+const bytes = new Uint8Array(await response.arrayBuffer());
+let binary = '';
+for (let i = 0; i < bytes.length; i += 0x8000) {{
+  binary += String.fromCharCode.apply(null, bytes.subarray(i, i + 0x8000));
+}}
+let headers = '';
+response.headers.forEach((value, name) => {{
+  headers += name + ': ' + value + '\n';
+}});
+return response.status + '\n' + headers + '\n' + btoa(binary);
+"#,
+      body = general_purpose::STANDARD.encode(request.body()),
+      url = escape_js_string(&request.uri().to_string()),
+      method = escape_js_string(request.method().as_str()),
+    );
+
+    (id, FetchFuture { state }, body)
+  }
+
+  /// The script running the function `body` of the request `id` in the page world, sending
+  /// `{id}\n{response}` or `{id}\nerror\n{message}` with the internal IPC messages.
+  #[cfg(target_os = "android")]
+  pub(crate) fn script(&self, id: &str, body: &str) -> String {
+    format!(
+      r#"
+(function () {{
+  const ipc = window.ipc;
+  const postMessage = ipc.postMessage.bind(ipc);
+  const send = (message) => postMessage('{prefix}{id}\n' + message);
+  (async function () {{
+{body}
+  }})().then(send, (error) => send('error\n' + String(error)));
+}})();
+"#,
+      prefix = self.message_prefix.borrow(),
+    )
+  }
+
+  /// Resolve the request of the IPC `message` of its script, without its prefix.
+  ///
+  /// The messages with an unknown id are forged, and ignored.
+  #[cfg(target_os = "android")]
+  pub(crate) fn complete(&self, message: &str) {
+    let Some((id, response)) = message.split_once('\n') else {
+      return;
+    };
+    self.resolve(id, parse_response(response));
+  }
+
+  /// Resolve the request `id` with `result`.
+  pub(crate) fn resolve(&self, id: &str, result: Result<Response<Vec<u8>>>) {
+    let Some(state) = self.requests.borrow_mut().remove(id) else {
+      return;
+    };
+    let mut state = state.lock().unwrap();
+    state.response = Some(result);
+    if let Some(waker) = state.waker.take() {
+      waker.wake();
+    }
+  }
+
+  /// Fail all the requests, as their page was unloaded.
+  pub(crate) fn cancel_all(&self) {
+    let ids: Vec<String> = self.requests.borrow().keys().cloned().collect();
+    for id in ids {
+      self.resolve(
+        &id,
+        Err(Error::FetchFailed("the page was unloaded".to_string())),
+      );
+    }
+  }
+}
+
+/// Parse the `{status}\n{name}: {value}\n...\n\n{base64 body}` response returned by the script of
+/// a request, or the `error\n{message}` it sends on Android.
+pub(crate) fn parse_response(message: &str) -> Result<Response<Vec<u8>>> {
+  let mut lines = message.split('\n');
+
+  let status = lines.next().unwrap_or_default();
+  if status == "error" {
+    return Err(Error::FetchFailed(lines.collect::<Vec<_>>().join("\n")));
+  }
+  let status: u16 = status
+    .parse()
+    .map_err(|_| Error::FetchFailed(format!("invalid status {status}")))?;
+
+  let mut response = Response::builder().status(status);
+  for line in lines.by_ref() {
+    if line.is_empty() {
+      break;
+    }
+    if let Some((name, value)) = line.split_once(": ") {
+      response = response.header(name, value);
+    }
+  }
+
+  let body = general_purpose::STANDARD
+    .decode(lines.next().unwrap_or_default())
+    .map_err(|e| Error::FetchFailed(e.to_string()))?;
+  response.body(body).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn fetch_responses_are_parsed() {
+    let response = parse_response("201\ncontent-type: text/plain\nx-a: b: c\n\naGVsbG8=").unwrap();
+    assert_eq!(response.status(), 201);
+    assert_eq!(response.headers()["content-type"], "text/plain");
+    assert_eq!(response.headers()["x-a"], "b: c");
+    assert_eq!(response.body(), b"hello");
+
+    let response = parse_response("204\n\n").unwrap();
+    assert_eq!(response.status(), 204);
+    assert!(response.headers().is_empty() && response.body().is_empty());
+
+    assert!(matches!(
+      parse_response("error\nTypeError: Failed to fetch\nat line 1"),
+      Err(Error::FetchFailed(message)) if message == "TypeError: Failed to fetch\nat line 1"
+    ));
+    assert!(matches!(
+      parse_response("ok\n\n"),
+      Err(Error::FetchFailed(_))
+    ));
+    assert!(matches!(
+      parse_response("200\n\nnot base64!"),
+      Err(Error::FetchFailed(_))
+    ));
+  }
+
+  #[test]
+  fn fetches_are_resolved_by_their_random_id() {
+    let fetches = PendingFetches::default();
+    let request = Request::builder()
+      .uri("https://tauri.app/api")
+      .body(b"body".to_vec())
+      .unwrap();
+    let (id, future, body) = fetches.start(&request);
+    let (other_id, other_future, _) = fetches.start(&request);
+    assert_ne!(id, other_id);
+    assert!(body.contains("'https://tauri.app/api'") && body.contains("'Ym9keQ=='"));
+
+    // a guessed id doesn't resolve the requests
+    fetches.resolve("0", Ok(Response::new(Vec::new())));
+    fetches.resolve(&id, parse_response("200\n\naGk="));
+    assert_eq!(pollster::block_on(future).unwrap().body(), b"hi");
+
+    fetches.cancel_all();
+    assert!(matches!(
+      pollster::block_on(other_future),
+      Err(Error::FetchFailed(_))
+    ));
+  }
+}
@@ -0,0 +1,332 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! A C API over [`WebViewBuilder`] and [`WebView`], to embed wry from other languages.
+//!
+//! The functions are exported by the `cdylib` or `staticlib` crate depending on wry with the `ffi`
+//! feature and declared in `include/wry.h`. All of them must be called on the thread running the
+//! event loop of the window, and the strings are NUL-terminated UTF-8.
+//!
+//! ```c
+//! WryBuilder *builder = wry_builder_new();
+//! wry_builder_set_url(builder, "https://tauri.app");
+//! wry_builder_set_ipc_callback(builder, on_message, user_data);
+//! WryWebView *webview = wry_builder_build(builder, window);
+//! wry_webview_evaluate_script(webview, "window.ipc.postMessage('hello')");
+//! wry_webview_destroy(webview);
+//! ```
+
+use std::{
+  ffi::{c_char, c_void, CStr, CString},
+  ptr,
+};
+
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "ios"))]
+use raw_window_handle::{HandleError, HasWindowHandle, RawWindowHandle, WindowHandle};
+
+use crate::{WebView, WebViewBuilder};
+
+/// A webview builder, created with [`wry_builder_new`].
+pub struct WryBuilder(Option<WebViewBuilder<'static>>);
+
+/// A webview, created with [`wry_builder_build`].
+pub struct WryWebView(WebView);
+
+/// The callback receiving the IPC messages, with the message and the user data given to
+/// [`wry_builder_set_ipc_callback`].
+pub type WryIpcCallback = extern "C" fn(message: *const c_char, user_data: *mut c_void);
+
+/// A window of the embedder, from the native handle given to [`wry_builder_build`].
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "ios"))]
+struct RawWindow(RawWindowHandle);
+
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "ios"))]
+impl HasWindowHandle for RawWindow {
+  fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+    // Safety: the embedder guarantees the window outlives the call to `wry_builder_build`
+    Ok(unsafe { WindowHandle::borrow_raw(self.0) })
+  }
+}
+
+unsafe fn to_str<'a>(s: *const c_char) -> Option<&'a str> {
+  if s.is_null() {
+    None
+  } else {
+    CStr::from_ptr(s).to_str().ok()
+  }
+}
+
+/// Apply `f` to the builder, returning whether the builder and the string are valid.
+unsafe fn update_builder(
+  builder: *mut WryBuilder,
+  s: *const c_char,
+  f: impl FnOnce(WebViewBuilder<'static>, &str) -> WebViewBuilder<'static>,
+) -> bool {
+  let (Some(builder), Some(s)) = (builder.as_mut(), to_str(s)) else {
+    return false;
+  };
+  match builder.0.take() {
+    Some(b) => {
+      builder.0 = Some(f(b, s));
+      true
+    }
+    None => false,
+  }
+}
+
+/// Create a webview builder, to be consumed by [`wry_builder_build`] or freed by
+/// [`wry_builder_destroy`].
+#[no_mangle]
+pub extern "C" fn wry_builder_new() -> *mut WryBuilder {
+  Box::into_raw(Box::new(WryBuilder(Some(WebViewBuilder::new()))))
+}
+
+/// Load `url` when the webview is created.
+///
+/// # Safety
+///
+/// `builder` must come from [`wry_builder_new`] and `url` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn wry_builder_set_url(builder: *mut WryBuilder, url: *const c_char) -> bool {
+  update_builder(builder, url, |b, url| b.with_url(url))
+}
+
+/// Load the `html` string when the webview is created.
+///
+/// # Safety
+///
+/// `builder` must come from [`wry_builder_new`] and `html` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn wry_builder_set_html(
+  builder: *mut WryBuilder,
+  html: *const c_char,
+) -> bool {
+  update_builder(builder, html, |b, html| b.with_html(html))
+}
+
+/// Call `callback` with the messages sent by the pages with `window.ipc.postMessage`.
+///
+/// # Safety
+///
+/// `builder` must come from [`wry_builder_new`] and `user_data` must be valid for as long as the
+/// webview lives.
+#[no_mangle]
+pub unsafe extern "C" fn wry_builder_set_ipc_callback(
+  builder: *mut WryBuilder,
+  callback: WryIpcCallback,
+  user_data: *mut c_void,
+) -> bool {
+  let Some(builder) = builder.as_mut() else {
+    return false;
+  };
+  let Some(b) = builder.0.take() else {
+    return false;
+  };
+
+  // Moved as an integer to satisfy the `send-handlers` feature, the handler runs on the UI thread
+  let user_data = user_data as usize;
+  builder.0 = Some(b.with_ipc_handler(move |request| {
+    if let Ok(message) = CString::new(request.into_body()) {
+      callback(message.as_ptr(), user_data as *mut c_void);
+    }
+  }));
+  true
+}
+
+/// Create the webview in `window` and free the builder, returning `NULL` on failure.
+///
+/// `window` is a `HWND` on Windows, a `NSView*` on macOS, a `UIView*` on iOS and a `GtkContainer*`
+/// on Linux. Android is unsupported.
+///
+/// # Safety
+///
+/// `builder` must come from [`wry_builder_new`] and `window` must be a valid native window.
+#[no_mangle]
+pub unsafe extern "C" fn wry_builder_build(
+  builder: *mut WryBuilder,
+  window: *mut c_void,
+) -> *mut WryWebView {
+  if builder.is_null() {
+    return ptr::null_mut();
+  }
+  let Some(builder) = Box::from_raw(builder).0 else {
+    return ptr::null_mut();
+  };
+  if window.is_null() {
+    return ptr::null_mut();
+  }
+
+  match build(builder, window) {
+    Ok(webview) => Box::into_raw(Box::new(WryWebView(webview))),
+    Err(_e) => {
+      #[cfg(feature = "tracing")]
+      tracing::error!("failed to create the webview: {_e}");
+      ptr::null_mut()
+    }
+  }
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn build(builder: WebViewBuilder<'static>, window: *mut c_void) -> crate::Result<WebView> {
+  let hwnd =
+    std::num::NonZeroIsize::new(window as isize).ok_or(crate::Error::UnsupportedWindowHandle)?;
+  let window = RawWindow(RawWindowHandle::Win32(
+    raw_window_handle::Win32WindowHandle::new(hwnd),
+  ));
+  builder.build(&window)
+}
+
+#[cfg(target_os = "macos")]
+unsafe fn build(builder: WebViewBuilder<'static>, window: *mut c_void) -> crate::Result<WebView> {
+  let ns_view = ptr::NonNull::new(window).ok_or(crate::Error::UnsupportedWindowHandle)?;
+  let window = RawWindow(RawWindowHandle::AppKit(
+    raw_window_handle::AppKitWindowHandle::new(ns_view),
+  ));
+  builder.build(&window)
+}
+
+#[cfg(target_os = "ios")]
+unsafe fn build(builder: WebViewBuilder<'static>, window: *mut c_void) -> crate::Result<WebView> {
+  let ui_view = ptr::NonNull::new(window).ok_or(crate::Error::UnsupportedWindowHandle)?;
+  let window = RawWindow(RawWindowHandle::UiKit(
+    raw_window_handle::UiKitWindowHandle::new(ui_view),
+  ));
+  builder.build(&window)
+}
+
+#[cfg(gtk)]
+unsafe fn build(builder: WebViewBuilder<'static>, window: *mut c_void) -> crate::Result<WebView> {
+  use gtk::glib::translate::from_glib_none;
+
+  use crate::WebViewBuilderExtUnix;
+
+  let container: gtk::Container = from_glib_none(window as *mut gtk::ffi::GtkContainer);
+  builder.build_gtk(&container)
+}
+
+#[cfg(target_os = "android")]
+unsafe fn build(_builder: WebViewBuilder<'static>, _window: *mut c_void) -> crate::Result<WebView> {
+  Err(crate::Error::UnsupportedWindowHandle)
+}
+
+/// Free a builder that wasn't consumed by [`wry_builder_build`].
+///
+/// # Safety
+///
+/// `builder` must come from [`wry_builder_new`] and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn wry_builder_destroy(builder: *mut WryBuilder) {
+  if !builder.is_null() {
+    drop(Box::from_raw(builder));
+  }
+}
+
+/// Navigate the webview to `url`.
+///
+/// # Safety
+///
+/// `webview` must come from [`wry_builder_build`] and `url` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn wry_webview_load_url(
+  webview: *mut WryWebView,
+  url: *const c_char,
+) -> bool {
+  match (webview.as_ref(), to_str(url)) {
+    (Some(webview), Some(url)) => webview.0.load_url(url).is_ok(),
+    _ => false,
+  }
+}
+
+/// Load the `html` string in the webview.
+///
+/// # Safety
+///
+/// `webview` must come from [`wry_builder_build`] and `html` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn wry_webview_load_html(
+  webview: *mut WryWebView,
+  html: *const c_char,
+) -> bool {
+  match (webview.as_ref(), to_str(html)) {
+    (Some(webview), Some(html)) => webview.0.load_html(html).is_ok(),
+    _ => false,
+  }
+}
+
+/// Evaluate the `js` script in the webview.
+///
+/// # Safety
+///
+/// `webview` must come from [`wry_builder_build`] and `js` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn wry_webview_evaluate_script(
+  webview: *mut WryWebView,
+  js: *const c_char,
+) -> bool {
+  match (webview.as_ref(), to_str(js)) {
+    (Some(webview), Some(js)) => webview.0.evaluate_script(js).is_ok(),
+    _ => false,
+  }
+}
+
+/// Destroy the webview.
+///
+/// # Safety
+///
+/// `webview` must come from [`wry_builder_build`] and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn wry_webview_destroy(webview: *mut WryWebView) {
+  if !webview.is_null() {
+    drop(Box::from_raw(webview));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn null_pointers_are_rejected() {
+    let url = c"https://tauri.app";
+    unsafe {
+      assert!(!wry_builder_set_url(ptr::null_mut(), url.as_ptr()));
+      assert!(!wry_builder_set_html(ptr::null_mut(), url.as_ptr()));
+      assert!(wry_builder_build(ptr::null_mut(), ptr::null_mut()).is_null());
+      wry_builder_destroy(ptr::null_mut());
+
+      let builder = wry_builder_new();
+      assert!(!wry_builder_set_url(builder, ptr::null()));
+      assert!(wry_builder_set_url(builder, url.as_ptr()));
+      assert!(wry_builder_set_html(builder, c"<p>wry</p>".as_ptr()));
+      wry_builder_destroy(builder);
+
+      // the builder is freed even if the webview isn't created
+      let builder = wry_builder_new();
+      assert!(wry_builder_build(builder, ptr::null_mut()).is_null());
+
+      assert!(!wry_webview_load_url(ptr::null_mut(), url.as_ptr()));
+      assert!(!wry_webview_load_html(ptr::null_mut(), url.as_ptr()));
+      assert!(!wry_webview_evaluate_script(ptr::null_mut(), url.as_ptr()));
+      wry_webview_destroy(ptr::null_mut());
+    }
+  }
+
+  #[test]
+  fn header_declares_the_exported_functions() {
+    let header = include_str!("../include/wry.h");
+    let source = include_str!("ffi.rs");
+    let exported: Vec<_> = source
+      .lines()
+      .filter_map(|line| line.split(" extern \"C\" fn ").nth(1))
+      .filter_map(|line| line.split('(').next())
+      .collect();
+    assert_eq!(exported.len(), 10);
+    for function in exported {
+      assert!(
+        header.contains(&format!("{function}(")),
+        "{function} isn't declared in include/wry.h"
+      );
+    }
+  }
+}
@@ -0,0 +1,149 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The IPC messages sent by the scripts of wry, which are handled by wry and not passed to the
+//! IPC handler of the app.
+//!
+//! The messages of each kind start with `__WRY__:<kind>:<token>:`, where the token is drawn from
+//! the random number generator of the OS for each webview. The scripts keep their prefix and the
+//! native `postMessage` in a closure, so the pages can't read the prefix or forge the messages.
+//!
+//! The internal messages are not subject to the [`IpcLimits`](crate::IpcLimits) of the app.
+
+use http::Request;
+
+use crate::util::random_token;
+
+/// The prefix reserved to the internal messages.
+pub(crate) const PREFIX: &str = "__WRY__:";
+
+type Handler = Box<dyn Fn(&Request<String>, &str)>;
+
+/// The handlers of the internal messages of a webview, see the [module docs](self).
+pub(crate) struct InternalMessages {
+  routes: Vec<(String, Handler)>,
+}
+
+impl InternalMessages {
+  pub fn new() -> Self {
+    Self { routes: Vec::new() }
+  }
+
+  /// Route the messages of `kind` to `handler`, without their prefix, returning the prefix.
+  pub fn route(
+    &mut self,
+    kind: &str,
+    handler: impl Fn(&Request<String>, &str) + 'static,
+  ) -> String {
+    let prefix = format!("{PREFIX}{kind}:{}:", random_token());
+    self.routes.push((prefix.clone(), Box::new(handler)));
+    prefix
+  }
+
+  /// Route the messages of `kind` to `handler` like [`Self::route`], returning `script` wrapped to
+  /// be added to the initialization scripts.
+  ///
+  /// `script` sends the messages with the `send` function in its scope.
+  pub fn add(
+    &mut self,
+    kind: &str,
+    script: &str,
+    handler: impl Fn(&Request<String>, &str) + 'static,
+  ) -> String {
+    let prefix = self.route(kind, handler);
+    format!(
+      r#"
+(function () {{
+  const ipc = (window.webkit && window.webkit.messageHandlers && window.webkit.messageHandlers.ipc)
+    || (window.chrome && window.chrome.webview)
+    || window.ipc;
+  if (!ipc) return;
+  const postMessage = ipc.postMessage.bind(ipc);
+  const send = (message) => {{
+    postMessage('{prefix}' + message);
+  }};
+{script}
+}})();
+"#
+    )
+  }
+
+  /// Wrap `ipc_handler` to pass the internal messages to their handler instead.
+  ///
+  /// The messages starting with the reserved prefix without a known token are forged, and dropped.
+  pub fn wrap(
+    self,
+    ipc_handler: Option<Box<dyn Fn(Request<String>)>>,
+  ) -> Option<Box<dyn Fn(Request<String>)>> {
+    if self.routes.is_empty() {
+      return ipc_handler;
+    }
+
+    Some(Box::new(move |request| {
+      let body = request.body();
+      if !body.starts_with(PREFIX) {
+        if let Some(ipc_handler) = &ipc_handler {
+          ipc_handler(request);
+        }
+        return;
+      }
+
+      match self
+        .routes
+        .iter()
+        .find_map(|(prefix, handler)| Some((body.strip_prefix(prefix.as_str())?, handler)))
+      {
+        Some((message, handler)) => handler(&request, message),
+        None => {
+          #[cfg(feature = "tracing")]
+          tracing::warn!(
+            "dropped an internal IPC message of {} with an unknown token",
+            request.uri()
+          );
+        }
+      }
+    }))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::{Arc, Mutex},
+  };
+
+  use super::*;
+
+  #[test]
+  fn internal_messages_are_routed() {
+    let routed = Rc::new(RefCell::new(Vec::new()));
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let received_ = received.clone();
+    let mut messages = InternalMessages::new();
+    let routed_ = routed.clone();
+    let prefix = messages.route("test", move |_, message| {
+      routed_.borrow_mut().push(message.to_string())
+    });
+    let script = messages.add("script", "send('ready');", |_, _| {});
+    assert!(script.contains("send('ready');"));
+    assert!(!script.contains(&prefix));
+
+    let ipc_handler = messages
+      .wrap(Some(Box::new(move |request: Request<String>| {
+        received_.lock().unwrap().push(request.into_body())
+      })))
+      .unwrap();
+    for message in [
+      format!("{prefix}hello"),
+      "__WRY__:test:forged:hello".to_string(),
+      "hello".to_string(),
+    ] {
+      ipc_handler(Request::new(message));
+    }
+    assert_eq!(*routed.borrow(), ["hello"]);
+    assert_eq!(*received.lock().unwrap(), ["hello"]);
+  }
+}
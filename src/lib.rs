@@ -180,6 +180,15 @@
 //! - `linux-body`: Enables body support of custom protocol request on Linux. Requires
 //! webkit2gtk v2.40 or above.
 //! - `tracing`: enables [`tracing`] for `evaluate_script`, `ipc_handler` and `custom_protocols.
+//! - `serde`: Implements `Serialize` and `Deserialize` for [`WebViewAttributesConfig`] and [`Rect`].
+//! - `send-handlers`: Requires the handlers given to [`WebViewBuilder`] and [`WebView`] to be [`Send`],
+//! see [`MaybeSend`].
+//! - `ffi`: Exports a C API over [`WebViewBuilder`] and [`WebView`] in the `ffi` module.
+//! - `devserver`: Enables [`DevServer`] to reload the webviews when the files of a directory change.
+//! - `reader`: Enables [`WebView::extract_article`] to extract the article of a page for a reader
+//! mode, bundling its script in the binary.
+//! - `webview2-bootstrap`: Exports `windows::ensure_webview2_runtime` to install the WebView2
+//! Runtime when it is missing on **Windows**.
 //!
 //! [`tao`]: https://docs.rs/tao
 //! [`winit`]: https://docs.rs/winit
@@ -194,9 +203,43 @@
 // #[macro_use]
 // extern crate objc;
 
+#[cfg(any(gtk, target_os = "macos", target_os = "ios"))]
+mod blob_download;
+mod broadcast;
+mod deferred;
+#[cfg(feature = "protocol")]
+mod dev_proxy;
+#[cfg(feature = "devserver")]
+mod devserver;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+mod drag_region;
 mod error;
+mod fetch;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod internal_messages;
+#[cfg(any(
+  target_os = "linux",
+  target_os = "dragonfly",
+  target_os = "freebsd",
+  target_os = "netbsd",
+  target_os = "openbsd",
+))]
+pub mod linux;
+#[cfg(feature = "protocol")]
+mod local_socket;
+#[cfg(feature = "protocol")]
+mod loopback;
+mod media_session;
+mod opener;
+mod path_normalization;
+mod prepare;
+mod protocol_limits;
 mod proxy;
-#[cfg(any(target_os = "macos", target_os = "android", target_os = "ios"))]
+#[cfg(feature = "reader")]
+mod reader;
+mod scripts;
+mod url_pattern;
 mod util;
 mod web_context;
 
@@ -206,7 +249,7 @@ pub(crate) mod android;
 pub use crate::android::android_setup;
 #[cfg(target_os = "android")]
 pub mod prelude {
-  pub use crate::android::{binding::*, dispatch, find_class, Context};
+  pub use crate::android::{binding::*, dispatch, dispatch_with_result, find_class, Context};
   pub use tao_macros::{android_fn, generate_package_name};
 }
 #[cfg(target_os = "android")]
@@ -220,6 +263,8 @@ pub(crate) mod webkitgtk;
 pub use raw_window_handle;
 use raw_window_handle::HasWindowHandle;
 #[cfg(gtk)]
+pub use webkitgtk::WebExtensionMessage;
+#[cfg(gtk)]
 use webkitgtk::*;
 
 #[cfg(any(target_os = "macos", target_os = "ios"))]
@@ -233,7 +278,7 @@ pub(crate) mod wkwebview;
 #[cfg(any(target_os = "macos", target_os = "ios"))]
 use wkwebview::*;
 #[cfg(any(target_os = "macos", target_os = "ios"))]
-pub use wkwebview::{PrintMargin, PrintOptions, WryWebView};
+pub use wkwebview::{TitlebarSeparatorStyle, WryWebView};
 
 #[cfg(target_os = "windows")]
 pub(crate) mod webview2;
@@ -244,19 +289,74 @@ use self::webview2::*;
 #[cfg(target_os = "windows")]
 use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Controller;
 
-use std::{borrow::Cow, collections::HashMap, path::PathBuf, rc::Rc};
+/// Helpers specific to Windows.
+#[cfg(all(target_os = "windows", feature = "webview2-bootstrap"))]
+pub mod windows {
+  pub use crate::webview2::bootstrap::{ensure_webview2_runtime, InstallMode};
+}
+
+use once_cell::unsync::OnceCell;
+use std::{
+  borrow::Cow,
+  cell::{Cell, RefCell},
+  collections::HashMap,
+  panic::AssertUnwindSafe,
+  path::{Path, PathBuf},
+  rc::{Rc, Weak},
+  sync::{
+    atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+    Arc, Mutex,
+  },
+  time::{Duration, Instant},
+};
 
+use broadcast::{broadcast, BroadcastReceiver};
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+use drag_region::{DragRegion, ResizeEdge};
 use http::{Request, Response};
+use scripts::*;
+use url_pattern::*;
 
 pub use cookie;
+pub use deferred::DeferredWebView;
+#[cfg(feature = "devserver")]
+pub use devserver::DevServer;
 pub use dpi;
 pub use error::*;
+pub use fetch::FetchFuture;
 pub use http;
+#[cfg(feature = "protocol")]
+pub use loopback::LoopbackConnection;
+pub use media_session::{MediaMetadata, MediaPlaybackState, MediaSessionAction, MediaSessionEvent};
+pub use path_normalization::PathNormalization;
 pub use proxy::{ProxyConfig, ProxyEndpoint};
-pub use web_context::WebContext;
+#[cfg(feature = "reader")]
+pub use reader::Article;
+pub use url_pattern::UrlPattern;
+pub use web_context::{DataMigration, Profile, ProfileOptions, WebContext};
+
+macro_rules! maybe_send {
+  ($($bound:tt)*) => {
+    /// The bound of the handlers and callbacks given to [`WebViewBuilder`], [`WebView`] and
+    /// [`WebContext`].
+    ///
+    /// It is implemented for every type, or only for the [`Send`] types with the `send-handlers`
+    /// feature. The handlers are always called on the thread that created the webview, the bound
+    /// only lets the compiler check that they can be created on another thread, like in an async
+    /// runtime, and moved to the UI thread.
+    pub trait MaybeSend: $($bound)* {}
+    impl<T: $($bound)*> MaybeSend for T {}
+  };
+}
+
+#[cfg(feature = "send-handlers")]
+maybe_send!(Send);
+#[cfg(not(feature = "send-handlers"))]
+maybe_send!();
 
 /// A rectangular region.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rect {
   /// Rect position.
   pub position: dpi::Position,
@@ -273,11 +373,39 @@ impl Default for Rect {
   }
 }
 
+/// The headers of a custom protocol [`Request`] as received from the webview, in their original
+/// order and with their duplicates, found in the extensions of the request.
+///
+/// The [`http::HeaderMap`] of the request groups the values of each header, so it loses the order
+/// of the headers with different names.
+///
+/// ```no_run
+/// # use wry::{http::Request, RawHeaders};
+/// # fn handler(request: Request<Vec<u8>>) {
+/// if let Some(RawHeaders(headers)) = request.extensions().get::<RawHeaders>() {
+///   for (name, value) in headers {
+///     println!("{name}: {value}");
+///   }
+/// }
+/// # }
+/// ```
+///
+/// ## Platform-specific
+///
+/// - **macOS / iOS / Android**: The headers are received as a dictionary, so their order is
+///   arbitrary and the duplicates are already merged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RawHeaders(pub Vec<(String, String)>);
+
+/// Sends the response of a custom protocol request to the webview.
+pub(crate) type ResponderFn = Box<dyn FnOnce(Response<Cow<'static, [u8]>>) -> Result<()>>;
+
 /// Resolves a custom protocol [`Request`] asynchronously.
 ///
 /// See [`WebViewBuilder::with_asynchronous_custom_protocol`] for more information.
 pub struct RequestAsyncResponder {
-  pub(crate) responder: Box<dyn FnOnce(Response<Cow<'static, [u8]>>)>,
+  pub(crate) responder: ResponderFn,
+  pub(crate) cancellation: CancellationToken,
 }
 
 // SAFETY: even though the webview bindings do not indicate the responder is Send,
@@ -287,15 +415,147 @@ unsafe impl Send for RequestAsyncResponder {}
 
 impl RequestAsyncResponder {
   /// Resolves the request with the given response.
-  pub fn respond<T: Into<Cow<'static, [u8]>>>(self, response: Response<T>) {
+  ///
+  /// Fails with [`Error::CustomProtocolTaskInvalid`], without sending the response, when the
+  /// webview doesn't wait for it anymore, see [`Self::cancellation_token`].
+  pub fn respond<T: Into<Cow<'static, [u8]>>>(self, response: Response<T>) -> Result<()> {
+    if self.cancellation.is_cancelled() {
+      return Err(Error::CustomProtocolTaskInvalid);
+    }
+
     let (parts, body) = response.into_parts();
     (self.responder)(Response::from_parts(parts, body.into()))
   }
+
+  /// The token telling when the webview doesn't wait for the response anymore, e.g. when the page
+  /// navigated away, so the handler can stop its work early.
+  ///
+  /// The requests are cancelled when a new page is committed in the webview, or when the webview
+  /// is dropped. Responding to a cancelled request does nothing and returns an error.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS**: Also cancelled as soon as WebKit stops the `WKURLSchemeTask`.
+  /// - **Windows / Linux / Android**: The backends don't report the aborted requests, so the
+  ///   requests of an iframe navigating away are only cancelled with the page.
+  pub fn cancellation_token(&self) -> CancellationToken {
+    self.cancellation.clone()
+  }
+}
+
+/// The limits of a custom protocol, see [`WebViewBuilder::with_custom_protocol_config`].
+///
+/// The requests beyond the limits are answered by wry, so the webview doesn't wait forever for a
+/// handler that never responds.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct CustomProtocolConfig {
+  /// How long the handler can take to respond, after which wry responds
+  /// `504 Gateway Timeout`. The later response of the handler is then discarded.
+  pub timeout: Option<Duration>,
+  /// How many requests can wait for the response of the handler at the same time. The next
+  /// requests are responded `503 Service Unavailable` without calling the handler.
+  pub max_concurrent: Option<usize>,
+}
+
+/// Tells whether the webview gave up on a custom protocol request,
+/// see [`RequestAsyncResponder::cancellation_token`].
+///
+/// ```no_run
+/// # use wry::{http::Response, RequestAsyncResponder};
+/// # fn query(responder: RequestAsyncResponder) {
+/// let token = responder.cancellation_token();
+/// std::thread::spawn(move || {
+///   let mut rows = Vec::new();
+///   for row in 0..1000u16 {
+///     if token.is_cancelled() {
+///       return;
+///     }
+///     rows.extend_from_slice(&row.to_le_bytes());
+///   }
+///   let _ = responder.respond(Response::new(rows));
+/// });
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+  /// Whether the request was cancelled.
+  pub fn is_cancelled(&self) -> bool {
+    self.0.load(Ordering::Acquire)
+  }
+
+  pub(crate) fn cancel(&self) {
+    self.0.store(true, Ordering::Release);
+  }
+
+  /// Whether both tokens belong to the same request.
+  pub(crate) fn is_same(&self, other: &Self) -> bool {
+    Arc::ptr_eq(&self.0, &other.0)
+  }
 }
 
 /// An id for a webview
 pub type WebViewId<'a> = &'a str;
 
+/// The plain data subset of [`WebViewAttributes`], to load webview presets from configuration
+/// files with the `serde` feature. The options that are `None` keep the value of the builder.
+///
+/// See [`WebViewBuilder::with_attributes_config`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(default)
+)]
+pub struct WebViewAttributesConfig {
+  /// See [`WebViewAttributes::url`].
+  pub url: Option<String>,
+  /// See [`WebViewAttributes::html`].
+  pub html: Option<String>,
+  /// See [`WebViewAttributes::user_agent`].
+  pub user_agent: Option<String>,
+  /// See [`WebViewAttributes::visible`].
+  pub visible: Option<bool>,
+  /// See [`WebViewAttributes::transparent`].
+  pub transparent: Option<bool>,
+  /// See [`WebViewAttributes::background_color`].
+  pub background_color: Option<RGBA>,
+  /// See [`WebViewAttributes::bounds`].
+  pub bounds: Option<Rect>,
+  /// The theme of the webview.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: Same as [`WebViewBuilderExtWindows::with_theme`].
+  /// - **Android**: Same as [`WebViewBuilderExtAndroid::with_force_dark`].
+  /// - **macOS / Linux / iOS**: Unsupported.
+  pub theme: Option<Theme>,
+  /// See [`WebViewAttributes::focused`].
+  pub focused: Option<bool>,
+  /// See [`WebViewAttributes::devtools`].
+  pub devtools: Option<bool>,
+  /// See [`WebViewAttributes::incognito`].
+  pub incognito: Option<bool>,
+  /// See [`WebViewAttributes::autoplay`].
+  pub autoplay: Option<bool>,
+  /// See [`WebViewAttributes::clipboard`].
+  pub clipboard: Option<bool>,
+  /// See [`WebViewAttributes::javascript_enabled`].
+  pub javascript_enabled: Option<bool>,
+  /// See [`WebViewAttributes::images_enabled`].
+  pub images_enabled: Option<bool>,
+  /// See [`WebViewAttributes::zoom_hotkeys_enabled`].
+  pub zoom_hotkeys_enabled: Option<bool>,
+  /// See [`WebViewAttributes::back_forward_navigation_gestures`].
+  pub back_forward_navigation_gestures: Option<bool>,
+  /// See [`WebViewAttributes::accept_first_mouse`].
+  pub accept_first_mouse: Option<bool>,
+  /// See [`WebViewAttributes::forced_dark_content`].
+  pub forced_dark_content: Option<bool>,
+}
+
 pub struct WebViewAttributes<'a> {
   /// An id that will be passed when this webview makes requests in certain callbacks.
   pub id: Option<WebViewId<'a>>,
@@ -338,6 +598,12 @@ pub struct WebViewAttributes<'a> {
   /// Headers used when loading the requested [`url`](Self::url).
   pub headers: Option<http::HeaderMap>,
 
+  /// Whether the [`headers`](Self::headers) are sent with all the navigations to the origin of
+  /// the [`url`](Self::url), instead of only the first request.
+  ///
+  /// See [`WebViewBuilder::with_persistent_headers`].
+  pub persistent_headers: bool,
+
   /// Whether page zooming by hotkeys is enabled
   ///
   /// ## Platform-specific
@@ -354,9 +620,16 @@ pub struct WebViewAttributes<'a> {
   ///
   /// ## PLatform-specific:
   ///
-  /// - **Windows:** the string can not be larger than 2 MB (2 * 1024 * 1024 bytes) in total size
+  /// - **Windows:** The strings larger than the 2 MB supported by WebView2 are served from an
+  ///   internal `https://wry-html.localhost/` URL instead, which is then the origin of the page.
   pub html: Option<String>,
 
+  /// The script building the document of the webview from `about:blank`, ignoring the `url` and
+  /// the `html`.
+  ///
+  /// See [`WebViewBuilder::with_blank_bootstrap`].
+  pub blank_bootstrap: Option<String>,
+
   /// Initialize javascript code when loading new pages. When webview load a new page, this
   /// initialization code will be executed. It is guaranteed that code is executed before
   /// `window.onload`.
@@ -367,6 +640,12 @@ pub struct WebViewAttributes<'a> {
   /// so we prepend them to each HTML head. They are only implemented on custom protocol URLs.
   pub initialization_scripts: Vec<String>,
 
+  /// Style sheets applied to the documents loaded by the webview, as `(css, main_frame_only)`
+  /// pairs.
+  ///
+  /// See [`WebViewBuilder::with_user_style_sheet`].
+  pub user_style_sheets: Vec<(String, bool)>,
+
   /// A list of custom loading protocols with pairs of scheme uri string and a handling
   /// closure.
   ///
@@ -394,6 +673,11 @@ pub struct WebViewAttributes<'a> {
   pub custom_protocols:
     HashMap<String, Box<dyn Fn(WebViewId, Request<Vec<u8>>, RequestAsyncResponder)>>,
 
+  /// The limits of the custom protocols, by protocol name.
+  ///
+  /// See [`WebViewBuilder::with_custom_protocol_config`].
+  pub custom_protocol_configs: HashMap<String, CustomProtocolConfig>,
+
   /// The IPC handler to receive the message from Javascript on webview
   /// using `window.ipc.postMessage("insert_message_here")` to host Rust code.
   pub ipc_handler: Option<Box<dyn Fn(Request<String>)>>,
@@ -440,12 +724,127 @@ pub struct WebViewAttributes<'a> {
   /// due to API limitations.
   pub download_completed_handler: Option<Rc<dyn Fn(String, Option<PathBuf>, bool) + 'static>>,
 
+  /// How the paths passed to the drag drop and download handlers are normalized.
+  ///
+  /// See [`WebViewBuilder::with_path_normalization`].
+  pub path_normalization: PathNormalization,
+
   /// A new window handler to decide if incoming url is allowed to open in a new window.
   ///
   /// The closure take a `String` parameter as url and return `bool` to determine whether the window should open.
   /// `true` allows to open and `false` does not.
   pub new_window_req_handler: Option<Box<dyn Fn(String) -> bool>>,
 
+  /// The URLs the webview is allowed to navigate to, or open in a new window. When not empty,
+  /// navigations to URLs not matching any of the patterns are blocked before the
+  /// [`Self::navigation_handler`] and [`Self::new_window_req_handler`] are called.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **iOS**: Also sets `limitsNavigationsToAppBoundDomains`, so the domains must be listed in
+  ///   the `WKAppBoundDomains` key of the `Info.plist` of the app. Requires iOS 14+.
+  pub navigation_allowlist: Vec<UrlPattern>,
+
+  /// The origins of the pages allowed to send IPC messages. When not empty, the messages sent by
+  /// pages whose URL doesn't match any of the patterns are dropped before reaching the
+  /// [`Self::ipc_handler`].
+  ///
+  /// See [`WebViewBuilder::with_ipc_allowed_origins`].
+  pub ipc_allowed_origins: Vec<UrlPattern>,
+
+  /// The size and rate limits of the IPC messages, the messages beyond them are dropped before
+  /// reaching the [`Self::ipc_handler`].
+  ///
+  /// See [`WebViewBuilder::with_ipc_limits`].
+  pub ipc_limits: Option<IpcLimits>,
+
+  /// A handler called with the URL of the page and the reason when an IPC message is dropped
+  /// because of the [`Self::ipc_limits`].
+  ///
+  /// See [`WebViewBuilder::with_ipc_dropped_handler`].
+  pub ipc_dropped_handler: Option<Box<dyn Fn(String, IpcDropReason)>>,
+
+  /// Whether the links leaving the app are opened in the system browser.
+  ///
+  /// See [`WebViewBuilder::with_external_links_in_browser`].
+  pub external_links_in_browser: bool,
+
+  /// A handler deciding whether wry can do something bringing a window to the front.
+  ///
+  /// See [`WebViewBuilder::with_window_activation_handler`].
+  pub window_activation_handler: Option<Box<dyn Fn(WindowActivation) -> bool>>,
+
+  /// The redirect URI and the handler of an authentication session.
+  ///
+  /// See [`WebViewBuilder::with_auth_session`].
+  pub auth_session: Option<(String, Box<dyn FnOnce(String)>)>,
+
+  /// Whether building the webview fails when options unsupported on the current platform are set.
+  ///
+  /// See [`WebViewBuilder::with_strict_validation`].
+  pub strict_validation: bool,
+
+  /// The minimum version of the engine, building the webview fails on older engines.
+  ///
+  /// See [`WebViewBuilder::with_minimum_engine_version`].
+  pub minimum_engine_version: Option<String>,
+
+  /// Whether the WebAuthn authenticators are replaced by a virtual one.
+  ///
+  /// See [`WebViewBuilder::with_virtual_authenticator`].
+  pub virtual_authenticator: bool,
+
+  /// Whether the `data-wry-drag-region` elements move or resize the window.
+  ///
+  /// See [`WebViewBuilder::with_drag_region_support`].
+  pub drag_region_support: bool,
+
+  /// The regions of the webview where the mouse events fall through to the window beneath.
+  ///
+  /// See [`WebViewBuilder::with_hit_test_ignore_regions`].
+  pub hit_test_ignore_regions: Vec<Rect>,
+
+  /// Whether the IPC bridge and the initialization scripts run in an isolated JavaScript world.
+  ///
+  /// See [`WebViewBuilder::with_isolated_world`].
+  pub isolated_world: bool,
+
+  /// A Content Security Policy applied to the documents loaded by the webview.
+  ///
+  /// See [`WebViewBuilder::with_content_security_policy`] for how it is enforced.
+  pub content_security_policy: Option<String>,
+
+  /// Whether the scripts of the pages are run. The initialization scripts and
+  /// [`WebView::evaluate_script`] are not affected.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Android**: Disabling JavaScript also disables the initialization scripts and [`WebView::evaluate_script`].
+  pub javascript_enabled: bool,
+
+  /// Whether the images of the pages are loaded.
+  pub images_enabled: bool,
+
+  /// Whether the display is kept awake while media is playing.
+  ///
+  /// See [`WebViewBuilder::with_media_keeps_display_awake`].
+  pub media_keeps_display_awake: bool,
+
+  /// How the webview uses the HTTP cache.
+  ///
+  /// See [`WebViewBuilder::with_cache_mode`].
+  pub cache_mode: CacheMode,
+
+  /// Whether the webview can load `file://` URLs and what the pages loaded from them can access.
+  ///
+  /// See [`WebViewBuilder::with_file_access`].
+  pub file_access: FileAccessPolicy,
+
+  /// Whether the content of the pages is darkened, even for pages that only have a light theme.
+  ///
+  /// See [`WebViewBuilder::with_forced_dark_content`].
+  pub forced_dark_content: bool,
+
   /// Enables clipboard access for the page rendered on **Linux** and **Windows**.
   ///
   /// macOS doesn't provide such method and is always enabled by default. But your app will still need to add menu
@@ -485,6 +884,58 @@ pub struct WebViewAttributes<'a> {
   /// Set a handler closure to process the change of the webview's document title.
   pub document_title_changed_handler: Option<Box<dyn Fn(String)>>,
 
+  /// A handler deciding whether a page whose `beforeunload` listener asked for a confirmation can
+  /// be unloaded.
+  ///
+  /// See [`WebViewBuilder::with_before_unload_handler`].
+  pub before_unload_handler: Option<Box<dyn Fn(String, String) -> bool>>,
+
+  /// A handler called when the page calls `window.close()`.
+  ///
+  /// See [`WebViewBuilder::with_close_requested_handler`].
+  pub close_requested_handler: Option<Box<dyn Fn() -> bool>>,
+
+  /// A handler deciding whether a page is granted a permission.
+  ///
+  /// See [`WebViewBuilder::with_permission_handler`].
+  pub permission_handler: Option<Box<dyn Fn(PermissionRequest) -> PermissionResponse>>,
+
+  /// A handler called when a process of the webview crashes or exits.
+  ///
+  /// See [`WebViewBuilder::with_process_failed_handler`].
+  pub process_failed_handler: Option<Box<dyn Fn(ProcessFailure)>>,
+
+  /// A handler closure called when the webview becomes visible or hidden on screen.
+  ///
+  /// See [`WebViewBuilder::with_visibility_changed_handler`].
+  pub visibility_changed_handler: Option<Box<dyn Fn(VisibilityState)>>,
+
+  /// A handler called on the IME compositions of the page and when the virtual keyboard is shown
+  /// or hidden.
+  ///
+  /// See [`WebViewBuilder::with_text_input_handler`].
+  pub text_input_handler: Option<Box<dyn Fn(TextInputEvent)>>,
+
+  /// A handler called when the page updates its media session.
+  ///
+  /// See [`WebViewBuilder::with_media_session_handler`].
+  pub media_session_handler: Option<Box<dyn Fn(MediaSessionEvent)>>,
+
+  /// How the webview makes room for the virtual keyboard.
+  ///
+  /// See [`WebViewBuilder::with_virtual_keyboard_policy`].
+  pub virtual_keyboard_policy: VirtualKeyboardPolicy,
+
+  /// The strings of the native UI shown by wry for the webview, replacing its defaults.
+  ///
+  /// See [`WebViewBuilder::with_ui_strings`].
+  pub ui_strings: HashMap<UiString, String>,
+
+  /// The layout direction of the native UI shown by wry for the webview.
+  ///
+  /// See [`WebViewBuilder::with_ui_layout_direction`].
+  pub ui_layout_direction: UiLayoutDirection,
+
   /// Run the WebView with incognito mode. Note that WebContext will be ingored if incognito is
   /// enabled.
   ///
@@ -492,15 +943,83 @@ pub struct WebViewAttributes<'a> {
   ///
   /// - Windows: Requires WebView2 Runtime version 101.0.1210.39 or higher, does nothing on older versions,
   /// see https://learn.microsoft.com/en-us/microsoft-edge/webview2/release-notes/archive?tabs=dotnetcsharp#10121039
-  /// - **Android:** Unsupported yet.
+  /// - **Android:** Disables cookies, DOM storage, the database storage and the HTTP cache for the webview.
+  /// Note that Android only exposes a process-wide cookie manager, so cookies are disabled for all webviews.
   pub incognito: bool,
 
+  /// The data directory of this webview, overriding the one of the [`WebContext`].
+  ///
+  /// This gives the webview its own data store (cookies, local storage, cache...), e.g. to isolate
+  /// the sessions of multiple accounts. Ignored if incognito is enabled.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: Creates a WebView2 environment with this user data folder.
+  /// - **Linux**: Creates a new webkit2gtk context for the webview with this data directory.
+  /// - **macOS / iOS**: Unsupported, use `WebViewBuilderExtDarwin::with_data_store_identifier` instead.
+  /// - **Android**: Unsupported.
+  pub data_directory: Option<PathBuf>,
+
+  /// The profile of this webview, see [`WebViewBuilder::with_profile`].
+  pub profile: Option<Profile>,
+
+  /// Use a non-persistent data store for the webview, all the data is lost when the webview is
+  /// dropped. Unlike [`Self::incognito`], it doesn't enable any other private browsing behavior.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Uses `WKWebsiteDataStore.nonPersistentDataStore`, same as incognito.
+  /// - **Linux**: Uses an ephemeral webkit2gtk context, same as incognito. The [`WebContext`] is
+  ///   ignored.
+  /// - **Windows**: WebView2 has no in-memory data store, so this enables InPrivate mode.
+  ///   Requires WebView2 Runtime version 101.0.1210.39 or higher.
+  /// - **Android**: Unsupported.
+  pub ephemeral_storage: bool,
+
   /// Whether all media can be played without user interaction.
   pub autoplay: bool,
 
   /// Set a handler closure to process page load events.
   pub on_page_load_handler: Option<Box<dyn Fn(PageLoadEvent, String)>>,
 
+  /// A handler receiving the [`WebViewEvent`]s, called before the handler of each event.
+  ///
+  /// See [`WebViewBuilder::with_event_handler`].
+  pub event_handler: Option<Rc<dyn Fn(WebViewEvent)>>,
+
+  /// A handler receiving the panics of the other handlers, which are caught instead of unwinding
+  /// across the callbacks of the platform webview.
+  ///
+  /// See [`WebViewBuilder::with_panic_handler`].
+  pub panic_handler: Option<Rc<dyn Fn(HandlerPanic)>>,
+
+  /// A handler receiving the [`CreationMetrics`] of the webview once its first page is loaded.
+  ///
+  /// See [`WebViewBuilder::with_creation_metrics_handler`].
+  pub creation_metrics_handler: Option<Box<dyn Fn(CreationMetrics)>>,
+
+  /// A handler called once the webview has painted its first page.
+  ///
+  /// See [`WebViewBuilder::with_first_paint_handler`].
+  pub first_paint_handler: Option<Box<dyn Fn()>>,
+
+  /// Whether a visible webview is kept hidden until it has painted its first page.
+  ///
+  /// See [`WebViewBuilder::with_show_after_first_paint`].
+  pub show_after_first_paint: bool,
+
+  /// The origins of the pages which can send and receive the messages of
+  /// [`WebContext::broadcast`].
+  ///
+  /// See [`WebViewBuilder::with_broadcast_origins`].
+  pub broadcast_origins: Vec<UrlPattern>,
+
+  /// Whether the `BroadcastChannel` of the pages is replaced by one sending its messages through
+  /// [`WebContext::broadcast`].
+  ///
+  /// See [`WebViewBuilder::with_broadcast_channel_polyfill`].
+  pub broadcast_channel_polyfill: bool,
+
   /// Set a proxy configuration for the webview. Supports HTTP CONNECT and SOCKSv5 proxies
   ///
   /// - **macOS**: Requires macOS 14.0+ and the `mac-proxy` feature flag to be enabled.
@@ -511,13 +1030,18 @@ pub struct WebViewAttributes<'a> {
   ///
   /// ## Platform-specific:
   ///
-  /// - **macOS / Android / iOS:** Unsupported.
+  /// - **iOS:** Unsupported.
   pub focused: bool,
 
   /// The webview bounds. Defaults to `x: 0, y: 0, width: 200, height: 200`.
   /// This is only effective if the webview was created by [`WebView::new_as_child`] or [`WebViewBuilder::new_as_child`]
   /// or on Linux, if was created by [`WebViewExtUnix::new_gtk`] or [`WebViewBuilderExtUnix::new_gtk`] with [`gtk::Fixed`].
   pub bounds: Option<Rect>,
+
+  /// A handler called with the new bounds of the webview when they change.
+  ///
+  /// See [`WebViewBuilder::with_bounds_changed_handler`].
+  pub bounds_changed_handler: Option<Box<dyn Fn(Rect)>>,
 }
 
 impl<'a> Default for WebViewAttributes<'a> {
@@ -532,14 +1056,39 @@ impl<'a> Default for WebViewAttributes<'a> {
       url: None,
       headers: None,
       html: None,
+      blank_bootstrap: None,
       initialization_scripts: Default::default(),
+      user_style_sheets: Vec::new(),
       custom_protocols: Default::default(),
+      custom_protocol_configs: HashMap::new(),
       ipc_handler: None,
       drag_drop_handler: None,
       navigation_handler: None,
       download_started_handler: None,
       download_completed_handler: None,
       new_window_req_handler: None,
+      navigation_allowlist: Vec::new(),
+      ipc_allowed_origins: Vec::new(),
+      ipc_limits: None,
+      ipc_dropped_handler: None,
+      external_links_in_browser: false,
+      window_activation_handler: None,
+      persistent_headers: false,
+      auth_session: None,
+      drag_region_support: false,
+      hit_test_ignore_regions: Vec::new(),
+      isolated_world: false,
+      virtual_authenticator: false,
+      strict_validation: false,
+      minimum_engine_version: None,
+      content_security_policy: None,
+      javascript_enabled: true,
+      images_enabled: true,
+      media_keeps_display_awake: false,
+      cache_mode: CacheMode::Default,
+      file_access: FileAccessPolicy::Default,
+      path_normalization: PathNormalization::Raw,
+      forced_dark_content: false,
       clipboard: false,
       #[cfg(debug_assertions)]
       devtools: true,
@@ -549,22 +1098,129 @@ impl<'a> Default for WebViewAttributes<'a> {
       accept_first_mouse: false,
       back_forward_navigation_gestures: false,
       document_title_changed_handler: None,
+      before_unload_handler: None,
+      close_requested_handler: None,
+      permission_handler: None,
+      process_failed_handler: None,
+      visibility_changed_handler: None,
+      text_input_handler: None,
+      media_session_handler: None,
+      virtual_keyboard_policy: VirtualKeyboardPolicy::default(),
+      ui_strings: HashMap::new(),
+      ui_layout_direction: UiLayoutDirection::default(),
       incognito: false,
+      data_directory: None,
+      profile: None,
+      ephemeral_storage: false,
       autoplay: true,
       on_page_load_handler: None,
+      event_handler: None,
+      panic_handler: None,
+      creation_metrics_handler: None,
+      first_paint_handler: None,
+      show_after_first_paint: false,
+      broadcast_origins: Vec::new(),
+      broadcast_channel_polyfill: false,
       proxy_config: None,
       focused: true,
       bounds: Some(Rect {
         position: dpi::LogicalPosition::new(0, 0).into(),
         size: dpi::LogicalSize::new(200, 200).into(),
       }),
+      bounds_changed_handler: None,
     }
   }
 }
 
+type DownloadStartedHandlerWithRawPath = Box<dyn FnMut(String, &Path, &mut PathBuf) -> bool>;
+type DownloadCompletedHandlerWithRawPath =
+  Rc<dyn Fn(String, Option<PathBuf>, Option<PathBuf>, bool)>;
+
 struct WebviewBuilderParts<'a> {
   attrs: WebViewAttributes<'a>,
   platform_specific: PlatformSpecificWebViewAttributes,
+  /// The id of the webview, shared with the handlers registered with the `*_with_id` methods.
+  ///
+  /// It is only known after the webview is created if [`WebViewAttributes::id`] is not set.
+  webview_id: Rc<OnceCell<String>>,
+  /// The scripts of wry defining or patching globals of the page, e.g. `navigator.mediaSession`,
+  /// which run in the page world even with [`WebViewAttributes::isolated_world`], see
+  /// [`WebviewBuilderParts::apply_page_world_scripts`].
+  page_world_scripts: Vec<String>,
+  /// See [`WebViewBuilder::with_download_started_handler_with_raw_path`].
+  download_started_handler_with_raw_path: Option<DownloadStartedHandlerWithRawPath>,
+  /// See [`WebViewBuilder::with_download_completed_handler_with_raw_path`].
+  download_completed_handler_with_raw_path: Option<DownloadCompletedHandlerWithRawPath>,
+  /// The servers of [`WebViewBuilder::with_loopback_websocket`], kept by the [`RuntimeHandlers`]
+  /// of the webview.
+  #[cfg(feature = "protocol")]
+  loopback_servers: Vec<loopback::LoopbackServer>,
+  /// The key of the webview in the webviews of its thread, allocated before the webview is created
+  /// so the handlers can reach it with a [`WebViewProxy`].
+  key: u32,
+}
+
+/// A webview whose attributes are prepared, completed once the platform webview is created, see
+/// [`WebviewBuilderParts::prepare`].
+struct PreparedWebView {
+  key: u32,
+  handlers: Rc<RuntimeHandlers>,
+  webview_id: Rc<OnceCell<String>>,
+  creation_timer: Option<Rc<CreationTimer>>,
+  /// Whether the content of the webview is hidden until its first paint,
+  /// see [`WebviewBuilderParts::apply_show_after_first_paint`].
+  #[cfg(any(gtk, target_os = "macos"))]
+  hide_content: bool,
+  /// See [`WebViewAttributes::hit_test_ignore_regions`].
+  hit_test_ignore_regions: Vec<Rect>,
+}
+
+impl PreparedWebView {
+  fn finish(self, webview: InnerWebView) -> WebView {
+    if let Some(timer) = self.creation_timer {
+      timer.webview_created();
+    }
+    #[cfg(any(gtk, target_os = "macos"))]
+    if self.hide_content {
+      let _ = webview.set_content_visible(false);
+    }
+    if !self.hit_test_ignore_regions.is_empty() {
+      let _ = webview.set_hit_test_ignore_regions(self.hit_test_ignore_regions);
+    }
+    let _ = self.webview_id.set(webview.id().to_string());
+    WebView::from_inner(webview, self.handlers, self.key)
+  }
+}
+
+/// The schemes handled by the webviews themselves, which can't be custom protocols.
+#[cfg(feature = "protocol")]
+const RESERVED_SCHEMES: &[&str] = &["http", "https", "file", "blob"];
+
+/// Check that `name` is a valid [RFC 3986] scheme that can be registered as a custom protocol.
+///
+/// [RFC 3986]: https://datatracker.ietf.org/doc/html/rfc3986#section-3.1
+#[cfg(feature = "protocol")]
+fn validate_scheme(name: &str) -> Result<()> {
+  let invalid = |reason| Err(Error::InvalidScheme(name.to_string(), reason));
+
+  let mut chars = name.chars();
+  match chars.next() {
+    None => return invalid("the scheme is empty"),
+    Some(c) if !c.is_ascii_alphabetic() => return invalid("the scheme must start with a letter"),
+    _ => {}
+  }
+  if !chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')) {
+    return invalid("the scheme may only contain letters, digits, `+`, `-` and `.`");
+  }
+  // WebKit and WebView2 lowercase the scheme of the URLs, so the handler would never be called
+  if name.chars().any(|c| c.is_ascii_uppercase()) {
+    return invalid("the scheme must be lowercase");
+  }
+  if RESERVED_SCHEMES.contains(&name) {
+    return invalid("the scheme is handled by the webview");
+  }
+
+  Ok(())
 }
 
 /// Builder type of [`WebView`].
@@ -584,6 +1240,13 @@ impl<'a> WebViewBuilder<'a> {
         attrs: WebViewAttributes::default(),
         #[allow(clippy::default_constructed_unit_structs)]
         platform_specific: PlatformSpecificWebViewAttributes::default(),
+        webview_id: Default::default(),
+        page_world_scripts: Vec::new(),
+        download_started_handler_with_raw_path: None,
+        download_completed_handler_with_raw_path: None,
+        #[cfg(feature = "protocol")]
+        loopback_servers: Vec::new(),
+        key: WEBVIEW_KEY.fetch_add(1, Ordering::Relaxed),
       }),
     }
   }
@@ -598,6 +1261,13 @@ impl<'a> WebViewBuilder<'a> {
         attrs,
         #[allow(clippy::default_constructed_unit_structs)]
         platform_specific: PlatformSpecificWebViewAttributes::default(),
+        webview_id: Default::default(),
+        page_world_scripts: Vec::new(),
+        download_started_handler_with_raw_path: None,
+        download_completed_handler_with_raw_path: None,
+        #[cfg(feature = "protocol")]
+        loopback_servers: Vec::new(),
+        key: WEBVIEW_KEY.fetch_add(1, Ordering::Relaxed),
       }),
     }
   }
@@ -609,6 +1279,13 @@ impl<'a> WebViewBuilder<'a> {
         attrs,
         #[allow(clippy::default_constructed_unit_structs)]
         platform_specific: PlatformSpecificWebViewAttributes::default(),
+        webview_id: Default::default(),
+        page_world_scripts: Vec::new(),
+        download_started_handler_with_raw_path: None,
+        download_completed_handler_with_raw_path: None,
+        #[cfg(feature = "protocol")]
+        loopback_servers: Vec::new(),
+        key: WEBVIEW_KEY.fetch_add(1, Ordering::Relaxed),
       }),
     }
   }
@@ -679,6 +1356,65 @@ impl<'a> WebViewBuilder<'a> {
     })
   }
 
+  /// Sets whether the webview, if visible, is kept hidden until it has painted its first page, see
+  /// [`Self::with_first_paint_handler`], to avoid showing a blank page while it loads.
+  ///
+  /// The webview is also shown once its first page finished loading, e.g. if it fails to load.
+  /// Combine it with [`Self::with_background_color`] to avoid a flash of the default background
+  /// color.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Linux / macOS**: The content of the webview is transparent until its first paint.
+  /// - **Windows / Android / iOS**: The webview is hidden with [`WebView::set_visible`], so the page
+  ///   isn't painted until it is shown, and the webview is shown once its content is loaded
+  ///   instead.
+  ///
+  /// Default is `false`.
+  pub fn with_show_after_first_paint(self, enabled: bool) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.show_after_first_paint = enabled;
+      Ok(b)
+    })
+  }
+
+  /// Add the `window.wry.broadcast` and `window.wry.onBroadcast` API of [`WebContext::broadcast`] to
+  /// the pages whose origin matches one of `origins`, e.g. `wry://localhost`, so they can send and
+  /// receive the messages of the other webviews of the [`WebContext`]. The messages of the pages of
+  /// other origins are dropped and they receive none.
+  ///
+  /// The API is only added to the webviews created with a [`WebContext`]. Default is no origin.
+  pub fn with_broadcast_origins(self, origins: Vec<String>) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.broadcast_origins = origins
+        .iter()
+        .map(|origin| UrlPattern::new(origin))
+        .collect();
+      Ok(b)
+    })
+  }
+
+  /// Sets whether the [`BroadcastChannel`] of the pages is replaced by one sending its messages to
+  /// the other webviews of the [`WebContext`] with [`WebContext::broadcast`], for the webviews that
+  /// can't share a native one.
+  ///
+  /// The webviews sharing a data store communicate with the native `BroadcastChannel`, which is
+  /// the case for the webviews created with the same [`WebContext`], unless they are incognito or
+  /// use their own data directory or data store. The polyfill must be enabled on all the webviews
+  /// of the context that communicate together, with their origins allowed with
+  /// [`Self::with_broadcast_origins`], its messages must be serializable to JSON and it has no
+  /// effect on the webviews created without a context.
+  ///
+  /// Default is `false`.
+  ///
+  /// [`BroadcastChannel`]: https://developer.mozilla.org/docs/Web/API/BroadcastChannel
+  pub fn with_broadcast_channel_polyfill(self, enabled: bool) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.broadcast_channel_polyfill = enabled;
+      Ok(b)
+    })
+  }
+
   /// Sets whether all media can be played without user interaction.
   pub fn with_autoplay(self, autoplay: bool) -> Self {
     self.and_then(|mut b| {
@@ -687,6 +1423,128 @@ impl<'a> WebViewBuilder<'a> {
     })
   }
 
+  /// Apply the style sheet `css` to every document loaded by the webview, or only to the
+  /// documents of the main frame if `main_frame_only` is `true`. It can be removed later with
+  /// [`WebView::remove_css`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux**: The style sheet is a native user style sheet.
+  /// - **macOS / iOS / Windows / Android**: The style sheet is injected as a `<style>` element
+  ///   appended to the `<head>` of the documents, so it applies to the documents that have
+  ///   JavaScript enabled.
+  /// - **Android**: Follows the limitations of [`Self::with_initialization_script`].
+  pub fn with_user_style_sheet(self, css: impl Into<String>, main_frame_only: bool) -> Self {
+    self.and_then(|mut b| {
+      b.attrs
+        .user_style_sheets
+        .push((css.into(), main_frame_only));
+      Ok(b)
+    })
+  }
+
+  /// Prevent the display from sleeping while the webview is playing media, which is detected
+  /// with the audio playback state of the webview.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: Uses `SetThreadExecutionState`.
+  /// - **Linux**: Uses [`gtk::Application::inhibit`], so the webview must be in a window of a
+  ///   [`gtk::Application`].
+  /// - **macOS**: Uses an IOKit power assertion.
+  /// - **iOS**: Disables the idle timer of the app.
+  /// - **Android**: Unsupported.
+  pub fn with_media_keeps_display_awake(self, keep_awake: bool) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.media_keeps_display_awake = keep_awake;
+      Ok(b)
+    })
+  }
+
+  /// Set how the webview uses the HTTP cache when loading resources.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux**: [`CacheMode::NoCache`] sets the cache model of the web context to
+  ///   `DocumentViewer`, which disables caching for all the webviews of the context. [`CacheMode::CacheOnly`] is unsupported.
+  /// - **Windows**: [`CacheMode::NoCache`] disables the cache through the DevTools protocol.
+  ///   [`CacheMode::CacheOnly`] is unsupported.
+  /// - **macOS / iOS**: Only applies to the navigations started with [`WebViewBuilder::with_url`],
+  ///   [`WebView::load_url`] and their `with_headers` variants.
+  pub fn with_cache_mode(self, cache_mode: CacheMode) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.cache_mode = cache_mode;
+      Ok(b)
+    })
+  }
+
+  /// Set whether the webview can load `file://` URLs and what the pages loaded from them can
+  /// access, see [`FileAccessPolicy`].
+  ///
+  /// [`FileAccessPolicy::Deny`] blocks the navigations to `file://` URLs, including the
+  /// navigations started with [`WebViewBuilder::with_url`] and [`WebView::load_url`], on top of
+  /// the settings of the platform.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: [`FileAccessPolicy::AllowFiles`] and [`FileAccessPolicy::Universal`] both add
+  ///   the `--allow-file-access-from-files` browser argument, which grants the universal access.
+  ///   The browser arguments are shared by all the webviews of the same data directory and are
+  ///   replaced by [`WebViewBuilderExtWindows::with_additional_browser_args`].
+  /// - **macOS / iOS**: Uses the private `allowFileAccessFromFileURLs` and
+  ///   `allowUniversalAccessFromFileURLs` settings of WebKit.
+  /// - **Android**: [`FileAccessPolicy::Deny`] doesn't block `file:///android_asset/` and
+  ///   `file:///android_res/`.
+  pub fn with_file_access(self, policy: FileAccessPolicy) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.file_access = policy;
+      Ok(b)
+    })
+  }
+
+  /// Darken the content of the pages, so pages that only have a light theme are rendered in dark
+  /// mode.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: Uses the automatic dark mode of Chromium.
+  /// - **Android**: Same as [`WebViewBuilderExtAndroid::with_force_dark`] with [`Theme::Dark`],
+  ///   which takes precedence if set.
+  /// - **Linux / macOS / iOS**: The engines don't support darkening the content, so the pages are
+  ///   inverted with a user style sheet, see [`Self::with_user_style_sheet`]. The images and videos
+  ///   are inverted back.
+  pub fn with_forced_dark_content(self, forced_dark_content: bool) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.forced_dark_content = forced_dark_content;
+      Ok(b)
+    })
+  }
+
+  /// Sets whether the scripts of the pages are run. The initialization scripts and
+  /// [`WebView::evaluate_script`] are not affected.
+  ///
+  /// Defaults to `true`.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Android**: Disabling JavaScript also disables the initialization scripts and [`WebView::evaluate_script`].
+  pub fn with_javascript_enabled(self, enabled: bool) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.javascript_enabled = enabled;
+      Ok(b)
+    })
+  }
+
+  /// Sets whether the images of the pages are loaded.
+  ///
+  /// Defaults to `true`.
+  pub fn with_images_enabled(self, enabled: bool) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.images_enabled = enabled;
+      Ok(b)
+    })
+  }
+
   /// Initialize javascript code when loading new pages. When webview load a new page, this
   /// initialization code will be executed. It is guaranteed that code is executed before
   /// `window.onload`.
@@ -696,6 +1554,10 @@ impl<'a> WebViewBuilder<'a> {
   /// - **Android:** When [addDocumentStartJavaScript] is not supported,
   /// we prepend them to each HTML head (implementation only supported on custom protocol URLs).
   /// For remote URLs, we use [onPageStarted] which is not guaranteed to run before other scripts.
+  /// - **Windows:** The scripts larger than 2 MB are split in smaller scripts and evaluated with
+  /// `eval`, so they need `unsafe-eval` in the Content-Security-Policy of the pages. Building the
+  /// webview fails with [`Error::InitScriptTooLarge`] when the policy set with
+  /// [`Self::with_content_security_policy`] doesn't allow it.
   ///
   /// [addDocumentStartJavaScript]: https://developer.android.com/reference/androidx/webkit/WebViewCompat#addDocumentStartJavaScript(android.webkit.WebView,java.lang.String,java.util.Set%3Cjava.lang.String%3E)
   /// [onPageStarted]: https://developer.android.com/reference/android/webkit/WebViewClient#onPageStarted(android.webkit.WebView,%20java.lang.String,%20android.graphics.Bitmap)
@@ -715,6 +1577,10 @@ impl<'a> WebViewBuilder<'a> {
   ///
   /// When registering a custom protocol with the same name, only the last regisered one will be used.
   ///
+  /// The name must be a lowercase [RFC 3986](https://datatracker.ietf.org/doc/html/rfc3986#section-3.1)
+  /// scheme other than `http`, `https`, `file` and `blob`, otherwise building the webview returns
+  /// [`Error::InvalidScheme`].
+  ///
   /// # Warning
   ///
   /// Pages loaded from custom protocol will have different Origin on different platforms. And
@@ -736,9 +1602,11 @@ impl<'a> WebViewBuilder<'a> {
   #[cfg(feature = "protocol")]
   pub fn with_custom_protocol<F>(self, name: String, handler: F) -> Self
   where
-    F: Fn(WebViewId, Request<Vec<u8>>) -> Response<Cow<'static, [u8]>> + 'static,
+    F: Fn(WebViewId, Request<Vec<u8>>) -> Response<Cow<'static, [u8]>> + MaybeSend + 'static,
   {
     self.and_then(|mut b| {
+      validate_scheme(&name)?;
+
       #[cfg(any(
         target_os = "linux",
         target_os = "dragonfly",
@@ -746,8 +1614,8 @@ impl<'a> WebViewBuilder<'a> {
         target_os = "netbsd",
         target_os = "openbsd",
       ))]
-      if let Some(context) = &mut b.attrs.context {
-        context.register_custom_protocol(name.clone())?;
+      if let Some(context) = &b.attrs.context {
+        context.check_custom_protocol(&name)?;
       }
 
       if b.attrs.custom_protocols.iter().any(|(n, _)| n == &name) {
@@ -758,7 +1626,7 @@ impl<'a> WebViewBuilder<'a> {
         name,
         Box::new(move |id, request, responder| {
           let http_response = handler(id, request);
-          responder.respond(http_response);
+          let _ = responder.respond(http_response);
         }),
       );
 
@@ -770,6 +1638,8 @@ impl<'a> WebViewBuilder<'a> {
   ///
   /// When registering a custom protocol with the same name, only the last regisered one will be used.
   ///
+  /// The name must be a valid scheme, see [`Self::with_custom_protocol`].
+  ///
   /// # Examples
   ///
   /// ```no_run
@@ -781,16 +1651,18 @@ impl<'a> WebViewBuilder<'a> {
   ///     // e.g. downloading files, opening the camera...
   ///     std::thread::spawn(move || {
   ///       std::thread::sleep(std::time::Duration::from_secs(2));
-  ///       responder.respond(http::Response::builder().body(Vec::new()).unwrap());
+  ///       let _ = responder.respond(http::Response::builder().body(Vec::new()).unwrap());
   ///     });
   ///   });
   /// ```
   #[cfg(feature = "protocol")]
   pub fn with_asynchronous_custom_protocol<F>(self, name: String, handler: F) -> Self
   where
-    F: Fn(WebViewId, Request<Vec<u8>>, RequestAsyncResponder) + 'static,
+    F: Fn(WebViewId, Request<Vec<u8>>, RequestAsyncResponder) + MaybeSend + 'static,
   {
     self.and_then(|mut b| {
+      validate_scheme(&name)?;
+
       #[cfg(any(
         target_os = "linux",
         target_os = "dragonfly",
@@ -798,8 +1670,8 @@ impl<'a> WebViewBuilder<'a> {
         target_os = "netbsd",
         target_os = "openbsd",
       ))]
-      if let Some(context) = &mut b.attrs.context {
-        context.register_custom_protocol(name.clone())?;
+      if let Some(context) = &b.attrs.context {
+        context.check_custom_protocol(&name)?;
       }
 
       if b.attrs.custom_protocols.iter().any(|(n, _)| n == &name) {
@@ -812,15 +1684,125 @@ impl<'a> WebViewBuilder<'a> {
     })
   }
 
-  /// Set the IPC handler to receive the message from Javascript on webview
-  /// using `window.ipc.postMessage("insert_message_here")` to host Rust code.
+  /// Limit the time the handler of the custom protocol `name` can take to respond, and how many of
+  /// its requests can be in flight, see [`CustomProtocolConfig`].
   ///
-  /// ## Platform-specific
+  /// The limits apply to each webview separately, and only when `name` is registered with
+  /// [`Self::with_custom_protocol`], [`Self::with_asynchronous_custom_protocol`] or on the
+  /// [`WebContext`].
+  ///
+  /// ```no_run
+  /// use std::time::Duration;
+  /// use wry::{CustomProtocolConfig, WebViewBuilder};
+  ///
+  /// WebViewBuilder::new()
+  ///   .with_asynchronous_custom_protocol("db".into(), |_webview_id, _request, _responder| {
+  ///     // query the database on another thread
+  ///   })
+  ///   .with_custom_protocol_config(
+  ///     "db".into(),
+  ///     CustomProtocolConfig {
+  ///       timeout: Some(Duration::from_secs(30)),
+  ///       max_concurrent: Some(8),
+  ///     },
+  ///   );
+  /// ```
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux**: The protocols registered on the [`WebContext`] aren't limited.
+  #[cfg(feature = "protocol")]
+  pub fn with_custom_protocol_config(self, name: String, config: CustomProtocolConfig) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.custom_protocol_configs.insert(name, config);
+      Ok(b)
+    })
+  }
+
+  /// Serve the `custom_scheme` custom protocol from a running development server, e.g. Vite or
+  /// webpack, so the app is developed on the origin it uses in production and the code depending
+  /// on it, like the storage and the cookies, behaves the same.
+  ///
+  /// The requests are forwarded to `dev_url`, e.g. `http://localhost:5173`, which must be an `http`
+  /// URL. The custom protocols can't upgrade their requests to WebSockets, so the WebSockets opened
+  /// by the pages to their own origin, e.g. for hot module replacement, connect to the server
+  /// instead.
+  ///
+  /// See [`Self::with_custom_protocol`] for the origin of the custom protocols on each platform.
+  #[cfg(feature = "protocol")]
+  pub fn with_dev_url_proxy(self, dev_url: &str, custom_scheme: &str) -> Self {
+    match dev_proxy::DevServerUrl::parse(dev_url) {
+      Ok(server) => self
+        .with_initialization_script(&server.websocket_script(custom_scheme))
+        .with_asynchronous_custom_protocol(custom_scheme.to_string(), server.handler()),
+      Err(e) => self.and_then(|_| Err(e)),
+    }
+  }
+
+  /// Serve the `custom_scheme` custom protocol from another process listening on a local socket,
+  /// so an app can be split in processes without running an HTTP server on localhost.
+  ///
+  /// `path` is the path of a Unix domain socket, or the path of a named pipe on Windows, e.g.
+  /// `\\.\pipe\my-app`. Each request is sent on a new connection as an HTTP/1.1 request with
+  /// `Connection: close`, and the response is read until the process closes the connection, so
+  /// the process can use any HTTP server library. The responses are buffered before being handed
+  /// to the webview. The requests failing to reach the process are answered with
+  /// `502 Bad Gateway`.
+  ///
+  /// Any process of the user can listen on the socket, so the app should create it in a directory
+  /// only the user can access, or check the process at the other end.
+  ///
+  /// See [`Self::with_custom_protocol`] for the origin of the custom protocols on each platform.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: The requests made while all the instances of the named pipe are busy are
+  ///   answered with `502 Bad Gateway`, so the process should create enough instances.
+  #[cfg(feature = "protocol")]
+  pub fn with_local_socket_protocol(self, custom_scheme: &str, path: impl Into<PathBuf>) -> Self {
+    let socket = local_socket::LocalSocket::new(path.into());
+    self.with_asynchronous_custom_protocol(custom_scheme.to_string(), socket.handler())
+  }
+
+  /// Accept the WebSockets opened by the pages of the `custom_scheme` custom protocol with
+  /// `handler`.
+  ///
+  /// The custom protocols can't upgrade their requests to WebSockets, so a TCP listener is bound to
+  /// an ephemeral port of `127.0.0.1` and the WebSockets opened by the pages to their own origin, or
+  /// to a `custom_scheme` URL, e.g. `new WebSocket("wry://localhost/chat")`, connect to it instead.
+  /// `handler` receives each [`LoopbackConnection`], which reads the whole upgrade request, so it
+  /// can be handed to a WebSocket library, e.g. `tungstenite::accept`. It is called on the thread
+  /// of the listener and must not block, e.g. it moves the connection to its own thread.
+  ///
+  /// The connections must carry a random token, added to the `wry_token` query parameter of their
+  /// URL by the pages, and the others are refused, so other local processes can't connect. The
+  /// listener is closed when the webview is dropped, or when the builder is dropped if the webview
+  /// isn't built.
+  ///
+  /// See [`Self::with_custom_protocol`] for the origin of the custom protocols on each platform.
+  #[cfg(feature = "protocol")]
+  pub fn with_loopback_websocket<F>(self, custom_scheme: &str, handler: F) -> Self
+  where
+    F: Fn(LoopbackConnection) + Send + 'static,
+  {
+    self.and_then(|mut b| {
+      let server = loopback::LoopbackServer::bind(handler)?;
+      b.page_world_scripts
+        .push(server.websocket_script(custom_scheme));
+      b.loopback_servers.push(server);
+      Ok(b)
+    })
+  }
+
+  /// Set the IPC handler to receive the message from Javascript on webview
+  /// using `window.ipc.postMessage("insert_message_here")` to host Rust code.
+  ///
+  /// ## Platform-specific
   ///
   /// - **Linux / Android**: The request URL is not supported on iframes and the main frame URL is used instead.
   pub fn with_ipc_handler<F>(self, handler: F) -> Self
   where
-    F: Fn(Request<String>) + 'static,
+    F: Fn(Request<String>) + MaybeSend + 'static,
   {
     self.and_then(|mut b| {
       b.attrs.ipc_handler = Some(Box::new(handler));
@@ -828,6 +1810,20 @@ impl<'a> WebViewBuilder<'a> {
     })
   }
 
+  /// Same as [`Self::with_ipc_handler`] but the handler also receives the id of the webview.
+  pub fn with_ipc_handler_with_id<F>(self, handler: F) -> Self
+  where
+    F: Fn(WebViewId, Request<String>) + MaybeSend + 'static,
+  {
+    self.and_then(|mut b| {
+      let id = b.webview_id.clone();
+      b.attrs.ipc_handler = Some(Box::new(move |request| {
+        handler(id.get().map(String::as_str).unwrap_or_default(), request)
+      }));
+      Ok(b)
+    })
+  }
+
   /// Set a handler closure to process incoming [`DragDropEvent`] of the webview.
   ///
   /// # Blocking OS Default Behavior
@@ -839,7 +1835,7 @@ impl<'a> WebViewBuilder<'a> {
   #[cfg_attr(docsrs, doc(cfg(feature = "drag-drop")))]
   pub fn with_drag_drop_handler<F>(self, handler: F) -> Self
   where
-    F: Fn(DragDropEvent) -> bool + 'static,
+    F: Fn(DragDropEvent) -> bool + MaybeSend + 'static,
   {
     self.and_then(|mut b| {
       b.attrs.drag_drop_handler = Some(Box::new(handler));
@@ -847,6 +1843,28 @@ impl<'a> WebViewBuilder<'a> {
     })
   }
 
+  /// Set how the paths passed to the drag drop handler, the download handlers and the
+  /// [`WebViewEvent::DownloadCompleted`] events are normalized, see [`PathNormalization`].
+  ///
+  /// The paths as reported by the platform stay available in [`DragDropEvent`],
+  /// [`WebViewEvent::DownloadCompleted`] and the download handlers set with
+  /// [`Self::with_download_started_handler_with_raw_path`] and
+  /// [`Self::with_download_completed_handler_with_raw_path`]. The destination of a download is
+  /// only changed when the download started handler assigns a new path.
+  ///
+  /// Default is [`PathNormalization::Raw`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / Linux / iOS / Android**: The paths are never prefixed, so both policies are the
+  ///   same.
+  pub fn with_path_normalization(self, normalization: PathNormalization) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.path_normalization = normalization;
+      Ok(b)
+    })
+  }
+
   /// Load the provided URL with given headers when the builder calling [`WebViewBuilder::build`] to create the [`WebView`].
   /// The provided URL must be valid.
   ///
@@ -875,6 +1893,25 @@ impl<'a> WebViewBuilder<'a> {
     })
   }
 
+  /// Send the headers of [`WebViewBuilder::with_headers`] and [`WebView::load_url_with_headers`]
+  /// with all the navigations to the origin of the URL they were loaded with, including the
+  /// redirections and the navigations started by the page, instead of only the first request.
+  ///
+  /// The headers are never sent to other origins. Loading a URL without headers stops sending them.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: The headers are added to all the document requests to the origin,
+  ///   including the iframes.
+  /// - **Linux / macOS / iOS / Android**: The `GET` navigations of the main frame that don't have
+  ///   the headers are canceled and started again with the headers.
+  pub fn with_persistent_headers(self, persistent: bool) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.persistent_headers = persistent;
+      Ok(b)
+    })
+  }
+
   /// Set headers used when loading the requested [`url`](Self::with_url).
   pub fn with_headers(self, headers: http::HeaderMap) -> Self {
     self.and_then(|mut b| {
@@ -892,7 +1929,8 @@ impl<'a> WebViewBuilder<'a> {
   ///
   /// ## PLatform-specific:
   ///
-  /// - **Windows:** the string can not be larger than 2 MB (2 * 1024 * 1024 bytes) in total size
+  /// - **Windows:** The strings larger than the 2 MB supported by WebView2 are served from an
+  ///   internal `https://wry-html.localhost/` URL instead, which is then the origin of the page.
   pub fn with_html(self, html: impl Into<String>) -> Self {
     self.and_then(|mut b| {
       b.attrs.html = Some(html.into());
@@ -900,6 +1938,43 @@ impl<'a> WebViewBuilder<'a> {
     })
   }
 
+  /// Load `about:blank` and build the document with the script `js`, for fully dynamic UIs which
+  /// never load a URL. [`Self::with_url`] and [`Self::with_html`] are ignored.
+  ///
+  /// `js` runs in its own function in the main frame, once the blank document is parsed, so it can
+  /// use `document.write` or the DOM APIs. It runs again when the page is reloaded.
+  ///
+  /// The IPC and the custom protocols work from this page, with some differences:
+  ///
+  /// - The page has an opaque origin, so the requests to the custom protocols are cross-origin and
+  ///   their responses need the `Access-Control-Allow-Origin: *` header.
+  /// - The URL of the IPC requests is `about:blank`, which must be allowed by
+  ///   [`Self::with_ipc_allowed_origins`] when it is used.
+  ///
+  /// ```no_run
+  /// use wry::WebViewBuilder;
+  ///
+  /// WebViewBuilder::new().with_blank_bootstrap(
+  ///   "document.body.textContent = 'Hello';
+  ///    window.ipc.postMessage('ready');",
+  /// );
+  /// ```
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / Android**: The custom protocols are requested from their
+  ///   `http://<scheme_name>.<path>` URLs, see [`Self::with_custom_protocol`].
+  /// - **Android**: Requires [addDocumentStartJavaScript] support, the initialization scripts are
+  ///   only prepended to the custom protocol documents otherwise.
+  ///
+  /// [addDocumentStartJavaScript]: https://developer.android.com/reference/androidx/webkit/WebViewCompat#addDocumentStartJavaScript(android.webkit.WebView,java.lang.String,java.util.Set%3Cjava.lang.String%3E)
+  pub fn with_blank_bootstrap(self, js: &str) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.blank_bootstrap = Some(js.to_string());
+      Ok(b)
+    })
+  }
+
   /// Set a custom [user-agent](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/User-Agent) for the WebView.
   ///
   /// ## Platform-specific
@@ -950,22 +2025,51 @@ impl<'a> WebViewBuilder<'a> {
   ///
   /// The closure take a `String` parameter as url and returns a `bool` to determine whether the navigation should happen.
   /// `true` allows to navigate and `false` does not.
-  pub fn with_navigation_handler(self, callback: impl Fn(String) -> bool + 'static) -> Self {
+  pub fn with_navigation_handler(
+    self,
+    callback: impl Fn(String) -> bool + MaybeSend + 'static,
+  ) -> Self {
     self.and_then(|mut b| {
       b.attrs.navigation_handler = Some(Box::new(callback));
       Ok(b)
     })
   }
 
+  /// Same as [`Self::with_navigation_handler`] but the handler also receives the id of the webview.
+  pub fn with_navigation_handler_with_id(
+    self,
+    callback: impl Fn(WebViewId, String) -> bool + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      let id = b.webview_id.clone();
+      b.attrs.navigation_handler = Some(Box::new(move |url| {
+        callback(id.get().map(String::as_str).unwrap_or_default(), url)
+      }));
+      Ok(b)
+    })
+  }
+
   /// Set a download started handler to manage incoming downloads.
   ///
   //// The closure takes two parameters, the first is a `String` representing the url being downloaded from and and the
   /// second is a mutable `PathBuf` reference that (possibly) represents where the file will be downloaded to. The latter
   /// parameter can be used to set the download location by assigning a new path to it, the assigned path _must_ be
   /// absolute. The closure returns a `bool` to allow or deny the download.
+  ///
+  /// Downloads of `blob:` URLs created in JavaScript (e.g. `<a download href="blob:...">`) also go
+  /// through this handler, with the `blob:` URL as the url and the file name of the `download`
+  /// attribute in the downloads directory of the user as the default path. Names with a directory,
+  /// such as `../file.txt`, are reduced to their file name, absolute names and `.`/`..` are
+  /// rejected, and existing files get a ` (n)` suffix instead of being overwritten.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS / Linux**: `blob:` downloads are intercepted by an initialization script that
+  ///   reads the blob and sends its content to wry, which writes it to the path set by the handler.
+  /// - **Windows**: `blob:` downloads are natively handled by WebView2.
   pub fn with_download_started_handler(
     self,
-    download_started_handler: impl FnMut(String, &mut PathBuf) -> bool + 'static,
+    download_started_handler: impl FnMut(String, &mut PathBuf) -> bool + MaybeSend + 'static,
   ) -> Self {
     self.and_then(|mut b| {
       b.attrs.download_started_handler = Some(Box::new(download_started_handler));
@@ -973,6 +2077,22 @@ impl<'a> WebViewBuilder<'a> {
     })
   }
 
+  /// Same as [`Self::with_download_started_handler`] but the handler also receives the id of the webview.
+  pub fn with_download_started_handler_with_id(
+    self,
+    mut download_started_handler: impl FnMut(WebViewId, String, &mut PathBuf) -> bool
+      + MaybeSend
+      + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      let id = b.webview_id.clone();
+      b.attrs.download_started_handler = Some(Box::new(move |url, path| {
+        download_started_handler(id.get().map(String::as_str).unwrap_or_default(), url, path)
+      }));
+      Ok(b)
+    })
+  }
+
   /// Sets a download completion handler to manage downloads that have finished.
   ///
   /// The closure is fired when the download completes, whether it was successful or not.
@@ -988,7 +2108,7 @@ impl<'a> WebViewBuilder<'a> {
   /// due to API limitations.
   pub fn with_download_completed_handler(
     self,
-    download_completed_handler: impl Fn(String, Option<PathBuf>, bool) + 'static,
+    download_completed_handler: impl Fn(String, Option<PathBuf>, bool) + MaybeSend + 'static,
   ) -> Self {
     self.and_then(|mut b| {
       b.attrs.download_completed_handler = Some(Rc::new(download_completed_handler));
@@ -996,6 +2116,57 @@ impl<'a> WebViewBuilder<'a> {
     })
   }
 
+  /// Same as [`Self::with_download_completed_handler`] but the handler also receives the id of the webview.
+  pub fn with_download_completed_handler_with_id(
+    self,
+    download_completed_handler: impl Fn(WebViewId, String, Option<PathBuf>, bool) + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      let id = b.webview_id.clone();
+      b.attrs.download_completed_handler = Some(Rc::new(move |url, path, success| {
+        download_completed_handler(
+          id.get().map(String::as_str).unwrap_or_default(),
+          url,
+          path,
+          success,
+        )
+      }));
+      Ok(b)
+    })
+  }
+
+  /// Same as [`Self::with_download_started_handler`] but the handler also receives the path as
+  /// reported by the platform, before the second path is normalized according to
+  /// [`Self::with_path_normalization`].
+  ///
+  /// This replaces the handler set with [`Self::with_download_started_handler`].
+  pub fn with_download_started_handler_with_raw_path(
+    self,
+    download_started_handler: impl FnMut(String, &Path, &mut PathBuf) -> bool + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      b.download_started_handler_with_raw_path = Some(Box::new(download_started_handler));
+      Ok(b)
+    })
+  }
+
+  /// Same as [`Self::with_download_completed_handler`] but the handler also receives the path as
+  /// reported by the platform after the path normalized according to
+  /// [`Self::with_path_normalization`].
+  ///
+  /// This replaces the handler set with [`Self::with_download_completed_handler`].
+  pub fn with_download_completed_handler_with_raw_path(
+    self,
+    download_completed_handler: impl Fn(String, Option<PathBuf>, Option<PathBuf>, bool)
+      + MaybeSend
+      + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      b.download_completed_handler_with_raw_path = Some(Rc::new(download_completed_handler));
+      Ok(b)
+    })
+  }
+
   /// Enables clipboard access for the page rendered on **Linux** and **Windows**.
   ///
   /// macOS doesn't provide such method and is always enabled by default. But your app will still need to add menu
@@ -1007,155 +2178,1034 @@ impl<'a> WebViewBuilder<'a> {
     })
   }
 
-  /// Set a new window request handler to decide if incoming url is allowed to be opened.
+  /// Only allow the webview to navigate to, or open in a new window, the URLs matching one of
+  /// `patterns`. Navigations to other URLs are blocked before the navigation handler and the new
+  /// window request handler are called, even if the page tries to navigate with JavaScript.
   ///
-  /// The closure take a `String` parameter as url and return `bool` to determine whether the window should open.
-  /// `true` allows to open and `false` does not.
-  pub fn with_new_window_req_handler(self, callback: impl Fn(String) -> bool + 'static) -> Self {
+  /// Remember to allow the URL loaded by the webview, including custom protocols URLs, e.g.
+  /// `wry://localhost` or `http://wry.localhost` on Windows and Android.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **iOS**: Also sets `limitsNavigationsToAppBoundDomains`, so the domains must be listed in
+  ///   the `WKAppBoundDomains` key of the `Info.plist` of the app. Requires iOS 14+.
+  pub fn with_navigation_allowlist(self, patterns: Vec<UrlPattern>) -> Self {
     self.and_then(|mut b| {
-      b.attrs.new_window_req_handler = Some(Box::new(callback));
+      b.attrs.navigation_allowlist = patterns;
       Ok(b)
     })
   }
 
-  /// Sets whether clicking an inactive window also clicks through to the webview. Default is `false`.
+  /// Only accept the IPC messages sent with `window.ipc.postMessage` by the pages whose origin
+  /// matches one of `origins`, e.g. `wry://localhost` or `https://*.tauri.app`, so remote content
+  /// loaded in the webview can't reach the IPC handler. The other messages are dropped, including
+  /// the ones wry sends internally, e.g. for [`Self::with_text_input_handler`].
   ///
-  /// ## Platform-specific
+  /// ## Platform-specific:
   ///
-  /// This configuration only impacts macOS.
-  pub fn with_accept_first_mouse(self, accept_first_mouse: bool) -> Self {
+  /// - **Linux / Android**: The origin of the main frame is checked instead of the one of the frame
+  ///   sending the message, so iframes are allowed when the page embedding them is.
+  pub fn with_ipc_allowed_origins(self, origins: Vec<String>) -> Self {
     self.and_then(|mut b| {
-      b.attrs.accept_first_mouse = accept_first_mouse;
+      b.attrs.ipc_allowed_origins = origins
+        .iter()
+        .map(|origin| UrlPattern::new(origin))
+        .collect();
       Ok(b)
     })
   }
 
-  /// Set a handler closure to process the change of the webview's document title.
-  pub fn with_document_title_changed_handler(self, callback: impl Fn(String) + 'static) -> Self {
+  /// Drop the IPC messages longer than `max_len` bytes, and the messages beyond
+  /// `max_msgs_per_sec` in a second, so a buggy or malicious page flooding
+  /// `window.ipc.postMessage` can't freeze the app.
+  ///
+  /// The dropped messages are reported to the handler set with
+  /// [`Self::with_ipc_dropped_handler`]. The messages of the origins not allowed by
+  /// [`Self::with_ipc_allowed_origins`] are dropped before being counted. The messages wry sends
+  /// internally, e.g. for [`Self::with_text_input_handler`], are not limited.
+  ///
+  /// `window.ipc.postMessage` also throws a `RangeError` for the messages longer than `max_len`
+  /// UTF-16 code units, so they don't cross the IPC bridge, and aren't reported.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Android**: `window.ipc.postMessage` doesn't check the length of the messages.
+  pub fn with_ipc_limits(self, max_len: usize, max_msgs_per_sec: u32) -> Self {
     self.and_then(|mut b| {
-      b.attrs.document_title_changed_handler = Some(Box::new(callback));
+      b.attrs.ipc_limits = Some(IpcLimits {
+        max_len,
+        max_msgs_per_sec,
+      });
       Ok(b)
     })
   }
 
-  /// Run the WebView with incognito mode. Note that WebContext will be ingored if incognito is
-  /// enabled.
+  /// Set a handler called with the URL of the page and the reason when an IPC message is dropped
+  /// because of the limits set with [`Self::with_ipc_limits`].
+  pub fn with_ipc_dropped_handler(
+    self,
+    handler: impl Fn(String, IpcDropReason) + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.ipc_dropped_handler = Some(Box::new(handler));
+      Ok(b)
+    })
+  }
+
+  /// Open the links leaving the app in the system browser instead of the webview.
   ///
-  /// ## Platform-specific:
+  /// The navigations to `http`, `https`, `mailto` and `tel` URLs with an origin other than the
+  /// one of [`WebViewBuilder::with_url`] or of the custom protocols, and the new window requests
+  /// (e.g. `target="_blank"` links and `window.open`) for these URLs, are opened in the browser.
+  /// The other navigations are passed to the navigation handler and the new window request handler.
   ///
-  /// - Windows: Requires WebView2 Runtime version 101.0.1210.39 or higher, does nothing on older versions,
-  /// see https://learn.microsoft.com/en-us/microsoft-edge/webview2/release-notes/archive?tabs=dotnetcsharp#10121039
-  /// - **Android:** Unsupported yet.
-  pub fn with_incognito(self, incognito: bool) -> Self {
+  /// The links are opened in the browser even if they are not allowed by
+  /// [`WebViewBuilder::with_navigation_allowlist`].
+  pub fn with_external_links_in_browser(self, enabled: bool) -> Self {
     self.and_then(|mut b| {
-      b.attrs.incognito = incognito;
+      b.attrs.external_links_in_browser = enabled;
       Ok(b)
     })
   }
 
-  /// Set a handler to process page loading events.
-  pub fn with_on_page_load_handler(
+  /// Set a handler called before wry does something bringing a window to the front, e.g. to keep
+  /// the focus on the current window or to move the window to the active monitor or space first.
+  /// Returning `false` prevents it.
+  ///
+  /// See [`WindowActivation`] for the cases the handler is called for.
+  pub fn with_window_activation_handler(
     self,
-    handler: impl Fn(PageLoadEvent, String) + 'static,
+    handler: impl Fn(WindowActivation) -> bool + MaybeSend + 'static,
   ) -> Self {
     self.and_then(|mut b| {
-      b.attrs.on_page_load_handler = Some(Box::new(handler));
+      b.attrs.window_activation_handler = Some(Box::new(handler));
       Ok(b)
     })
   }
 
-  /// Set a proxy configuration for the webview.
+  /// Use the webview for an OAuth authentication session: load `auth_url` with a non-persistent
+  /// data store, so the session doesn't share the cookies of the other webviews, and call `handler`
+  /// with the URL the provider redirects to once the user is authenticated, instead of loading it.
   ///
-  /// - **macOS**: Requires macOS 14.0+ and the `mac-proxy` feature flag to be enabled. Supports HTTP CONNECT and SOCKSv5 proxies.
-  /// - **Windows / Linux**: Supports HTTP CONNECT and SOCKSv5 proxies.
-  /// - **Android / iOS:** Not supported.
-  pub fn with_proxy_config(self, configuration: ProxyConfig) -> Self {
+  /// The redirection is the first navigation to `redirect_uri`, with the same scheme, host, port
+  /// and path whatever its query, usually a custom scheme URL, e.g. `com.example.app:/oauth`, or a
+  /// loopback URL, e.g. `http://127.0.0.1:8000/callback`. The navigations to `redirect_uri` are
+  /// blocked, even if the navigation handler allows them, and the webview is usually dropped once
+  /// `handler` is called.
+  ///
+  /// This overrides [`WebViewBuilder::with_url`] and enables
+  /// [`WebViewAttributes::ephemeral_storage`].
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Android**: The data store isn't isolated, see [`WebViewAttributes::ephemeral_storage`].
+  pub fn with_auth_session<F: FnOnce(String) + MaybeSend + 'static>(
+    self,
+    auth_url: impl Into<String>,
+    redirect_uri: impl Into<String>,
+    handler: F,
+  ) -> Self {
     self.and_then(|mut b| {
-      b.attrs.proxy_config = Some(configuration);
+      b.attrs.url = Some(auth_url.into());
+      b.attrs.headers = None;
+      b.attrs.auth_session = Some((redirect_uri.into(), Box::new(handler)));
       Ok(b)
     })
   }
 
-  /// Set whether the webview should be focused when created.
+  /// Move or resize the window when the user presses the primary mouse button on an element with
+  /// the `data-wry-drag-region` attribute, or in one of its descendants, for undecorated windows.
+  ///
+  /// The value of the attribute of the closest element with it is:
+  /// - empty or `move` to move the window,
+  /// - `n`, `ne`, `e`, `se`, `s`, `sw`, `w` or `nw` to resize the window from this edge or corner,
+  /// - `false` to opt out descendants of a drag region, e.g. buttons in a title bar.
+  ///
+  /// The regions send internal messages through the IPC bridge, which are handled by wry and not
+  /// passed to the IPC handler.
   ///
   /// ## Platform-specific:
   ///
-  /// - **macOS / Android / iOS:** Unsupported.
-  pub fn with_focused(self, focused: bool) -> Self {
+  /// - **macOS**: Resizing is unsupported.
+  /// - **Linux**: The top-level window of the webview is moved, so it isn't supported for child
+  ///   webviews created with [`WebViewBuilder::build_as_child`].
+  /// - **Android / iOS**: Unsupported.
+  pub fn with_drag_region_support(self, enabled: bool) -> Self {
     self.and_then(|mut b| {
-      b.attrs.focused = focused;
+      b.attrs.drag_region_support = enabled;
       Ok(b)
     })
   }
 
-  /// Specify the webview position relative to its parent if it will be created as a child
-  /// or if created using [`WebViewBuilderExtUnix::new_gtk`] with [`gtk::Fixed`].
+  /// Set the regions of the webview where the mouse events fall through to the window beneath
+  /// once it is created, see [`WebView::set_hit_test_ignore_regions`].
   ///
-  /// Defaults to `x: 0, y: 0, width: 200, height: 200`.
-  pub fn with_bounds(self, bounds: Rect) -> Self {
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: The webview is not drawn in the regions either.
+  /// - **Android / iOS**: Unsupported.
+  pub fn with_hit_test_ignore_regions(self, regions: Vec<Rect>) -> Self {
     self.and_then(|mut b| {
-      b.attrs.bounds = Some(bounds);
+      b.attrs.hit_test_ignore_regions = regions;
       Ok(b)
     })
   }
 
-  /// Consume the builder and create the [`WebView`] from a type that implements [`HasWindowHandle`].
+  /// Run wry's IPC bridge and the initialization scripts in an isolated JavaScript world, sharing
+  /// the DOM of the page but not its globals, so the page scripts can't tamper with the bridge or
+  /// the scripts.
   ///
-  /// # Platform-specific:
+  /// The page keeps a frozen `window.ipc.postMessage` shim, which forwards the messages to the
+  /// isolated world with a DOM event. The scripts evaluated with [`WebView::evaluate_script`] still
+  /// run in the page world, as do the scripts of wry defining or patching globals of the page,
+  /// i.e. for [`WebViewBuilder::with_media_session_handler`], [`WebContext::broadcast`] and
+  /// [`WebViewBuilder::with_loopback_websocket`], whose internal messages go through the shim.
   ///
-  /// - **Linux**: Only X11 is supported, if you want to support Wayland too, use [`WebViewBuilderExtUnix::new_gtk`].
+  /// ## Platform-specific:
   ///
-  ///   Although this methods only needs an X11 window handle, we use webkit2gtk, so you still need to initialize gtk
-  ///   by callling [`gtk::init`] and advance its loop alongside your event loop using [`gtk::main_iteration_do`].
-  ///   Checkout the [Platform Considerations](https://docs.rs/wry/latest/wry/#platform-considerations) section in the crate root documentation.
-  /// - **Windows**: The webview will auto-resize when the passed handle is resized.
-  /// - **Linux (X11)**: Unlike macOS and Windows, the webview will not auto-resize and you'll need to call [`WebView::set_bounds`] manually.
+  /// - **macOS / iOS**: Requires macOS 11+ and iOS 14+, the page world is used on older versions.
+  /// - **Windows / Android**: Unsupported.
+  pub fn with_isolated_world(self, isolated: bool) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.isolated_world = isolated;
+      Ok(b)
+    })
+  }
+
+  /// Replace the WebAuthn authenticators of the webview with a virtual one, which creates and
+  /// uses passkeys without user interaction, to test the credential flows of the app end to end.
   ///
-  /// # Panics:
+  /// The credentials are kept in memory and lost when the webview is dropped. This is meant for
+  /// the tests only.
   ///
-  /// - Panics if the provided handle was not supported or invalid.
-  /// - Panics on Linux, if [`gtk::init`] was not called in this thread.
-  pub fn build<W: HasWindowHandle>(self, window: &'a W) -> Result<WebView> {
-    let parts = self.inner?;
-
-    InnerWebView::new(window, parts.attrs, parts.platform_specific)
-      .map(|webview| WebView { webview })
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / Linux / Android / iOS**: Unsupported.
+  pub fn with_virtual_authenticator(self, enabled: bool) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.virtual_authenticator = enabled;
+      Ok(b)
+    })
   }
 
-  /// Consume the builder and create the [`WebView`] as a child window inside the provided [`HasWindowHandle`].
+  /// Apply the Content Security Policy `policy` to the documents loaded by the webview,
+  /// replacing the policy of the custom protocols responses.
   ///
-  /// ## Platform-specific
+  /// The policy is set as the `Content-Security-Policy` header of the responses of the custom
+  /// protocols registered on the builder, and injected as a
+  /// `<meta http-equiv="Content-Security-Policy">` element in every document, including remote
+  /// pages whose response headers can't be modified. Browsers ignore the `frame-ancestors`,
+  /// `report-uri` and `sandbox` directives in `<meta>` elements, and the element only applies to
+  /// the resources loaded after the initialization scripts ran.
   ///
-  /// - **Windows**: This will create the webview as a child window of the `parent` window.
-  /// - **macOS**: This will create the webview as a `NSView` subview of the `parent` window's
-  /// content view.
-  /// - **Linux**: This will create the webview as a child window of the `parent` window. Only X11
-  /// is supported. This method won't work on Wayland.
+  /// Returns [`Error::HttpError`] on build if `policy` is not a valid header value.
   ///
-  ///   Although this methods only needs an X11 window handle, you use webkit2gtk, so you still need to initialize gtk
-  ///   by callling [`gtk::init`] and advance its loop alongside your event loop using [`gtk::main_iteration_do`].
-  ///   Checkout the [Platform Considerations](https://docs.rs/wry/latest/wry/#platform-considerations) section in the crate root documentation.
+  /// ## Platform-specific:
   ///
-  ///   If you want to support child webviews on X11 and Wayland at the same time,
-  ///   we recommend using [`WebViewBuilderExtUnix::new_gtk`] with [`gtk::Fixed`].
-  /// - **Android/iOS:** Unsupported.
+  /// - **Linux**: The custom protocols registered on the [`WebContext`] are shared by webviews
+  ///   and only get the `<meta>` element.
+  /// - **Android**: The `<meta>` element is only injected in remote pages when
+  ///   [addDocumentStartJavaScript] is supported, see [`Self::with_initialization_script`].
   ///
-  /// # Panics:
+  /// [addDocumentStartJavaScript]: https://developer.android.com/reference/androidx/webkit/WebViewCompat#addDocumentStartJavaScript(android.webkit.WebView,java.lang.String,java.util.Set%3Cjava.lang.String%3E)
+  pub fn with_content_security_policy(self, policy: impl Into<String>) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.content_security_policy = Some(policy.into());
+      Ok(b)
+    })
+  }
+
+  /// Set a new window request handler to decide if incoming url is allowed to be opened.
   ///
-  /// - Panics if the provided handle was not support or invalid.
-  /// - Panics on Linux, if [`gtk::init`] was not called in this thread.
-  pub fn build_as_child<W: HasWindowHandle>(self, window: &'a W) -> Result<WebView> {
-    let parts = self.inner?;
+  /// The closure take a `String` parameter as url and return `bool` to determine whether the window should open.
+  /// `true` allows to open and `false` does not.
+  pub fn with_new_window_req_handler(
+    self,
+    callback: impl Fn(String) -> bool + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.new_window_req_handler = Some(Box::new(callback));
+      Ok(b)
+    })
+  }
 
-    InnerWebView::new_as_child(window, parts.attrs, parts.platform_specific)
-      .map(|webview| WebView { webview })
+  /// Same as [`Self::with_new_window_req_handler`] but the handler also receives the id of the webview.
+  pub fn with_new_window_req_handler_with_id(
+    self,
+    callback: impl Fn(WebViewId, String) -> bool + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      let id = b.webview_id.clone();
+      b.attrs.new_window_req_handler = Some(Box::new(move |url| {
+        callback(id.get().map(String::as_str).unwrap_or_default(), url)
+      }));
+      Ok(b)
+    })
   }
-}
 
-#[cfg(any(target_os = "macos", target_os = "ios",))]
-#[derive(Clone, Default)]
+  /// Sets whether clicking an inactive window also clicks through to the webview. Default is `false`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// This configuration only impacts macOS.
+  pub fn with_accept_first_mouse(self, accept_first_mouse: bool) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.accept_first_mouse = accept_first_mouse;
+      Ok(b)
+    })
+  }
+
+  /// Set a handler closure to process the change of the webview's document title.
+  pub fn with_document_title_changed_handler(
+    self,
+    callback: impl Fn(String) + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.document_title_changed_handler = Some(Box::new(callback));
+      Ok(b)
+    })
+  }
+
+  /// Same as [`Self::with_document_title_changed_handler`] but the handler also receives the id of the webview.
+  pub fn with_document_title_changed_handler_with_id(
+    self,
+    callback: impl Fn(WebViewId, String) + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      let id = b.webview_id.clone();
+      b.attrs.document_title_changed_handler = Some(Box::new(move |title| {
+        callback(id.get().map(String::as_str).unwrap_or_default(), title)
+      }));
+      Ok(b)
+    })
+  }
+
+  /// Set a handler deciding whether the page can be unloaded when its `beforeunload` listener
+  /// asks for a confirmation, e.g. to keep the unsaved changes of a form, instead of showing the
+  /// default dialog of the webview.
+  ///
+  /// The closure takes the URL of the page and the message of its confirmation, and returns `true`
+  /// to unload the page or `false` to stay on it. It's only called when the page asked for a
+  /// confirmation, which the webviews only allow after the user interacted with the page. The
+  /// message may be a generic one, as most engines ignore the message set by the page.
+  ///
+  /// The page is only unloaded by navigations and reloads, not when the webview is dropped, so
+  /// apps closing a window should check the state of its page themselves.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS**: Uses the private `runBeforeUnloadConfirmPanelWithMessage` method of
+  ///   `WKUIDelegate`, so the default behavior is kept on the versions of WebKit not calling it.
+  /// - **Windows**: Unsupported, the `ScriptDialogOpening` event of WebView2 requires disabling all
+  ///   the default script dialogs.
+  pub fn with_before_unload_handler(
+    self,
+    handler: impl Fn(String, String) -> bool + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.before_unload_handler = Some(Box::new(handler));
+      Ok(b)
+    })
+  }
+
+  /// Set a handler called when the page calls `window.close()`, so the app decides what to do
+  /// with the webview and its window.
+  ///
+  /// The closure returns `true` to let the webview close itself, or `false` to keep it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: Closing destroys the window containing the webview, which is also done when no
+  ///   handler is set.
+  /// - **Linux / macOS / iOS / Android**: The webviews never close themselves, so the returned
+  ///   value is ignored.
+  pub fn with_close_requested_handler(
+    self,
+    handler: impl Fn() -> bool + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.close_requested_handler = Some(Box::new(handler));
+      Ok(b)
+    })
+  }
+
+  /// Set a handler deciding whether a page is granted a permission, see [`PermissionKind`] for
+  /// the permissions going through it.
+  ///
+  /// When the handler returns [`PermissionResponse::Allow`] or
+  /// [`PermissionResponse::AllowDisplayCapture`] for a display capture, the user chooses what to
+  /// share in the picker of the platform after the page calls
+  /// `navigator.mediaDevices.getDisplayMedia()`; allowing the capture doesn't skip the picker.
+  /// [`PermissionResponse::Default`] denies the display captures.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS**: Uses the private `screenCaptureEnabled` preference and
+  ///   `requestDisplayCapturePermissionForOrigin` method of `WKUIDelegate`, `getDisplayMedia()` is
+  ///   only exposed to the pages when a handler is set and the WebKit version has them.
+  ///   [`PermissionResponse::AllowDisplayCapture`] picks whether the user shares a screen or a
+  ///   window.
+  /// - **Windows**: Uses the `ScreenCaptureStarting` event of WebView2, which requires a WebView2
+  ///   Runtime supporting `ICoreWebView2_27`. Without a handler, WebView2 shows its picker.
+  ///   [`PermissionResponse::AllowDisplayCapture`] is the same as [`PermissionResponse::Allow`].
+  /// - **Linux**: `getDisplayMedia()` is only exposed to the pages when a handler is set. The
+  ///   picker is the one of the XDG desktop portal.
+  ///   [`PermissionResponse::AllowDisplayCapture`] is the same as [`PermissionResponse::Allow`].
+  /// - **Android / iOS**: Unsupported, the webviews don't implement `getDisplayMedia()`.
+  pub fn with_permission_handler(
+    self,
+    handler: impl Fn(PermissionRequest) -> PermissionResponse + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.permission_handler = Some(Box::new(handler));
+      Ok(b)
+    })
+  }
+
+  /// Set a handler called when a process of the webview crashes or exits, e.g. to reload the page
+  /// and collect diagnostics for bug reports.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: Called for the failures of all the WebView2 processes, including the browser
+  ///   process, after which the webview must be recreated.
+  /// - **macOS / iOS**: Only called when the web content process terminates, with the
+  ///   [`ProcessFailureReason::Unknown`] reason.
+  /// - **Android**: Setting a handler keeps the app running when the renderer process is gone,
+  ///   but the webview can't be used anymore and must be recreated.
+  pub fn with_process_failed_handler(
+    self,
+    handler: impl Fn(ProcessFailure) + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.process_failed_handler = Some(Box::new(handler));
+      Ok(b)
+    })
+  }
+
+  /// Set a handler closure called when the webview becomes visible or hidden on screen, e.g. when
+  /// its window is minimized, so the app can throttle its work.
+  ///
+  /// The handler is not called with the initial state.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Linux**: The webview is hidden when its widget is unmapped, which doesn't happen when
+  ///   its window is minimized on some window managers.
+  /// - **Windows**: The webview is hidden when [`WebView::set_visible`] is called with `false`,
+  ///   or when its top-level window is minimized or hidden.
+  /// - **macOS**: The webview is hidden when [`WebView::set_visible`] is called with `false`,
+  ///   or when its window is occluded.
+  /// - **iOS**: The webview is hidden when [`WebView::set_visible`] is called with `false`.
+  /// - **Android**: Unsupported.
+  pub fn with_visibility_changed_handler(
+    self,
+    handler: impl Fn(VisibilityState) + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.visibility_changed_handler = Some(Box::new(handler));
+      Ok(b)
+    })
+  }
+
+  /// Set a handler called on the IME compositions of the text inputs of the page, and when the
+  /// virtual keyboard is shown or hidden, e.g. to move the native controls above the keyboard.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / Linux**: [`TextInputEvent::VirtualKeyboardShown`] and
+  ///   [`TextInputEvent::VirtualKeyboardHidden`] are never sent.
+  pub fn with_text_input_handler(
+    self,
+    handler: impl Fn(TextInputEvent) + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.text_input_handler = Some(Box::new(handler));
+      Ok(b)
+    })
+  }
+
+  /// Set a handler called when the main frame updates its [Media Session], its metadata, its
+  /// playback state or the actions it handles. [`WebView::media_session_action`] runs the
+  /// handlers of its actions.
+  ///
+  /// wry only relays the media session between the page and the app, it doesn't integrate with
+  /// the media controls of the system. The app can connect the media session to them itself, e.g.
+  /// to the System Media Transport Controls on Windows, `MPNowPlayingInfoCenter` on macOS and iOS,
+  /// or MPRIS on Linux.
+  ///
+  /// [Media Session]: https://developer.mozilla.org/en-US/docs/Web/API/Media_Session_API
+  pub fn with_media_session_handler(
+    self,
+    handler: impl Fn(MediaSessionEvent) + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.media_session_handler = Some(Box::new(handler));
+      Ok(b)
+    })
+  }
+
+  /// Set how the webview makes room for the virtual keyboard when a text input of the page is
+  /// focused. Defaults to [`VirtualKeyboardPolicy::Resize`].
+  ///
+  /// With [`VirtualKeyboardPolicy::Overlay`], use [`Self::with_text_input_handler`] to know the
+  /// height of the keyboard and lay out the app around it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / Linux**: Unsupported.
+  /// - **Android**: Sets the soft input mode of the window of the activity, so it applies to all
+  ///   its webviews.
+  /// - **iOS**: [`VirtualKeyboardPolicy::Resize`] is the scrolling of the content above the
+  ///   keyboard done by WebKit, which [`VirtualKeyboardPolicy::Overlay`] disables.
+  pub fn with_virtual_keyboard_policy(self, policy: VirtualKeyboardPolicy) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.virtual_keyboard_policy = policy;
+      Ok(b)
+    })
+  }
+
+  /// Translate the native UI shown by wry for the webview, e.g. the buttons of the JavaScript
+  /// dialogs. The strings which aren't in `strings` keep their default value.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / Linux / iOS**: Unsupported, the UI is shown by the webview in the language of
+  ///   the system.
+  /// - **macOS**: Only the strings of the file open panel are used.
+  /// - **Android**: Only the strings of the JavaScript dialogs are used.
+  pub fn with_ui_strings(self, strings: HashMap<UiString, String>) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.ui_strings = strings;
+      Ok(b)
+    })
+  }
+
+  /// Set the layout direction of the native UI shown by wry for the webview, e.g. to mirror the
+  /// JavaScript dialogs of an app translated to a right-to-left language on a system using a
+  /// left-to-right language. Defaults to [`UiLayoutDirection::Auto`].
+  ///
+  /// The direction applies to the same UI as [`Self::with_ui_strings`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / Linux / iOS**: Unsupported, the UI follows the language of the system, or of
+  ///   the app bundle on iOS.
+  /// - **macOS**: Only the content of the file open panel is mirrored.
+  /// - **Android**: Only the JavaScript dialogs are mirrored.
+  pub fn with_ui_layout_direction(self, direction: UiLayoutDirection) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.ui_layout_direction = direction;
+      Ok(b)
+    })
+  }
+
+  /// Run the WebView with incognito mode. Note that WebContext will be ingored if incognito is
+  /// enabled.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - Windows: Requires WebView2 Runtime version 101.0.1210.39 or higher, does nothing on older versions,
+  /// see https://learn.microsoft.com/en-us/microsoft-edge/webview2/release-notes/archive?tabs=dotnetcsharp#10121039
+  /// - **Android:** Disables cookies, DOM storage, the database storage and the HTTP cache for the webview.
+  /// Note that Android only exposes a process-wide cookie manager, so cookies are disabled for all webviews.
+  pub fn with_incognito(self, incognito: bool) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.incognito = incognito;
+      Ok(b)
+    })
+  }
+
+  /// Set the data directory of this webview, overriding the one of the [`WebContext`].
+  ///
+  /// This gives the webview its own data store (cookies, local storage, cache...), e.g. to isolate
+  /// the sessions of multiple accounts without creating a [`WebContext`] for each of them.
+  /// Ignored if incognito is enabled.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: Creates a WebView2 environment with this user data folder.
+  /// - **Linux**: Creates a new webkit2gtk context for the webview with this data directory, the
+  ///   [`WebContext`] set with [`Self::with_web_context`] is ignored.
+  /// - **macOS / iOS**: Unsupported, use `WebViewBuilderExtDarwin::with_data_store_identifier` instead.
+  /// - **Android**: Unsupported.
+  pub fn with_data_directory<P: Into<PathBuf>>(self, data_directory: P) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.data_directory = Some(data_directory.into());
+      Ok(b)
+    })
+  }
+
+  /// Use the data of `profile`, created with [`WebContext::create_profile`], for this webview.
+  ///
+  /// The data directory set with [`Self::with_data_directory`] and the data store identifier set
+  /// with `WebViewBuilderExtDarwin::with_data_store_identifier` take precedence over the profile.
+  /// Ignored if incognito is enabled.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Linux**: Uses the webkit2gtk context of the profile, shared by its webviews, the
+  ///   [`WebContext`] set with [`Self::with_web_context`] is ignored.
+  /// - **Android**: Unsupported.
+  pub fn with_profile(self, profile: &Profile) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.profile = Some(profile.clone());
+      Ok(b)
+    })
+  }
+
+  /// Use a non-persistent data store for the webview, all the data is lost when the webview is
+  /// dropped. Unlike [`Self::with_incognito`], it doesn't enable any other private browsing
+  /// behavior, e.g. on Android incognito also disables cookies and DOM storage.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Uses `WKWebsiteDataStore.nonPersistentDataStore`, same as incognito.
+  /// - **Linux**: Uses an ephemeral webkit2gtk context, same as incognito. The [`WebContext`] is
+  ///   ignored.
+  /// - **Windows**: WebView2 has no in-memory data store, so this enables InPrivate mode.
+  ///   Requires WebView2 Runtime version 101.0.1210.39 or higher.
+  /// - **Android**: Unsupported.
+  pub fn with_ephemeral_storage(self, ephemeral_storage: bool) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.ephemeral_storage = ephemeral_storage;
+      Ok(b)
+    })
+  }
+
+  /// Set a handler to process page loading events.
+  pub fn with_on_page_load_handler(
+    self,
+    handler: impl Fn(PageLoadEvent, String) + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.on_page_load_handler = Some(Box::new(handler));
+      Ok(b)
+    })
+  }
+
+  /// Get the options set on the builder that are unsupported on the current platform.
+  ///
+  /// These options are ignored when the webview is built, or make the build fail with
+  /// [`Error::UnsupportedOptions`] when [`WebViewBuilder::with_strict_validation`] is enabled.
+  pub fn validate(&self) -> Vec<UnsupportedOption> {
+    self
+      .inner
+      .as_ref()
+      .map(|parts| parts.unsupported_options())
+      .unwrap_or_default()
+  }
+
+  /// Fail to build the webview with [`Error::UnsupportedOptions`] when options unsupported on the
+  /// current platform are set, instead of ignoring them. See [`WebViewBuilder::validate`].
+  pub fn with_strict_validation(self, strict: bool) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.strict_validation = strict;
+      Ok(b)
+    })
+  }
+
+  /// Fail to build the webview with [`Error::EngineTooOld`] when the version of the engine is
+  /// older than `version`, e.g. `110.0`, instead of showing a blank page when the app relies on
+  /// features of newer engines.
+  ///
+  /// The versions are compared component by component, the missing ones being `0`, with the
+  /// [`EngineInfo::version`] of the platform, so `version` must use its format.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: The version of the WebView2 Runtime, e.g. `120.0.2210.91`.
+  /// - **Linux**: The version of WebKitGTK, e.g. `2.42.5`.
+  /// - **macOS / iOS**: The build version of WebKit, e.g. `19617.1.17.11.12`.
+  /// - **Android**: The version of the Android System WebView, e.g. `120.0.6099.230`.
+  pub fn with_minimum_engine_version(self, version: &str) -> Self {
+    self.and_then(|mut b| {
+      if version.is_empty() || version.split('.').any(|c| c.parse::<u32>().is_err()) {
+        return Err(Error::InvalidEngineVersion(version.to_string()));
+      }
+      b.attrs.minimum_engine_version = Some(version.to_string());
+      Ok(b)
+    })
+  }
+
+  /// Set a handler receiving the events of the webview as [`WebViewEvent`]s, an alternative to
+  /// registering a handler for each of them.
+  ///
+  /// The handler only observes the events: it is called before the handler set for the event, if
+  /// any, which still decides whether the navigations and new windows are allowed.
+  pub fn with_event_handler(self, handler: impl Fn(WebViewEvent) + MaybeSend + 'static) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.event_handler = Some(Rc::new(handler));
+      Ok(b)
+    })
+  }
+
+  /// Set a handler receiving the panics of the other handlers as [`HandlerPanic`]s.
+  ///
+  /// The panics of the handlers are always caught, as unwinding across the callbacks of the
+  /// platform webview is undefined behavior. The handler that panicked then falls back to its
+  /// default: the navigations, new windows and downloads are denied, and the custom protocol
+  /// requests get an empty `500` response.
+  ///
+  /// The panics of the callbacks given to the methods of [`WebView`], like
+  /// [`WebView::evaluate_script_with_callback`], are also caught and passed to this handler on the
+  /// thread of the webview.
+  pub fn with_panic_handler(self, handler: impl Fn(HandlerPanic) + MaybeSend + 'static) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.panic_handler = Some(Rc::new(handler));
+      Ok(b)
+    })
+  }
+
+  /// Set a handler receiving the [`CreationMetrics`] of the webview, the durations of the stages of
+  /// its creation, once its first page is loaded.
+  ///
+  /// This lets apps track the regressions of the webview startup time in the wild.
+  pub fn with_creation_metrics_handler(
+    self,
+    handler: impl Fn(CreationMetrics) + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.creation_metrics_handler = Some(Box::new(handler));
+      Ok(b)
+    })
+  }
+
+  /// Set a handler called once the webview has painted the content of its first page, e.g. to keep
+  /// a native splash screen visible until then and avoid a white flash.
+  ///
+  /// The handler is also called when the first page finished loading before its paint is
+  /// reported, e.g. when it fails to load.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: The first visually non-empty layout or paint reported by WebKit.
+  /// - **Windows / Linux / Android**: The second animation frame after the `DOMContentLoaded` event
+  ///   of the main frame, as the platform doesn't report the paints, reported through the IPC
+  ///   bridge with an internal message handled by wry and not passed to the IPC handler.
+  pub fn with_first_paint_handler(self, handler: impl Fn() + MaybeSend + 'static) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.first_paint_handler = Some(Box::new(handler));
+      Ok(b)
+    })
+  }
+
+  /// Same as [`Self::with_on_page_load_handler`] but the handler also receives the id of the webview.
+  pub fn with_on_page_load_handler_with_id(
+    self,
+    handler: impl Fn(WebViewId, PageLoadEvent, String) + MaybeSend + 'static,
+  ) -> Self {
+    self.and_then(|mut b| {
+      let id = b.webview_id.clone();
+      b.attrs.on_page_load_handler = Some(Box::new(move |event, url| {
+        handler(id.get().map(String::as_str).unwrap_or_default(), event, url)
+      }));
+      Ok(b)
+    })
+  }
+
+  /// Set a proxy configuration for the webview.
+  ///
+  /// - **macOS**: Requires macOS 14.0+ and the `mac-proxy` feature flag to be enabled. Supports HTTP CONNECT and SOCKSv5 proxies.
+  /// - **Windows / Linux**: Supports HTTP CONNECT and SOCKSv5 proxies.
+  /// - **Android / iOS:** Not supported.
+  pub fn with_proxy_config(self, configuration: ProxyConfig) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.proxy_config = Some(configuration);
+      Ok(b)
+    })
+  }
+
+  /// Set whether the webview should be focused when created.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS:** The webview is made the first responder of its window, disable it to keep the
+  ///   focus on the native views.
+  /// - **iOS:** Unsupported.
+  pub fn with_focused(self, focused: bool) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.focused = focused;
+      Ok(b)
+    })
+  }
+
+  /// Specify the webview position relative to its parent if it will be created as a child
+  /// or if created using [`WebViewBuilderExtUnix::new_gtk`] with [`gtk::Fixed`].
+  ///
+  /// Defaults to `x: 0, y: 0, width: 200, height: 200`.
+  pub fn with_bounds(self, bounds: Rect) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.bounds = Some(bounds);
+      Ok(b)
+    })
+  }
+
+  /// Apply the options set in a [`WebViewAttributesConfig`], like a preset loaded from a
+  /// configuration file with the `serde` feature.
+  pub fn with_attributes_config(self, config: WebViewAttributesConfig) -> Self {
+    self.and_then(|mut b| {
+      let WebViewAttributesConfig {
+        url,
+        html,
+        user_agent,
+        visible,
+        transparent,
+        background_color,
+        bounds,
+        theme,
+        focused,
+        devtools,
+        incognito,
+        autoplay,
+        clipboard,
+        javascript_enabled,
+        images_enabled,
+        zoom_hotkeys_enabled,
+        back_forward_navigation_gestures,
+        accept_first_mouse,
+        forced_dark_content,
+      } = config;
+
+      b.attrs.url = url.or(b.attrs.url);
+      b.attrs.html = html.or(b.attrs.html);
+      b.attrs.user_agent = user_agent.or(b.attrs.user_agent);
+      b.attrs.background_color = background_color.or(b.attrs.background_color);
+      b.attrs.bounds = bounds.or(b.attrs.bounds);
+      for (value, attr) in [
+        (visible, &mut b.attrs.visible),
+        (transparent, &mut b.attrs.transparent),
+        (focused, &mut b.attrs.focused),
+        (devtools, &mut b.attrs.devtools),
+        (incognito, &mut b.attrs.incognito),
+        (autoplay, &mut b.attrs.autoplay),
+        (clipboard, &mut b.attrs.clipboard),
+        (javascript_enabled, &mut b.attrs.javascript_enabled),
+        (images_enabled, &mut b.attrs.images_enabled),
+        (zoom_hotkeys_enabled, &mut b.attrs.zoom_hotkeys_enabled),
+        (
+          back_forward_navigation_gestures,
+          &mut b.attrs.back_forward_navigation_gestures,
+        ),
+        (accept_first_mouse, &mut b.attrs.accept_first_mouse),
+        (forced_dark_content, &mut b.attrs.forced_dark_content),
+      ] {
+        if let Some(value) = value {
+          *attr = value;
+        }
+      }
+
+      #[cfg(windows)]
+      if theme.is_some() {
+        b.platform_specific.theme = theme;
+      }
+      #[cfg(target_os = "android")]
+      if theme.is_some() {
+        b.platform_specific.force_dark = theme;
+      }
+      #[cfg(not(any(windows, target_os = "android")))]
+      let _ = theme;
+
+      Ok(b)
+    })
+  }
+
+  /// Set a handler called with the new bounds of the webview when the platform changes them, like
+  /// when the parent is resized or its scale factor changes, instead of polling [`WebView::bounds`].
+  ///
+  /// The bounds are the same as the ones returned by [`WebView::bounds`], and the handler is also
+  /// called when they are changed with [`WebView::set_bounds`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux**: The bounds are the allocation of the webview in its GTK container, so the
+  ///   position is always `(0, 0)` for the webviews created with a window handle.
+  /// - **Android / iOS**: Unsupported.
+  pub fn with_bounds_changed_handler(self, handler: impl Fn(Rect) + MaybeSend + 'static) -> Self {
+    self.and_then(|mut b| {
+      b.attrs.bounds_changed_handler = Some(Box::new(handler));
+      Ok(b)
+    })
+  }
+
+  /// Consume the builder and create the [`WebView`] from a type that implements [`HasWindowHandle`].
+  ///
+  /// # Platform-specific:
+  ///
+  /// - **Linux**: Only X11 is supported, if you want to support Wayland too, use [`WebViewBuilderExtUnix::new_gtk`].
+  ///
+  ///   Although this methods only needs an X11 window handle, we use webkit2gtk, so you still need to initialize gtk
+  ///   by callling [`gtk::init`] and advance its loop alongside your event loop using [`gtk::main_iteration_do`].
+  ///   Checkout the [Platform Considerations](https://docs.rs/wry/latest/wry/#platform-considerations) section in the crate root documentation.
+  /// - **Windows**: The webview will auto-resize when the passed handle is resized.
+  /// - **Linux (X11)**: Unlike macOS and Windows, the webview will not auto-resize and you'll need to call [`WebView::set_bounds`] manually,
+  ///   unless [`WebViewBuilderExtUnix::with_auto_resize`] is enabled.
+  ///
+  /// # Panics:
+  ///
+  /// - Panics if the provided handle was not supported or invalid.
+  /// - Panics on Linux, if [`gtk::init`] was not called in this thread.
+  pub fn build<W: HasWindowHandle>(self, window: &W) -> Result<WebView> {
+    let mut parts = self.inner?;
+    let prepared = parts.prepare()?;
+    InnerWebView::new(window, parts.attrs, parts.platform_specific)
+      .map(|webview| prepared.finish(webview))
+  }
+
+  /// Consume the builder and create the [`WebView`] as a child window inside the provided [`HasWindowHandle`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: This will create the webview as a child window of the `parent` window.
+  /// - **macOS**: This will create the webview as a `NSView` subview of the `parent` window's
+  /// content view.
+  /// - **Linux**: This will create the webview as a child window of the `parent` window. Only X11
+  /// is supported. This method won't work on Wayland.
+  ///
+  ///   Although this methods only needs an X11 window handle, you use webkit2gtk, so you still need to initialize gtk
+  ///   by callling [`gtk::init`] and advance its loop alongside your event loop using [`gtk::main_iteration_do`].
+  ///   Checkout the [Platform Considerations](https://docs.rs/wry/latest/wry/#platform-considerations) section in the crate root documentation.
+  ///
+  ///   If you want to support child webviews on X11 and Wayland at the same time,
+  ///   we recommend using [`WebViewBuilderExtUnix::new_gtk`] with [`gtk::Fixed`].
+  /// - **iOS**: This will create the webview as a subview of the provided `UIView`, positioned using the
+  /// webview bounds.
+  /// - **Android**: This will add the webview to the activity content view, positioned using the
+  /// webview bounds, instead of replacing it.
+  ///
+  /// # Panics:
+  ///
+  /// - Panics if the provided handle was not support or invalid.
+  /// - Panics on Linux, if [`gtk::init`] was not called in this thread.
+  pub fn build_as_child<W: HasWindowHandle>(self, window: &W) -> Result<WebView> {
+    let mut parts = self.inner?;
+    let prepared = parts.prepare()?;
+    InnerWebView::new_as_child(window, parts.attrs, parts.platform_specific)
+      .map(|webview| prepared.finish(webview))
+  }
+}
+
+impl WebViewBuilder<'static> {
+  /// Same as [`Self::build`], but without blocking the thread while the platform webview is
+  /// created: `callback` is called with the [`WebView`] from the event loop once it is ready.
+  ///
+  /// The errors of the builder are returned immediately, the ones of the creation of the webview
+  /// are passed to `callback`. The builder can't borrow a [`WebContext`] or an id, as they are kept
+  /// until the webview is created.
+  ///
+  /// Unlike the handlers, `callback` isn't bound by [`MaybeSend`], as it is called on the thread
+  /// of this function and usually keeps the [`WebView`], which isn't [`Send`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: The WebView2 environment and controller are created without pumping the
+  ///   messages of the thread until they are ready, so the event loop keeps running meanwhile.
+  /// - **macOS / Linux / iOS / Android**: The webview is created synchronously, and `callback` is
+  ///   called before this function returns.
+  pub fn build_async<W: HasWindowHandle>(
+    self,
+    window: &W,
+    callback: impl FnOnce(Result<WebView>) + 'static,
+  ) -> Result<()> {
+    let mut parts = self.inner?;
+    let prepared = parts.prepare()?;
+    let finish =
+      move |result: Result<InnerWebView>| callback(result.map(|webview| prepared.finish(webview)));
+
+    #[cfg(windows)]
+    InnerWebView::new_async(window, parts.attrs, parts.platform_specific, finish)?;
+    #[cfg(not(windows))]
+    finish(InnerWebView::new(
+      window,
+      parts.attrs,
+      parts.platform_specific,
+    ));
+
+    Ok(())
+  }
+}
+
+/// Create several webviews at once with [`WebViewBuilder::build_async`], so they are initialized
+/// concurrently where the platform allows it, and call `callback` with the results, in the order
+/// of `webviews`, once they are all created.
+///
+/// ## Platform-specific
+///
+/// - **Windows**: The WebView2 environments and controllers of the webviews are created in
+///   parallel instead of one after the other.
+/// - **macOS / Linux / iOS / Android**: The webviews are created one after the other, and
+///   `callback` is called before this function returns.
+pub fn create_many<W: HasWindowHandle>(
+  webviews: Vec<(WebViewBuilder<'static>, &W)>,
+  callback: impl FnOnce(Vec<Result<WebView>>) + 'static,
+) {
+  if webviews.is_empty() {
+    callback(Vec::new());
+    return;
+  }
+
+  let results: Rc<RefCell<Vec<Option<Result<WebView>>>>> = Rc::new(RefCell::new(
+    std::iter::repeat_with(|| None)
+      .take(webviews.len())
+      .collect(),
+  ));
+  let callback = Rc::new(Cell::new(Some(callback)));
+
+  for (index, (builder, window)) in webviews.into_iter().enumerate() {
+    let results = results.clone();
+    let callback = callback.clone();
+    let store = move |result: Result<WebView>| {
+      let complete = {
+        let mut results = results.borrow_mut();
+        results[index] = Some(result);
+        results.iter().all(Option::is_some)
+      };
+      if complete {
+        if let Some(callback) = callback.take() {
+          callback(results.take().into_iter().flatten().collect());
+        }
+      }
+    };
+
+    // Either the builder fails or the callback is called, so each webview stores one result
+    let store_ = store.clone();
+    if let Err(e) = builder.build_async(window, store_) {
+      store(Err(e));
+    }
+  }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios",))]
+#[derive(Clone)]
 pub(crate) struct PlatformSpecificWebViewAttributes {
   data_store_identifier: Option<[u8; 16]>,
+  #[cfg_attr(target_os = "ios", allow(dead_code))]
+  synthetic_mouse_events: bool,
+  #[cfg_attr(target_os = "ios", allow(dead_code))]
+  activate_app: bool,
+  #[cfg_attr(target_os = "ios", allow(dead_code))]
+  titlebar_separator: Option<TitlebarSeparatorStyle>,
+  #[cfg_attr(target_os = "ios", allow(dead_code))]
+  replace_content_view: bool,
+  /// The session of the webview duplicated with [`WebView::duplicate`].
+  session: Option<wkwebview::Session>,
+  /// See [`WebviewBuilderParts::apply_page_world_scripts`].
+  page_world_scripts: Vec<String>,
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios",))]
+impl Default for PlatformSpecificWebViewAttributes {
+  fn default() -> Self {
+    Self {
+      data_store_identifier: None,
+      synthetic_mouse_events: true,
+      activate_app: true,
+      titlebar_separator: Some(TitlebarSeparatorStyle::None),
+      replace_content_view: true,
+      session: None,
+      page_world_scripts: Vec::new(),
+    }
+  }
 }
 
 #[cfg(any(target_os = "macos", target_os = "ios",))]
@@ -1165,6 +3215,54 @@ pub trait WebViewBuilderExtDarwin {
   ///
   /// - **macOS / iOS**: Available on macOS >= 14 and iOS >= 17
   fn with_data_store_identifier(self, identifier: [u8; 16]) -> Self;
+
+  /// Whether the presses on the back and forward mouse buttons, which WebKit doesn't send to the
+  /// page, are dispatched as `mousedown` and `mouseup` events with the `button` 3 and 4.
+  /// Default is `true`.
+  ///
+  /// Disable it when the app handles these buttons itself, e.g. with an `NSEvent` monitor,
+  /// so the page doesn't receive them twice. The events are then handled by WebKit.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS**: Unsupported.
+  fn with_synthetic_mouse_events(self, enabled: bool) -> Self;
+
+  /// Whether the app is activated when the webview is created, bringing its windows to the front
+  /// of the other apps. Default is `true`.
+  ///
+  /// Disable it when the webview is created in the background, e.g. in a window of a menu bar
+  /// app, so it doesn't steal the focus of the active app. See also
+  /// [`WebViewBuilder::with_window_activation_handler`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS**: Unsupported.
+  fn with_activate_app(self, activate: bool) -> Self;
+
+  /// The titlebar separator style set on the window of the webview, `None` leaves the window
+  /// as it is.
+  ///
+  /// Default is `Some(TitlebarSeparatorStyle::None)`, which hides the separator. This default
+  /// is deprecated and will be `None` in the next release, set the style explicitly to keep it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS**: Available on macOS >= 11.
+  /// - **iOS**: Unsupported.
+  fn with_titlebar_separator(self, style: Option<TitlebarSeparatorStyle>) -> Self;
+
+  /// Whether the webview built with [`WebViewBuilder::build`] replaces the content view of the
+  /// window. Default is `true`.
+  ///
+  /// Disable it to keep the view hierarchy of the window intact, the webview is then added on
+  /// top of the other subviews of the content view and resized with it. To keep the focus on
+  /// the native views too, see [`WebViewBuilder::with_focused`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS**: Unsupported.
+  fn with_replace_content_view(self, replace: bool) -> Self;
 }
 
 #[cfg(any(target_os = "macos", target_os = "ios",))]
@@ -1175,6 +3273,34 @@ impl WebViewBuilderExtDarwin for WebViewBuilder<'_> {
       Ok(b)
     })
   }
+
+  fn with_synthetic_mouse_events(self, enabled: bool) -> Self {
+    self.and_then(|mut b| {
+      b.platform_specific.synthetic_mouse_events = enabled;
+      Ok(b)
+    })
+  }
+
+  fn with_activate_app(self, activate: bool) -> Self {
+    self.and_then(|mut b| {
+      b.platform_specific.activate_app = activate;
+      Ok(b)
+    })
+  }
+
+  fn with_titlebar_separator(self, style: Option<TitlebarSeparatorStyle>) -> Self {
+    self.and_then(|mut b| {
+      b.platform_specific.titlebar_separator = style;
+      Ok(b)
+    })
+  }
+
+  fn with_replace_content_view(self, replace: bool) -> Self {
+    self.and_then(|mut b| {
+      b.platform_specific.replace_content_view = replace;
+      Ok(b)
+    })
+  }
 }
 
 #[cfg(windows)]
@@ -1186,6 +3312,9 @@ pub(crate) struct PlatformSpecificWebViewAttributes {
   use_https: bool,
   scroll_bar_style: ScrollBarStyle,
   browser_extensions_enabled: bool,
+  creation_timer: Option<Rc<CreationTimer>>,
+  /// The session of the webview duplicated with [`WebView::duplicate`].
+  session: Option<webview2::Session>,
 }
 
 #[cfg(windows)]
@@ -1198,6 +3327,8 @@ impl Default for PlatformSpecificWebViewAttributes {
       use_https: false, // To match macOS & Linux behavior in the context of mixed content.
       scroll_bar_style: ScrollBarStyle::default(),
       browser_extensions_enabled: false,
+      creation_timer: None,
+      session: None,
     }
   }
 }
@@ -1312,6 +3443,10 @@ pub(crate) struct PlatformSpecificWebViewAttributes {
   with_asset_loader: bool,
   asset_loader_domain: Option<String>,
   https_scheme: bool,
+  open_external_urls: bool,
+  safe_browsing: Option<bool>,
+  force_dark: Option<Theme>,
+  javascript_interfaces: HashMap<String, android::JavascriptInterface>,
 }
 
 #[cfg(target_os = "android")]
@@ -1339,6 +3474,48 @@ pub trait WebViewBuilderExtAndroid {
   ///
   /// The default value is `false`.
   fn with_https_scheme(self, enabled: bool) -> Self;
+
+  /// Determines whether navigations to URLs the webview cannot load, such as `tel:`, `mailto:`,
+  /// `market://` or `intent://`, should be opened with the system using an `ACTION_VIEW` intent.
+  ///
+  /// The [navigation handler](WebViewBuilder::with_navigation_handler) is still called first
+  /// and can block the navigation.
+  ///
+  /// The intents are restricted to the activities that can be opened from a browser, so the
+  /// component and selector of `intent://` URLs are ignored, and their `browser_fallback_url` is
+  /// only loaded if it is a `http` or `https` URL.
+  ///
+  /// The default value is `false`.
+  fn with_open_external_urls(self, enabled: bool) -> Self;
+
+  /// Enables or disables [Safe Browsing](https://developer.android.com/develop/ui/views/layout/webapps/managing-webview#safe-browsing).
+  ///
+  /// Does nothing if the WebView implementation does not support changing it.
+  /// Defaults to the WebView's behavior, which is enabled.
+  fn with_safe_browsing(self, enabled: bool) -> Self;
+
+  /// Sets how the webview content should be darkened to match the app theme.
+  ///
+  /// - [`Theme::Dark`] always darkens the content.
+  /// - [`Theme::Light`] never darkens the content.
+  /// - [`Theme::Auto`] darkens the content based on the app theme.
+  ///
+  /// On WebView versions supporting algorithmic darkening, [`Theme::Dark`] and [`Theme::Auto`]
+  /// both allow darkening, which is only applied when the app uses a dark theme.
+  fn with_force_dark(self, theme: Theme) -> Self;
+
+  /// Injects an object named `name` in the webview `window` object, backed by the given handler.
+  ///
+  /// Javascript can call it with `window.<name>.invoke(method, payload)`, where both `method`
+  /// and `payload` are strings. The handler receives the method name and the payload and its return
+  /// value is synchronously returned to Javascript (or `null` if it returns `None`).
+  ///
+  /// This uses [`addJavascriptInterface`](https://developer.android.com/reference/android/webkit/WebView#addJavascriptInterface(java.lang.Object,%20java.lang.String)),
+  /// so the object is available to all frames and the handler is called on a background thread,
+  /// possibly concurrently.
+  fn with_javascript_interface<F>(self, name: String, handler: F) -> Self
+  where
+    F: Fn(&str, String) -> Option<String> + Send + Sync + 'static;
 }
 
 #[cfg(target_os = "android")]
@@ -1364,7 +3541,7 @@ impl WebViewBuilderExtAndroid for WebViewBuilder<'_> {
       b.attrs.custom_protocols.insert(
         protocol.clone(),
         Box::new(|_, _, api| {
-          api.respond(Response::builder().body(Vec::new()).unwrap());
+          let _ = api.respond(Response::builder().body(Vec::new()).unwrap());
         }),
       );
       b.platform_specific.with_asset_loader = true;
@@ -1379,6 +3556,39 @@ impl WebViewBuilderExtAndroid for WebViewBuilder<'_> {
       Ok(b)
     })
   }
+
+  fn with_open_external_urls(self, enabled: bool) -> Self {
+    self.and_then(|mut b| {
+      b.platform_specific.open_external_urls = enabled;
+      Ok(b)
+    })
+  }
+
+  fn with_safe_browsing(self, enabled: bool) -> Self {
+    self.and_then(|mut b| {
+      b.platform_specific.safe_browsing = Some(enabled);
+      Ok(b)
+    })
+  }
+
+  fn with_force_dark(self, theme: Theme) -> Self {
+    self.and_then(|mut b| {
+      b.platform_specific.force_dark = Some(theme);
+      Ok(b)
+    })
+  }
+
+  fn with_javascript_interface<F>(self, name: String, handler: F) -> Self
+  where
+    F: Fn(&str, String) -> Option<String> + Send + Sync + 'static,
+  {
+    self.and_then(|mut b| {
+      b.platform_specific
+        .javascript_interfaces
+        .insert(name, Box::new(handler));
+      Ok(b)
+    })
+  }
 }
 
 #[cfg(any(
@@ -1399,9 +3609,30 @@ pub trait WebViewBuilderExtUnix<'a> {
   /// # Panics:
   ///
   /// - Panics if [`gtk::init`] was not called in this thread.
-  fn build_gtk<W>(self, widget: &'a W) -> Result<WebView>
+  fn build_gtk<W>(self, widget: &W) -> Result<WebView>
   where
     W: gtk::prelude::IsA<gtk::Container>;
+
+  /// Set a handler for the messages sent by the WebKit web process extensions with
+  /// `webkit_web_page_send_message_to_view`. The message returned by the handler, if any, is sent
+  /// back to the extension as the reply.
+  ///
+  /// The extensions are loaded with [`WebContextExtUnix::set_web_extensions_directory`].
+  fn with_web_extension_message_handler<F>(self, handler: F) -> Self
+  where
+    F: Fn(WebExtensionMessage) -> Option<WebExtensionMessage> + MaybeSend + 'static;
+
+  /// Whether the webviews created with [`WebViewBuilder::build`] and
+  /// [`WebViewBuilder::build_as_child`] on X11 are resized to fill their parent window when it is
+  /// resized, like the ones created with [`WebViewBuilder::build`] on macOS and Windows.
+  ///
+  /// The size of the parent window is polled on the GTK main loop, as the X11 windows of other
+  /// toolkits don't send their configure events to GTK. The child webviews are moved to the
+  /// top-left corner of their parent.
+  ///
+  /// Default is `false`. It has no effect on the webviews created with [`Self::build_gtk`], which
+  /// are sized by their GTK container.
+  fn with_auto_resize(self, auto_resize: bool) -> Self;
 }
 
 #[cfg(any(
@@ -1412,14 +3643,31 @@ pub trait WebViewBuilderExtUnix<'a> {
   target_os = "openbsd",
 ))]
 impl<'a> WebViewBuilderExtUnix<'a> for WebViewBuilder<'a> {
-  fn build_gtk<W>(self, widget: &'a W) -> Result<WebView>
+  fn build_gtk<W>(self, widget: &W) -> Result<WebView>
   where
     W: gtk::prelude::IsA<gtk::Container>,
   {
-    let parts = self.inner?;
-
+    let mut parts = self.inner?;
+    let prepared = parts.prepare()?;
     InnerWebView::new_gtk(widget, parts.attrs, parts.platform_specific)
-      .map(|webview| WebView { webview })
+      .map(|webview| prepared.finish(webview))
+  }
+
+  fn with_web_extension_message_handler<F>(self, handler: F) -> Self
+  where
+    F: Fn(WebExtensionMessage) -> Option<WebExtensionMessage> + MaybeSend + 'static,
+  {
+    self.and_then(|mut b| {
+      b.platform_specific.web_extension_message_handler = Some(Box::new(handler));
+      Ok(b)
+    })
+  }
+
+  fn with_auto_resize(self, auto_resize: bool) -> Self {
+    self.and_then(|mut b| {
+      b.platform_specific.auto_resize = auto_resize;
+      Ok(b)
+    })
   }
 }
 
@@ -1429,10 +3677,150 @@ impl<'a> WebViewBuilderExtUnix<'a> for WebViewBuilder<'a> {
 /// scripts for those who prefer to control fine grained window creation and event handling.
 /// [`WebView`] presents the actual WebView window and let you still able to perform actions on it.
 pub struct WebView {
-  webview: InnerWebView,
+  webview: Rc<InnerWebView>,
+  handlers: Rc<RuntimeHandlers>,
+  key: u32,
+}
+
+/// A handler of a webview that can be replaced once it is created,
+/// see [`WebviewBuilderParts::apply_runtime_handlers`].
+struct HandlerSlot<T: ?Sized> {
+  /// Whether the webview calls the slot, see [`Self::new`] and [`Self::installed`].
+  installed: bool,
+  handler: RefCell<Option<Rc<T>>>,
+  /// The handler of the [`WebContext`], called when `handler` is cleared.
+  fallback: Option<Rc<T>>,
+}
+
+impl<T: ?Sized> HandlerSlot<T> {
+  /// A slot only installed if a handler was set on the builder or the context.
+  fn new(handler: Option<Rc<T>>, fallback: Option<Rc<T>>) -> Self {
+    Self {
+      installed: handler.is_some() || fallback.is_some(),
+      handler: RefCell::new(handler),
+      fallback,
+    }
+  }
+
+  /// A slot always installed, so a handler can be set once the webview is created.
+  fn installed(handler: Option<Rc<T>>, fallback: Option<Rc<T>>) -> Self {
+    Self {
+      installed: true,
+      handler: RefCell::new(handler),
+      fallback,
+    }
+  }
+
+  /// The handler to call, cloned out of the slot so it can replace itself.
+  fn get(&self) -> Option<Rc<T>> {
+    let handler = self.handler.borrow().clone();
+    handler.or_else(|| self.fallback.clone())
+  }
+
+  /// Replace the handler, or clear it with `None`, if the slot is installed.
+  fn set(&self, name: &'static str, handler: Option<Rc<T>>) -> Result<()> {
+    if !self.installed {
+      return Err(Error::HandlerNotInstalled(name));
+    }
+    *self.handler.borrow_mut() = handler;
+    Ok(())
+  }
+}
+
+/// The handlers of a webview that can be replaced once it is created,
+/// see [`WebviewBuilderParts::apply_runtime_handlers`].
+struct RuntimeHandlers {
+  ipc_handler: HandlerSlot<dyn Fn(Request<String>)>,
+  navigation_handler: HandlerSlot<dyn Fn(String) -> bool>,
+  new_window_req_handler: HandlerSlot<dyn Fn(String) -> bool>,
+  document_title_changed_handler: HandlerSlot<dyn Fn(String)>,
+  on_page_load_handler: HandlerSlot<dyn Fn(PageLoadEvent, String)>,
+  /// The requests of [`WebView::fetch`] waiting for their response.
+  fetches: fetch::PendingFetches,
+  /// The URL of the page that started loading but didn't finish yet.
+  pending_navigation: RefCell<Option<String>>,
+  /// The custom protocol requests of the page waiting for their response.
+  custom_protocol_requests: Arc<Mutex<Vec<CancellationToken>>>,
+  /// See [`WebViewAttributes::panic_handler`], called with the panics of the callbacks given to the
+  /// methods of [`WebView`].
+  panic_handler: Option<Rc<dyn Fn(HandlerPanic)>>,
+  /// The servers of [`WebViewBuilder::with_loopback_websocket`], closed with the webview.
+  #[cfg(feature = "protocol")]
+  _loopback_servers: Vec<loopback::LoopbackServer>,
+}
+
+impl RuntimeHandlers {
+  /// Cancel the custom protocol requests waiting for their response, see
+  /// [`RequestAsyncResponder::cancellation_token`].
+  fn cancel_custom_protocol_requests(&self) {
+    for token in self.custom_protocol_requests.lock().unwrap().drain(..) {
+      token.cancel();
+    }
+  }
+}
+
+impl Drop for RuntimeHandlers {
+  fn drop(&mut self) {
+    self.cancel_custom_protocol_requests();
+  }
+}
+
+static WEBVIEW_KEY: AtomicU32 = AtomicU32::new(0);
+
+thread_local! {
+  /// The webviews owned by the current thread, used to find the webview of a dispatched closure.
+  static WEBVIEWS: RefCell<HashMap<u32, (Weak<InnerWebView>, Rc<RuntimeHandlers>)>> =
+    RefCell::new(HashMap::new());
+}
+
+impl Drop for WebView {
+  fn drop(&mut self) {
+    // The webview can be temporarily shared with a dispatched closure,
+    // so only unregister it when this is the last reference.
+    if Rc::strong_count(&self.webview) == 1 {
+      let _ = WEBVIEWS.try_with(|webviews| webviews.borrow_mut().remove(&self.key));
+    }
+  }
 }
 
 impl WebView {
+  fn from_inner(webview: InnerWebView, handlers: Rc<RuntimeHandlers>, key: u32) -> Self {
+    let webview = Rc::new(webview);
+    WEBVIEWS.with(|webviews| {
+      webviews
+        .borrow_mut()
+        .insert(key, (Rc::downgrade(&webview), handlers.clone()))
+    });
+    Self {
+      webview,
+      handlers,
+      key,
+    }
+  }
+
+  /// The webview of the current thread using `handlers`, if it is created.
+  fn inner_from_handlers(handlers: &Rc<RuntimeHandlers>) -> Option<Rc<InnerWebView>> {
+    WEBVIEWS.with(|webviews| {
+      webviews
+        .borrow()
+        .values()
+        .find(|(_, h)| Rc::ptr_eq(h, handlers))
+        .and_then(|(webview, _)| webview.upgrade())
+    })
+  }
+
+  fn from_key(key: u32) -> Option<Self> {
+    WEBVIEWS.with(|webviews| {
+      let webviews = webviews.borrow();
+      let (webview, handlers) = webviews.get(&key)?;
+      Some(Self {
+        webview: webview.upgrade()?,
+        handlers: handlers.clone(),
+        key,
+      })
+    })
+  }
+
   /// Create a [`WebView`] from from a type that implements [`HasWindowHandle`].
   /// Note that calling this directly loses
   /// abilities to initialize scripts, add ipc handler, and many more before starting WebView. To
@@ -1446,7 +3834,8 @@ impl WebView {
   ///   by callling [`gtk::init`] and advance its loop alongside your event loop using [`gtk::main_iteration_do`].
   ///   Checkout the [Platform Considerations](https://docs.rs/wry/latest/wry/#platform-considerations) section in the crate root documentation.
   /// - **macOS / Windows**: The webview will auto-resize when the passed handle is resized.
-  /// - **Linux (X11)**: Unlike macOS and Windows, the webview will not auto-resize and you'll need to call [`WebView::set_bounds`] manually.
+  /// - **Linux (X11)**: Unlike macOS and Windows, the webview will not auto-resize and you'll need to call [`WebView::set_bounds`] manually,
+  ///   unless [`WebViewBuilderExtUnix::with_auto_resize`] is enabled.
   ///
   /// # Panics:
   ///
@@ -1472,7 +3861,10 @@ impl WebView {
   ///
   ///   If you want to support child webviews on X11 and Wayland at the same time,
   ///   we recommend using [`WebViewBuilderExtUnix::new_gtk`] with [`gtk::Fixed`].
-  /// - **Android/iOS:** Unsupported.
+  /// - **iOS**: This will create the webview as a subview of the provided `UIView`, positioned using the
+  /// webview bounds.
+  /// - **Android**: This will add the webview to the activity content view, positioned using the
+  /// webview bounds, instead of replacing it.
   ///
   /// # Panics:
   ///
@@ -1482,6 +3874,45 @@ impl WebView {
     WebViewBuilder::with_attributes(attrs).build_as_child(parent)
   }
 
+  /// Build `builder` in `window`, sharing the session of this webview, its cookies, storage and
+  /// cache, e.g. for a "duplicate tab" feature. The new webview is navigated to the URL of this
+  /// webview unless `builder` sets a URL or HTML content.
+  ///
+  /// The history of this webview is copied where the platform allows it. The handlers, the
+  /// custom protocols and the other options of this webview aren't copied, set them on `builder`.
+  /// The data directory, the web context and the profile of `builder` are ignored.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: Uses the WebView2 environment and profile of this webview. The history isn't
+  ///   copied.
+  /// - **macOS / iOS**: Uses the `WKWebsiteDataStore` of this webview. The history is copied on
+  ///   macOS 12 and iOS 15 or newer.
+  /// - **Linux**: Uses the webkit2gtk context of this webview, and copies its history.
+  /// - **Android**: The session is shared by all the webviews. The history isn't copied.
+  ///
+  /// # Panics:
+  ///
+  /// - Panics if the provided handle was not supported or invalid.
+  pub fn duplicate<W: HasWindowHandle>(
+    &self,
+    builder: WebViewBuilder,
+    window: &W,
+  ) -> Result<WebView> {
+    let mut parts = builder.inner?;
+    if parts.attrs.url.is_none() && parts.attrs.html.is_none() {
+      let url = self.url()?;
+      if !url.is_empty() {
+        parts.attrs.url = Some(url);
+      }
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+      parts.platform_specific.session = Some(self.webview.session()?);
+    }
+    WebViewBuilder { inner: Ok(parts) }.build(window)
+  }
+
   /// Returns the id of this webview.
   pub fn id(&self) -> WebViewId {
     self.webview.id()
@@ -1510,7 +3941,50 @@ impl WebView {
     js: &str,
     callback: impl Fn(String) + Send + 'static,
   ) -> Result<()> {
-    self.webview.eval(js, Some(callback))
+    let proxy = self.proxy();
+    self.webview.eval(
+      js,
+      Some(move |result| {
+        catch_callback_panic(&proxy, "evaluate script", || callback(result));
+      }),
+    )
+  }
+
+  /// Extract the article of the current page, its title, author, content and text without the
+  /// navigation and the ads of the page, e.g. for a reader mode. The handler receives `None` if the
+  /// page doesn't look like an article.
+  ///
+  /// The article is extracted by a compact version of the scoring of Mozilla's Readability, run
+  /// in an isolated JavaScript world on a copy of the document, so the page scripts can't
+  /// interfere with it and the page isn't modified.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Requires macOS 11+ and iOS 14+ for the isolated world, the page world is
+  ///   used on older versions.
+  /// - **Windows / Android**: Runs in the page world.
+  #[cfg(feature = "reader")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "reader")))]
+  pub fn extract_article(
+    &self,
+    handler: impl Fn(Result<Option<Article>>) + Send + 'static,
+  ) -> Result<()> {
+    let proxy = self.proxy();
+    let callback = move |result: String| {
+      catch_callback_panic(&proxy, "extract article", || {
+        handler(reader::parse_article(&result))
+      });
+    };
+    #[cfg(any(gtk, target_os = "macos", target_os = "ios"))]
+    {
+      self
+        .webview
+        .eval_in_world(reader::READER_SCRIPT, reader::READER_WORLD_NAME, callback)
+    }
+    #[cfg(not(any(gtk, target_os = "macos", target_os = "ios")))]
+    {
+      self.webview.eval(reader::READER_SCRIPT, Some(callback))
+    }
   }
 
   /// Launch print modal for the webview content.
@@ -1518,6 +3992,40 @@ impl WebView {
     self.webview.print()
   }
 
+  /// Print the webview content with the given options.
+  ///
+  /// The options that aren't supported by the platform are ignored,
+  /// see [`WebView::print_capabilities`]. They only apply to this print job.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: Requires WebView2 Runtime 1.0.1518.46+. The print dialog of WebView2 can't be
+  ///   given the options, so they are only used with [`PrintOptions::silent`].
+  /// - **iOS / Android**: Unsupported.
+  pub fn print_with_options(&self, options: &PrintOptions) -> Result<()> {
+    self.webview.print_with_options(options)
+  }
+
+  /// Get which of the [`PrintOptions`] are supported by the webview.
+  pub fn print_capabilities(&self) -> PrintCapabilities {
+    self.webview.print_capabilities()
+  }
+
+  /// Get which of the web APIs giving access to the devices of the user can be used by the pages.
+  ///
+  /// The pages can't be granted access to the HID, USB and serial devices: none of the webviews
+  /// has a device chooser or lets the app select the device, so their requests are denied without
+  /// a prompt.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: The Web Authentication API only works for the domains associated with the
+  ///   app, see the `webcredentials` service of the
+  ///   [Associated Domains Entitlement](https://developer.apple.com/documentation/bundleresources/entitlements/com_apple_developer_associated-domains).
+  pub fn api_capabilities(&self) -> ApiCapabilities {
+    self.webview.api_capabilities()
+  }
+
   /// Get a list of cookies for specific url.
   pub fn cookies_for_url(&self, url: &str) -> Result<Vec<cookie::Cookie<'static>>> {
     self.webview.cookies_for_url(url)
@@ -1587,6 +4095,163 @@ impl WebView {
     self.webview.set_background_color(background_color)
   }
 
+  /// Apply the style sheet `css` to the current document and every document loaded afterwards,
+  /// see [`WebViewBuilder::with_user_style_sheet`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS / Windows**: The style sheet is only applied to the main frame of the current document.
+  /// - **Android**: The style sheet is only applied to the current document.
+  pub fn add_css(&self, css: &str, main_frame_only: bool) -> Result<()> {
+    self.webview.add_css(css, main_frame_only)
+  }
+
+  /// Remove a style sheet added with [`WebView::add_css`] or [`WebViewBuilder::with_user_style_sheet`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS / Windows**: The style sheet is only removed from the main frame of the current document.
+  /// - **Android**: The style sheet is only removed from the current document.
+  pub fn remove_css(&self, css: &str) -> Result<()> {
+    self.webview.remove_css(css)
+  }
+
+  /// Sets whether the scripts of the pages are run, see [`WebViewBuilder::with_javascript_enabled`].
+  ///
+  /// The current page must be reloaded for the change to take effect.
+  pub fn set_javascript_enabled(&self, enabled: bool) -> Result<()> {
+    self.webview.set_javascript_enabled(enabled)
+  }
+
+  /// Sets whether the images of the pages are loaded, see [`WebViewBuilder::with_images_enabled`].
+  ///
+  /// The images already loaded are not removed, the current page must be reloaded for them to be removed.
+  pub fn set_images_enabled(&self, enabled: bool) -> Result<()> {
+    self.webview.set_images_enabled(enabled)
+  }
+
+  /// Whether the webview is loading a page, from the start of a navigation until it finishes
+  /// or fails.
+  pub fn is_loading(&self) -> bool {
+    self.webview.navigation_state().is_loading()
+  }
+
+  /// The id of the last navigation started in the webview, or `None` before the first navigation.
+  ///
+  /// Use it with [`WebView::is_loading`] to know whether the page loading is the one you started.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: The ids are the WebView2 navigation ids, which are kept by the redirections.
+  pub fn current_navigation_id(&self) -> Option<u64> {
+    self.webview.navigation_state().navigation_id()
+  }
+
+  /// Get the native webview, e.g. for FFI embedders, without the platform specific extension traits.
+  pub fn platform_handle(&self) -> PlatformWebViewHandle {
+    self.webview.platform_handle()
+  }
+
+  /// Replace the IPC handler set with [`WebViewBuilder::with_ipc_handler`], or the one of its
+  /// [`WebContext`].
+  ///
+  /// The pages only get an IPC channel if a handler is set when the webview is built, so an error
+  /// is returned if neither the builder nor the context set one. The other `set_*` methods work on
+  /// any webview.
+  pub fn set_ipc_handler(
+    &self,
+    handler: impl Fn(Request<String>) + MaybeSend + 'static,
+  ) -> Result<()> {
+    self.handlers.ipc_handler.set("ipc", Some(Rc::new(handler)))
+  }
+
+  /// Remove the IPC handler of the webview, the handler of its [`WebContext`] is called instead
+  /// if there is one.
+  pub fn clear_ipc_handler(&self) {
+    let _ = self.handlers.ipc_handler.set("ipc", None);
+  }
+
+  /// Replace the navigation handler set with [`WebViewBuilder::with_navigation_handler`], or the
+  /// one of its [`WebContext`], or set one if none was.
+  ///
+  /// The navigation rules of the builder, e.g. [`WebViewBuilder::with_navigation_allowlist`],
+  /// still apply before the new handler is called.
+  pub fn set_navigation_handler(
+    &self,
+    handler: impl Fn(String) -> bool + MaybeSend + 'static,
+  ) -> Result<()> {
+    self
+      .handlers
+      .navigation_handler
+      .set("navigation", Some(Rc::new(handler)))
+  }
+
+  /// Remove the navigation handler of the webview, the handler of its [`WebContext`] is called
+  /// instead if there is one, otherwise all the navigations are allowed.
+  pub fn clear_navigation_handler(&self) {
+    let _ = self.handlers.navigation_handler.set("navigation", None);
+  }
+
+  /// Replace the new window request handler set with
+  /// [`WebViewBuilder::with_new_window_req_handler`], or set one if none was.
+  ///
+  /// The navigation rules of the builder, e.g. [`WebViewBuilder::with_external_links_in_browser`],
+  /// still apply before the new handler is called.
+  pub fn set_new_window_req_handler(
+    &self,
+    handler: impl Fn(String) -> bool + MaybeSend + 'static,
+  ) -> Result<()> {
+    self
+      .handlers
+      .new_window_req_handler
+      .set("new window request", Some(Rc::new(handler)))
+  }
+
+  /// Remove the new window request handler of the webview, so all the requests are allowed.
+  pub fn clear_new_window_req_handler(&self) {
+    let _ = self
+      .handlers
+      .new_window_req_handler
+      .set("new window request", None);
+  }
+
+  /// Replace the document title changed handler set with
+  /// [`WebViewBuilder::with_document_title_changed_handler`], or set one if none was.
+  pub fn set_document_title_changed_handler(
+    &self,
+    handler: impl Fn(String) + MaybeSend + 'static,
+  ) -> Result<()> {
+    self
+      .handlers
+      .document_title_changed_handler
+      .set("document title changed", Some(Rc::new(handler)))
+  }
+
+  /// Remove the document title changed handler of the webview.
+  pub fn clear_document_title_changed_handler(&self) {
+    let _ = self
+      .handlers
+      .document_title_changed_handler
+      .set("document title changed", None);
+  }
+
+  /// Replace the page load handler set with [`WebViewBuilder::with_on_page_load_handler`], or set
+  /// one if none was.
+  pub fn set_on_page_load_handler(
+    &self,
+    handler: impl Fn(PageLoadEvent, String) + MaybeSend + 'static,
+  ) -> Result<()> {
+    self
+      .handlers
+      .on_page_load_handler
+      .set("page load", Some(Rc::new(handler)))
+  }
+
+  /// Remove the page load handler of the webview.
+  pub fn clear_on_page_load_handler(&self) {
+    let _ = self.handlers.on_page_load_handler.set("page load", None);
+  }
+
   /// Navigate to the specified url
   pub fn load_url(&self, url: &str) -> Result<()> {
     self.webview.load_url(url)
@@ -1597,7 +4262,61 @@ impl WebView {
     self.webview.load_url_with_headers(url, headers)
   }
 
+  /// Hint the webview to fetch the resource at `url` in the background, so a later navigation or
+  /// request to it is faster. This adds a `<link rel="prefetch">` element to the current page on
+  /// all the platforms, so:
+  ///
+  /// - The hint is dropped with the page, a fetch which didn't start before the next navigation
+  ///   doesn't happen. Call it once the page is loaded, e.g. from the page load handler.
+  /// - The fetch is subject to the Content Security Policy of the page, e.g. its `default-src`
+  ///   directive, and fails like any request of the page.
+  /// - `Ok` only means the script was evaluated, not that the resource was fetched.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: The DevTools protocol of WebView2 has no prefetch command, so the same element
+  ///   is used.
+  /// - **Linux / macOS / iOS**: Unsupported by WebKit, only [`WebView::preconnect`] has an effect.
+  pub fn prefetch(&self, url: &str) -> Result<()> {
+    self.evaluate_script(&link_hint_script("prefetch", url))
+  }
+
+  /// Hint the webview to open a connection to `origin`, e.g. `https://tauri.app`, so a later
+  /// navigation or request to it is faster. This adds a `<link rel="preconnect">` element to the
+  /// current page, with the same limits as [`WebView::prefetch`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: The DevTools protocol of WebView2 has no preconnect command, so the same
+  ///   element is used.
+  pub fn preconnect(&self, origin: &str) -> Result<()> {
+    self.evaluate_script(&link_hint_script("preconnect", origin))
+  }
+
+  /// Download the resource at `url` to `destination` using the session of the webview,
+  /// so cookies and authentication of the webview are used for the request.
+  ///
+  /// The download goes through the download handlers if any are set: `destination` is passed as
+  /// the default path to [`WebViewBuilder::with_download_started_handler`], which can still change
+  /// it or cancel the download.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: The download is started by clicking a link with the `download` attribute,
+  ///   so `url` must be an absolute URL and a page must be loaded in the webview.
+  /// - **macOS**: available on macOS 11.3+ only.
+  /// - **iOS**: available on iOS 14.5+ only.
+  /// - **Android**: Unsupported.
+  pub fn download<P: AsRef<Path>>(&self, url: &str, destination: P) -> Result<()> {
+    self.webview.download(url, destination.as_ref())
+  }
+
   /// Load html content into the webview
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows:** The strings larger than the 2 MB supported by WebView2 are served from an
+  ///   internal `https://wry-html.localhost/` URL instead, which is then the origin of the page.
   pub fn load_html(&self, html: &str) -> Result<()> {
     self.webview.load_html(html)
   }
@@ -1607,6 +4326,149 @@ impl WebView {
     self.webview.clear_all_browsing_data()
   }
 
+  /// Clear the HTTP cache of the webview, without clearing the rest of the browsing data.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Linux / Windows / macOS / iOS**: Clears the cache of the whole data store of the webview,
+  ///   which may be shared with other webviews.
+  pub fn clear_http_cache(&self) -> Result<()> {
+    self.webview.clear_http_cache()
+  }
+
+  /// Get the origins that have data stored in the data store of this webview and their usage.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Linux**: The data is grouped by domain and only the size of the cache is known.
+  /// - **macOS / iOS**: The data is grouped by domain and the sizes are unknown.
+  /// - **Windows / Android**: Unsupported, returns an empty list.
+  pub fn storage_usage(&self) -> Result<Vec<OriginStorageUsage>> {
+    self.webview.storage_usage()
+  }
+
+  /// Make an HTTP request with the `fetch` function of the current page, so it uses the network
+  /// stack and the cookies of the webview, e.g. to call an API as the user logged in the webview.
+  ///
+  /// The request runs in an isolated JavaScript world, so the page scripts can't tamper with it or
+  /// read its response, but it is still subject to the CORS rules of the page. It includes the
+  /// credentials of the page and fails if the page is unloaded before receiving the response. The
+  /// returned future can be polled from any thread, but the response is received by the event loop,
+  /// so it must not be blocked while waiting for it.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **macOS / iOS**: Requires macOS 11+ and iOS 14+, the request fails on older versions.
+  /// - **Android**: The request runs in the page world, so the page scripts can replace the
+  ///   `fetch` function and read the response.
+  pub fn fetch(&self, request: Request<Vec<u8>>) -> FetchFuture {
+    let (id, future, body) = self.handlers.fetches.start(&request);
+
+    #[cfg(not(target_os = "android"))]
+    let result = {
+      let handlers = Rc::downgrade(&self.handlers);
+      let id = id.clone();
+      self.webview.eval_fetch(&body, move |response| {
+        if let Some(handlers) = handlers.upgrade() {
+          let response = response
+            .map_err(Error::FetchFailed)
+            .and_then(|response| fetch::parse_response(&response));
+          // resolving the future wakes its task, which can run code of the app
+          catch_handler_panic(&handlers.panic_handler, "fetch", || {
+            handlers.fetches.resolve(&id, response)
+          });
+        }
+      })
+    };
+    #[cfg(target_os = "android")]
+    let result = self.evaluate_script(&self.handlers.fetches.script(&id, &body));
+
+    if let Err(e) = result {
+      self.handlers.fetches.resolve(&id, Err(e));
+    }
+    future
+  }
+
+  /// Get the id of the process rendering the webview, e.g. to show its resource usage or kill it
+  /// when it hangs, or `None` if it is unknown.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: Returns the renderer process of the main frame. Requires WebView2 Runtime
+  ///   version 114 or higher, and pumps the messages of the thread until it is found.
+  /// - **macOS / iOS**: Returns `None` until the web content process is launched, or if WebKit
+  ///   doesn't have the private `_webProcessIdentifier` method.
+  /// - **Linux / Android**: Unsupported, returns `None`.
+  pub fn renderer_pid(&self) -> Result<Option<u32>> {
+    self.webview.renderer_pid()
+  }
+
+  /// Get the resident memory in bytes of the process rendering the webview, see
+  /// [`WebView::renderer_pid`], passing it to `handler`, or `None` if it is unknown.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows**: The handler is called once the renderer process is found, with its working set.
+  /// - **Linux / iOS / Android**: Unsupported, the handler receives `None`.
+  pub fn memory_usage(&self, handler: impl Fn(Option<u64>) + Send + 'static) -> Result<()> {
+    let proxy = self.proxy();
+    self.webview.memory_usage(move |usage| {
+      catch_callback_panic(&proxy, "memory usage", || handler(usage));
+    })
+  }
+
+  /// Get the engine rendering the webview, including the information specific to the webview
+  /// like its data directory, see [`engine_info`].
+  pub fn engine_info(&self) -> Result<EngineInfo> {
+    self.webview.engine_info()
+  }
+
+  /// Run the handler the main frame set for `action` on its [Media Session], e.g. when a media key
+  /// is pressed. `seek_time` is the position in seconds for [`MediaSessionAction::SeekTo`].
+  ///
+  /// Does nothing if the page didn't set a handler for `action`, see
+  /// [`MediaSessionEvent::ActionsChanged`].
+  ///
+  /// [Media Session]: https://developer.mozilla.org/en-US/docs/Web/API/Media_Session_API
+  pub fn media_session_action(
+    &self,
+    action: MediaSessionAction,
+    seek_time: Option<f64>,
+  ) -> Result<()> {
+    let seek_time = seek_time
+      .filter(|time| time.is_finite())
+      .map_or_else(|| "undefined".to_string(), |time| time.to_string());
+    self.evaluate_script(&format!(
+      "window.__WRY_MEDIA_SESSION_ACTION__ && window.__WRY_MEDIA_SESSION_ACTION__('{}', {seek_time})",
+      action.as_str(),
+    ))
+  }
+
+  /// Capture the state of the webview, e.g. to attach it to the bug reports sent from the
+  /// [`WebViewBuilder::with_process_failed_handler`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / iOS / Android**: The history length is unsupported and always `None`.
+  pub fn dump_state(&self) -> Result<WebViewStateDump> {
+    Ok(WebViewStateDump {
+      url: self.url()?,
+      history_length: self.webview.history_length()?,
+      pending_navigation: self.handlers.pending_navigation.borrow().clone(),
+    })
+  }
+
+  /// Remove all the data (cookies, cache, storage...) stored by an origin, e.g. `https://tauri.app`.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Linux / macOS / iOS**: The data is grouped by domain, so the data of the whole domain of
+  ///   the origin is removed.
+  /// - **Android**: Unsupported.
+  pub fn remove_origin_data(&self, origin: &str) -> Result<()> {
+    self.webview.remove_origin_data(origin)
+  }
+
   pub fn bounds(&self) -> Result<Rect> {
     self.webview.bounds()
   }
@@ -1619,7 +4481,24 @@ impl WebView {
     self.webview.set_bounds(bounds)
   }
 
+  /// Sets the regions of the webview, relative to its top-left corner, where the mouse events
+  /// fall through to the window beneath, e.g. for transparent webviews overlaying native content.
+  ///
+  /// The regions replace the previous ones, an empty list makes the whole webview hit-testable again.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: The webview is not drawn in the regions either.
+  /// - **Android / iOS**: Unsupported.
+  pub fn set_hit_test_ignore_regions(&self, regions: Vec<Rect>) -> Result<()> {
+    self.webview.set_hit_test_ignore_regions(regions)
+  }
+
   /// Shows or hides the webview.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android:** The webview is made invisible but still takes up space in the layout.
   pub fn set_visible(&self, visible: bool) -> Result<()> {
     self.webview.set_visible(visible)
   }
@@ -1630,13 +4509,104 @@ impl WebView {
   }
 
   /// Try moving focus away from the webview back to the parent window.
-  ///
-  /// ## Platform-specific:
-  ///
-  /// - **Android**: Not implemented.
   pub fn focus_parent(&self) -> Result<()> {
     self.webview.focus_parent()
   }
+
+  /// Run a closure on the thread that owns this webview.
+  ///
+  /// The closure is queued on the webview's event loop and runs asynchronously,
+  /// it is not called if the webview is dropped before it gets a chance to run.
+  ///
+  /// See [`WebView::proxy`] to dispatch closures from other threads.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux**: The closure runs on the default [`glib::MainContext`](gtk::glib::MainContext).
+  /// - **Android**: The closure runs on the thread that created the webview when its looper is
+  ///   polled, which the event loop does.
+  pub fn dispatch<F>(&self, f: F)
+  where
+    F: FnOnce(&WebView) + Send + 'static,
+  {
+    self.proxy().dispatch(f)
+  }
+
+  /// Returns a [`WebViewProxy`] that can be sent to other threads to control this webview.
+  pub fn proxy(&self) -> WebViewProxy {
+    WebViewProxy {
+      key: self.key,
+      dispatcher: self.webview.dispatcher(),
+    }
+  }
+}
+
+/// A handle to control a [`WebView`] from any thread.
+///
+/// Every call is marshalled to the thread that owns the webview and runs asynchronously,
+/// so the methods don't return the result of the operation. Calls made after the webview
+/// is dropped are ignored.
+///
+/// Created with [`WebView::proxy`].
+#[derive(Clone)]
+pub struct WebViewProxy {
+  key: u32,
+  dispatcher: MainThreadDispatcher,
+}
+
+impl WebViewProxy {
+  /// Run a closure on the thread that owns the webview.
+  ///
+  /// See [`WebView::dispatch`] for more information.
+  pub fn dispatch<F>(&self, f: F)
+  where
+    F: FnOnce(&WebView) + Send + 'static,
+  {
+    let key = self.key;
+    self.dispatcher.dispatch(Box::new(move || {
+      if let Some(webview) = WebView::from_key(key) {
+        f(&webview);
+      }
+    }));
+  }
+
+  /// Navigate to the specified url.
+  ///
+  /// See [`WebView::load_url`].
+  pub fn load_url(&self, url: &str) {
+    let url = url.to_string();
+    self.dispatch(move |webview| {
+      let _ = webview.load_url(&url);
+    });
+  }
+
+  /// Evaluate and run javascript code, without waiting for its result.
+  ///
+  /// See [`WebView::evaluate_script`].
+  pub fn evaluate_script(&self, js: &str) {
+    let js = js.to_string();
+    self.dispatch(move |webview| {
+      let _ = webview.evaluate_script(&js);
+    });
+  }
+
+  /// Shows or hides the webview.
+  ///
+  /// See [`WebView::set_visible`].
+  pub fn set_visible(&self, visible: bool) {
+    self.dispatch(move |webview| {
+      let _ = webview.set_visible(visible);
+    });
+  }
+
+  /// Set the webview bounds.
+  ///
+  /// See [`WebView::set_bounds`].
+  pub fn set_bounds(&self, bounds: Rect) {
+    self.dispatch(move |webview| {
+      let _ = webview.set_bounds(bounds);
+    });
+  }
 }
 
 /// An event describing drag and drop operations on the webview.
@@ -1645,8 +4615,11 @@ impl WebView {
 pub enum DragDropEvent {
   /// A drag operation has entered the webview.
   Enter {
-    /// List of paths that are being dragged onto the webview.
+    /// List of paths that are being dragged onto the webview, normalized according to
+    /// [`WebViewBuilder::with_path_normalization`].
     paths: Vec<PathBuf>,
+    /// The paths as reported by the platform.
+    raw_paths: Vec<PathBuf>,
     /// Position of the drag operation, relative to the webview top-left corner.
     position: (i32, i32),
   },
@@ -1657,8 +4630,11 @@ pub enum DragDropEvent {
   },
   /// The file(s) have been dropped onto the window.
   Drop {
-    /// List of paths that are being dropped onto the window.
+    /// List of paths that are being dropped onto the window, normalized according to
+    /// [`WebViewBuilder::with_path_normalization`].
     paths: Vec<PathBuf>,
+    /// The paths as reported by the platform.
+    raw_paths: Vec<PathBuf>,
     /// Position of the drag operation, relative to the webview top-left corner.
     position: (i32, i32),
   },
@@ -1666,9 +4642,217 @@ pub enum DragDropEvent {
   Leave,
 }
 
-/// Get WebView/Webkit version on current platform.
-pub fn webview_version() -> Result<String> {
-  platform_webview_version()
+/// Get WebView/Webkit version on current platform.
+pub fn webview_version() -> Result<String> {
+  platform_webview_version()
+}
+
+/// Get the engine used by the webviews on the current platform, see [`EngineInfo`].
+///
+/// Use [`WebView::engine_info`] to also get the information specific to a webview.
+pub fn engine_info() -> Result<EngineInfo> {
+  platform_engine_info()
+}
+
+/// What to do when the engine is older than [`WebViewBuilder::with_minimum_engine_version`].
+#[cfg(target_os = "windows")]
+const ENGINE_UPDATE_HINT: &str =
+  "update the WebView2 Runtime from https://developer.microsoft.com/microsoft-edge/webview2/";
+#[cfg(gtk)]
+const ENGINE_UPDATE_HINT: &str = "update the webkit2gtk package of your distribution";
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+const ENGINE_UPDATE_HINT: &str = "update the operating system";
+#[cfg(target_os = "android")]
+const ENGINE_UPDATE_HINT: &str = "update Android System WebView from the Play Store";
+
+/// Compare two dotted versions component by component, ignoring what follows the digits of the
+/// components, the missing ones being `0`.
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+  let parse = |version: &str| -> Vec<u32> {
+    version
+      .split('.')
+      .map(|component| {
+        let digits = component
+          .find(|c: char| !c.is_ascii_digit())
+          .map_or(component, |end| &component[..end]);
+        digits.parse().unwrap_or_default()
+      })
+      .collect()
+  };
+  let (a, b) = (parse(a), parse(b));
+  let len = a.len().max(b.len());
+  let component = |v: &[u32], i: usize| v.get(i).copied().unwrap_or_default();
+  (0..len)
+    .map(|i| component(&a, i).cmp(&component(&b, i)))
+    .find(|ordering| ordering.is_ne())
+    .unwrap_or(std::cmp::Ordering::Equal)
+}
+
+/// The engine rendering the webviews.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+  /// Microsoft Edge WebView2, on Windows.
+  WebView2,
+  /// WebKitGTK, on Linux.
+  WebKitGTK,
+  /// WebKit, on macOS and iOS.
+  WebKit,
+  /// Android System WebView.
+  AndroidWebView,
+}
+
+/// Diagnostics about the engine rendering the webviews, see [`engine_info`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineInfo {
+  /// The engine rendering the webviews.
+  pub engine: Engine,
+  /// The version of the engine, as returned by [`webview_version`] without the channel suffix of
+  /// the WebView2 preview runtimes.
+  pub version: String,
+  /// The release channel of the runtime, e.g. `stable`, `beta`, `dev` or `canary`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / Linux / iOS / Android**: Unsupported, always `None`.
+  pub runtime_channel: Option<String>,
+  /// The directory the runtime is loaded from.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: Only known from [`WebView::engine_info`], as it is the directory of the
+  ///   browser process of the webview.
+  /// - **macOS / iOS**: The path of the `WebKit.framework` bundle.
+  /// - **Linux / Android**: Unsupported, always `None`.
+  pub runtime_path: Option<PathBuf>,
+  /// The directory where the webview stores its data, only known from [`WebView::engine_info`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS / Android**: Unsupported, always `None`.
+  pub user_data_dir: Option<PathBuf>,
+}
+
+/// The data stored by an origin, see [`WebView::storage_usage`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OriginStorageUsage {
+  /// The origin the data belongs to, a domain on Linux, macOS and iOS.
+  pub origin: String,
+  /// Size of the HTTP cache in bytes, `None` if unknown.
+  pub cache: Option<u64>,
+  /// Size of the IndexedDB databases in bytes, `None` if unknown.
+  pub indexed_db: Option<u64>,
+  /// Size of the local storage in bytes, `None` if unknown.
+  pub local_storage: Option<u64>,
+}
+
+/// The navigation state of a webview, updated by the same native signals as the page load handler.
+///
+/// See [`WebView::is_loading`] and [`WebView::current_navigation_id`].
+pub(crate) struct NavigationState {
+  is_loading: AtomicBool,
+  /// The id of the current navigation, `0` before the first navigation.
+  navigation_id: AtomicU64,
+}
+
+impl NavigationState {
+  pub const fn new() -> Self {
+    Self {
+      is_loading: AtomicBool::new(false),
+      navigation_id: AtomicU64::new(0),
+    }
+  }
+
+  /// A navigation started, with the id given by the platform if any.
+  pub fn start(&self, navigation_id: Option<u64>) {
+    match navigation_id {
+      Some(id) => self.navigation_id.store(id, Ordering::Relaxed),
+      None => {
+        self.navigation_id.fetch_add(1, Ordering::Relaxed);
+      }
+    }
+    self.is_loading.store(true, Ordering::Relaxed);
+  }
+
+  /// A navigation finished or failed, ignored if it isn't the current one.
+  pub fn finish(&self, navigation_id: Option<u64>) {
+    let current = self.navigation_id.load(Ordering::Relaxed);
+    if navigation_id.is_none() || navigation_id == Some(current) {
+      self.is_loading.store(false, Ordering::Relaxed);
+    }
+  }
+
+  pub fn is_loading(&self) -> bool {
+    self.is_loading.load(Ordering::Relaxed)
+  }
+
+  pub fn navigation_id(&self) -> Option<u64> {
+    let id = self.navigation_id.load(Ordering::Relaxed);
+    (id != 0).then_some(id)
+  }
+}
+
+/// The headers sent with the navigations to an origin, see [`WebViewAttributes::persistent_headers`].
+#[derive(Default)]
+pub(crate) struct PersistentHeaders(std::sync::Mutex<Option<(String, http::HeaderMap)>>);
+
+impl PersistentHeaders {
+  pub fn new(url: Option<&str>, headers: Option<http::HeaderMap>) -> Self {
+    let persistent_headers = Self::default();
+    if let Some(url) = url {
+      persistent_headers.set(url, headers);
+    }
+    persistent_headers
+  }
+
+  /// Send `headers` with the navigations to the origin of `url`, or stop sending headers if `None`.
+  pub fn set(&self, url: &str, headers: Option<http::HeaderMap>) {
+    *self.0.lock().unwrap() = headers
+      .filter(|headers| !headers.is_empty())
+      .zip(url_origin(url))
+      .map(|(headers, origin)| (origin, headers));
+  }
+
+  /// The headers to send with a navigation to `url`.
+  pub fn get(&self, url: &str) -> Option<http::HeaderMap> {
+    let origin = url_origin(url)?;
+    self
+      .0
+      .lock()
+      .unwrap()
+      .as_ref()
+      .filter(|(o, _)| *o == origin)
+      .map(|(_, headers)| headers.clone())
+  }
+}
+
+/// Open `url` in the system browser if it is a web, email or phone link and the activation
+/// handler allows it, returns whether the webview should ignore it.
+fn open_in_browser(
+  url: &str,
+  activation_handler: Option<&dyn Fn(WindowActivation) -> bool>,
+) -> bool {
+  let scheme = url.split_once(':').map_or("", |(scheme, _)| scheme);
+  if !["http", "https", "mailto", "tel"]
+    .iter()
+    .any(|s| s.eq_ignore_ascii_case(scheme))
+  {
+    return false;
+  }
+
+  if let Some(handler) = activation_handler {
+    if !handler(WindowActivation::ExternalUrl(url.to_string())) {
+      return true;
+    }
+  }
+
+  if let Err(_e) = opener::open_url(url) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!("failed to open {url} in the browser: {_e}");
+  }
+  true
 }
 
 /// The [memory usage target level][1]. There are two levels 'Low' and 'Normal' and the default
@@ -1688,6 +4872,30 @@ pub enum MemoryUsageLevel {
   Low,
 }
 
+/// The native webview of a [`WebView`], see [`WebView::platform_handle`].
+#[non_exhaustive]
+#[derive(Clone)]
+pub enum PlatformWebViewHandle {
+  /// The WebView2 controller and the `HWND` of the window hosting it.
+  #[cfg(target_os = "windows")]
+  WebView2 {
+    hwnd: isize,
+    controller: ICoreWebView2Controller,
+  },
+  /// The webkit2gtk webview.
+  #[cfg(gtk)]
+  WebKitGtk(webkit2gtk::WebView),
+  /// The `WKWebView` and its [user content controller](https://developer.apple.com/documentation/webkit/wkusercontentcontroller).
+  #[cfg(any(target_os = "macos", target_os = "ios"))]
+  WkWebView {
+    webview: Retained<WryWebView>,
+    manager: Retained<WKUserContentController>,
+  },
+  /// The handle to run closures with the Android webview.
+  #[cfg(target_os = "android")]
+  Android(JniHandle),
+}
+
 /// Additional methods on `WebView` that are specific to Windows.
 #[cfg(target_os = "windows")]
 pub trait WebViewExtWindows {
@@ -1716,6 +4924,15 @@ pub trait WebViewExtWindows {
 
   /// Attaches this webview to the given HWND and removes it from the current one.
   fn reparent(&self, hwnd: isize) -> Result<()>;
+
+  /// Installs and enables the unpacked browser extension located in `path` on the profile of
+  /// the webview, returning the id of the extension.
+  ///
+  /// Extensions must be enabled with [`WebViewBuilderExtWindows::with_browser_extensions_enabled`].
+  ///
+  /// Requires WebView2 Runtime version 1.0.2210.55 or higher, returns error on older versions,
+  /// see https://learn.microsoft.com/en-us/microsoft-edge/webview2/release-notes/archive?tabs=dotnetcsharp#10221055
+  fn add_extension<P: AsRef<Path>>(&self, path: P) -> Result<String>;
 }
 
 #[cfg(target_os = "windows")]
@@ -1735,6 +4952,10 @@ impl WebViewExtWindows for WebView {
   fn reparent(&self, hwnd: isize) -> Result<()> {
     self.webview.reparent(hwnd)
   }
+
+  fn add_extension<P: AsRef<Path>>(&self, path: P) -> Result<String> {
+    self.webview.add_extension(path.as_ref())
+  }
 }
 
 /// Additional methods on `WebView` that are specific to Linux.
@@ -1761,6 +4982,15 @@ pub trait WebViewExtUnix: Sized {
   fn reparent<W>(&self, widget: &W) -> Result<()>
   where
     W: gtk::prelude::IsA<gtk::Container>;
+
+  /// Send a message to the WebKit web process extension of this webview, received by the
+  /// extension with the `user-message-received` signal of its `WebKitWebPage`.
+  ///
+  /// `reply_handler` is called with the reply of the extension, or an error if the extension
+  /// doesn't handle the message.
+  fn send_web_extension_message<F>(&self, message: WebExtensionMessage, reply_handler: F)
+  where
+    F: FnOnce(Result<WebExtensionMessage>) + MaybeSend + 'static;
 }
 
 #[cfg(gtk)]
@@ -1782,6 +5012,55 @@ impl WebViewExtUnix for WebView {
   {
     self.webview.reparent(widget)
   }
+
+  fn send_web_extension_message<F>(&self, message: WebExtensionMessage, reply_handler: F)
+  where
+    F: FnOnce(Result<WebExtensionMessage>) + MaybeSend + 'static,
+  {
+    let proxy = self.proxy();
+    self
+      .webview
+      .send_web_extension_message(message, move |reply| {
+        catch_callback_panic(&proxy, "web extension reply", || reply_handler(reply));
+      })
+  }
+}
+
+/// Additional methods on [`WebContext`] that are specific to Linux.
+#[cfg(gtk)]
+pub trait WebContextExtUnix {
+  /// Set the directory from which the WebKit web process extensions are loaded.
+  ///
+  /// Web process extensions are shared libraries loaded in the web process of every webview using
+  /// this context, see the [WebKitWebExtension] documentation.
+  ///
+  /// Must be called before creating a webview with this context.
+  ///
+  /// [WebKitWebExtension]: https://webkitgtk.org/reference/webkit2gtk-web-extension/stable/class.WebExtension.html
+  fn set_web_extensions_directory<P: AsRef<Path>>(&mut self, path: P);
+
+  /// Set the data passed to the `webkit_web_extension_initialize_with_user_data` function of the
+  /// web process extensions, e.g. to pass them the configuration of the application.
+  ///
+  /// Must be called before creating a webview with this context.
+  fn set_web_extensions_initialization_user_data(&mut self, user_data: &gtk::glib::Variant);
+}
+
+#[cfg(gtk)]
+impl WebContextExtUnix for WebContext {
+  fn set_web_extensions_directory<P: AsRef<Path>>(&mut self, path: P) {
+    use webkit2gtk::WebContextExt as _;
+    self
+      .context()
+      .set_web_extensions_directory(&path.as_ref().to_string_lossy());
+  }
+
+  fn set_web_extensions_initialization_user_data(&mut self, user_data: &gtk::glib::Variant) {
+    use webkit2gtk::WebContextExt as _;
+    self
+      .context()
+      .set_web_extensions_initialization_user_data(user_data);
+  }
 }
 
 /// Additional methods on `WebView` that are specific to macOS.
@@ -1857,6 +5136,7 @@ impl WebViewExtAndroid for WebView {
 
 /// WebView theme.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Theme {
   /// Dark
   Dark,
@@ -1871,7 +5151,559 @@ pub enum Theme {
 /// Each value can be 0..255 inclusive.
 pub type RGBA = (u8, u8, u8, u8);
 
+/// The margins of the printed pages, in points (1/72 of an inch).
+#[derive(Debug, Default, Copy, Clone)]
+pub struct PrintMargin {
+  /// The margin above the content, in points.
+  pub top: f32,
+  /// The margin at the right of the content, in points.
+  pub right: f32,
+  /// The margin below the content, in points.
+  pub bottom: f32,
+  /// The margin at the left of the content, in points.
+  pub left: f32,
+}
+
+/// Options for [`WebView::print_with_options`].
+///
+/// The options set to `None` use the default of the platform.
+#[derive(Debug, Default, Clone)]
+pub struct PrintOptions {
+  /// The margins of the pages, in points. All zero by default.
+  pub margins: PrintMargin,
+  /// The text printed in the header of the pages.
+  pub header: Option<String>,
+  /// The text printed in the footer of the pages.
+  pub footer: Option<String>,
+  /// The scale of the content, `1.0` being the actual size.
+  pub scale: Option<f64>,
+  /// Whether the background colors and images are printed.
+  pub print_backgrounds: Option<bool>,
+  /// Print to the default printer without showing the print dialog.
+  pub silent: bool,
+}
+
+/// The [`PrintOptions`] supported by a webview, see [`WebView::print_capabilities`].
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PrintCapabilities {
+  /// Whether [`PrintOptions::margins`] is supported.
+  pub margins: bool,
+  /// Whether [`PrintOptions::header`] is supported.
+  pub header: bool,
+  /// Whether [`PrintOptions::footer`] is supported.
+  pub footer: bool,
+  /// Whether [`PrintOptions::scale`] is supported.
+  pub scale: bool,
+  /// Whether [`PrintOptions::print_backgrounds`] is supported.
+  pub print_backgrounds: bool,
+}
+
+/// An option set on a [`WebViewBuilder`] but unsupported on the current platform,
+/// see [`WebViewBuilder::validate`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedOption {
+  /// The name of the builder method setting the option, e.g. `with_hotkeys_zoom`.
+  pub name: &'static str,
+}
+
+impl std::fmt::Display for UnsupportedOption {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(self.name)
+  }
+}
+
+/// The device web APIs supported by a webview, see [`WebView::api_capabilities`].
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ApiCapabilities {
+  /// Whether the [Gamepad API](https://developer.mozilla.org/en-US/docs/Web/API/Gamepad_API) is supported.
+  pub gamepad: bool,
+  /// Whether the [WebHID API](https://developer.mozilla.org/en-US/docs/Web/API/WebHID_API) is supported.
+  pub hid: bool,
+  /// Whether the [WebUSB API](https://developer.mozilla.org/en-US/docs/Web/API/WebUSB_API) is supported.
+  pub usb: bool,
+  /// Whether the [Web Serial API](https://developer.mozilla.org/en-US/docs/Web/API/Web_Serial_API) is supported.
+  pub serial: bool,
+  /// Whether the [Web Authentication API](https://developer.mozilla.org/en-US/docs/Web/API/Web_Authentication_API)
+  /// is supported.
+  pub webauthn: bool,
+}
+
+/// How a webview uses the HTTP cache, see [`WebViewBuilder::with_cache_mode`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheMode {
+  /// Use the cache according to the HTTP caching headers of the responses.
+  #[default]
+  Default,
+  /// Never use the cache, always load the resources from the network.
+  NoCache,
+  /// Only load the resources from the cache, never from the network.
+  CacheOnly,
+}
+
+/// Whether a webview can load `file://` URLs and what the pages loaded from them can access, see
+/// [`WebViewBuilder::with_file_access`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileAccessPolicy {
+  /// Keep the default of the platform.
+  #[default]
+  Default,
+  /// The webview can't load `file://` URLs.
+  Deny,
+  /// The webview can load `file://` URLs, but their pages can't read the other files.
+  Isolated,
+  /// The pages loaded from `file://` URLs can read the other files.
+  AllowFiles,
+  /// The pages loaded from `file://` URLs can read the other files and any other URL, regardless
+  /// of CORS.
+  Universal,
+}
+
+/// Visibility of a webview on screen, see [`WebViewBuilder::with_visibility_changed_handler`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisibilityState {
+  /// The webview is visible, at least partially.
+  Visible,
+  /// The webview is hidden, minimized or occluded.
+  Hidden,
+}
+
+/// An event of the text inputs of a webview, see [`WebViewBuilder::with_text_input_handler`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextInputEvent {
+  /// An IME composition started in a text input of the page.
+  CompositionStarted,
+  /// The text of the IME composition changed.
+  CompositionUpdated(String),
+  /// The IME composition ended, with the text it committed.
+  CompositionEnded(String),
+  /// The virtual keyboard is shown or changed its size.
+  VirtualKeyboardShown {
+    /// The height of the keyboard, in logical pixels.
+    height: f64,
+  },
+  /// The virtual keyboard is hidden.
+  VirtualKeyboardHidden,
+}
+
+/// How a webview makes room for the virtual keyboard, see
+/// [`WebViewBuilder::with_virtual_keyboard_policy`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VirtualKeyboardPolicy {
+  /// Resize the webview, or scroll its content, so the focused input stays above the keyboard.
+  #[default]
+  Resize,
+  /// Show the keyboard over the webview, leaving the layout to the app.
+  Overlay,
+}
+
+/// A string of the native UI shown by wry for a webview, see [`WebViewBuilder::with_ui_strings`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UiString {
+  /// The button accepting the `alert`, `confirm` and `prompt` dialogs, `OK` by default.
+  DialogOk,
+  /// The button dismissing the `confirm` and `prompt` dialogs, `Cancel` by default.
+  DialogCancel,
+  /// The button of the panel opened by the file inputs, e.g. `Upload`.
+  OpenPanelPrompt,
+  /// The message shown at the top of the panel opened by the file inputs.
+  OpenPanelMessage,
+}
+
+/// The layout direction of the native UI shown by wry for a webview, see
+/// [`WebViewBuilder::with_ui_layout_direction`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UiLayoutDirection {
+  /// Follow the language of the system.
+  #[default]
+  Auto,
+  /// Lay out the UI from left to right.
+  LeftToRight,
+  /// Lay out the UI from right to left, mirroring it.
+  RightToLeft,
+}
+
+/// Tracks the visibility of a webview and calls its visibility changed handler when it changes.
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "ios"))]
+pub(crate) struct VisibilityTracker {
+  webview_visible: std::cell::Cell<bool>,
+  window_visible: std::cell::Cell<bool>,
+  handler: Box<dyn Fn(VisibilityState)>,
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "ios"))]
+impl VisibilityTracker {
+  pub(crate) fn new(visible: bool, handler: Box<dyn Fn(VisibilityState)>) -> Self {
+    Self {
+      webview_visible: std::cell::Cell::new(visible),
+      window_visible: std::cell::Cell::new(true),
+      handler,
+    }
+  }
+
+  /// Sets whether the webview itself is visible, see [`WebView::set_visible`].
+  pub(crate) fn set_webview_visible(&self, visible: bool) {
+    self.update(|| self.webview_visible.set(visible));
+  }
+
+  /// Sets whether the window of the webview is visible, i.e. not minimized or occluded.
+  #[cfg(not(target_os = "ios"))]
+  pub(crate) fn set_window_visible(&self, visible: bool) {
+    self.update(|| self.window_visible.set(visible));
+  }
+
+  fn state(&self) -> VisibilityState {
+    if self.webview_visible.get() && self.window_visible.get() {
+      VisibilityState::Visible
+    } else {
+      VisibilityState::Hidden
+    }
+  }
+
+  fn update(&self, f: impl FnOnce()) {
+    let previous = self.state();
+    f();
+    let state = self.state();
+    if state != previous {
+      (self.handler)(state);
+    }
+  }
+}
+
+/// An event of a webview, see [`WebViewBuilder::with_event_handler`].
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub enum WebViewEvent {
+  /// A navigation of the webview was requested, see [`WebViewBuilder::with_navigation_handler`].
+  NavigationRequested { url: String },
+  /// A new window was requested, see [`WebViewBuilder::with_new_window_req_handler`].
+  NewWindowRequested { url: String },
+  /// A page started or finished loading, see [`WebViewBuilder::with_on_page_load_handler`].
+  PageLoad { event: PageLoadEvent, url: String },
+  /// The title of the document changed, see [`WebViewBuilder::with_document_title_changed_handler`].
+  TitleChanged { title: String },
+  /// The visibility of the webview changed, see [`WebViewBuilder::with_visibility_changed_handler`].
+  VisibilityChanged(VisibilityState),
+  /// A download completed, see [`WebViewBuilder::with_download_completed_handler`].
+  DownloadCompleted {
+    /// The URL of the download.
+    url: String,
+    /// The path of the downloaded file, if known, normalized according to
+    /// [`WebViewBuilder::with_path_normalization`].
+    path: Option<PathBuf>,
+    /// The path of the downloaded file as reported by the platform.
+    raw_path: Option<PathBuf>,
+    /// Whether the download succeeded.
+    success: bool,
+  },
+}
+
+/// The limits of the IPC messages, see [`WebViewBuilder::with_ipc_limits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpcLimits {
+  /// The maximum length of a message, in bytes.
+  pub max_len: usize,
+  /// The maximum number of messages accepted in a second.
+  pub max_msgs_per_sec: u32,
+}
+
+/// Why an IPC message was dropped, see [`WebViewBuilder::with_ipc_dropped_handler`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpcDropReason {
+  /// The message is longer than [`IpcLimits::max_len`], with its length in bytes.
+  TooLong(usize),
+  /// The page sent more than [`IpcLimits::max_msgs_per_sec`] messages in the last second.
+  RateLimited,
+}
+
+/// Something wry is about to do that brings a window to the front,
+/// see [`WebViewBuilder::with_window_activation_handler`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowActivation {
+  /// The webview was created and wry activates the app, bringing its windows to the front.
+  /// Prevented, the webview is created without activating the app.
+  ///
+  /// Only on macOS, see also `WebViewBuilderExtDarwin::with_activate_app`.
+  WebViewCreated,
+  /// A link is opened in the system browser, which comes to the front, see
+  /// [`WebViewBuilder::with_external_links_in_browser`]. Prevented, the link isn't opened, in the
+  /// browser nor in the webview.
+  ExternalUrl(String),
+}
+
+/// A permission requested by a page, see [`WebViewBuilder::with_permission_handler`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionRequest {
+  /// The URL of the page requesting the permission.
+  pub url: String,
+  /// The requested permission.
+  pub kind: PermissionKind,
+}
+
+/// The kind of a [`PermissionRequest`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionKind {
+  /// Capturing a screen or a window with `navigator.mediaDevices.getDisplayMedia()`.
+  DisplayCapture,
+}
+
+/// The answer to a [`PermissionRequest`], see [`WebViewBuilder::with_permission_handler`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PermissionResponse {
+  /// Let the webview decide, which usually means asking the user.
+  #[default]
+  Default,
+  /// Grant the permission.
+  Allow,
+  /// Deny the permission.
+  Deny,
+  /// Grant a [`PermissionKind::DisplayCapture`] and only offer the user the surfaces of this kind.
+  AllowDisplayCapture(DisplaySurface),
+}
+
+/// The kind of surface shared by a display capture, see [`PermissionResponse::AllowDisplayCapture`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplaySurface {
+  /// A whole screen.
+  Monitor,
+  /// A window.
+  Window,
+}
+
+/// A panic caught in a handler, see [`WebViewBuilder::with_panic_handler`].
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub struct HandlerPanic {
+  /// The handler that panicked, like `ipc` or `navigation`.
+  pub handler: &'static str,
+  /// The message of the panic, empty if its payload isn't a string.
+  pub message: String,
+}
+
+/// Why the process of a webview stopped, see [`ProcessFailure`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessFailureReason {
+  /// The process crashed.
+  Crashed,
+  /// The process exceeded its memory limit or ran out of memory.
+  OutOfMemory,
+  /// The process stopped responding.
+  Unresponsive,
+  /// The process was terminated, e.g. by the system or another app.
+  Terminated,
+  /// The process exited for another or an unknown reason.
+  Unknown,
+}
+
+/// The failure of a process of a webview, see [`WebViewBuilder::with_process_failed_handler`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessFailure {
+  /// Why the process stopped.
+  pub reason: ProcessFailureReason,
+  /// The exit code of the process, if it exited.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / Linux / iOS / Android**: Unsupported, always `None`.
+  pub exit_code: Option<i32>,
+  /// A description of the failed process, e.g. `renderer` or `GPU process`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / Linux / iOS / Android**: Unsupported, always `None`.
+  pub description: Option<String>,
+  /// The directory where the crash dumps of the processes are written.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: The `FailureReportFolderPath` of the WebView2 environment. Requires WebView2
+  ///   Runtime version 112.0.1722.45 or higher.
+  /// - **macOS / Linux / iOS / Android**: Unsupported, always `None`.
+  pub crash_dumps_dir: Option<PathBuf>,
+}
+
+/// A snapshot of the state of a webview for bug reports, see [`WebView::dump_state`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebViewStateDump {
+  /// The URL of the page.
+  pub url: String,
+  /// The number of entries of the back/forward history, or `None` if it is unknown.
+  pub history_length: Option<usize>,
+  /// The URL of the navigation that started loading but didn't finish yet.
+  pub pending_navigation: Option<String>,
+}
+
+/// The durations of the stages of the creation of a webview, in milliseconds,
+/// see [`WebViewBuilder::with_creation_metrics_handler`].
+///
+/// ## Platform-specific
+///
+/// - **macOS / Linux / iOS / Android**: The platform webview is created in a single stage, timed
+///   by `settings_ms`, so `env_ms` and `controller_ms` are `0`.
+/// - **Windows**: The first page can finish loading while the webview is created, as WebView2
+///   pumps the messages meanwhile, `first_navigation_ms` is then `0`.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CreationMetrics {
+  /// The creation of the WebView2 environment.
+  pub env_ms: u64,
+  /// The creation of the WebView2 controller.
+  pub controller_ms: u64,
+  /// The configuration of the webview, until it is ready to navigate.
+  pub settings_ms: u64,
+  /// The load of the first page, from the creation of the webview until its load is finished.
+  pub first_navigation_ms: u64,
+}
+
+/// Times the stages of the creation of a webview, each one lasting since the end of the previous.
+///
+/// The messages pumped while the webview is created, e.g. by WebView2, can finish the first
+/// navigation before the creation returns, the metrics are then reported once it returns.
+pub(crate) struct CreationTimer {
+  stage_start: Cell<Instant>,
+  metrics: Cell<CreationMetrics>,
+  created: Cell<bool>,
+  navigation_finished: Cell<bool>,
+  handler: Cell<Option<Box<dyn Fn(CreationMetrics)>>>,
+}
+
+impl CreationTimer {
+  fn new(handler: Box<dyn Fn(CreationMetrics)>) -> Self {
+    Self {
+      stage_start: Cell::new(Instant::now()),
+      metrics: Cell::new(CreationMetrics::default()),
+      created: Cell::new(false),
+      navigation_finished: Cell::new(false),
+      handler: Cell::new(Some(handler)),
+    }
+  }
+
+  /// The milliseconds elapsed since the start of the stage, starting the next one.
+  fn end_stage(&self) -> u64 {
+    let now = Instant::now();
+    let elapsed = now.duration_since(self.stage_start.replace(now));
+    elapsed.as_millis() as u64
+  }
+
+  #[cfg(windows)]
+  pub(crate) fn environment_created(&self) {
+    let mut metrics = self.metrics.get();
+    metrics.env_ms = self.end_stage();
+    self.metrics.set(metrics);
+  }
+
+  #[cfg(windows)]
+  pub(crate) fn controller_created(&self) {
+    let mut metrics = self.metrics.get();
+    metrics.controller_ms = self.end_stage();
+    self.metrics.set(metrics);
+  }
+
+  fn webview_created(&self) {
+    let mut metrics = self.metrics.get();
+    metrics.settings_ms = self.end_stage();
+    self.metrics.set(metrics);
+    self.created.set(true);
+
+    if self.navigation_finished.get() {
+      self.report();
+    }
+  }
+
+  fn first_navigation_finished(&self) {
+    if self.navigation_finished.replace(true) || !self.created.get() {
+      return;
+    }
+    let mut metrics = self.metrics.get();
+    metrics.first_navigation_ms = self.end_stage();
+    self.metrics.set(metrics);
+    self.report();
+  }
+
+  fn report(&self) {
+    if let Some(handler) = self.handler.take() {
+      handler(self.metrics.get());
+    }
+  }
+}
+
+/// Call a user handler, reporting its panic, if any, to `panic_handler` instead of unwinding
+/// across the callbacks of the backends.
+fn catch_handler_panic<R>(
+  panic_handler: &Option<Rc<dyn Fn(HandlerPanic)>>,
+  handler: &'static str,
+  f: impl FnOnce() -> R,
+) -> Option<R> {
+  let payload = match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+    Ok(r) => return Some(r),
+    Err(payload) => payload,
+  };
+
+  let message = panic_message(&*payload);
+  #[cfg(feature = "tracing")]
+  tracing::error!("the {handler} handler panicked: {message}");
+  if let Some(panic_handler) = panic_handler {
+    panic_handler(HandlerPanic { handler, message });
+  }
+  None
+}
+
+/// Call a callback given to a method of the webview of `proxy`, reporting its panic, if any, to the
+/// panic handler of the webview on its thread instead of unwinding across the callbacks of the
+/// backends, which can call it on another thread.
+fn catch_callback_panic<R>(
+  proxy: &WebViewProxy,
+  callback: &'static str,
+  f: impl FnOnce() -> R,
+) -> Option<R> {
+  let payload = match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+    Ok(r) => return Some(r),
+    Err(payload) => payload,
+  };
+
+  let message = panic_message(&*payload);
+  #[cfg(feature = "tracing")]
+  tracing::error!("the {callback} callback panicked: {message}");
+  proxy.dispatch(move |webview| {
+    if let Some(panic_handler) = &webview.handlers.panic_handler {
+      panic_handler(HandlerPanic {
+        handler: callback,
+        message,
+      });
+    }
+  });
+  None
+}
+
+/// The message of a panic, if it is a string.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+  payload
+    .downcast_ref::<&str>()
+    .map(|message| message.to_string())
+    .or_else(|| payload.downcast_ref::<String>().cloned())
+    .unwrap_or_default()
+}
+
 /// Type of of page loading event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PageLoadEvent {
   /// Indicates that the content of the page has started loading
   Started,
@@ -1887,12 +5719,295 @@ pub enum PageLoadEvent {
   target_os = "openbsd",
 ))]
 #[derive(Default)]
-pub(crate) struct PlatformSpecificWebViewAttributes;
+pub(crate) struct PlatformSpecificWebViewAttributes {
+  web_extension_message_handler:
+    Option<Box<dyn Fn(WebExtensionMessage) -> Option<WebExtensionMessage>>>,
+  auto_resize: bool,
+  /// The session of the webview duplicated with [`WebView::duplicate`].
+  session: Option<webkitgtk::Session>,
+  /// See [`WebviewBuilderParts::apply_page_world_scripts`].
+  page_world_scripts: Vec<String>,
+}
 
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  #[test]
+  fn versions_are_compared_by_component() {
+    use std::cmp::Ordering;
+    assert_eq!(
+      compare_versions("120.0.2210.91", "110.0"),
+      Ordering::Greater
+    );
+    assert_eq!(compare_versions("99.0.1150.55", "110.0"), Ordering::Less);
+    assert_eq!(compare_versions("110", "110.0.0"), Ordering::Equal);
+    assert_eq!(compare_versions("2.42.5", "2.42.10"), Ordering::Less);
+    assert_eq!(
+      compare_versions("121.0.2277.4 beta", "121.0.2277.4"),
+      Ordering::Equal
+    );
+  }
+
+  #[test]
+  fn deferred_webviews_keep_the_builder_background_color() {
+    let deferred = DeferredWebView::new(WebViewBuilder::new());
+    assert_eq!(deferred.background_color(), None);
+
+    let deferred = DeferredWebView::new(WebViewBuilder::new().with_background_color((0, 0, 0, 0)));
+    assert_eq!(deferred.background_color(), Some((0, 0, 0, 0)));
+
+    let deferred = deferred.with_background_color((255, 0, 0, 255));
+    assert_eq!(deferred.background_color(), Some((255, 0, 0, 255)));
+  }
+
+  #[test]
+  fn cancelled_requests_are_not_answered() {
+    let answered = Arc::new(AtomicU32::new(0));
+    let responder = |answered: &Arc<AtomicU32>| {
+      let answered = answered.clone();
+      RequestAsyncResponder {
+        responder: Box::new(move |_| {
+          answered.fetch_add(1, Ordering::SeqCst);
+          Ok(())
+        }),
+        cancellation: CancellationToken::default(),
+      }
+    };
+
+    let cancelled = responder(&answered);
+    cancelled.cancellation_token().cancel();
+    assert!(cancelled.respond(Response::new(Vec::new())).is_err());
+    assert_eq!(answered.load(Ordering::SeqCst), 0);
+
+    // the requests are cancelled, as when the page navigates away, while the handlers are responding
+    let responders: Vec<RequestAsyncResponder> = (0..256).map(|_| responder(&answered)).collect();
+    let tokens: Vec<CancellationToken> = responders
+      .iter()
+      .map(RequestAsyncResponder::cancellation_token)
+      .collect();
+    let navigation = std::thread::spawn(move || tokens.iter().for_each(CancellationToken::cancel));
+    let handlers: Vec<_> = responders
+      .into_iter()
+      .map(|responder| {
+        std::thread::spawn(move || responder.respond(Response::new(Vec::new())).is_ok())
+      })
+      .collect();
+    navigation.join().unwrap();
+    let responded = handlers
+      .into_iter()
+      .map(|handler| handler.join().unwrap())
+      .filter(|responded| *responded)
+      .count();
+    assert_eq!(responded, answered.load(Ordering::SeqCst) as usize);
+  }
+
+  #[test]
+  fn custom_protocol_limits_are_enforced() {
+    let statuses = Arc::new(Mutex::new(Vec::new()));
+    let responder = || {
+      let statuses = statuses.clone();
+      RequestAsyncResponder {
+        responder: Box::new(move |response| {
+          statuses.lock().unwrap().push(response.status().as_u16());
+          Ok(())
+        }),
+        cancellation: CancellationToken::default(),
+      }
+    };
+
+    // the handler keeps the responders without answering
+    let kept = Arc::new(Mutex::new(Vec::new()));
+    let kept_ = kept.clone();
+    let handler = protocol_limits::limit(
+      "wry".into(),
+      CustomProtocolConfig {
+        timeout: Some(Duration::from_millis(50)),
+        max_concurrent: Some(2),
+      },
+      Box::new(move |_, _, responder| kept_.lock().unwrap().push(responder)),
+    );
+
+    for _ in 0..3 {
+      handler("webview", Request::new(Vec::new()), responder());
+    }
+    assert_eq!(*statuses.lock().unwrap(), [503]);
+
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(*statuses.lock().unwrap(), [503, 504, 504]);
+
+    // the requests timed out, so the late responses are discarded
+    for responder in kept.lock().unwrap().drain(..) {
+      assert!(responder.respond(Response::new(Vec::new())).is_err());
+    }
+
+    // and the requests timed out don't count anymore
+    handler("webview", Request::new(Vec::new()), responder());
+    let responder = kept.lock().unwrap().pop().unwrap();
+    responder.respond(Response::new(Vec::new())).unwrap();
+    assert_eq!(*statuses.lock().unwrap(), [503, 504, 504, 200]);
+  }
+
+  #[test]
+  #[cfg(feature = "protocol")]
+  fn custom_protocol_schemes_are_validated() {
+    for scheme in ["wry", "tauri", "my-app+v1.0"] {
+      assert!(validate_scheme(scheme).is_ok(), "{scheme}");
+    }
+    for scheme in [
+      "", "1wry", "wry:", "wry/", "my app", "Wry", "http", "https", "file", "blob",
+    ] {
+      assert!(
+        matches!(validate_scheme(scheme), Err(Error::InvalidScheme(name, _)) if name == scheme),
+        "{scheme}"
+      );
+    }
+
+    let builder = WebViewBuilder::new()
+      .with_custom_protocol("https".into(), |_, _| Response::new(Cow::Borrowed(&[][..])));
+    assert!(matches!(builder.inner, Err(Error::InvalidScheme(..))));
+  }
+
+  #[test]
+  #[cfg(all(unix, feature = "protocol"))]
+  fn local_socket_protocol_forwards_requests() {
+    use std::{
+      io::{BufRead, BufReader, Write},
+      os::unix::net::UnixListener,
+    };
+
+    let path = std::env::temp_dir().join(format!("wry-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+    let server = std::thread::spawn(move || {
+      let (stream, _) = listener.accept().unwrap();
+      let mut reader = BufReader::new(stream);
+      let mut request_line = String::new();
+      reader.read_line(&mut request_line).unwrap();
+      reader
+        .get_mut()
+        .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\n\r\nwry")
+        .unwrap();
+      request_line
+    });
+
+    let response = Arc::new(Mutex::new(None));
+    let response_ = response.clone();
+    let responder = RequestAsyncResponder {
+      responder: Box::new(move |r| {
+        *response_.lock().unwrap() = Some((r.status(), r.body().to_vec()));
+        Ok(())
+      }),
+      cancellation: CancellationToken::default(),
+    };
+    let handler = local_socket::LocalSocket::new(path.clone()).handler();
+    handler(
+      "webview",
+      Request::get("wry://localhost/index.html?a=b")
+        .body(Vec::new())
+        .unwrap(),
+      responder,
+    );
+
+    assert_eq!(server.join().unwrap(), "GET /index.html?a=b HTTP/1.1\r\n");
+    for _ in 0..100 {
+      if response.lock().unwrap().is_some() {
+        break;
+      }
+      std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(
+      *response.lock().unwrap(),
+      Some((http::StatusCode::OK, b"wry".to_vec()))
+    );
+    let _ = std::fs::remove_file(&path);
+  }
+
+  #[test]
+  #[cfg(feature = "reader")]
+  fn articles_are_parsed() {
+    use base64::{engine::general_purpose, Engine};
+
+    let encode = |field: &str| general_purpose::STANDARD.encode(field);
+    let result = format!(
+      "\"{},{},{},{}\"",
+      encode("Tauri 2.0"),
+      encode(""),
+      encode("<p>Stable release ✨</p>"),
+      encode("Stable release ✨")
+    );
+    let article = reader::parse_article(&result).unwrap().unwrap();
+    assert_eq!(article.title, "Tauri 2.0");
+    assert_eq!(article.byline, None);
+    assert_eq!(article.content, "<p>Stable release ✨</p>");
+    assert_eq!(article.text_content, "Stable release ✨");
+
+    assert_eq!(reader::parse_article("\"\"").unwrap(), None);
+    assert_eq!(reader::parse_article("null").unwrap(), None);
+    assert!(reader::parse_article(&format!("\"{}\"", encode("title"))).is_err());
+  }
+
+  #[test]
+  fn data_directories_are_migrated() {
+    let root = std::env::temp_dir().join(format!("wry-migration-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let (old, new) = (root.join("old"), root.join("app").join("new"));
+    std::fs::create_dir_all(old.join("EBWebView")).unwrap();
+    std::fs::write(old.join("EBWebView").join("Local State"), "{}").unwrap();
+    std::fs::write(old.join("cookies"), "wry").unwrap();
+
+    let migration = WebContext::migrate_data_directory(&old, &new).unwrap();
+    let mut migrated = migration.migrated;
+    migrated.sort();
+    assert_eq!(
+      migrated,
+      [PathBuf::from("EBWebView"), PathBuf::from("cookies")]
+    );
+    assert!(migration.failed.is_empty());
+    assert!(!old.exists());
+    assert_eq!(
+      std::fs::read_to_string(new.join("EBWebView").join("Local State")).unwrap(),
+      "{}"
+    );
+
+    // the entries already in the new directory are kept
+    std::fs::create_dir_all(&old).unwrap();
+    std::fs::write(old.join("cookies"), "old").unwrap();
+    std::fs::write(old.join("storage"), "old").unwrap();
+    let migration = WebContext::migrate_data_directory(&old, &new).unwrap();
+    assert_eq!(migration.migrated, [PathBuf::from("storage")]);
+    assert_eq!(migration.failed.len(), 1);
+    assert_eq!(migration.failed[0].0, PathBuf::from("cookies"));
+    assert_eq!(std::fs::read_to_string(new.join("cookies")).unwrap(), "wry");
+    assert_eq!(std::fs::read_to_string(old.join("cookies")).unwrap(), "old");
+
+    assert!(WebContext::migrate_data_directory(&root, &new).is_err());
+    assert_eq!(
+      WebContext::migrate_data_directory(&root.join("missing"), &new).unwrap(),
+      DataMigration::default()
+    );
+    let _ = std::fs::remove_dir_all(&root);
+  }
+
+  #[test]
+  fn external_urls_ask_the_activation_handler() {
+    let activations = std::cell::RefCell::new(Vec::new());
+    let refuse = |activation| {
+      activations.borrow_mut().push(activation);
+      false
+    };
+
+    // refused links are neither opened nor loaded in the webview
+    assert!(open_in_browser("https://tauri.app", Some(&refuse)));
+    assert!(!open_in_browser("wry://localhost", Some(&refuse)));
+    assert_eq!(
+      *activations.borrow(),
+      [WindowActivation::ExternalUrl(
+        "https://tauri.app".to_string()
+      )]
+    );
+  }
+
   #[test]
   #[cfg_attr(miri, ignore)]
   fn should_get_webview_version() {
@@ -0,0 +1,198 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Helpers specific to Linux and the BSDs.
+
+use std::{env, fmt, fs, path::Path};
+
+/// The directories searched for the libraries when they aren't in the cache of the dynamic linker.
+const LIBRARY_DIRS: &[&str] = &[
+  "/usr/lib",
+  "/usr/lib64",
+  "/usr/local/lib",
+  "/lib",
+  "/lib64",
+  "/usr/lib/x86_64-linux-gnu",
+  "/usr/lib/aarch64-linux-gnu",
+  "/usr/lib/arm-linux-gnueabihf",
+  "/usr/lib/i386-linux-gnu",
+];
+
+/// The libraries wry links to, with their package on each distribution family, in the order of
+/// [`DistroFamily`].
+const LIBRARIES: &[(&str, [&str; 5])] = &[
+  (
+    "libwebkit2gtk-4.1.so.0",
+    [
+      "libwebkit2gtk-4.1-0",
+      "webkit2gtk4.1",
+      "webkit2gtk-4.1",
+      "libwebkit2gtk-4_1-0",
+      "webkit2gtk-4.1",
+    ],
+  ),
+  (
+    "libjavascriptcoregtk-4.1.so.0",
+    [
+      "libjavascriptcoregtk-4.1-0",
+      "javascriptcoregtk4.1",
+      "webkit2gtk-4.1",
+      "libjavascriptcoregtk-4_1-0",
+      "webkit2gtk-4.1",
+    ],
+  ),
+  (
+    "libgtk-3.so.0",
+    ["libgtk-3-0", "gtk3", "gtk3", "libgtk-3-0", "gtk+3.0"],
+  ),
+  (
+    "libsoup-3.0.so.0",
+    [
+      "libsoup-3.0-0",
+      "libsoup3",
+      "libsoup3",
+      "libsoup-3_0-0",
+      "libsoup3",
+    ],
+  ),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DistroFamily {
+  Debian,
+  Fedora,
+  Arch,
+  OpenSuse,
+  Alpine,
+}
+
+impl DistroFamily {
+  /// The family of the running distribution, from the `ID` and `ID_LIKE` of `/etc/os-release`.
+  fn current() -> Option<Self> {
+    let os_release = fs::read_to_string("/etc/os-release")
+      .or_else(|_| fs::read_to_string("/usr/lib/os-release"))
+      .ok()?;
+
+    let ids: Vec<String> = os_release
+      .lines()
+      .filter_map(|line| {
+        line
+          .strip_prefix("ID=")
+          .or_else(|| line.strip_prefix("ID_LIKE="))
+      })
+      .flat_map(|value| value.trim_matches('"').split_whitespace())
+      .map(str::to_lowercase)
+      .collect();
+
+    ids.iter().find_map(|id| match id.as_str() {
+      "debian" | "ubuntu" => Some(Self::Debian),
+      "fedora" | "rhel" | "centos" => Some(Self::Fedora),
+      "arch" => Some(Self::Arch),
+      "suse" | "opensuse" => Some(Self::OpenSuse),
+      "alpine" => Some(Self::Alpine),
+      id if id.starts_with("opensuse") => Some(Self::OpenSuse),
+      _ => None,
+    })
+  }
+
+  fn install_command(self) -> &'static str {
+    match self {
+      Self::Debian => "sudo apt install",
+      Self::Fedora => "sudo dnf install",
+      Self::Arch => "sudo pacman -S",
+      Self::OpenSuse => "sudo zypper install",
+      Self::Alpine => "sudo apk add",
+    }
+  }
+}
+
+/// The libraries needed by wry that are missing, see [`check_webkit`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingWebKit {
+  /// The file names of the missing libraries, e.g. `libwebkit2gtk-4.1.so.0`.
+  pub libraries: Vec<&'static str>,
+  /// The packages providing the missing libraries on the running distribution, empty when its
+  /// family is unknown.
+  pub packages: Vec<&'static str>,
+  /// The command installing the packages, e.g. `sudo apt install libwebkit2gtk-4.1-0`.
+  pub install_command: Option<String>,
+}
+
+impl fmt::Display for MissingWebKit {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "The libraries needed to show webviews are missing: {}",
+      self.libraries.join(", ")
+    )?;
+    match &self.install_command {
+      Some(command) => write!(f, ", install them with `{command}`"),
+      None => write!(
+        f,
+        ", install WebKitGTK 4.1 with the package manager of your distribution"
+      ),
+    }
+  }
+}
+
+impl std::error::Error for MissingWebKit {}
+
+/// Check that WebKitGTK and the libraries it needs are installed, returning the missing ones and
+/// the packages to install on the running distribution, to show an actionable error instead of
+/// the error of the dynamic linker.
+///
+/// The binaries creating webviews link to WebKitGTK, so they fail to start when it is missing.
+/// Call this function from an installer or a launcher which doesn't create webviews, e.g. a small
+/// binary spawning the app.
+///
+/// ```no_run
+/// if let Err(missing) = wry::linux::check_webkit() {
+///   eprintln!("{missing}");
+///   std::process::exit(1);
+/// }
+/// ```
+pub fn check_webkit() -> Result<(), MissingWebKit> {
+  // the cache of the dynamic linker contains the file names of the libraries it knows
+  let cache = fs::read("/etc/ld.so.cache").unwrap_or_default();
+  let library_path = env::var("LD_LIBRARY_PATH").unwrap_or_default();
+  let dirs: Vec<&str> = library_path
+    .split(':')
+    .filter(|dir| !dir.is_empty())
+    .chain(LIBRARY_DIRS.iter().copied())
+    .collect();
+
+  let is_installed = |library: &str| {
+    cache
+      .windows(library.len())
+      .any(|window| window == library.as_bytes())
+      || dirs.iter().any(|dir| Path::new(dir).join(library).exists())
+  };
+
+  let missing: Vec<&(&str, [&str; 5])> = LIBRARIES
+    .iter()
+    .filter(|(library, _)| !is_installed(library))
+    .collect();
+  if missing.is_empty() {
+    return Ok(());
+  }
+
+  let family = DistroFamily::current();
+  let mut packages = Vec::new();
+  if let Some(family) = family {
+    for (_, family_packages) in &missing {
+      let package = family_packages[family as usize];
+      if !packages.contains(&package) {
+        packages.push(package);
+      }
+    }
+  }
+
+  Err(MissingWebKit {
+    libraries: missing.iter().map(|(library, _)| *library).collect(),
+    install_command: family
+      .map(|family| format!("{} {}", family.install_command(), packages.join(" "))),
+    packages,
+  })
+}
@@ -0,0 +1,69 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! A custom protocol served by another process over a local socket, see
+//! [`WebViewBuilder::with_local_socket_protocol`](crate::WebViewBuilder::with_local_socket_protocol).
+//!
+//! Each request is sent on its own connection as an HTTP/1.1 request, so the process can use any
+//! HTTP server library able to listen on a Unix domain socket or a named pipe.
+
+use std::{io, path::PathBuf};
+
+use http::{Request, Response, StatusCode};
+
+use crate::{dev_proxy, RequestAsyncResponder, WebViewId};
+
+/// The `Host` header of the requests, the process is identified by its socket.
+const HOST: &str = "localhost";
+
+/// The path of the socket of a process serving a custom protocol.
+#[derive(Clone)]
+pub(crate) struct LocalSocket {
+  path: PathBuf,
+}
+
+impl LocalSocket {
+  pub(crate) fn new(path: PathBuf) -> Self {
+    Self { path }
+  }
+
+  /// The custom protocol handler forwarding the requests to the process.
+  pub(crate) fn handler(self) -> impl Fn(WebViewId, Request<Vec<u8>>, RequestAsyncResponder) {
+    move |_, request, responder| {
+      let socket = self.clone();
+      std::thread::spawn(move || {
+        let response = socket.forward(request).unwrap_or_else(|e| {
+          #[cfg(feature = "tracing")]
+          tracing::warn!(
+            "failed to forward a request to {}: {e}",
+            socket.path.display()
+          );
+          Response::builder()
+            .status(StatusCode::BAD_GATEWAY)
+            .body(e.to_string().into_bytes())
+            .unwrap()
+        });
+        let _ = responder.respond(response);
+      });
+    }
+  }
+
+  fn forward(&self, request: Request<Vec<u8>>) -> io::Result<Response<Vec<u8>>> {
+    dev_proxy::exchange(self.connect()?, &request, "", HOST)
+  }
+
+  #[cfg(unix)]
+  fn connect(&self) -> io::Result<std::os::unix::net::UnixStream> {
+    std::os::unix::net::UnixStream::connect(&self.path)
+  }
+
+  /// Open the client end of the named pipe, which reads like a file until the server closes it.
+  #[cfg(windows)]
+  fn connect(&self) -> io::Result<std::fs::File> {
+    std::fs::OpenOptions::new()
+      .read(true)
+      .write(true)
+      .open(&self.path)
+  }
+}
@@ -0,0 +1,278 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! A loopback server accepting the WebSockets of the pages of a custom protocol, see
+//! [`WebViewBuilder::with_loopback_websocket`](crate::WebViewBuilder::with_loopback_websocket).
+//!
+//! The custom protocols of the webviews only handle request/response exchanges and can't be
+//! upgraded, so the WebSockets are redirected to a TCP listener bound to an ephemeral port of
+//! `127.0.0.1`. As any local process can connect to it, the connections must carry a random token
+//! only known by the pages.
+
+use std::{
+  io::{self, Read, Write},
+  net::{TcpListener, TcpStream},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+  thread,
+  time::{Duration, Instant},
+};
+
+use crate::{escape_js_string, util::random_token};
+
+/// The query parameter carrying the token of the connections.
+pub(crate) const TOKEN_QUERY_PARAMETER: &str = "wry_token";
+
+/// The maximum size of the request line read to check the token.
+const MAX_REQUEST_LINE_LEN: usize = 8 * 1024;
+
+/// The time a connection has to send its request line, the other connections wait meanwhile.
+const REQUEST_LINE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A TCP listener handing its authenticated connections to a handler, from a thread running until
+/// the server is dropped with the webview, or with its builder if the webview isn't built.
+pub(crate) struct LoopbackServer {
+  port: u16,
+  token: String,
+  stopped: Arc<AtomicBool>,
+}
+
+impl LoopbackServer {
+  /// Bind an ephemeral port of `127.0.0.1` and accept its connections from a background thread.
+  pub(crate) fn bind<F>(handler: F) -> io::Result<Self>
+  where
+    F: Fn(LoopbackConnection) + Send + 'static,
+  {
+    let listener = TcpListener::bind(("127.0.0.1", 0))?;
+    let port = listener.local_addr()?.port();
+    let token = random_token();
+    let stopped = Arc::new(AtomicBool::new(false));
+
+    let expected = format!("{TOKEN_QUERY_PARAMETER}={token}");
+    let stopped_ = stopped.clone();
+    thread::spawn(move || {
+      for stream in listener.incoming() {
+        if stopped_.load(Ordering::Acquire) {
+          break;
+        }
+        if let Ok(connection) = stream.and_then(|stream| authenticate(stream, &expected)) {
+          handler(connection);
+        }
+      }
+    });
+
+    Ok(Self {
+      port,
+      token,
+      stopped,
+    })
+  }
+
+  /// Redirect the WebSockets opened by the pages of the `scheme` custom protocol to their own
+  /// origin to the server.
+  pub(crate) fn websocket_script(&self, scheme: &str) -> String {
+    websocket_script(
+      scheme,
+      &format!("127.0.0.1:{}", self.port),
+      Some(&self.token),
+    )
+  }
+}
+
+impl Drop for LoopbackServer {
+  fn drop(&mut self) {
+    self.stopped.store(true, Ordering::Release);
+    // wake the thread waiting for a connection so it stops and closes the listener
+    let _ = TcpStream::connect(("127.0.0.1", self.port));
+  }
+}
+
+/// A connection accepted by [`WebViewBuilder::with_loopback_websocket`], reading the start of the
+/// upgrade request already read to check its token before the rest of the stream, so it can be
+/// handed to a WebSocket library, e.g. `tungstenite::accept`.
+///
+/// [`WebViewBuilder::with_loopback_websocket`]: crate::WebViewBuilder::with_loopback_websocket
+#[derive(Debug)]
+pub struct LoopbackConnection {
+  head: io::Cursor<Vec<u8>>,
+  stream: TcpStream,
+}
+
+impl LoopbackConnection {
+  /// The TCP stream of the connection, e.g. to set its timeouts.
+  pub fn get_ref(&self) -> &TcpStream {
+    &self.stream
+  }
+
+  /// The start of the upgrade request already read from the stream, and the stream, e.g. to hand
+  /// the connection to an asynchronous runtime.
+  pub fn into_parts(self) -> (Vec<u8>, TcpStream) {
+    let position = self.head.position() as usize;
+    let mut head = self.head.into_inner();
+    head.drain(..position);
+    (head, self.stream)
+  }
+}
+
+impl Read for LoopbackConnection {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    match self.head.read(buf)? {
+      0 => self.stream.read(buf),
+      len => Ok(len),
+    }
+  }
+}
+
+impl Write for LoopbackConnection {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    self.stream.write(buf)
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    self.stream.flush()
+  }
+}
+
+/// Read the request line of `stream` and check its token, `expected` is the query parameter
+/// carrying it.
+fn authenticate(mut stream: TcpStream, expected: &str) -> io::Result<LoopbackConnection> {
+  let deadline = Instant::now() + REQUEST_LINE_TIMEOUT;
+  let mut head = Vec::new();
+  let mut buf = [0; 1024];
+  let line_end = loop {
+    if let Some(line_end) = head.windows(2).position(|window| window == b"\r\n") {
+      break line_end;
+    }
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if remaining.is_zero() || head.len() >= MAX_REQUEST_LINE_LEN {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "invalid request line",
+      ));
+    }
+    stream.set_read_timeout(Some(remaining))?;
+    match stream.read(&mut buf)? {
+      0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+      len => head.extend_from_slice(&buf[..len]),
+    }
+  };
+
+  if !request_line_has_token(&String::from_utf8_lossy(&head[..line_end]), expected) {
+    let _ = stream.write_all(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n\r\n");
+    return Err(io::Error::new(
+      io::ErrorKind::PermissionDenied,
+      "invalid token",
+    ));
+  }
+
+  stream.set_read_timeout(None)?;
+  Ok(LoopbackConnection {
+    head: io::Cursor::new(head),
+    stream,
+  })
+}
+
+/// Whether the query of the target of `line` has the `expected` parameter.
+fn request_line_has_token(line: &str, expected: &str) -> bool {
+  line
+    .split(' ')
+    .nth(1)
+    .and_then(|target| target.split_once('?'))
+    .is_some_and(|(_, query)| query.split('&').any(|pair| pair == expected))
+}
+
+/// Redirect the WebSockets opened by the pages of the `scheme` custom protocol to their own
+/// origin, or to a `scheme` URL, to `authority`, adding `token` to their query.
+pub(crate) fn websocket_script(scheme: &str, authority: &str, token: Option<&str>) -> String {
+  let token = match token {
+    Some(token) => format!(
+      "target.searchParams.set('{TOKEN_QUERY_PARAMETER}', '{}');",
+      escape_js_string(token)
+    ),
+    None => String::new(),
+  };
+  format!(
+    r#"
+(function () {{
+  const scheme = '{scheme}';
+  const {{ protocol, hostname }} = window.location;
+  if (protocol !== scheme + ':' && hostname !== scheme + '.localhost') return;
+  const NativeWebSocket = window.WebSocket;
+  window.WebSocket = new Proxy(NativeWebSocket, {{
+    construct(target, args) {{
+      const url = new URL(args[0], window.location.href);
+      if (url.host === window.location.host || url.protocol === scheme + ':') {{
+        // the scheme of a custom protocol URL can't be changed to a special one like `ws:`
+        const target = new URL(url.pathname + url.search, 'ws://{authority}');
+        {token}
+        args[0] = target.href;
+      }}
+      return new target(...args);
+    }},
+  }});
+}})();
+"#,
+    scheme = escape_js_string(scheme),
+    authority = escape_js_string(authority),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn connections_need_the_token() {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let server = LoopbackServer::bind(move |mut connection| {
+      let mut request = String::new();
+      let _ = connection.read_to_string(&mut request);
+      let _ = tx.send(request);
+    })
+    .unwrap();
+
+    let mut stream = TcpStream::connect(("127.0.0.1", server.port)).unwrap();
+    write!(stream, "GET /?a=b HTTP/1.1\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 403"));
+
+    let request = format!(
+      "GET /chat?{TOKEN_QUERY_PARAMETER}={} HTTP/1.1\r\nUpgrade: websocket\r\n\r\n",
+      server.token
+    );
+    let mut stream = TcpStream::connect(("127.0.0.1", server.port)).unwrap();
+    stream.write_all(request.as_bytes()).unwrap();
+    stream.shutdown(std::net::Shutdown::Write).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), request);
+
+    // the listener is closed with the server
+    let port = server.port;
+    drop(server);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while TcpStream::connect(("127.0.0.1", port)).is_ok() {
+      assert!(Instant::now() < deadline, "the listener wasn't closed");
+      thread::sleep(Duration::from_millis(10));
+    }
+  }
+
+  #[test]
+  fn websockets_are_redirected_to_the_server() {
+    let script = websocket_script("wry", "127.0.0.1:1234", Some("token"));
+    // a new URL is built as setting `protocol` to `ws:` is ignored for the `wry:` URLs
+    assert!(
+      script.contains("const target = new URL(url.pathname + url.search, 'ws://127.0.0.1:1234');")
+    );
+    assert!(script.contains(&format!(
+      "target.searchParams.set('{TOKEN_QUERY_PARAMETER}', 'token');"
+    )));
+    assert!(script.contains("args[0] = target.href;"));
+    assert!(!script.contains("url.protocol = 'ws:'"));
+
+    let script = websocket_script("wry", "127.0.0.1:1234", None);
+    assert!(!script.contains(TOKEN_QUERY_PARAMETER));
+  }
+}
@@ -0,0 +1,226 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The media session of the pages relayed to the app, see
+//! [`WebViewBuilder::with_media_session_handler`].
+
+#[cfg(doc)]
+use crate::{WebView, WebViewBuilder};
+
+/// The separator of the fields of the messages sent by [`MEDIA_SESSION_SCRIPT`].
+const MEDIA_SESSION_FIELD_SEPARATOR: char = '\u{1f}';
+
+/// Sends the metadata, the playback state and the actions of the media session of the main frame
+/// as internal messages, as fields separated by [`MEDIA_SESSION_FIELD_SEPARATOR`], and runs the
+/// action handlers for [`WebView::media_session_action`].
+pub(crate) const MEDIA_SESSION_SCRIPT: &str = r#"
+(function () {
+  const session = navigator.mediaSession;
+  if (window.top !== window || !session) return;
+  const separator = '\x1f';
+  const sendFields = (...fields) => send(
+    fields.map((field) => String(field ?? '').split(separator).join('')).join(separator));
+
+  const prototype = Object.getPrototypeOf(session);
+  const metadata = Object.getOwnPropertyDescriptor(prototype, 'metadata');
+  const sendMetadata = (value) => sendFields('metadata', value?.title, value?.artist,
+    value?.album, ...Array.from(value?.artwork ?? [], (artwork) => artwork.src));
+  Object.defineProperty(session, 'metadata', {
+    get: () => metadata.get.call(session),
+    set: (value) => {
+      metadata.set.call(session, value);
+      sendMetadata(metadata.get.call(session));
+    },
+  });
+  // the fields of the metadata of the session can also be set in place
+  if (window.MediaMetadata) {
+    for (const field of ['title', 'artist', 'album', 'artwork']) {
+      const descriptor = Object.getOwnPropertyDescriptor(MediaMetadata.prototype, field);
+      if (!descriptor?.set) continue;
+      Object.defineProperty(MediaMetadata.prototype, field, {
+        ...descriptor,
+        set(value) {
+          descriptor.set.call(this, value);
+          if (metadata.get.call(session) === this) sendMetadata(this);
+        },
+      });
+    }
+  }
+  const playbackState = Object.getOwnPropertyDescriptor(prototype, 'playbackState');
+  Object.defineProperty(session, 'playbackState', {
+    get: () => playbackState.get.call(session),
+    set: (value) => {
+      playbackState.set.call(session, value);
+      sendFields('playbackState', playbackState.get.call(session));
+    },
+  });
+
+  const handlers = new Map();
+  const setActionHandler = session.setActionHandler.bind(session);
+  session.setActionHandler = (action, handler) => {
+    try {
+      setActionHandler(action, handler);
+    } catch (_) {
+      // the actions unsupported by the webview are still run by the app
+    }
+    if (handler) handlers.set(action, handler);
+    else handlers.delete(action);
+    sendFields('actions', ...handlers.keys());
+  };
+  Object.defineProperty(window, '__WRY_MEDIA_SESSION_ACTION__', {
+    value: (action, seekTime) => handlers.get(action)?.({ action, seekTime }),
+  });
+})();
+"#;
+
+/// An update of the media session of a webview, see
+/// [`WebViewBuilder::with_media_session_handler`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaSessionEvent {
+  /// The page set the metadata of the playing media.
+  MetadataChanged(MediaMetadata),
+  /// The page set its playback state.
+  PlaybackStateChanged(MediaPlaybackState),
+  /// The page set or removed the handler of an action, these are the actions it now handles.
+  ActionsChanged(Vec<MediaSessionAction>),
+}
+
+impl MediaSessionEvent {
+  /// Parse a message of [`MEDIA_SESSION_SCRIPT`].
+  pub(crate) fn from_message(message: &str) -> Option<Self> {
+    let mut fields = message.split(MEDIA_SESSION_FIELD_SEPARATOR);
+    let event = match fields.next()? {
+      "metadata" => {
+        let mut next = || fields.next().unwrap_or_default().to_string();
+        let (title, artist, album) = (next(), next(), next());
+        Self::MetadataChanged(MediaMetadata {
+          title,
+          artist,
+          album,
+          artwork: fields.map(str::to_string).collect(),
+        })
+      }
+      "playbackState" => Self::PlaybackStateChanged(match fields.next()? {
+        "playing" => MediaPlaybackState::Playing,
+        "paused" => MediaPlaybackState::Paused,
+        _ => MediaPlaybackState::None,
+      }),
+      "actions" => Self::ActionsChanged(fields.filter_map(MediaSessionAction::from_name).collect()),
+      _ => return None,
+    };
+    Some(event)
+  }
+}
+
+/// The metadata of the media played by a page, see [`MediaSessionEvent::MetadataChanged`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MediaMetadata {
+  /// The title of the media.
+  pub title: String,
+  /// The artist, or the author, of the media.
+  pub artist: String,
+  /// The album, or the collection, containing the media.
+  pub album: String,
+  /// The URLs of the artwork images.
+  pub artwork: Vec<String>,
+}
+
+/// The playback state of a media session, see [`MediaSessionEvent::PlaybackStateChanged`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaPlaybackState {
+  /// The page didn't set the state.
+  None,
+  /// The media is paused.
+  Paused,
+  /// The media is playing.
+  Playing,
+}
+
+/// An action of a media session, see [`WebView::media_session_action`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MediaSessionAction {
+  /// Start or resume the playback.
+  Play,
+  /// Pause the playback.
+  Pause,
+  /// Stop the playback and reset its position.
+  Stop,
+  /// Move the playback position backward by a few seconds.
+  SeekBackward,
+  /// Move the playback position forward by a few seconds.
+  SeekForward,
+  /// Move the playback position to the `seek_time` given to [`WebView::media_session_action`].
+  SeekTo,
+  /// Play the previous track.
+  PreviousTrack,
+  /// Play the next track.
+  NextTrack,
+}
+
+impl MediaSessionAction {
+  /// The name of the action in the Media Session API.
+  pub(crate) fn as_str(self) -> &'static str {
+    match self {
+      Self::Play => "play",
+      Self::Pause => "pause",
+      Self::Stop => "stop",
+      Self::SeekBackward => "seekbackward",
+      Self::SeekForward => "seekforward",
+      Self::SeekTo => "seekto",
+      Self::PreviousTrack => "previoustrack",
+      Self::NextTrack => "nexttrack",
+    }
+  }
+
+  fn from_name(name: &str) -> Option<Self> {
+    [
+      Self::Play,
+      Self::Pause,
+      Self::Stop,
+      Self::SeekBackward,
+      Self::SeekForward,
+      Self::SeekTo,
+      Self::PreviousTrack,
+      Self::NextTrack,
+    ]
+    .into_iter()
+    .find(|action| action.as_str() == name)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn media_session_messages_are_parsed() {
+    assert_eq!(
+      MediaSessionEvent::from_message("metadata\u{1f}Song\u{1f}Artist\u{1f}\u{1f}a.png\u{1f}b.png"),
+      Some(MediaSessionEvent::MetadataChanged(MediaMetadata {
+        title: "Song".to_string(),
+        artist: "Artist".to_string(),
+        album: String::new(),
+        artwork: vec!["a.png".to_string(), "b.png".to_string()],
+      }))
+    );
+    assert_eq!(
+      MediaSessionEvent::from_message("playbackState\u{1f}playing"),
+      Some(MediaSessionEvent::PlaybackStateChanged(
+        MediaPlaybackState::Playing
+      ))
+    );
+    assert_eq!(
+      MediaSessionEvent::from_message("actions\u{1f}play\u{1f}skipad\u{1f}seekto"),
+      Some(MediaSessionEvent::ActionsChanged(vec![
+        MediaSessionAction::Play,
+        MediaSessionAction::SeekTo
+      ]))
+    );
+    assert_eq!(MediaSessionEvent::from_message("unknown"), None);
+  }
+}
@@ -0,0 +1,116 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Opens URLs with the default application of the system, usually the browser.
+
+use crate::Result;
+
+#[cfg(target_os = "windows")]
+pub(crate) fn open_url(url: &str) -> Result<()> {
+  use windows::{
+    core::{w, HSTRING, PCWSTR},
+    Win32::{
+      Foundation::HWND,
+      UI::{Shell::ShellExecuteW, WindowsAndMessaging::SW_SHOWNORMAL},
+    },
+  };
+
+  let result = unsafe {
+    ShellExecuteW(
+      HWND::default(),
+      w!("open"),
+      &HSTRING::from(url),
+      PCWSTR::null(),
+      PCWSTR::null(),
+      SW_SHOWNORMAL,
+    )
+  };
+  // ShellExecuteW returns a value greater than 32 on success
+  if result.0 as isize > 32 {
+    Ok(())
+  } else {
+    Err(std::io::Error::last_os_error().into())
+  }
+}
+
+#[cfg(gtk)]
+pub(crate) fn open_url(url: &str) -> Result<()> {
+  use gtk::gio::{AppInfo, AppLaunchContext};
+
+  AppInfo::launch_default_for_uri(url, None::<&AppLaunchContext>).map_err(Into::into)
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn open_url(url: &str) -> Result<()> {
+  use objc2_app_kit::NSWorkspace;
+  use objc2_foundation::{NSString, NSURL};
+
+  let opened = unsafe {
+    NSURL::URLWithString(&NSString::from_str(url))
+      .is_some_and(|url| NSWorkspace::sharedWorkspace().openURL(&url))
+  };
+  if opened {
+    Ok(())
+  } else {
+    Err(std::io::Error::other(format!("failed to open {url}")).into())
+  }
+}
+
+#[cfg(target_os = "ios")]
+pub(crate) fn open_url(url: &str) -> Result<()> {
+  use objc2::{msg_send, runtime::Bool};
+  use objc2_foundation::{MainThreadMarker, NSDictionary, NSString, NSURL};
+  use objc2_ui_kit::UIApplication;
+
+  let mtm = MainThreadMarker::new().ok_or(crate::Error::NotMainThread)?;
+  let url = unsafe { NSURL::URLWithString(&NSString::from_str(url)) }
+    .ok_or_else(|| std::io::Error::other(format!("invalid URL {url}")))?;
+  let options = NSDictionary::<NSString, objc2::runtime::AnyObject>::new();
+  let completion_handler: Option<&block2::Block<dyn Fn(Bool)>> = None;
+  unsafe {
+    let app = UIApplication::sharedApplication(mtm);
+    let () =
+      msg_send![&app, openURL: &*url, options: &*options, completionHandler: completion_handler];
+  }
+  Ok(())
+}
+
+#[cfg(target_os = "android")]
+pub(crate) fn open_url(url: &str) -> Result<()> {
+  use jni::{errors::Result as JniResult, objects::JObject, JNIEnv};
+
+  fn start_view_activity(env: &mut JNIEnv, activity: &JObject, url: &str) -> JniResult<()> {
+    let url = env.new_string(url)?;
+    let uri = env
+      .call_static_method(
+        "android/net/Uri",
+        "parse",
+        "(Ljava/lang/String;)Landroid/net/Uri;",
+        &[(&url).into()],
+      )?
+      .l()?;
+    let action = env.new_string("android.intent.action.VIEW")?;
+    let intent = env.new_object(
+      "android/content/Intent",
+      "(Ljava/lang/String;Landroid/net/Uri;)V",
+      &[(&action).into(), (&uri).into()],
+    )?;
+    env.call_method(
+      activity,
+      "startActivity",
+      "(Landroid/content/Intent;)V",
+      &[(&intent).into()],
+    )?;
+    Ok(())
+  }
+
+  let url = url.to_string();
+  crate::android::dispatch(move |env, activity, _webview| {
+    if start_view_activity(env, activity, &url).is_err() {
+      // e.g. an ActivityNotFoundException when no app can open the URL
+      let _ = env.exception_clear();
+    }
+  });
+  Ok(())
+}
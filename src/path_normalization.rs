@@ -0,0 +1,153 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The normalization of the paths reported by the platform, see
+//! [`WebViewBuilder::with_path_normalization`].
+
+#[cfg(doc)]
+use crate::WebViewBuilder;
+use std::path::{Path, PathBuf};
+
+/// How the paths reported by the platform are normalized, see
+/// [`WebViewBuilder::with_path_normalization`].
+///
+/// The paths are never canonicalized, so the symbolic links aren't resolved.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PathNormalization {
+  /// Keep the paths as reported by the platform, e.g. `\\?\C:\file.txt` on Windows.
+  #[default]
+  Raw,
+  /// Remove the `\\?\` prefix of the paths on Windows when the path means the same without it,
+  /// e.g. `\\?\C:\file.txt` becomes `C:\file.txt` and `\\?\UNC\server\share\file.txt`
+  /// becomes `\\server\share\file.txt`.
+  ///
+  /// The paths which can't be written without the prefix, like the paths longer than `MAX_PATH`
+  /// or with reserved names, are kept as is.
+  Simplified,
+}
+
+impl PathNormalization {
+  pub(crate) fn normalize(self, path: &Path) -> PathBuf {
+    match self {
+      Self::Raw => path.to_path_buf(),
+      #[cfg(windows)]
+      Self::Simplified => simplified_path(path),
+      #[cfg(not(windows))]
+      Self::Simplified => path.to_path_buf(),
+    }
+  }
+
+  pub(crate) fn normalize_all(self, paths: &[PathBuf]) -> Vec<PathBuf> {
+    paths.iter().map(|path| self.normalize(path)).collect()
+  }
+}
+
+/// Remove the `\\?\` prefix of `path` if it means the same without it.
+#[cfg(windows)]
+fn simplified_path(path: &Path) -> PathBuf {
+  // dunce only simplifies the paths of the disks, not the paths of the network shares
+  match path
+    .to_str()
+    .and_then(|path| path.strip_prefix(r"\\?\UNC\"))
+  {
+    Some(unc) if is_simple_unc_path(unc) => PathBuf::from(format!(r"\\{unc}")),
+    Some(_) => path.to_path_buf(),
+    None => dunce::simplified(path).to_path_buf(),
+  }
+}
+
+/// Whether `server\share\path`, a network share path without its `\\?\UNC\` prefix, means the
+/// same with the `\\` prefix.
+///
+/// Without the verbatim prefix, Windows limits the length of the path, removes the trailing dots
+/// and spaces of the components and maps the reserved names to devices.
+#[cfg(windows)]
+fn is_simple_unc_path(unc: &str) -> bool {
+  const MAX_PATH: usize = 260;
+  const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "CONIN$", "CONOUT$", "COM0", "COM1", "COM2", "COM3", "COM4",
+    "COM5", "COM6", "COM7", "COM8", "COM9", "LPT0", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6",
+    "LPT7", "LPT8", "LPT9",
+  ];
+
+  if unc.len() + 2 >= MAX_PATH {
+    return false;
+  }
+
+  let components: Vec<&str> = unc.split('\\').collect();
+  // the server and the share are required, and only the last component may be empty, for a
+  // trailing separator
+  if components.len() < 2
+    || components[1].is_empty()
+    || components[..components.len() - 1].contains(&"")
+  {
+    return false;
+  }
+
+  components
+    .iter()
+    .filter(|component| !component.is_empty())
+    .all(|component| {
+      let stem = component.split('.').next().unwrap_or_default().trim_end();
+      // also rejects `.` and `..`
+      !component.ends_with(['.', ' '])
+        && !component
+          .chars()
+          .any(|c| c < ' ' || matches!(c, '<' | '>' | ':' | '"' | '/' | '|' | '?' | '*'))
+        && !RESERVED_NAMES
+          .iter()
+          .any(|name| stem.eq_ignore_ascii_case(name))
+    })
+}
+
+#[cfg(all(test, windows))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn paths_are_simplified() {
+    let simplified = |path: &str| PathNormalization::Simplified.normalize(Path::new(path));
+
+    assert_eq!(
+      simplified(r"\\?\C:\Users\wry\file.txt"),
+      Path::new(r"C:\Users\wry\file.txt")
+    );
+    assert_eq!(
+      simplified(r"C:\Users\wry\file.txt"),
+      Path::new(r"C:\Users\wry\file.txt")
+    );
+
+    // network shares
+    assert_eq!(
+      simplified(r"\\?\UNC\server\share\folder\file.txt"),
+      Path::new(r"\\server\share\folder\file.txt")
+    );
+    assert_eq!(
+      simplified(r"\\?\UNC\server\share\"),
+      Path::new(r"\\server\share\")
+    );
+    assert_eq!(
+      simplified(r"\\server\share\file.txt"),
+      Path::new(r"\\server\share\file.txt")
+    );
+    for path in [
+      r"\\?\UNC\server",
+      r"\\?\UNC\server\share\con.txt",
+      r"\\?\UNC\server\share\folder.\file.txt",
+      r"\\?\UNC\server\share\..\file.txt",
+      r"\\?\UNC\server\share\a\\file.txt",
+      r"\\?\UNC\server\share\a:b",
+    ] {
+      assert_eq!(simplified(path), Path::new(path));
+    }
+    let long = format!(r"\\?\UNC\server\share\{}", "a".repeat(300));
+    assert_eq!(simplified(&long), Path::new(&long));
+
+    assert_eq!(
+      PathNormalization::Raw.normalize(Path::new(r"\\?\UNC\server\share\file.txt")),
+      Path::new(r"\\?\UNC\server\share\file.txt")
+    );
+  }
+}
@@ -0,0 +1,1872 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The steps preparing the attributes of a [`WebViewBuilder`] for the
+//! backends, see [`WebviewBuilderParts::prepare`].
+
+use std::{
+  cell::Cell,
+  path::{Path, PathBuf},
+  rc::Rc,
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use http::{
+  header::{HeaderValue, CONTENT_SECURITY_POLICY},
+  Request, Response,
+};
+
+#[cfg(target_os = "ios")]
+use crate::TitlebarSeparatorStyle;
+#[cfg(any(gtk, target_os = "macos", target_os = "ios"))]
+use crate::FORCED_DARK_CONTENT_CSS;
+#[cfg(target_os = "android")]
+use crate::{
+  android::{self, MainThreadDispatcher},
+  catch_callback_panic, WebViewProxy,
+};
+use crate::{
+  blank_bootstrap_script, broadcast,
+  broadcast::{BROADCAST_CHANNEL_POLYFILL_SCRIPT, BROADCAST_SCRIPT},
+  catch_handler_panic, compare_versions, content_security_policy_script, engine_info,
+  internal_messages::InternalMessages,
+  is_custom_protocol_origin, is_redirect_uri,
+  media_session::MEDIA_SESSION_SCRIPT,
+  open_in_browser, protocol_limits, url_origin, BroadcastReceiver, CacheMode, CreationTimer,
+  DownloadCompletedHandlerWithRawPath, DragDropEvent, Error, FileAccessPolicy, HandlerSlot,
+  IpcDropReason, MediaSessionEvent, PageLoadEvent, PathNormalization, PermissionResponse,
+  PreparedWebView, RequestAsyncResponder, Result, RuntimeHandlers, TextInputEvent,
+  UiLayoutDirection, UnsupportedOption, UrlPattern, VirtualKeyboardPolicy, WebView, WebViewEvent,
+  WebViewId, WebviewBuilderParts, WindowActivation, ENGINE_UPDATE_HINT, FIRST_PAINT_SCRIPT,
+  TEXT_INPUT_SCRIPT,
+};
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+use crate::{drag_region::DRAG_REGION_SCRIPT, DragRegion};
+#[cfg(doc)]
+use crate::{WebContext, WebViewAttributes, WebViewBuilder};
+
+impl WebviewBuilderParts<'_> {
+  /// Validate the attributes and apply them to the handlers and the scripts of the webview, in
+  /// the order the steps depend on each other.
+  pub(crate) fn prepare(&mut self) -> Result<PreparedWebView> {
+    self.validate()?;
+    self.apply_blank_bootstrap();
+    self.apply_profile();
+    #[cfg(not(gtk))]
+    self.apply_context_custom_protocols()?;
+    self.apply_content_security_policy()?;
+    self.apply_forced_dark_content();
+    self.apply_context_handlers();
+    let handlers = self.apply_runtime_handlers();
+    self.apply_path_normalization();
+    self.apply_event_handler();
+    self.apply_file_access();
+    self.apply_navigation_allowlist();
+    let mut messages = InternalMessages::new();
+    self.apply_broadcast(&handlers, &mut messages);
+    #[cfg(any(gtk, target_os = "macos"))]
+    let hide_content = self.attrs.show_after_first_paint && self.attrs.visible;
+    self.apply_show_after_first_paint(&handlers);
+    self.apply_first_paint_handler(&mut messages);
+    self.apply_text_input_events(&mut messages);
+    self.apply_media_session_events(&mut messages);
+    self.apply_fetch_responses(&handlers, &mut messages);
+    self.apply_drag_region_support(&handlers, &mut messages);
+    self.apply_ipc_filters(messages);
+    self.apply_page_world_scripts();
+    self.apply_navigation_tracking(&handlers);
+    self.apply_custom_protocol_configs();
+    self.apply_custom_protocol_cancellation(&handlers);
+    self.apply_external_links_in_browser();
+    self.apply_auth_session();
+    let creation_timer = self.apply_creation_metrics();
+    self.apply_panic_isolation();
+
+    if let Some(id) = self.attrs.id {
+      let _ = self.webview_id.set(id.to_string());
+    }
+
+    Ok(PreparedWebView {
+      key: self.key,
+      handlers,
+      webview_id: self.webview_id.clone(),
+      creation_timer,
+      #[cfg(any(gtk, target_os = "macos"))]
+      hide_content,
+      hit_test_ignore_regions: std::mem::take(&mut self.attrs.hit_test_ignore_regions),
+    })
+  }
+
+  /// Block the navigations to URLs not matching [`WebViewAttributes::navigation_allowlist`].
+  fn apply_navigation_allowlist(&mut self) {
+    if self.attrs.navigation_allowlist.is_empty() {
+      return;
+    }
+
+    let allowlist = Rc::new(self.attrs.navigation_allowlist.clone());
+
+    let navigation_handler = self.attrs.navigation_handler.take();
+    let allowlist_ = allowlist.clone();
+    self.attrs.navigation_handler = Some(Box::new(move |url| {
+      allowlist_.iter().any(|pattern| pattern.matches(&url))
+        && navigation_handler
+          .as_ref()
+          .map_or(true, |handler| handler(url))
+    }));
+
+    let new_window_req_handler = self.attrs.new_window_req_handler.take();
+    self.attrs.new_window_req_handler = Some(Box::new(move |url| {
+      allowlist.iter().any(|pattern| pattern.matches(&url))
+        && new_window_req_handler
+          .as_ref()
+          .map_or(true, |handler| handler(url))
+    }));
+  }
+
+  /// Normalize the paths passed to the drag drop and download handlers according to
+  /// [`WebViewAttributes::path_normalization`].
+  ///
+  /// This is applied before the event handler, which reports both the raw and normalized paths.
+  /// The download handlers set with the `*_with_raw_path` methods replace the other ones here.
+  fn apply_path_normalization(&mut self) {
+    let normalization = self.attrs.path_normalization;
+
+    if let Some(drag_drop_handler) = self.attrs.drag_drop_handler.take() {
+      self.attrs.drag_drop_handler = Some(match normalization {
+        PathNormalization::Raw => drag_drop_handler,
+        _ => Box::new(move |event| {
+          drag_drop_handler(match event {
+            DragDropEvent::Enter {
+              raw_paths,
+              position,
+              ..
+            } => DragDropEvent::Enter {
+              paths: normalization.normalize_all(&raw_paths),
+              raw_paths,
+              position,
+            },
+            DragDropEvent::Drop {
+              raw_paths,
+              position,
+              ..
+            } => DragDropEvent::Drop {
+              paths: normalization.normalize_all(&raw_paths),
+              raw_paths,
+              position,
+            },
+            event => event,
+          })
+        }),
+      });
+    }
+
+    let download_started_handler = match (
+      self.download_started_handler_with_raw_path.take(),
+      self.attrs.download_started_handler.take(),
+    ) {
+      (Some(handler), _) => Some(handler),
+      (None, Some(mut handler)) => {
+        Some(Box::new(move |url, _: &Path, path: &mut PathBuf| handler(url, path)) as _)
+      }
+      (None, None) => None,
+    };
+    if let Some(mut download_started_handler) = download_started_handler {
+      self.attrs.download_started_handler = Some(Box::new(move |url, path| {
+        let normalized = normalization.normalize(path);
+        let mut destination = normalized.clone();
+        let allow = download_started_handler(url, path, &mut destination);
+        // the platform keeps its own destination unless the handler changed it
+        if destination != normalized {
+          *path = destination;
+        }
+        allow
+      }));
+    }
+
+    let download_completed_handler = match (
+      self.download_completed_handler_with_raw_path.take(),
+      self.attrs.download_completed_handler.take(),
+    ) {
+      (Some(handler), _) => Some(handler),
+      (None, Some(handler)) => {
+        Some(
+          Rc::new(move |url, path, _, success| handler(url, path, success))
+            as DownloadCompletedHandlerWithRawPath,
+        )
+      }
+      (None, None) => None,
+    };
+    if let Some(download_completed_handler) = download_completed_handler {
+      self.attrs.download_completed_handler = Some(Rc::new(move |url, raw_path, success| {
+        let path = raw_path
+          .as_deref()
+          .map(|path| normalization.normalize(path));
+        download_completed_handler(url, path, raw_path, success)
+      }));
+    }
+  }
+
+  /// Block the navigations to `file://` URLs when [`WebViewAttributes::file_access`] is
+  /// [`FileAccessPolicy::Deny`].
+  fn apply_file_access(&mut self) {
+    if self.attrs.file_access != FileAccessPolicy::Deny {
+      return;
+    }
+
+    let is_file_url = |url: &str| {
+      url
+        .get(..5)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("file:"))
+    };
+
+    let navigation_handler = self.attrs.navigation_handler.take();
+    self.attrs.navigation_handler = Some(Box::new(move |url| {
+      if is_file_url(&url) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!("blocked the navigation to {url}, the file access is denied");
+        return false;
+      }
+      match &navigation_handler {
+        Some(handler) => handler(url),
+        None => true,
+      }
+    }));
+
+    let new_window_req_handler = self.attrs.new_window_req_handler.take();
+    self.attrs.new_window_req_handler = Some(Box::new(move |url| {
+      !is_file_url(&url)
+        && match &new_window_req_handler {
+          Some(handler) => handler(url),
+          None => true,
+        }
+    }));
+  }
+
+  /// Drop the IPC messages of the pages not matching [`WebViewAttributes::ipc_allowed_origins`].
+  fn apply_ipc_allowed_origins(&mut self) {
+    if self.attrs.ipc_allowed_origins.is_empty() {
+      return;
+    }
+
+    let Some(ipc_handler) = self.attrs.ipc_handler.take() else {
+      return;
+    };
+    let allowed_origins = self.attrs.ipc_allowed_origins.clone();
+    self.attrs.ipc_handler = Some(Box::new(move |request| {
+      let url = request.uri().to_string();
+      if allowed_origins.iter().any(|pattern| pattern.matches(&url)) {
+        ipc_handler(request);
+      } else {
+        #[cfg(feature = "tracing")]
+        tracing::warn!("dropped the IPC message of {url}, its origin is not allowed");
+      }
+    }));
+  }
+
+  /// Filter the IPC messages before they reach the IPC handler.
+  ///
+  /// The messages of the pages not matching [`WebViewAttributes::ipc_allowed_origins`] are dropped
+  /// first, so they don't count towards the [`WebViewAttributes::ipc_limits`]. The internal
+  /// messages are then routed, and are not subject to the limits, which only apply to the
+  /// messages passed to the IPC handler of the app.
+  fn apply_ipc_filters(&mut self, messages: InternalMessages) {
+    self.apply_ipc_limits();
+    self.apply_internal_messages(messages);
+    self.apply_ipc_allowed_origins();
+  }
+
+  /// Drop the IPC messages beyond [`WebViewAttributes::ipc_limits`].
+  fn apply_ipc_limits(&mut self) {
+    let Some(limits) = self.attrs.ipc_limits else {
+      return;
+    };
+    let Some(ipc_handler) = self.attrs.ipc_handler.take() else {
+      return;
+    };
+
+    let ipc_dropped_handler = self.attrs.ipc_dropped_handler.take();
+    // The start of the current one-second window and the number of messages received in it
+    let window = Cell::new((Instant::now(), 0u32));
+    self.attrs.ipc_handler = Some(Box::new(move |request| {
+      let len = request.body().len();
+      let reason = if len > limits.max_len {
+        Some(IpcDropReason::TooLong(len))
+      } else {
+        let now = Instant::now();
+        let (start, count) = match window.get() {
+          (start, _) if now.duration_since(start) >= Duration::from_secs(1) => (now, 0),
+          window => window,
+        };
+        window.set((start, count.saturating_add(1)));
+        (count >= limits.max_msgs_per_sec).then_some(IpcDropReason::RateLimited)
+      };
+
+      let Some(reason) = reason else {
+        ipc_handler(request);
+        return;
+      };
+
+      let url = request.uri().to_string();
+      #[cfg(feature = "tracing")]
+      tracing::warn!("dropped an IPC message of {url}: {reason:?}");
+      if let Some(handler) = &ipc_dropped_handler {
+        handler(url, reason);
+      }
+    }));
+  }
+
+  /// Hide a visible webview until its first paint, see
+  /// [`WebViewAttributes::show_after_first_paint`].
+  ///
+  /// The hidden webviews don't paint, so only their content is hidden on Linux and macOS, once they
+  /// are created, and they keep painting. The webview is hidden on the other platforms.
+  ///
+  /// This must be applied before [`Self::apply_first_paint_handler`].
+  fn apply_show_after_first_paint(&mut self, handlers: &Rc<RuntimeHandlers>) {
+    if !self.attrs.show_after_first_paint || !self.attrs.visible {
+      return;
+    }
+
+    #[cfg(not(any(gtk, target_os = "macos")))]
+    {
+      self.attrs.visible = false;
+    }
+
+    let handlers = Rc::downgrade(handlers);
+    let first_paint_handler = self.attrs.first_paint_handler.take();
+    self.attrs.first_paint_handler = Some(Box::new(move || {
+      if let Some(webview) = handlers
+        .upgrade()
+        .and_then(|h| WebView::inner_from_handlers(&h))
+      {
+        #[cfg(any(gtk, target_os = "macos"))]
+        let _ = webview.set_content_visible(true);
+        #[cfg(not(any(gtk, target_os = "macos")))]
+        let _ = webview.set_visible(true);
+      }
+      if let Some(handler) = &first_paint_handler {
+        handler();
+      }
+    }));
+  }
+
+  /// Report the first paint of the webview once to [`WebViewAttributes::first_paint_handler`].
+  ///
+  /// The backends report it natively on macOS and iOS, and it is reported with the message of
+  /// [`FIRST_PAINT_SCRIPT`] on the other platforms. The first page finishing to load is reported
+  /// too, in case its paint isn't, e.g. when it fails to load.
+  #[cfg_attr(any(target_os = "macos", target_os = "ios"), allow(unused_variables))]
+  fn apply_first_paint_handler(&mut self, messages: &mut InternalMessages) {
+    let Some(first_paint_handler) = self.attrs.first_paint_handler.take() else {
+      return;
+    };
+
+    let painted = Cell::new(false);
+    let first_paint_handler = Rc::new(move || {
+      if !painted.replace(true) {
+        first_paint_handler();
+      }
+    });
+
+    let handler = first_paint_handler.clone();
+    let on_page_load_handler = self.attrs.on_page_load_handler.take();
+    self.attrs.on_page_load_handler = Some(Box::new(move |event, url| {
+      if event == PageLoadEvent::Finished {
+        handler();
+      }
+      if let Some(handler) = &on_page_load_handler {
+        handler(event, url);
+      }
+    }));
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    {
+      self.attrs.first_paint_handler = Some(Box::new(move || first_paint_handler()));
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    {
+      let script = messages.add("first-paint", FIRST_PAINT_SCRIPT, move |_, _| {
+        first_paint_handler()
+      });
+      self.attrs.initialization_scripts.push(script);
+    }
+  }
+
+  /// Report the IME compositions of the page to [`WebViewAttributes::text_input_handler`], with
+  /// the messages of [`TEXT_INPUT_SCRIPT`]. The backends report the virtual keyboard to the same
+  /// handler.
+  fn apply_text_input_events(&mut self, messages: &mut InternalMessages) {
+    let Some(text_input_handler) = self.attrs.text_input_handler.take() else {
+      return;
+    };
+    let text_input_handler = Rc::new(text_input_handler);
+
+    let handler = text_input_handler.clone();
+    let script = messages.add("text-input", TEXT_INPUT_SCRIPT, move |_, message| {
+      let Some((kind, data)) = message.split_once(':') else {
+        return;
+      };
+      let event = match kind {
+        "compositionstart" => TextInputEvent::CompositionStarted,
+        "compositionupdate" => TextInputEvent::CompositionUpdated(data.to_string()),
+        "compositionend" => TextInputEvent::CompositionEnded(data.to_string()),
+        _ => return,
+      };
+      handler(event);
+    });
+    self.attrs.initialization_scripts.push(script);
+
+    self.attrs.text_input_handler = Some(Box::new(move |event| text_input_handler(event)));
+  }
+
+  /// Report the media session of the page to [`WebViewAttributes::media_session_handler`], with
+  /// the messages of [`MEDIA_SESSION_SCRIPT`].
+  fn apply_media_session_events(&mut self, messages: &mut InternalMessages) {
+    let Some(media_session_handler) = self.attrs.media_session_handler.take() else {
+      return;
+    };
+
+    let script = messages.add("media-session", MEDIA_SESSION_SCRIPT, move |_, message| {
+      if let Some(event) = MediaSessionEvent::from_message(message) {
+        media_session_handler(event);
+      }
+    });
+    self.page_world_scripts.push(script);
+  }
+
+  /// Register the webview on its [`WebContext`] for [`WebContext::broadcast`] and relay the
+  /// messages broadcast by its pages with [`BROADCAST_SCRIPT`] to the other webviews, if the
+  /// [`WebViewAttributes::broadcast_origins`] are set.
+  fn apply_broadcast(&mut self, handlers: &Rc<RuntimeHandlers>, messages: &mut InternalMessages) {
+    let Some(context) = &self.attrs.context else {
+      return;
+    };
+    if self.attrs.broadcast_origins.is_empty() {
+      return;
+    }
+
+    let origins: Rc<[UrlPattern]> = self.attrs.broadcast_origins.clone().into();
+    let webviews = context.webviews.clone();
+    webviews.borrow_mut().push(BroadcastReceiver {
+      handlers: Rc::downgrade(handlers),
+      origins: origins.clone(),
+    });
+
+    let sender = Rc::downgrade(handlers);
+    let script = messages.add("broadcast", BROADCAST_SCRIPT, move |request, message| {
+      let url = request.uri().to_string();
+      if origins.iter().any(|pattern| pattern.matches(&url)) {
+        let _ = broadcast(&webviews, message, Some(&sender));
+      } else {
+        #[cfg(feature = "tracing")]
+        tracing::warn!("dropped the broadcast message of {url}, its origin is not allowed");
+      }
+    });
+    self.page_world_scripts.push(script);
+    if self.attrs.broadcast_channel_polyfill {
+      self
+        .page_world_scripts
+        .push(BROADCAST_CHANNEL_POLYFILL_SCRIPT.to_string());
+    }
+  }
+
+  /// Pass the responses of the requests of [`WebView::fetch`] to their future, and fail them when
+  /// their page is unloaded.
+  ///
+  /// The responses are only sent with the internal IPC messages on Android, which are checked by
+  /// [`Self::apply_ipc_allowed_origins`] before being routed, but not by
+  /// [`Self::apply_ipc_limits`].
+  #[cfg_attr(not(target_os = "android"), allow(unused_variables))]
+  fn apply_fetch_responses(
+    &mut self,
+    handlers: &Rc<RuntimeHandlers>,
+    messages: &mut InternalMessages,
+  ) {
+    #[cfg(target_os = "android")]
+    {
+      let handlers_ = Rc::downgrade(handlers);
+      let prefix = messages.route("fetch", move |_, message| {
+        if let Some(handlers) = handlers_.upgrade() {
+          handlers.fetches.complete(message);
+        }
+      });
+      handlers.fetches.set_message_prefix(prefix);
+    }
+
+    let handlers_ = Rc::downgrade(handlers);
+    let on_page_load_handler = self.attrs.on_page_load_handler.take();
+    self.attrs.on_page_load_handler = Some(Box::new(move |event, url| {
+      if let (PageLoadEvent::Started, Some(handlers)) = (&event, handlers_.upgrade()) {
+        handlers.fetches.cancel_all();
+      }
+      if let Some(handler) = &on_page_load_handler {
+        handler(event, url);
+      }
+    }));
+  }
+
+  /// Keep the navigation in progress for [`WebView::dump_state`].
+  fn apply_navigation_tracking(&mut self, handlers: &Rc<RuntimeHandlers>) {
+    let handlers_ = Rc::downgrade(handlers);
+    let on_page_load_handler = self.attrs.on_page_load_handler.take();
+    self.attrs.on_page_load_handler = Some(Box::new(move |event, url| {
+      if let Some(handlers) = handlers_.upgrade() {
+        *handlers.pending_navigation.borrow_mut() = match event {
+          PageLoadEvent::Started => Some(url.clone()),
+          PageLoadEvent::Finished => None,
+        };
+      }
+      if let Some(handler) = &on_page_load_handler {
+        handler(event, url);
+      }
+    }));
+  }
+
+  /// Enforce the [`WebViewAttributes::custom_protocol_configs`] on their protocols.
+  fn apply_custom_protocol_configs(&mut self) {
+    for (name, config) in &self.attrs.custom_protocol_configs {
+      if let Some(handler) = self.attrs.custom_protocols.remove(name) {
+        let handler = protocol_limits::limit(name.clone(), *config, handler);
+        self.attrs.custom_protocols.insert(name.clone(), handler);
+      }
+    }
+  }
+
+  /// Track the custom protocol requests to cancel them when the page is replaced, see
+  /// [`RequestAsyncResponder::cancellation_token`].
+  fn apply_custom_protocol_cancellation(&mut self, handlers: &Rc<RuntimeHandlers>) {
+    if self.attrs.custom_protocols.is_empty() {
+      return;
+    }
+
+    for handler in self.attrs.custom_protocols.values_mut() {
+      let inner = std::mem::replace(handler, Box::new(|_, _, _| {}));
+      let requests = handlers.custom_protocol_requests.clone();
+      *handler = Box::new(move |id, request, responder| {
+        let cancellation = responder.cancellation.clone();
+        requests.lock().unwrap().push(cancellation.clone());
+
+        let requests = requests.clone();
+        let cancellation_ = cancellation.clone();
+        let responder = RequestAsyncResponder {
+          responder: Box::new(move |response| {
+            requests
+              .lock()
+              .unwrap()
+              .retain(|token| !token.is_same(&cancellation_));
+            (responder.responder)(response)
+          }),
+          cancellation,
+        };
+        inner(id, request, responder)
+      });
+    }
+
+    // The requests of the previous page are abandoned once the new one is committed
+    let handlers_ = Rc::downgrade(handlers);
+    let on_page_load_handler = self.attrs.on_page_load_handler.take();
+    self.attrs.on_page_load_handler = Some(Box::new(move |event, url| {
+      if event == PageLoadEvent::Started {
+        if let Some(handlers) = handlers_.upgrade() {
+          handlers.cancel_custom_protocol_requests();
+        }
+      }
+      if let Some(handler) = &on_page_load_handler {
+        handler(event, url);
+      }
+    }));
+  }
+
+  /// Open the links leaving the app in the system browser instead of the webview,
+  /// see [`WebViewAttributes::external_links_in_browser`].
+  fn apply_external_links_in_browser(&mut self) {
+    if !self.attrs.external_links_in_browser {
+      return;
+    }
+
+    let app_origin = self.attrs.url.as_deref().and_then(url_origin);
+    let custom_protocols: Vec<String> = self.attrs.custom_protocols.keys().cloned().collect();
+
+    // shared with the backend, which may activate the app too
+    let activation_handler: Option<Rc<dyn Fn(WindowActivation) -> bool>> =
+      self.attrs.window_activation_handler.take().map(Rc::from);
+    if let Some(handler) = activation_handler.clone() {
+      self.attrs.window_activation_handler = Some(Box::new(move |activation| handler(activation)));
+    }
+
+    let navigation_handler = self.attrs.navigation_handler.take();
+    let activation_handler_ = activation_handler.clone();
+    self.attrs.navigation_handler = Some(Box::new(move |url| {
+      let is_app_url = url_origin(&url).is_some_and(|origin| {
+        Some(&origin) == app_origin.as_ref()
+          || is_custom_protocol_origin(&origin, &custom_protocols)
+      });
+      if !is_app_url && open_in_browser(&url, activation_handler_.as_deref()) {
+        return false;
+      }
+      navigation_handler
+        .as_ref()
+        .map_or(true, |handler| handler(url))
+    }));
+
+    let new_window_req_handler = self.attrs.new_window_req_handler.take();
+    self.attrs.new_window_req_handler = Some(Box::new(move |url| {
+      if open_in_browser(&url, activation_handler.as_deref()) {
+        return false;
+      }
+      new_window_req_handler
+        .as_ref()
+        .map_or(true, |handler| handler(url))
+    }));
+  }
+
+  /// Move or resize the window with the drag regions of the page,
+  /// see [`WebViewAttributes::drag_region_support`].
+  #[cfg_attr(any(target_os = "android", target_os = "ios"), allow(unused_variables))]
+  fn apply_drag_region_support(
+    &mut self,
+    handlers: &Rc<RuntimeHandlers>,
+    messages: &mut InternalMessages,
+  ) {
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    if self.attrs.drag_region_support {
+      let handlers = Rc::downgrade(handlers);
+      let script = messages.add("drag-region", DRAG_REGION_SCRIPT, move |_, message| {
+        let Some(region) = DragRegion::parse(message) else {
+          return;
+        };
+        if let Some(webview) = handlers
+          .upgrade()
+          .and_then(|h| WebView::inner_from_handlers(&h))
+        {
+          webview.drag_window(region);
+        }
+      });
+      self.attrs.initialization_scripts.push(script);
+    }
+  }
+
+  /// Pass the messages of the scripts of wry to their handler instead of the IPC handler, see
+  /// [`InternalMessages`].
+  fn apply_internal_messages(&mut self, messages: InternalMessages) {
+    let ipc_handler = self.attrs.ipc_handler.take();
+    self.attrs.ipc_handler = messages.wrap(ipc_handler);
+  }
+
+  /// Add the [`Self::page_world_scripts`] after the initialization scripts, or to the page world
+  /// when the initialization scripts run in an isolated world.
+  ///
+  /// Their internal messages are then sent with the `window.ipc` of the page world, which forwards
+  /// them to the isolated world like the messages of the page.
+  fn apply_page_world_scripts(&mut self) {
+    let scripts = std::mem::take(&mut self.page_world_scripts);
+    #[cfg(any(gtk, target_os = "macos", target_os = "ios"))]
+    if self.attrs.isolated_world {
+      self.platform_specific.page_world_scripts = scripts;
+      return;
+    }
+    self.attrs.initialization_scripts.extend(scripts);
+  }
+
+  /// Start timing the creation of the webview for [`WebViewAttributes::creation_metrics_handler`],
+  /// returning the timer to notify once the platform webview is created.
+  fn apply_creation_metrics(&mut self) -> Option<Rc<CreationTimer>> {
+    let handler = self.attrs.creation_metrics_handler.take()?;
+    let panic_handler = self.attrs.panic_handler.clone();
+    let timer = Rc::new(CreationTimer::new(Box::new(move |metrics| {
+      catch_handler_panic(&panic_handler, "creation metrics", || handler(metrics));
+    })));
+
+    let on_page_load_handler = self.attrs.on_page_load_handler.take();
+    let timer_ = timer.clone();
+    self.attrs.on_page_load_handler = Some(Box::new(move |event, url| {
+      if let PageLoadEvent::Finished = event {
+        timer_.first_navigation_finished();
+      }
+      if let Some(handler) = &on_page_load_handler {
+        handler(event, url);
+      }
+    }));
+
+    #[cfg(windows)]
+    {
+      self.platform_specific.creation_timer = Some(timer.clone());
+    }
+
+    Some(timer)
+  }
+
+  /// Catch the panics of the handlers, see [`WebViewAttributes::panic_handler`].
+  ///
+  /// This must be applied after every other step wrapping the handlers.
+  fn apply_panic_isolation(&mut self) {
+    let panic_handler = self.attrs.panic_handler.take();
+
+    if let Some(ipc_handler) = self.attrs.ipc_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.attrs.ipc_handler = Some(Box::new(move |request| {
+        catch_handler_panic(&panic_handler, "ipc", || ipc_handler(request));
+      }));
+    }
+
+    if let Some(drag_drop_handler) = self.attrs.drag_drop_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.attrs.drag_drop_handler = Some(Box::new(move |event| {
+        catch_handler_panic(&panic_handler, "drag drop", || drag_drop_handler(event))
+          .unwrap_or_default()
+      }));
+    }
+
+    if let Some(navigation_handler) = self.attrs.navigation_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.attrs.navigation_handler = Some(Box::new(move |url| {
+        catch_handler_panic(&panic_handler, "navigation", || navigation_handler(url))
+          .unwrap_or_default()
+      }));
+    }
+
+    if let Some(new_window_req_handler) = self.attrs.new_window_req_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.attrs.new_window_req_handler = Some(Box::new(move |url| {
+        catch_handler_panic(&panic_handler, "new window request", || {
+          new_window_req_handler(url)
+        })
+        .unwrap_or_default()
+      }));
+    }
+
+    if let Some(mut download_started_handler) = self.attrs.download_started_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.attrs.download_started_handler = Some(Box::new(move |url, path: &mut PathBuf| {
+        catch_handler_panic(&panic_handler, "download started", || {
+          download_started_handler(url, path)
+        })
+        .unwrap_or_default()
+      }));
+    }
+
+    if let Some(download_completed_handler) = self.attrs.download_completed_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.attrs.download_completed_handler = Some(Rc::new(move |url, path, success| {
+        catch_handler_panic(&panic_handler, "download completed", || {
+          download_completed_handler(url, path, success)
+        });
+      }));
+    }
+
+    if let Some(document_title_changed_handler) = self.attrs.document_title_changed_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.attrs.document_title_changed_handler = Some(Box::new(move |title| {
+        catch_handler_panic(&panic_handler, "document title changed", || {
+          document_title_changed_handler(title)
+        });
+      }));
+    }
+
+    if let Some(before_unload_handler) = self.attrs.before_unload_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.attrs.before_unload_handler = Some(Box::new(move |url, message| {
+        // let the page unload if the handler panics, as the user can't be asked anymore
+        catch_handler_panic(&panic_handler, "before unload", || {
+          before_unload_handler(url, message)
+        })
+        .unwrap_or(true)
+      }));
+    }
+
+    if let Some(window_activation_handler) = self.attrs.window_activation_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.attrs.window_activation_handler = Some(Box::new(move |activation| {
+        catch_handler_panic(&panic_handler, "window activation", || {
+          window_activation_handler(activation)
+        })
+        .unwrap_or(true)
+      }));
+    }
+
+    if let Some(permission_handler) = self.attrs.permission_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.attrs.permission_handler = Some(Box::new(move |request| {
+        catch_handler_panic(&panic_handler, "permission", || permission_handler(request))
+          .unwrap_or(PermissionResponse::Deny)
+      }));
+    }
+
+    if let Some(close_requested_handler) = self.attrs.close_requested_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.attrs.close_requested_handler = Some(Box::new(move || {
+        catch_handler_panic(&panic_handler, "close requested", &close_requested_handler)
+          .unwrap_or(true)
+      }));
+    }
+
+    if let Some(process_failed_handler) = self.attrs.process_failed_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.attrs.process_failed_handler = Some(Box::new(move |failure| {
+        catch_handler_panic(&panic_handler, "process failed", || {
+          process_failed_handler(failure)
+        });
+      }));
+    }
+
+    if let Some(text_input_handler) = self.attrs.text_input_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.attrs.text_input_handler = Some(Box::new(move |event| {
+        catch_handler_panic(&panic_handler, "text input", || text_input_handler(event));
+      }));
+    }
+
+    if let Some(visibility_changed_handler) = self.attrs.visibility_changed_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.attrs.visibility_changed_handler = Some(Box::new(move |state| {
+        catch_handler_panic(&panic_handler, "visibility changed", || {
+          visibility_changed_handler(state)
+        });
+      }));
+    }
+
+    if let Some(bounds_changed_handler) = self.attrs.bounds_changed_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.attrs.bounds_changed_handler = Some(Box::new(move |bounds| {
+        catch_handler_panic(&panic_handler, "bounds changed", || {
+          bounds_changed_handler(bounds)
+        });
+      }));
+    }
+
+    if let Some(on_page_load_handler) = self.attrs.on_page_load_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.attrs.on_page_load_handler = Some(Box::new(move |event, url| {
+        catch_handler_panic(&panic_handler, "page load", || {
+          on_page_load_handler(event, url)
+        });
+      }));
+    }
+
+    #[cfg(gtk)]
+    if let Some(handler) = self.platform_specific.web_extension_message_handler.take() {
+      let panic_handler = panic_handler.clone();
+      self.platform_specific.web_extension_message_handler = Some(Box::new(move |message| {
+        catch_handler_panic(&panic_handler, "web extension message", || handler(message)).flatten()
+      }));
+    }
+
+    // called on the JavaBridge thread, so the panics are reported on the thread of the webview
+    #[cfg(target_os = "android")]
+    {
+      let proxy = WebViewProxy {
+        key: self.key,
+        dispatcher: MainThreadDispatcher::current(),
+      };
+      let interfaces = std::mem::take(&mut self.platform_specific.javascript_interfaces);
+      for (name, interface) in interfaces {
+        let proxy = proxy.clone();
+        let handler: android::JavascriptInterface = Box::new(move |method: &str, payload| {
+          catch_callback_panic(&proxy, "javascript interface", || {
+            interface(method, payload)
+          })
+          .flatten()
+        });
+        self
+          .platform_specific
+          .javascript_interfaces
+          .insert(name, handler);
+      }
+    }
+
+    let custom_protocols = std::mem::take(&mut self.attrs.custom_protocols);
+    for (name, handler) in custom_protocols {
+      let panic_handler = panic_handler.clone();
+      let handler: Box<dyn Fn(WebViewId, Request<Vec<u8>>, RequestAsyncResponder)> =
+        Box::new(move |id, request, responder| {
+          // Keep the responder to answer the request if the handler panics before using it
+          let cancellation = responder.cancellation.clone();
+          let pending = Arc::new(Mutex::new(Some(responder)));
+          let pending_ = pending.clone();
+          let responder = RequestAsyncResponder {
+            responder: Box::new(move |response| {
+              let responder = pending_.lock().unwrap().take();
+              match responder {
+                Some(responder) => responder.respond(response),
+                None => Err(Error::CustomProtocolTaskInvalid),
+              }
+            }),
+            cancellation,
+          };
+
+          if catch_handler_panic(&panic_handler, "custom protocol", || {
+            handler(id, request, responder)
+          })
+          .is_none()
+          {
+            let responder = pending.lock().unwrap().take();
+            if let Some(responder) = responder {
+              let _ = responder.respond(
+                Response::builder()
+                  .status(http::StatusCode::INTERNAL_SERVER_ERROR)
+                  .body(Vec::<u8>::new())
+                  .unwrap(),
+              );
+            }
+          }
+        });
+      self.attrs.custom_protocols.insert(name, handler);
+    }
+  }
+
+  /// Capture the redirection of [`WebViewAttributes::auth_session`], before any other navigation
+  /// rule is applied.
+  ///
+  /// The data store is made non-persistent here, after [`Self::validate`], so the platforms without
+  /// [`WebViewAttributes::ephemeral_storage`] don't report an option the app didn't set.
+  fn apply_auth_session(&mut self) {
+    let Some((redirect_uri, handler)) = self.attrs.auth_session.take() else {
+      return;
+    };
+    self.attrs.ephemeral_storage = true;
+
+    let handler = Rc::new(Cell::new(Some(handler)));
+    let is_redirect = Rc::new(move |url: &str| {
+      if is_redirect_uri(url, &redirect_uri) {
+        if let Some(handler) = handler.take() {
+          handler(url.to_string());
+        }
+        true
+      } else {
+        false
+      }
+    });
+
+    let navigation_handler = self.attrs.navigation_handler.take();
+    let is_redirect_ = is_redirect.clone();
+    self.attrs.navigation_handler = Some(Box::new(move |url| {
+      if is_redirect_(&url) {
+        return false;
+      }
+      match &navigation_handler {
+        Some(handler) => handler(url),
+        None => true,
+      }
+    }));
+
+    let new_window_req_handler = self.attrs.new_window_req_handler.take();
+    self.attrs.new_window_req_handler = Some(Box::new(move |url| {
+      if is_redirect(&url) {
+        return false;
+      }
+      match &new_window_req_handler {
+        Some(handler) => handler(url),
+        None => true,
+      }
+    }));
+  }
+
+  /// Enforce [`WebViewAttributes::content_security_policy`] by setting the `Content-Security-Policy`
+  /// header on the custom protocols responses and injecting a `<meta>` element in every document.
+  fn apply_content_security_policy(&mut self) -> Result<()> {
+    let Some(policy) = &self.attrs.content_security_policy else {
+      return Ok(());
+    };
+
+    let header = HeaderValue::from_str(policy).map_err(http::Error::from)?;
+
+    for handler in self.attrs.custom_protocols.values_mut() {
+      let inner = std::mem::replace(handler, Box::new(|_, _, _| {}));
+      let header = header.clone();
+      *handler = Box::new(move |id, request, responder| {
+        let header = header.clone();
+        let cancellation = responder.cancellation.clone();
+        let responder = RequestAsyncResponder {
+          responder: Box::new(move |mut response| {
+            response
+              .headers_mut()
+              .insert(CONTENT_SECURITY_POLICY, header);
+            (responder.responder)(response)
+          }),
+          cancellation,
+        };
+        inner(id, request, responder)
+      });
+    }
+
+    self
+      .attrs
+      .initialization_scripts
+      .push(content_security_policy_script(policy));
+
+    Ok(())
+  }
+
+  /// Load `about:blank` and build the document with [`WebViewAttributes::blank_bootstrap`].
+  fn apply_blank_bootstrap(&mut self) {
+    let Some(js) = self.attrs.blank_bootstrap.take() else {
+      return;
+    };
+
+    self.attrs.url = Some("about:blank".to_string());
+    self.attrs.html = None;
+    self
+      .attrs
+      .initialization_scripts
+      .push(blank_bootstrap_script(&js));
+  }
+
+  /// Use the data store of the profile of the webview.
+  #[cfg_attr(gtk, allow(unused_variables))]
+  fn apply_profile(&mut self) {
+    let Some(profile) = &self.attrs.profile else {
+      return;
+    };
+
+    // the webkit2gtk context of the profile is shared by its webviews, and the WebView2 profile is
+    // set on the controller
+    #[cfg(not(gtk))]
+    if profile.options().ephemeral {
+      self.attrs.ephemeral_storage = true;
+    }
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    if self.platform_specific.data_store_identifier.is_none() {
+      self.platform_specific.data_store_identifier = Some(profile.identifier());
+    }
+  }
+
+  /// Darken the content with a user style sheet on the platforms without native support for
+  /// [`WebViewAttributes::forced_dark_content`].
+  fn apply_forced_dark_content(&mut self) {
+    #[cfg(any(gtk, target_os = "macos", target_os = "ios"))]
+    if self.attrs.forced_dark_content {
+      self
+        .attrs
+        .user_style_sheets
+        .push((FORCED_DARK_CONTENT_CSS.to_string(), false));
+    }
+  }
+
+  /// Use the download handlers registered on the [`WebContext`] for the handlers the webview did
+  /// not set.
+  ///
+  /// The IPC and navigation handlers of the context are used by [`Self::apply_runtime_handlers`].
+  fn apply_context_handlers(&mut self) {
+    let handlers = match &self.attrs.context {
+      Some(context) => context.handlers.clone(),
+      None => return,
+    };
+
+    if let (None, Some(handler)) = (
+      &self.attrs.download_started_handler,
+      handlers.download_started_handler,
+    ) {
+      let id = self.webview_id.clone();
+      self.attrs.download_started_handler = Some(Box::new(move |url, path| {
+        handler(id.get().map(String::as_str).unwrap_or_default(), url, path)
+      }));
+    }
+
+    if let (None, Some(handler)) = (
+      &self.attrs.download_completed_handler,
+      handlers.download_completed_handler,
+    ) {
+      let id = self.webview_id.clone();
+      self.attrs.download_completed_handler = Some(Rc::new(move |url, path, success| {
+        handler(
+          id.get().map(String::as_str).unwrap_or_default(),
+          url,
+          path,
+          success,
+        )
+      }));
+    }
+  }
+
+  /// The options set on the builder but unsupported on the current platform.
+  pub(crate) fn unsupported_options(&self) -> Vec<UnsupportedOption> {
+    let attrs = &self.attrs;
+    let options: &[(bool, &'static str)] = &[
+      #[cfg(not(windows))]
+      (attrs.zoom_hotkeys_enabled, "with_hotkeys_zoom"),
+      #[cfg(not(windows))]
+      (attrs.virtual_authenticator, "with_virtual_authenticator"),
+      #[cfg(any(gtk, windows))]
+      (attrs.cache_mode == CacheMode::CacheOnly, "with_cache_mode"),
+      #[cfg(not(any(gtk, windows)))]
+      (attrs.data_directory.is_some(), "with_data_directory"),
+      #[cfg(any(target_os = "android", target_os = "ios"))]
+      (
+        attrs.back_forward_navigation_gestures,
+        "with_back_forward_navigation_gestures",
+      ),
+      #[cfg(any(target_os = "android", target_os = "ios"))]
+      (attrs.drag_region_support, "with_drag_region_support"),
+      #[cfg(any(windows, target_os = "android"))]
+      (attrs.isolated_world, "with_isolated_world"),
+      #[cfg(windows)]
+      (
+        attrs.before_unload_handler.is_some(),
+        "with_before_unload_handler",
+      ),
+      #[cfg(any(target_os = "android", target_os = "ios"))]
+      (attrs.proxy_config.is_some(), "with_proxy_config"),
+      #[cfg(any(target_os = "android", target_os = "ios"))]
+      (
+        attrs.permission_handler.is_some(),
+        "with_permission_handler",
+      ),
+      #[cfg(target_os = "android")]
+      (attrs.ephemeral_storage, "with_ephemeral_storage"),
+      #[cfg(target_os = "android")]
+      (attrs.profile.is_some(), "with_profile"),
+      #[cfg(target_os = "android")]
+      (
+        attrs.media_keeps_display_awake,
+        "with_media_keeps_display_awake",
+      ),
+      #[cfg(target_os = "android")]
+      (
+        attrs.visibility_changed_handler.is_some(),
+        "with_visibility_changed_handler",
+      ),
+      #[cfg(any(target_os = "android", target_os = "ios"))]
+      (
+        attrs.bounds_changed_handler.is_some(),
+        "with_bounds_changed_handler",
+      ),
+      #[cfg(any(target_os = "android", target_os = "ios"))]
+      (
+        !attrs.hit_test_ignore_regions.is_empty(),
+        "with_hit_test_ignore_regions",
+      ),
+      #[cfg(target_os = "ios")]
+      (!attrs.focused, "with_focused"),
+      #[cfg(any(windows, gtk, target_os = "ios"))]
+      (!attrs.ui_strings.is_empty(), "with_ui_strings"),
+      #[cfg(any(windows, gtk, target_os = "ios"))]
+      (
+        attrs.ui_layout_direction != UiLayoutDirection::Auto,
+        "with_ui_layout_direction",
+      ),
+      #[cfg(not(any(target_os = "android", target_os = "ios")))]
+      (
+        attrs.virtual_keyboard_policy == VirtualKeyboardPolicy::Overlay,
+        "with_virtual_keyboard_policy",
+      ),
+      #[cfg(target_os = "ios")]
+      (
+        !self.platform_specific.synthetic_mouse_events,
+        "with_synthetic_mouse_events",
+      ),
+      #[cfg(target_os = "ios")]
+      (!self.platform_specific.activate_app, "with_activate_app"),
+      #[cfg(target_os = "ios")]
+      (
+        self.platform_specific.titlebar_separator != Some(TitlebarSeparatorStyle::None),
+        "with_titlebar_separator",
+      ),
+      #[cfg(target_os = "ios")]
+      (
+        !self.platform_specific.replace_content_view,
+        "with_replace_content_view",
+      ),
+    ];
+
+    options
+      .iter()
+      .filter(|(set, _)| *set)
+      .map(|&(_, name)| UnsupportedOption { name })
+      .collect()
+  }
+
+  /// Fail on the unsupported options in strict mode, see [`WebViewBuilder::validate`].
+  fn validate(&self) -> Result<()> {
+    if let Some(minimum) = &self.attrs.minimum_engine_version {
+      let version = engine_info()?.version;
+      if compare_versions(&version, minimum).is_lt() {
+        return Err(Error::EngineTooOld {
+          version,
+          minimum: minimum.clone(),
+          hint: ENGINE_UPDATE_HINT,
+        });
+      }
+    }
+
+    let unsupported = self.unsupported_options();
+    if unsupported.is_empty() {
+      return Ok(());
+    }
+
+    if self.attrs.strict_validation {
+      return Err(Error::UnsupportedOptions(unsupported));
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::warn!("options unsupported on this platform are ignored: {unsupported:?}");
+    Ok(())
+  }
+
+  /// Move the handlers to [`RuntimeHandlers`] so they can be replaced once the webview is created,
+  /// see [`WebView::set_navigation_handler`], with the IPC and navigation handlers of the
+  /// [`WebContext`] called when the ones of the webview are cleared or not set.
+  ///
+  /// The IPC handler is only installed if the builder or the context set one, so the pages of the
+  /// other webviews don't get an IPC channel nobody listens to. The other handlers are always
+  /// installed and pass the events to nobody until they are set. This is applied before the other
+  /// handler wrappers, which keep applying to the new handlers.
+  fn apply_runtime_handlers(&mut self) -> Rc<RuntimeHandlers> {
+    let context_handlers = self
+      .attrs
+      .context
+      .as_ref()
+      .map(|context| context.handlers.clone())
+      .unwrap_or_default();
+    let id = self.webview_id.clone();
+    let context_ipc_handler = context_handlers.ipc_handler.map(|handler| {
+      Rc::new(move |request| handler(id.get().map(String::as_str).unwrap_or_default(), request))
+        as Rc<dyn Fn(Request<String>)>
+    });
+    let id = self.webview_id.clone();
+    let context_navigation_handler = context_handlers.navigation_handler.map(|handler| {
+      Rc::new(move |url| handler(id.get().map(String::as_str).unwrap_or_default(), url))
+        as Rc<dyn Fn(String) -> bool>
+    });
+
+    let handlers = Rc::new(RuntimeHandlers {
+      ipc_handler: HandlerSlot::new(
+        self.attrs.ipc_handler.take().map(Rc::from),
+        context_ipc_handler,
+      ),
+      navigation_handler: HandlerSlot::installed(
+        self.attrs.navigation_handler.take().map(Rc::from),
+        context_navigation_handler,
+      ),
+      new_window_req_handler: HandlerSlot::installed(
+        self.attrs.new_window_req_handler.take().map(Rc::from),
+        None,
+      ),
+      document_title_changed_handler: HandlerSlot::installed(
+        self
+          .attrs
+          .document_title_changed_handler
+          .take()
+          .map(Rc::from),
+        None,
+      ),
+      on_page_load_handler: HandlerSlot::installed(
+        self.attrs.on_page_load_handler.take().map(Rc::from),
+        None,
+      ),
+      fetches: Default::default(),
+      pending_navigation: Default::default(),
+      custom_protocol_requests: Default::default(),
+      panic_handler: self.attrs.panic_handler.clone(),
+      #[cfg(feature = "protocol")]
+      _loopback_servers: std::mem::take(&mut self.loopback_servers),
+    });
+
+    // The handlers are cloned out of the slots so they can replace themselves
+    if handlers.ipc_handler.installed {
+      let handlers_ = handlers.clone();
+      self.attrs.ipc_handler = Some(Box::new(move |request| {
+        if let Some(handler) = handlers_.ipc_handler.get() {
+          handler(request);
+        }
+      }));
+    }
+
+    let handlers_ = handlers.clone();
+    self.attrs.navigation_handler = Some(Box::new(move |url| {
+      match handlers_.navigation_handler.get() {
+        Some(handler) => handler(url),
+        None => true,
+      }
+    }));
+
+    let handlers_ = handlers.clone();
+    self.attrs.new_window_req_handler = Some(Box::new(move |url| {
+      match handlers_.new_window_req_handler.get() {
+        Some(handler) => handler(url),
+        None => true,
+      }
+    }));
+
+    let handlers_ = handlers.clone();
+    self.attrs.document_title_changed_handler = Some(Box::new(move |title| {
+      if let Some(handler) = handlers_.document_title_changed_handler.get() {
+        handler(title);
+      }
+    }));
+
+    let handlers_ = handlers.clone();
+    self.attrs.on_page_load_handler = Some(Box::new(move |event, url| {
+      if let Some(handler) = handlers_.on_page_load_handler.get() {
+        handler(event, url);
+      }
+    }));
+
+    handlers
+  }
+
+  /// Send the events of the handlers to [`WebViewAttributes::event_handler`].
+  ///
+  /// This is applied before the navigation rules so only the navigations passed to the navigation
+  /// handler are reported.
+  fn apply_event_handler(&mut self) {
+    let Some(event_handler) = self.attrs.event_handler.take() else {
+      return;
+    };
+
+    let handler = event_handler.clone();
+    let navigation_handler = self.attrs.navigation_handler.take();
+    self.attrs.navigation_handler = Some(Box::new(move |url| {
+      handler(WebViewEvent::NavigationRequested { url: url.clone() });
+      navigation_handler
+        .as_ref()
+        .map_or(true, |handler| handler(url))
+    }));
+
+    let handler = event_handler.clone();
+    let new_window_req_handler = self.attrs.new_window_req_handler.take();
+    self.attrs.new_window_req_handler = Some(Box::new(move |url| {
+      handler(WebViewEvent::NewWindowRequested { url: url.clone() });
+      new_window_req_handler
+        .as_ref()
+        .map_or(true, |handler| handler(url))
+    }));
+
+    let handler = event_handler.clone();
+    let on_page_load_handler = self.attrs.on_page_load_handler.take();
+    self.attrs.on_page_load_handler = Some(Box::new(move |event, url| {
+      handler(WebViewEvent::PageLoad {
+        event,
+        url: url.clone(),
+      });
+      if let Some(on_page_load_handler) = &on_page_load_handler {
+        on_page_load_handler(event, url);
+      }
+    }));
+
+    let handler = event_handler.clone();
+    let document_title_changed_handler = self.attrs.document_title_changed_handler.take();
+    self.attrs.document_title_changed_handler = Some(Box::new(move |title| {
+      handler(WebViewEvent::TitleChanged {
+        title: title.clone(),
+      });
+      if let Some(document_title_changed_handler) = &document_title_changed_handler {
+        document_title_changed_handler(title);
+      }
+    }));
+
+    let handler = event_handler.clone();
+    let visibility_changed_handler = self.attrs.visibility_changed_handler.take();
+    self.attrs.visibility_changed_handler = Some(Box::new(move |state| {
+      handler(WebViewEvent::VisibilityChanged(state));
+      if let Some(visibility_changed_handler) = &visibility_changed_handler {
+        visibility_changed_handler(state);
+      }
+    }));
+
+    let handler = event_handler;
+    let normalization = self.attrs.path_normalization;
+    let download_completed_handler = self.attrs.download_completed_handler.take();
+    self.attrs.download_completed_handler = Some(Rc::new(move |url, path, success| {
+      handler(WebViewEvent::DownloadCompleted {
+        url: url.clone(),
+        path: path.as_deref().map(|path| normalization.normalize(path)),
+        raw_path: path.clone(),
+        success,
+      });
+      if let Some(download_completed_handler) = &download_completed_handler {
+        download_completed_handler(url, path, success);
+      }
+    }));
+  }
+
+  /// Add the custom protocols registered on the [`WebContext`] to the webview attributes.
+  ///
+  /// This is not needed on Linux as the protocols are registered on the webkit2gtk context.
+  #[cfg(not(gtk))]
+  fn apply_context_custom_protocols(&mut self) -> Result<()> {
+    if let Some(context) = &self.attrs.context {
+      for (name, handler) in &context.custom_protocol_handlers {
+        if self.attrs.custom_protocols.contains_key(name) {
+          return Err(Error::DuplicateCustomProtocol(name.clone()));
+        }
+
+        let handler = handler.clone();
+        self.attrs.custom_protocols.insert(
+          name.clone(),
+          Box::new(move |id, request, responder| handler(id, request, responder)),
+        );
+      }
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{cell::RefCell, collections::HashMap};
+
+  use super::*;
+  use crate::{
+    PermissionKind, PermissionRequest, ProfileOptions, Rect, UiString, WebContext, WebViewBuilder,
+  };
+
+  #[test]
+  fn handler_panics_are_caught() {
+    let panics = Arc::new(Mutex::new(Vec::new()));
+    let panics_ = panics.clone();
+    let mut parts = WebViewBuilder::new()
+      .with_ipc_handler(|_| panic!("ipc"))
+      .with_navigation_handler(|_| panic!("navigation"))
+      .with_panic_handler(move |panic| panics_.lock().unwrap().push((panic.handler, panic.message)))
+      .inner
+      .unwrap();
+    parts.apply_panic_isolation();
+
+    (parts.attrs.ipc_handler.take().unwrap())(Request::new(String::new()));
+    assert!(!(parts.attrs.navigation_handler.take().unwrap())(
+      "https://tauri.app".to_string()
+    ));
+    assert_eq!(
+      *panics.lock().unwrap(),
+      [
+        ("ipc", "ipc".to_string()),
+        ("navigation", "navigation".to_string())
+      ]
+    );
+  }
+
+  #[test]
+  fn creation_metrics_handler_panics_are_caught() {
+    let panics = Arc::new(Mutex::new(Vec::new()));
+    let panics_ = panics.clone();
+    let mut parts = WebViewBuilder::new()
+      .with_creation_metrics_handler(|_| panic!("metrics"))
+      .with_panic_handler(move |panic| panics_.lock().unwrap().push(panic.handler))
+      .inner
+      .unwrap();
+    let timer = parts.apply_creation_metrics().unwrap();
+
+    timer.webview_created();
+    timer.first_navigation_finished();
+    assert_eq!(*panics.lock().unwrap(), ["creation metrics"]);
+  }
+
+  #[test]
+  fn creation_metrics_are_reported_once_created() {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let reports_ = reports.clone();
+    let mut parts = WebViewBuilder::new()
+      .with_creation_metrics_handler(move |metrics| reports_.lock().unwrap().push(metrics))
+      .inner
+      .unwrap();
+    let timer = parts.apply_creation_metrics().unwrap();
+
+    // the first page loads in the messages pumped while the webview is created
+    timer.first_navigation_finished();
+    assert!(reports.lock().unwrap().is_empty());
+    timer.webview_created();
+    timer.first_navigation_finished();
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].first_navigation_ms, 0);
+  }
+
+  #[test]
+  fn handlers_can_be_set_once_created() {
+    let mut parts = WebViewBuilder::new().inner.unwrap();
+    let handlers = parts.apply_runtime_handlers();
+    assert!(parts.attrs.ipc_handler.is_none());
+    assert!(matches!(
+      handlers.ipc_handler.set("ipc", Some(Rc::new(|_| {}))),
+      Err(Error::HandlerNotInstalled("ipc"))
+    ));
+
+    let navigation_handler = parts.attrs.navigation_handler.take().unwrap();
+    assert!(navigation_handler("https://tauri.app".to_string()));
+    handlers
+      .navigation_handler
+      .set(
+        "navigation",
+        Some(Rc::new(|url: String| !url.starts_with("https://tauri"))),
+      )
+      .unwrap();
+    assert!(!navigation_handler("https://tauri.app".to_string()));
+
+    let titles = Rc::new(RefCell::new(Vec::new()));
+    let titles_ = titles.clone();
+    let document_title_changed_handler = parts.attrs.document_title_changed_handler.take().unwrap();
+    document_title_changed_handler("before".to_string());
+    handlers
+      .document_title_changed_handler
+      .set(
+        "document title changed",
+        Some(Rc::new(move |title| titles_.borrow_mut().push(title))),
+      )
+      .unwrap();
+    document_title_changed_handler("after".to_string());
+    assert_eq!(*titles.borrow(), ["after"]);
+
+    assert!(parts.attrs.new_window_req_handler.is_some());
+    assert!(parts.attrs.on_page_load_handler.is_some());
+  }
+
+  #[test]
+  fn no_page_load_handler_without_creation_metrics() {
+    let mut parts = WebViewBuilder::new().inner.unwrap();
+    assert!(parts.apply_creation_metrics().is_none());
+    assert!(parts.attrs.on_page_load_handler.is_none());
+  }
+
+  #[test]
+  fn permission_handler_panics_deny() {
+    let mut parts = WebViewBuilder::new()
+      .with_permission_handler(|_| panic!("permission"))
+      .with_panic_handler(|_| {})
+      .inner
+      .unwrap();
+    parts.apply_panic_isolation();
+
+    let permission_handler = parts.attrs.permission_handler.unwrap();
+    let response = permission_handler(PermissionRequest {
+      url: "https://tauri.app".into(),
+      kind: PermissionKind::DisplayCapture,
+    });
+    assert_eq!(response, PermissionResponse::Deny);
+  }
+
+  #[test]
+  fn blank_bootstrap_loads_about_blank() {
+    let mut parts = WebViewBuilder::new()
+      .with_url("https://tauri.app")
+      .with_blank_bootstrap("document.body.textContent = 'wry';")
+      .inner
+      .unwrap();
+    parts.apply_blank_bootstrap();
+
+    assert_eq!(parts.attrs.url.as_deref(), Some("about:blank"));
+    assert!(parts
+      .attrs
+      .initialization_scripts
+      .iter()
+      .any(|script| script.contains("document.body.textContent = 'wry';")));
+  }
+
+  #[test]
+  fn denied_file_access_blocks_file_urls() {
+    let mut parts = WebViewBuilder::new()
+      .with_file_access(FileAccessPolicy::Deny)
+      .with_navigation_handler(|url| !url.contains("blocked"))
+      .inner
+      .unwrap();
+    parts.apply_file_access();
+
+    let navigation_handler = parts.attrs.navigation_handler.unwrap();
+    assert!(!navigation_handler("file:///etc/passwd".into()));
+    assert!(!navigation_handler("FILE:///etc/passwd".into()));
+    assert!(navigation_handler("https://tauri.app".into()));
+    assert!(!navigation_handler("https://tauri.app/blocked".into()));
+
+    let new_window_req_handler = parts.attrs.new_window_req_handler.unwrap();
+    assert!(!new_window_req_handler("file:///etc/passwd".into()));
+    assert!(new_window_req_handler("https://tauri.app".into()));
+  }
+
+  #[test]
+  #[cfg(feature = "drag-drop")]
+  fn drag_drop_events_keep_the_raw_paths() {
+    let paths = Arc::new(Mutex::new(Vec::new()));
+    let paths_ = paths.clone();
+    let mut parts = WebViewBuilder::new()
+      .with_path_normalization(PathNormalization::Simplified)
+      .with_drag_drop_handler(move |event| {
+        if let DragDropEvent::Drop {
+          paths, raw_paths, ..
+        } = event
+        {
+          paths_.lock().unwrap().push((paths, raw_paths));
+        }
+        true
+      })
+      .inner
+      .unwrap();
+    parts.apply_path_normalization();
+
+    let raw = PathBuf::from(r"\\?\UNC\server\share\file.txt");
+    let drag_drop_handler = parts.attrs.drag_drop_handler.unwrap();
+    assert!(drag_drop_handler(DragDropEvent::Drop {
+      paths: vec![raw.clone()],
+      raw_paths: vec![raw.clone()],
+      position: (0, 0),
+    }));
+
+    let (paths, raw_paths) = paths.lock().unwrap().pop().unwrap();
+    assert_eq!(raw_paths, [raw.clone()]);
+    #[cfg(windows)]
+    assert_eq!(paths, [PathBuf::from(r"\\server\share\file.txt")]);
+    #[cfg(not(windows))]
+    assert_eq!(paths, [raw]);
+  }
+
+  #[test]
+  fn download_handlers_receive_the_raw_paths() {
+    let paths = Arc::new(Mutex::new(Vec::new()));
+    let paths_ = paths.clone();
+    let paths__ = paths.clone();
+    let mut parts = WebViewBuilder::new()
+      .with_path_normalization(PathNormalization::Simplified)
+      .with_download_started_handler_with_raw_path(move |url, raw_path, path| {
+        paths_
+          .lock()
+          .unwrap()
+          .push((Some(raw_path.to_path_buf()), Some(path.clone())));
+        if url.ends_with("moved") {
+          *path = PathBuf::from(r"C:\Downloads\file.txt");
+        }
+        true
+      })
+      .with_download_completed_handler_with_raw_path(move |_, path, raw_path, _| {
+        paths__.lock().unwrap().push((raw_path, path))
+      })
+      .inner
+      .unwrap();
+    parts.apply_path_normalization();
+
+    let raw = PathBuf::from(r"\\?\UNC\server\share\file.txt");
+    #[cfg(windows)]
+    let normalized = PathBuf::from(r"\\server\share\file.txt");
+    #[cfg(not(windows))]
+    let normalized = raw.clone();
+
+    // the destination of the platform is kept unless the handler changes it
+    let download_started_handler = parts.attrs.download_started_handler.as_mut().unwrap();
+    let mut destination = raw.clone();
+    assert!(download_started_handler(
+      "https://tauri.app/file".into(),
+      &mut destination
+    ));
+    assert_eq!(destination, raw);
+    assert!(download_started_handler(
+      "https://tauri.app/moved".into(),
+      &mut destination
+    ));
+    assert_eq!(destination, PathBuf::from(r"C:\Downloads\file.txt"));
+
+    let download_completed_handler = parts.attrs.download_completed_handler.take().unwrap();
+    download_completed_handler("https://tauri.app/file".into(), Some(raw.clone()), true);
+
+    let expected = (Some(raw), Some(normalized));
+    assert_eq!(
+      *paths.lock().unwrap(),
+      [expected.clone(), expected.clone(), expected]
+    );
+  }
+
+  #[test]
+  fn ipc_limits_drop_messages() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let received_ = received.clone();
+    let dropped = Arc::new(Mutex::new(Vec::new()));
+    let dropped_ = dropped.clone();
+    let mut parts = WebViewBuilder::new()
+      .with_ipc_handler(move |request| received_.lock().unwrap().push(request.into_body()))
+      .with_ipc_limits(8, 2)
+      .with_ipc_dropped_handler(move |_, reason| dropped_.lock().unwrap().push(reason))
+      .inner
+      .unwrap();
+    parts.apply_ipc_filters(InternalMessages::new());
+
+    let ipc_handler = parts.attrs.ipc_handler.take().unwrap();
+    for message in ["a", "too long message", "b", "c"] {
+      ipc_handler(Request::new(message.to_string()));
+    }
+    assert_eq!(*received.lock().unwrap(), ["a", "b"]);
+    assert_eq!(
+      *dropped.lock().unwrap(),
+      [IpcDropReason::TooLong(16), IpcDropReason::RateLimited]
+    );
+  }
+
+  #[test]
+  fn unsupported_options_are_reported() {
+    let builder = WebViewBuilder::new()
+      .with_hotkeys_zoom(true)
+      .with_virtual_authenticator(true)
+      .with_cache_mode(CacheMode::CacheOnly)
+      .with_data_directory("data")
+      .with_back_forward_navigation_gestures(true)
+      .with_drag_region_support(true)
+      .with_isolated_world(true)
+      .with_before_unload_handler(|_, _| true)
+      .with_permission_handler(|_| PermissionResponse::Default)
+      .with_ephemeral_storage(true)
+      .with_media_keeps_display_awake(true)
+      .with_visibility_changed_handler(|_| {})
+      .with_bounds_changed_handler(|_| {})
+      .with_hit_test_ignore_regions(vec![Rect::default()])
+      .with_focused(false)
+      .with_ui_strings(HashMap::from([(UiString::DialogOk, "OK".to_string())]))
+      .with_ui_layout_direction(UiLayoutDirection::RightToLeft)
+      .with_virtual_keyboard_policy(VirtualKeyboardPolicy::Overlay);
+    #[cfg(target_os = "ios")]
+    let builder = builder
+      .with_synthetic_mouse_events(false)
+      .with_activate_app(false)
+      .with_titlebar_separator(None)
+      .with_replace_content_view(false);
+
+    let unsupported: Vec<_> = builder
+      .validate()
+      .iter()
+      .map(|option| option.name)
+      .collect();
+    let expected: &[&str] = &[
+      #[cfg(not(windows))]
+      "with_hotkeys_zoom",
+      #[cfg(not(windows))]
+      "with_virtual_authenticator",
+      #[cfg(any(gtk, windows))]
+      "with_cache_mode",
+      #[cfg(not(any(gtk, windows)))]
+      "with_data_directory",
+      #[cfg(any(target_os = "android", target_os = "ios"))]
+      "with_back_forward_navigation_gestures",
+      #[cfg(any(target_os = "android", target_os = "ios"))]
+      "with_drag_region_support",
+      #[cfg(any(windows, target_os = "android"))]
+      "with_isolated_world",
+      #[cfg(windows)]
+      "with_before_unload_handler",
+      #[cfg(any(target_os = "android", target_os = "ios"))]
+      "with_permission_handler",
+      #[cfg(target_os = "android")]
+      "with_ephemeral_storage",
+      #[cfg(target_os = "android")]
+      "with_media_keeps_display_awake",
+      #[cfg(target_os = "android")]
+      "with_visibility_changed_handler",
+      #[cfg(any(target_os = "android", target_os = "ios"))]
+      "with_bounds_changed_handler",
+      #[cfg(any(target_os = "android", target_os = "ios"))]
+      "with_hit_test_ignore_regions",
+      #[cfg(target_os = "ios")]
+      "with_focused",
+      #[cfg(any(windows, gtk, target_os = "ios"))]
+      "with_ui_strings",
+      #[cfg(any(windows, gtk, target_os = "ios"))]
+      "with_ui_layout_direction",
+      #[cfg(not(any(target_os = "android", target_os = "ios")))]
+      "with_virtual_keyboard_policy",
+      #[cfg(target_os = "ios")]
+      "with_synthetic_mouse_events",
+      #[cfg(target_os = "ios")]
+      "with_activate_app",
+      #[cfg(target_os = "ios")]
+      "with_titlebar_separator",
+      #[cfg(target_os = "ios")]
+      "with_replace_content_view",
+    ];
+    assert_eq!(unsupported, expected);
+  }
+
+  #[test]
+  fn internal_messages_are_not_limited() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let received_ = received.clone();
+    let dropped = Arc::new(Mutex::new(Vec::new()));
+    let dropped_ = dropped.clone();
+    let mut parts = WebViewBuilder::new()
+      .with_ipc_handler(move |request| received_.lock().unwrap().push(request.into_body()))
+      .with_ipc_limits(8, 1)
+      .with_ipc_dropped_handler(move |_, reason| dropped_.lock().unwrap().push(reason))
+      .inner
+      .unwrap();
+
+    let routed = Rc::new(RefCell::new(Vec::new()));
+    let mut messages = InternalMessages::new();
+    let routed_ = routed.clone();
+    let first_paint = messages.route("first-paint", move |_, _| {
+      routed_.borrow_mut().push("first-paint".to_string())
+    });
+    let routed_ = routed.clone();
+    let fetch = messages.route("fetch", move |_, message| {
+      routed_.borrow_mut().push(message.to_string())
+    });
+    parts.apply_ipc_filters(messages);
+
+    let ipc_handler = parts.attrs.ipc_handler.take().unwrap();
+    let response = format!("1\n200\n\n{}", "QUJD".repeat(16));
+    for message in [
+      "a".to_string(),
+      "b".to_string(),
+      first_paint,
+      format!("{fetch}{response}"),
+    ] {
+      ipc_handler(Request::new(message));
+    }
+    assert_eq!(*received.lock().unwrap(), ["a"]);
+    assert_eq!(*dropped.lock().unwrap(), [IpcDropReason::RateLimited]);
+    assert_eq!(*routed.borrow(), ["first-paint".to_string(), response]);
+  }
+
+  #[test]
+  fn auth_sessions_are_ephemeral_without_being_reported() {
+    let builder = WebViewBuilder::new()
+      .with_strict_validation(true)
+      .with_auth_session("https://tauri.app/auth", "com.example.app:/oauth", |_| {});
+    assert!(builder.validate().is_empty());
+
+    let mut parts = builder.inner.unwrap();
+    parts.validate().unwrap();
+    parts.apply_auth_session();
+    assert!(parts.attrs.ephemeral_storage);
+  }
+
+  #[test]
+  fn profiles_are_created_by_name() {
+    let mut context = WebContext::new(Some(PathBuf::from("data")));
+    let work = context
+      .create_profile("work", ProfileOptions::default())
+      .unwrap();
+    let private = context
+      .create_profile("private", ProfileOptions { ephemeral: true })
+      .unwrap();
+    assert!(matches!(
+      context.create_profile("work", ProfileOptions::default()),
+      Err(Error::DuplicateProfile(_))
+    ));
+    for name in ["", "Work", "work/../..", &"a".repeat(65)] {
+      assert!(matches!(
+        context.create_profile(name, ProfileOptions::default()),
+        Err(Error::InvalidProfileName(_))
+      ));
+    }
+
+    // the data store of a profile is found again after a restart
+    let mut restarted = WebContext::new(Some(PathBuf::from("data")));
+    let work_again = restarted
+      .create_profile("work", ProfileOptions::default())
+      .unwrap();
+    assert_eq!(work_again, work);
+    assert_ne!(work.identifier(), private.identifier());
+    assert_eq!(work.identifier()[6] >> 4, 8);
+    assert!(private.data_directory().is_none());
+    #[cfg(any(gtk, windows))]
+    assert!(work.data_directory().unwrap().ends_with("work"));
+
+    assert_eq!(context.profiles(), [work, private.clone()]);
+    // WebKit removes the data stores on the main thread
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    {
+      context.delete_profile("work").unwrap();
+      assert_eq!(context.profiles(), [private.clone()]);
+    }
+
+    let mut parts = WebViewBuilder::new().with_profile(&private).inner.unwrap();
+    parts.apply_profile();
+    // the webkit2gtk context of the profile is ephemeral instead
+    assert_eq!(parts.attrs.ephemeral_storage, !cfg!(gtk));
+  }
+}
@@ -0,0 +1,151 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The timeout and the concurrency limit of the custom protocols, see [`CustomProtocolConfig`].
+
+use std::{
+  borrow::Cow,
+  collections::VecDeque,
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    mpsc::{self, RecvTimeoutError},
+    Arc, Mutex,
+  },
+  thread,
+  time::Instant,
+};
+
+use http::{Request, Response, StatusCode};
+
+use crate::{CustomProtocolConfig, Error, RequestAsyncResponder, Result, WebViewId};
+
+type Handler = Box<dyn Fn(WebViewId, Request<Vec<u8>>, RequestAsyncResponder)>;
+
+/// A request of a limited custom protocol, answered once by either its handler or wry.
+struct PendingRequest {
+  responder: Mutex<Option<RequestAsyncResponder>>,
+  in_flight: Arc<AtomicUsize>,
+}
+
+impl PendingRequest {
+  fn respond(&self, response: Response<Cow<'static, [u8]>>) -> Result<()> {
+    let responder = self.responder.lock().unwrap().take();
+    match responder {
+      Some(responder) => {
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        responder.respond(response)
+      }
+      None => Err(Error::CustomProtocolTaskInvalid),
+    }
+  }
+}
+
+impl Drop for PendingRequest {
+  fn drop(&mut self) {
+    // the handler dropped its responder without answering
+    if let Ok(Some(_)) = self.responder.get_mut() {
+      self.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+  }
+}
+
+/// The thread answering `504 Gateway Timeout` to the requests not answered in time.
+///
+/// The timeout is the same for all the requests of a protocol, so they expire in their order of
+/// arrival.
+struct Watchdog(mpsc::Sender<(Instant, Arc<PendingRequest>)>);
+
+impl Watchdog {
+  fn spawn(protocol: String) -> Option<Self> {
+    let (tx, rx) = mpsc::channel();
+    thread::Builder::new()
+      .name(format!("wry-{protocol}-timeouts"))
+      .spawn(move || {
+        let mut pending: VecDeque<(Instant, Arc<PendingRequest>)> = VecDeque::new();
+        loop {
+          let now = Instant::now();
+          while pending
+            .front()
+            .is_some_and(|(deadline, _)| *deadline <= now)
+          {
+            let (_, request) = pending.pop_front().unwrap();
+            if request
+              .respond(status_response(StatusCode::GATEWAY_TIMEOUT))
+              .is_ok()
+            {
+              #[cfg(feature = "tracing")]
+              tracing::warn!(
+                protocol = protocol.as_str(),
+                "custom protocol request timed out, responded 504"
+              );
+            }
+          }
+
+          let received = match pending.front() {
+            Some((deadline, _)) => rx.recv_timeout(deadline.saturating_duration_since(now)),
+            None => rx.recv().map_err(RecvTimeoutError::from),
+          };
+          match received {
+            Ok(request) => pending.push_back(request),
+            Err(RecvTimeoutError::Timeout) => {}
+            // the webview was dropped
+            Err(RecvTimeoutError::Disconnected) => break,
+          }
+        }
+      })
+      .ok()?;
+    Some(Self(tx))
+  }
+}
+
+/// Apply `config` to the `handler` of the custom protocol `protocol`.
+pub(crate) fn limit(protocol: String, config: CustomProtocolConfig, handler: Handler) -> Handler {
+  let in_flight = Arc::new(AtomicUsize::new(0));
+  let watchdog = config
+    .timeout
+    .and_then(|timeout| Some((timeout, Watchdog::spawn(protocol.clone())?)));
+
+  Box::new(move |id, request, responder| {
+    let admitted = in_flight
+      .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+        match config.max_concurrent {
+          Some(max) if count >= max => None,
+          _ => Some(count + 1),
+        }
+      })
+      .is_ok();
+    if !admitted {
+      #[cfg(feature = "tracing")]
+      tracing::warn!(
+        protocol = protocol.as_str(),
+        "too many custom protocol requests in flight, responded 503"
+      );
+      let _ = responder.respond(status_response(StatusCode::SERVICE_UNAVAILABLE));
+      return;
+    }
+
+    let cancellation = responder.cancellation.clone();
+    let pending = Arc::new(PendingRequest {
+      responder: Mutex::new(Some(responder)),
+      in_flight: in_flight.clone(),
+    });
+    if let Some((timeout, watchdog)) = &watchdog {
+      let _ = watchdog
+        .0
+        .send((Instant::now() + *timeout, pending.clone()));
+    }
+
+    let responder = RequestAsyncResponder {
+      responder: Box::new(move |response| pending.respond(response)),
+      cancellation,
+    };
+    handler(id, request, responder)
+  })
+}
+
+fn status_response(status: StatusCode) -> Response<Cow<'static, [u8]>> {
+  let mut response = Response::new(Cow::Borrowed(&[][..]));
+  *response.status_mut() = status;
+  response
+}
@@ -0,0 +1,202 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+// Extracts the article of the page for `WebView::extract_article`, with a compact version of the
+// scoring of Mozilla's Readability. It runs on a copy of the document, so the page isn't modified.
+//
+// The regular expressions and the scoring of the candidates are derived from Readability.js
+// (https://github.com/mozilla/readability), Copyright (c) 2010 Arc90 Inc, licensed under the
+// Apache License, Version 2.0 (http://www.apache.org/licenses/LICENSE-2.0). They are modified to
+// only keep the selection of the best candidate and its siblings.
+//
+// Returns the base64 of the title, the byline, the HTML and the text of the article separated by
+// commas, or an empty string when the page doesn't look like an article.
+(function () {
+  const UNLIKELY =
+    /-ad-|ai2html|banner|breadcrumbs|combx|comment|community|cover-wrap|disqus|extra|footer|gdpr|header|legends|menu|related|remark|replies|rss|shoutbox|sidebar|skyscraper|social|sponsor|supplemental|ad-break|agegate|pagination|pager|popup|yom-remote/i;
+  const MAYBE = /and|article|body|column|content|main|shadow/i;
+  const POSITIVE =
+    /article|body|content|entry|hentry|h-entry|main|page|pagination|post|text|blog|story/i;
+  const NEGATIVE =
+    /-ad-|hidden|^hid$| hid$| hid |^hid |banner|combx|comment|com-|contact|foot|footer|footnote|gdpr|masthead|media|meta|outbrain|promo|related|scroll|share|shoutbox|sidebar|skyscraper|sponsor|shopping|tags|tool|widget/i;
+
+  const doc = document.cloneNode(true);
+  doc
+    .querySelectorAll(
+      'script, style, noscript, template, iframe, object, embed, form, button, input, select, textarea, nav, aside, footer, svg, meta, link, base'
+    )
+    .forEach((node) => node.remove());
+  doc.querySelectorAll('body *').forEach((node) => {
+    const match = node.className + ' ' + node.id;
+    if (
+      UNLIKELY.test(match) &&
+      !MAYBE.test(match) &&
+      node.tagName !== 'A' &&
+      !node.closest('table, article')
+    ) {
+      node.remove();
+    }
+  });
+
+  const classWeight = (node) => {
+    let weight = 0;
+    for (const name of [node.className, node.id]) {
+      if (typeof name !== 'string' || !name) continue;
+      if (NEGATIVE.test(name)) weight -= 25;
+      if (POSITIVE.test(name)) weight += 25;
+    }
+    return weight;
+  };
+  const initialScore = (node) => {
+    const score = classWeight(node);
+    switch (node.tagName) {
+      case 'ARTICLE':
+        return score + 10;
+      case 'DIV':
+      case 'SECTION':
+      case 'MAIN':
+        return score + 5;
+      case 'PRE':
+      case 'TD':
+      case 'BLOCKQUOTE':
+        return score + 3;
+      case 'ADDRESS':
+      case 'OL':
+      case 'UL':
+      case 'DL':
+      case 'DD':
+      case 'DT':
+      case 'LI':
+        return score - 3;
+      case 'H1':
+      case 'H2':
+      case 'H3':
+      case 'H4':
+      case 'H5':
+      case 'H6':
+      case 'TH':
+        return score - 5;
+      default:
+        return score;
+    }
+  };
+  const linkDensity = (node) => {
+    const length = node.textContent.length;
+    if (!length) return 0;
+    let links = 0;
+    node.querySelectorAll('a').forEach((a) => (links += a.textContent.length));
+    return links / length;
+  };
+
+  // the paragraphs give their score to their parent and grandparents
+  const scores = new Map();
+  for (const paragraph of doc.querySelectorAll('p, pre, td')) {
+    const text = paragraph.textContent.trim();
+    if (text.length < 25) continue;
+    const score =
+      1 + text.split(/[,，]/).length + Math.min(Math.floor(text.length / 100), 3);
+    let ancestor = paragraph.parentElement;
+    for (let level = 0; ancestor && level < 3; level++) {
+      if (!scores.has(ancestor)) scores.set(ancestor, initialScore(ancestor));
+      const divider = level === 0 ? 1 : level === 1 ? 2 : level * 3;
+      scores.set(ancestor, scores.get(ancestor) + score / divider);
+      ancestor = ancestor.parentElement;
+    }
+  }
+
+  let top = null;
+  let topScore = 0;
+  scores.forEach((score, node) => {
+    score *= 1 - linkDensity(node);
+    if (score > topScore) {
+      top = node;
+      topScore = score;
+    }
+  });
+  if (!top) return '';
+
+  // the siblings of the best candidate which look like the same content
+  const article = doc.createElement('div');
+  const threshold = Math.max(10, topScore * 0.2);
+  const siblings = top.parentElement ? Array.from(top.parentElement.children) : [top];
+  for (const sibling of siblings) {
+    let append =
+      sibling === top ||
+      (scores.get(sibling) || 0) * (1 - linkDensity(sibling)) >= threshold;
+    if (!append && sibling.tagName === 'P') {
+      const text = sibling.textContent.trim();
+      const density = linkDensity(sibling);
+      append = (text.length > 80 && density < 0.25) || (density === 0 && /\.( |$)/.test(text));
+    }
+    if (append) article.appendChild(sibling);
+  }
+
+  article.querySelectorAll('div, section, ul, ol, table').forEach((node) => {
+    const text = node.textContent.trim();
+    if (
+      !node.querySelector('img, picture, video, pre') &&
+      (text.length === 0 || (linkDensity(node) > 0.5 && text.length < 500))
+    ) {
+      node.remove();
+    }
+  });
+  // only the web and mail links are kept, the `javascript:` and `data:` URLs can run scripts
+  const safeUrl = (value) => {
+    try {
+      const url = new URL(value, document.baseURI);
+      return ['http:', 'https:', 'mailto:'].includes(url.protocol) ? url.href : null;
+    } catch (_) {
+      return null;
+    }
+  };
+  for (const node of [article, ...article.querySelectorAll('*')]) {
+    for (const attribute of Array.from(node.attributes)) {
+      const name = attribute.name.toLowerCase();
+      if (
+        name === 'style' ||
+        name === 'srcset' ||
+        name === 'http-equiv' ||
+        name.startsWith('on') ||
+        name.includes(':')
+      ) {
+        node.removeAttribute(attribute.name);
+      } else if (['href', 'src', 'poster', 'action', 'formaction', 'cite'].includes(name)) {
+        const url = safeUrl(attribute.value);
+        if (url) {
+          node.setAttribute(attribute.name, url);
+        } else {
+          node.removeAttribute(attribute.name);
+        }
+      }
+    }
+  }
+
+  const meta = (selector) => {
+    const node = document.querySelector(selector);
+    return node ? (node.getAttribute('content') || '').trim() : '';
+  };
+  let title = meta('meta[property="og:title"]') || document.title.trim();
+  const headings = doc.querySelectorAll('h1');
+  if (!title && headings.length === 1) title = headings[0].textContent.trim();
+
+  let byline = meta('meta[name="author"]');
+  if (!byline) {
+    const node = document.querySelector(
+      '[rel="author"], [itemprop="author"], .byline, .author'
+    );
+    const text = node ? node.textContent.trim().replace(/\s+/g, ' ') : '';
+    if (text.length < 100) byline = text;
+  }
+
+  const encode = (text) => {
+    const bytes = new TextEncoder().encode(text);
+    let binary = '';
+    for (let i = 0; i < bytes.length; i += 0x8000) {
+      binary += String.fromCharCode.apply(null, bytes.subarray(i, i + 0x8000));
+    }
+    return btoa(binary);
+  };
+  const text = article.textContent.trim().replace(/\s+/g, ' ');
+  return [title, byline, article.innerHTML, text].map(encode).join(',');
+})();
@@ -0,0 +1,62 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The article extraction of [`WebView::extract_article`](crate::WebView::extract_article).
+//!
+//! The article is extracted by a script bundled in the binary only with the `reader` feature.
+
+use base64::{engine::general_purpose, Engine};
+
+use crate::{Error, Result};
+
+/// The name of the isolated world the [`READER_SCRIPT`] runs in.
+pub(crate) const READER_WORLD_NAME: &str = "wry-reader";
+
+/// Returns the base64 of the fields of the article separated by commas, or an empty string if
+/// the page isn't an article.
+pub(crate) const READER_SCRIPT: &str = include_str!("reader.js");
+
+/// An article extracted from a page, see [`WebView::extract_article`](crate::WebView::extract_article).
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Article {
+  /// The title of the article.
+  pub title: String,
+  /// The author of the article, if the page names one.
+  pub byline: Option<String>,
+  /// The HTML of the article, without the navigation, the scripts, the styles and the event
+  /// handlers of the page. Its links and sources are absolute `http`, `https` or `mailto` URLs,
+  /// the other URLs like `javascript:` and `data:` are removed.
+  pub content: String,
+  /// The text of the article, with its whitespace collapsed.
+  pub text_content: String,
+}
+
+/// Parse the JSON result of the [`READER_SCRIPT`].
+pub(crate) fn parse_article(result: &str) -> Result<Option<Article>> {
+  let fields = result.trim().trim_matches('"');
+  // `null` when the script failed without reporting the exception, e.g. on Windows
+  if fields.is_empty() || fields == "null" {
+    return Ok(None);
+  }
+
+  let fields = fields
+    .split(',')
+    .map(|field| {
+      let bytes = general_purpose::STANDARD
+        .decode(field)
+        .map_err(|e| Error::ArticleExtractionFailed(e.to_string()))?;
+      String::from_utf8(bytes).map_err(|e| Error::ArticleExtractionFailed(e.to_string()))
+    })
+    .collect::<Result<Vec<_>>>()?;
+  let [title, byline, content, text_content] = <[String; 4]>::try_from(fields)
+    .map_err(|_| Error::ArticleExtractionFailed("the article doesn't have 4 fields".to_string()))?;
+
+  Ok(Some(Article {
+    title,
+    byline: Some(byline).filter(|byline| !byline.is_empty()),
+    content,
+    text_content,
+  }))
+}
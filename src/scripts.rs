@@ -0,0 +1,225 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! The scripts injected by wry in the pages.
+
+use crate::{internal_messages, IpcLimits};
+#[cfg(doc)]
+use crate::{WebView, WebViewAttributes, WebviewBuilderParts};
+
+/// Script running `js` in the `about:blank` document of the main frame once it is parsed, see
+/// [`WebViewAttributes::blank_bootstrap`].
+pub(crate) fn blank_bootstrap_script(js: &str) -> String {
+  format!(
+    r#"
+(function () {{
+  if (window.top !== window || location.href !== 'about:blank') {{
+    return;
+  }}
+  const bootstrap = function () {{
+{js}
+  }};
+  if (document.readyState === 'loading') {{
+    document.addEventListener('DOMContentLoaded', bootstrap, {{ once: true }});
+  }} else {{
+    bootstrap();
+  }}
+}})();
+"#
+  )
+}
+
+/// Script injecting a `<meta http-equiv="Content-Security-Policy">` element with `policy` as
+/// soon as the `<head>` of the document is created.
+pub(crate) fn content_security_policy_script(policy: &str) -> String {
+  let content = escape_js_string(policy);
+  format!(
+    r#"
+(function () {{
+  const meta = document.createElement('meta');
+  meta.httpEquiv = 'Content-Security-Policy';
+  meta.content = '{content}';
+  if (document.head) {{
+    document.head.prepend(meta);
+    return;
+  }}
+  new MutationObserver(function (_, observer) {{
+    if (document.head) {{
+      observer.disconnect();
+      document.head.prepend(meta);
+    }}
+  }}).observe(document, {{ childList: true, subtree: true }});
+}})();
+"#
+  )
+}
+
+/// Script appending a `<style>` element with `css` to the document as soon as its `<head>` is
+/// created, see [`WebViewBuilder::with_user_style_sheet`].
+#[cfg(not(gtk))]
+pub(crate) fn user_style_sheet_script(css: &str, main_frame_only: bool) -> String {
+  let css = escape_js_string(css);
+  let main_frame_only = if main_frame_only {
+    "if (window !== window.top) return;"
+  } else {
+    ""
+  };
+
+  format!(
+    r#"
+(function () {{
+  {main_frame_only}
+  const style = document.createElement('style');
+  style.setAttribute('data-wry-user-style-sheet', '');
+  style.textContent = '{css}';
+  if (document.head) {{
+    document.head.appendChild(style);
+    return;
+  }}
+  new MutationObserver(function (_, observer) {{
+    if (document.head) {{
+      observer.disconnect();
+      document.head.appendChild(style);
+    }}
+  }}).observe(document, {{ childList: true, subtree: true }});
+}})();
+"#
+  )
+}
+
+/// Script removing the `<style>` elements added by [`user_style_sheet_script`] for `css`.
+#[cfg(not(gtk))]
+pub(crate) fn remove_user_style_sheet_script(css: &str) -> String {
+  let css = escape_js_string(css);
+  format!(
+    r#"document.querySelectorAll('style[data-wry-user-style-sheet]').forEach(function (style) {{
+  if (style.textContent === '{css}') style.remove();
+}});"#
+  )
+}
+
+/// Style sheet inverting the colors of the pages, and inverting back their media.
+#[cfg(any(gtk, target_os = "macos", target_os = "ios"))]
+pub(crate) const FORCED_DARK_CONTENT_CSS: &str = r#"
+html {
+  background-color: #fff !important;
+  filter: invert(1) hue-rotate(180deg) !important;
+}
+img, picture, video, canvas, iframe, embed, object {
+  filter: invert(1) hue-rotate(180deg) !important;
+}
+"#;
+
+/// Script adding a `<link rel="{rel}">` element to the current page, see [`WebView::prefetch`].
+pub(crate) fn link_hint_script(rel: &str, href: &str) -> String {
+  let href = escape_js_string(href);
+  format!(
+    r#"(function () {{
+  const link = document.createElement('link');
+  link.rel = '{rel}';
+  link.href = '{href}';
+  (document.head || document.documentElement).appendChild(link);
+}})();"#
+  )
+}
+
+/// Escape `s` to be used in a JavaScript string literal delimited by single quotes.
+pub(crate) fn escape_js_string(s: &str) -> String {
+  let mut escaped = String::with_capacity(s.len());
+  for c in s.chars() {
+    match c {
+      '\\' | '\'' => {
+        escaped.push('\\');
+        escaped.push(c);
+      }
+      '<' => escaped.push_str("\\x3c"),
+      '\n' => escaped.push_str("\\n"),
+      '\r' => escaped.push_str("\\r"),
+      '\u{2028}' => escaped.push_str("\\u2028"),
+      '\u{2029}' => escaped.push_str("\\u2029"),
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+/// The name of the isolated world of the IPC bridge, see [`WebViewAttributes::isolated_world`].
+#[cfg(any(gtk, target_os = "macos", target_os = "ios"))]
+pub(crate) const ISOLATED_WORLD_NAME: &str = "wry";
+
+/// The name of the isolated world running the requests of [`WebView::fetch`].
+#[cfg(any(gtk, target_os = "macos", target_os = "ios", target_os = "windows"))]
+pub(crate) const FETCH_WORLD_NAME: &str = "wry-fetch";
+
+/// The `window.ipc` of the page world when the IPC bridge is isolated, forwarding the messages to
+/// [`ISOLATED_WORLD_IPC_LISTENER_SCRIPT`].
+#[cfg(any(gtk, target_os = "macos", target_os = "ios"))]
+pub(crate) fn page_world_ipc_script(limits: Option<IpcLimits>) -> String {
+  format!(
+    r#"
+Object.defineProperty(window, 'ipc', {{
+  value: Object.freeze({{
+    postMessage: function(message) {{
+      message = String(message);
+      {check}
+      document.dispatchEvent(new CustomEvent('__wry_ipc__', {{ detail: message }}));
+    }}
+  }})
+}});"#,
+    check = ipc_length_check_script(limits),
+  )
+}
+
+/// The statement of the `window.ipc.postMessage` of the backends throwing a `RangeError` for the
+/// `message` longer than [`IpcLimits::max_len`], before it crosses the IPC bridge.
+///
+/// The length of a string in JS is counted in UTF-16 code units, which is never more than its
+/// length in bytes, so the messages which are only too long in bytes are still dropped by
+/// [`WebviewBuilderParts::apply_ipc_limits`]. The internal messages are not checked, see
+/// [`WebviewBuilderParts::apply_ipc_filters`].
+pub(crate) fn ipc_length_check_script(limits: Option<IpcLimits>) -> String {
+  match limits {
+    Some(limits) => format!(
+      "if (String(message).length > {} && !String(message).startsWith('{}')) throw new RangeError('the IPC message is too long');",
+      limits.max_len,
+      internal_messages::PREFIX,
+    ),
+    None => String::new(),
+  }
+}
+
+/// Sends the messages of [`page_world_ipc_script`] to the IPC bridge of the isolated world.
+#[cfg(any(gtk, target_os = "macos", target_os = "ios"))]
+pub(crate) const ISOLATED_WORLD_IPC_LISTENER_SCRIPT: &str = r#"
+document.addEventListener('__wry_ipc__', function(event) {
+  if (typeof event.detail === 'string') window.ipc.postMessage(event.detail);
+}, true);"#;
+
+/// Sends an internal message once the content of the main frame is painted, i.e. on the second
+/// animation frame after `DOMContentLoaded`, as the first one is rendered before being painted.
+///
+/// The animation frames of hidden pages are paused, so it is sent after `DOMContentLoaded` for them.
+#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+pub(crate) const FIRST_PAINT_SCRIPT: &str = r#"
+if (window.top === window) {
+  const painted = () => send('');
+  const firstPaint = () => document.hidden
+    ? setTimeout(painted, 0)
+    : requestAnimationFrame(() => requestAnimationFrame(painted));
+  if (document.readyState === 'loading') {
+    document.addEventListener('DOMContentLoaded', firstPaint, { once: true });
+  } else {
+    firstPaint();
+  }
+}
+"#;
+
+/// Sends the composition events of the page as internal messages, as `<type>:<data>`.
+pub(crate) const TEXT_INPUT_SCRIPT: &str = r#"
+for (const type of ['compositionstart', 'compositionupdate', 'compositionend']) {
+  window.addEventListener(type, (event) => {
+    send(type + ':' + (event.data || ''));
+  }, true);
+}
+"#;
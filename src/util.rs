@@ -1,7 +1,10 @@
+#[cfg(any(target_os = "macos", target_os = "android", target_os = "ios"))]
 use std::sync::atomic::{AtomicU32, Ordering};
 
+#[cfg(any(target_os = "macos", target_os = "android", target_os = "ios"))]
 pub struct Counter(AtomicU32);
 
+#[cfg(any(target_os = "macos", target_os = "android", target_os = "ios"))]
 impl Counter {
   pub const fn new() -> Self {
     Self(AtomicU32::new(1))
@@ -11,3 +14,10 @@ impl Counter {
     self.0.fetch_add(1, Ordering::Relaxed)
   }
 }
+
+/// A hexadecimal token drawn from the random number generator of the OS.
+pub fn random_token() -> String {
+  let mut bytes = [0u8; 16];
+  getrandom::getrandom(&mut bytes).expect("failed to get random bytes from the OS");
+  bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
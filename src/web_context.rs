@@ -2,14 +2,42 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+#[cfg(all(feature = "protocol", gtk))]
+use crate::webkitgtk::WebContextExt;
 #[cfg(gtk)]
 use crate::webkitgtk::WebContextImpl;
+#[cfg(feature = "protocol")]
+use crate::RequestAsyncResponder;
+use crate::{MaybeSend, WebViewId};
+use http::Request;
 
+#[cfg(not(gtk))]
+use std::collections::HashMap;
 use std::{
+  cell::RefCell,
   collections::HashSet,
+  fmt, fs, io,
   path::{Path, PathBuf},
+  rc::Rc,
 };
 
+/// The handler type of a custom protocol registered on a [`WebContext`].
+#[cfg(not(gtk))]
+pub(crate) type CustomProtocolHandler =
+  dyn Fn(crate::WebViewId, http::Request<Vec<u8>>, crate::RequestAsyncResponder);
+
+/// Handlers registered on a [`WebContext`] that are used by every webview created with it,
+/// unless the webview sets its own handler.
+#[derive(Default, Clone)]
+#[allow(clippy::type_complexity)]
+pub(crate) struct WebContextHandlers {
+  pub(crate) ipc_handler: Option<Rc<dyn Fn(WebViewId, Request<String>)>>,
+  pub(crate) navigation_handler: Option<Rc<dyn Fn(WebViewId, String) -> bool>>,
+  pub(crate) download_started_handler: Option<Rc<dyn Fn(WebViewId, String, &mut PathBuf) -> bool>>,
+  pub(crate) download_completed_handler:
+    Option<Rc<dyn Fn(WebViewId, String, Option<PathBuf>, bool)>>,
+}
+
 /// A context that is shared between multiple [`WebView`]s.
 ///
 /// A browser would have a context for all the normal tabs and a different context for all the
@@ -21,13 +49,34 @@ use std::{
 /// interact with them.
 ///
 /// [`WebView`]: crate::WebView
-#[derive(Debug)]
 pub struct WebContext {
   data_directory: Option<PathBuf>,
   #[allow(dead_code)] // It's not needed on Windows and macOS.
   pub(crate) os: WebContextImpl,
   #[allow(dead_code)] // It's not needed on Windows and macOS.
   pub(crate) custom_protocols: HashSet<String>,
+  /// Custom protocols registered with [`WebContext::register_custom_protocol`], they are added
+  /// to every [`WebView`](crate::WebView) created with this context.
+  ///
+  /// On Linux they are registered directly on the webkit2gtk context instead.
+  #[cfg(not(gtk))]
+  pub(crate) custom_protocol_handlers: HashMap<String, Rc<CustomProtocolHandler>>,
+  pub(crate) handlers: WebContextHandlers,
+  /// The webviews created with this context receiving the messages of [`WebContext::broadcast`].
+  pub(crate) webviews: Rc<RefCell<Vec<crate::BroadcastReceiver>>>,
+  /// The profiles created with [`WebContext::create_profile`].
+  profiles: Vec<Profile>,
+}
+
+impl fmt::Debug for WebContext {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("WebContext")
+      .field("data_directory", &self.data_directory)
+      .field("os", &self.os)
+      .field("custom_protocols", &self.custom_protocols)
+      .field("profiles", &self.profiles)
+      .finish()
+  }
 }
 
 impl WebContext {
@@ -41,15 +90,28 @@ impl WebContext {
       os: WebContextImpl::new(data_directory.as_deref()),
       data_directory,
       custom_protocols: Default::default(),
+      #[cfg(not(gtk))]
+      custom_protocol_handlers: Default::default(),
+      handlers: Default::default(),
+      webviews: Default::default(),
+      profiles: Default::default(),
     }
   }
 
   #[cfg(gtk)]
   pub(crate) fn new_ephemeral() -> Self {
+    Self::with_os(WebContextImpl::new_ephemeral())
+  }
+
+  #[cfg(gtk)]
+  pub(crate) fn with_os(os: WebContextImpl) -> Self {
     Self {
-      os: WebContextImpl::new_ephemeral(),
+      os,
       data_directory: None,
       custom_protocols: Default::default(),
+      handlers: Default::default(),
+      webviews: Default::default(),
+      profiles: Default::default(),
     }
   }
 
@@ -58,20 +120,288 @@ impl WebContext {
     self.data_directory.as_deref()
   }
 
+  /// Move the data of the webviews, like their cookies and storage, from the data directory `old`
+  /// to `new`, e.g. when an update of the app moves its data directory, so the users stay logged
+  /// in.
+  ///
+  /// It must be called before creating the contexts and the webviews using either directory, as
+  /// the engines lock their files while they run. The migration is best-effort: the entries of
+  /// `old` which can't be moved, e.g. because they already exist in `new`, are kept and reported
+  /// in [`DataMigration::failed`], and `old` is removed once it's empty. Nothing is migrated when
+  /// `old` doesn't exist.
+  ///
+  /// Returns an error if `new` is inside `old` or can't be created.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS**: The webviews don't use the data directory, their data is in the
+  ///   `WKWebsiteDataStore` of the app, or of the identifier set with
+  ///   `WebViewBuilderExtDarwin::with_data_store_identifier`, which can't be moved. Only the files
+  ///   of the directory are moved.
+  /// - **Android**: The webviews don't use the data directory, their data is in the directory of
+  ///   the app. Only the files of the directory are moved.
+  pub fn migrate_data_directory(old: &Path, new: &Path) -> crate::Result<DataMigration> {
+    let mut migration = DataMigration::default();
+    if !old.is_dir() {
+      return Ok(migration);
+    }
+    if new.starts_with(old) {
+      return Err(crate::Error::Io(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "the new data directory is inside the old one",
+      )));
+    }
+
+    // move the whole directory at once when it's on the same volume
+    if !new.exists() {
+      if let Some(parent) = new.parent() {
+        fs::create_dir_all(parent)?;
+      }
+      let names = fs::read_dir(old)?
+        .map(|entry| entry.map(|entry| PathBuf::from(entry.file_name())))
+        .collect::<io::Result<Vec<_>>>()?;
+      if fs::rename(old, new).is_ok() {
+        migration.migrated = names;
+        return Ok(migration);
+      }
+      fs::create_dir(new)?;
+    }
+
+    for entry in fs::read_dir(old)? {
+      let entry = entry?;
+      let name = PathBuf::from(entry.file_name());
+      let to = new.join(&name);
+      let result = if to.exists() {
+        Err(io::Error::new(
+          io::ErrorKind::AlreadyExists,
+          "it already exists in the new data directory",
+        ))
+      } else {
+        move_entry(&entry.path(), &to)
+      };
+      match result {
+        Ok(()) => migration.migrated.push(name),
+        Err(e) => migration.failed.push((name, e.to_string())),
+      }
+    }
+    let _ = fs::remove_dir(old);
+
+    Ok(migration)
+  }
+
   #[allow(dead_code)]
-  pub(crate) fn register_custom_protocol(&mut self, name: String) -> Result<(), crate::Error> {
-    if self.custom_protocols.contains(&name) {
-      return Err(crate::Error::ContextDuplicateCustomProtocol(name));
+  pub(crate) fn check_custom_protocol(&self, name: &str) -> Result<(), crate::Error> {
+    if self.custom_protocols.contains(name) {
+      return Err(crate::Error::ContextDuplicateCustomProtocol(
+        name.to_string(),
+      ));
     }
 
     Ok(())
   }
 
+  /// Register a custom protocol that is shared by all the [`WebView`]s created with this context.
+  ///
+  /// Unlike [`WebViewBuilder::with_asynchronous_custom_protocol`], the handler is registered once
+  /// and every webview using this context will route its requests to it. Use the [`WebViewId`]
+  /// passed to the handler to know which webview made the request.
+  ///
+  /// The protocol must be registered before creating the webviews that should use it.
+  ///
+  /// Returns [`Error::InvalidScheme`](crate::Error::InvalidScheme) if the name isn't a valid
+  /// scheme, see [`WebViewBuilder::with_custom_protocol`], and
+  /// [`Error::ContextDuplicateCustomProtocol`](crate::Error::ContextDuplicateCustomProtocol)
+  /// if a protocol with the same name was already registered on this context. Creating a webview
+  /// that registers a protocol with the same name as a context protocol returns
+  /// [`Error::DuplicateCustomProtocol`](crate::Error::DuplicateCustomProtocol).
+  ///
+  /// See [`WebViewBuilder::with_custom_protocol`] for the format of the URLs on each platform.
+  ///
+  /// [`WebView`]: crate::WebView
+  /// [`WebViewBuilder::with_asynchronous_custom_protocol`]: crate::WebViewBuilder::with_asynchronous_custom_protocol
+  /// [`WebViewBuilder::with_custom_protocol`]: crate::WebViewBuilder::with_custom_protocol
+  #[cfg(feature = "protocol")]
+  pub fn register_custom_protocol<F>(&mut self, name: String, handler: F) -> crate::Result<()>
+  where
+    F: Fn(WebViewId, Request<Vec<u8>>, RequestAsyncResponder) + MaybeSend + 'static,
+  {
+    crate::validate_scheme(&name)?;
+    self.check_custom_protocol(&name)?;
+
+    #[cfg(gtk)]
+    self.register_uri_scheme(&name, handler)?;
+    #[cfg(not(gtk))]
+    self
+      .custom_protocol_handlers
+      .insert(name.clone(), Rc::new(handler));
+
+    self.custom_protocols.insert(name);
+
+    Ok(())
+  }
+
   /// Check if a custom protocol has been registered on this context.
   pub fn is_custom_protocol_registered(&self, name: String) -> bool {
     self.custom_protocols.contains(&name)
   }
 
+  /// Set the IPC handler used by every [`WebView`] created with this context that does not set
+  /// its own handler with [`WebViewBuilder::with_ipc_handler`].
+  ///
+  /// The handler must be set before creating the webviews that should use it.
+  ///
+  /// [`WebView`]: crate::WebView
+  /// [`WebViewBuilder::with_ipc_handler`]: crate::WebViewBuilder::with_ipc_handler
+  pub fn set_ipc_handler<F>(&mut self, handler: F)
+  where
+    F: Fn(WebViewId, Request<String>) + MaybeSend + 'static,
+  {
+    self.handlers.ipc_handler = Some(Rc::new(handler));
+  }
+
+  /// Set the navigation handler used by every [`WebView`] created with this context that does
+  /// not set its own handler with [`WebViewBuilder::with_navigation_handler`].
+  ///
+  /// The handler must be set before creating the webviews that should use it.
+  ///
+  /// [`WebView`]: crate::WebView
+  /// [`WebViewBuilder::with_navigation_handler`]: crate::WebViewBuilder::with_navigation_handler
+  pub fn set_navigation_handler<F>(&mut self, handler: F)
+  where
+    F: Fn(WebViewId, String) -> bool + MaybeSend + 'static,
+  {
+    self.handlers.navigation_handler = Some(Rc::new(handler));
+  }
+
+  /// Set the download started handler used by every [`WebView`] created with this context that
+  /// does not set its own handler with [`WebViewBuilder::with_download_started_handler`].
+  ///
+  /// The handler must be set before creating the webviews that should use it.
+  ///
+  /// [`WebView`]: crate::WebView
+  /// [`WebViewBuilder::with_download_started_handler`]: crate::WebViewBuilder::with_download_started_handler
+  pub fn set_download_started_handler<F>(&mut self, handler: F)
+  where
+    F: Fn(WebViewId, String, &mut PathBuf) -> bool + MaybeSend + 'static,
+  {
+    self.handlers.download_started_handler = Some(Rc::new(handler));
+  }
+
+  /// Set the download completed handler used by every [`WebView`] created with this context that
+  /// does not set its own handler with [`WebViewBuilder::with_download_completed_handler`].
+  ///
+  /// The handler must be set before creating the webviews that should use it.
+  ///
+  /// [`WebView`]: crate::WebView
+  /// [`WebViewBuilder::with_download_completed_handler`]: crate::WebViewBuilder::with_download_completed_handler
+  pub fn set_download_completed_handler<F>(&mut self, handler: F)
+  where
+    F: Fn(WebViewId, String, Option<PathBuf>, bool) + MaybeSend + 'static,
+  {
+    self.handlers.download_completed_handler = Some(Rc::new(handler));
+  }
+
+  /// Send `message` to the pages of every [`WebView`] created with this context on the current
+  /// thread, which receive it with the listeners registered with `window.wry.onBroadcast`, if their
+  /// origin is allowed with [`WebViewBuilder::with_broadcast_origins`].
+  ///
+  /// The pages can also send a message to the other webviews of the context with
+  /// `window.wry.broadcast(message)`, e.g. to keep the state of multiple panels in sync.
+  ///
+  /// ```js
+  /// window.wry.onBroadcast((message) => console.log(JSON.parse(message)));
+  /// window.wry.broadcast(JSON.stringify({ theme: 'dark' }));
+  /// ```
+  ///
+  /// [`WebView`]: crate::WebView
+  /// [`WebViewBuilder::with_broadcast_origins`]: crate::WebViewBuilder::with_broadcast_origins
+  pub fn broadcast(&self, message: &str) -> crate::Result<()> {
+    crate::broadcast(&self.webviews, message, None)
+  }
+
+  /// Create a profile named `name`, with its own cookies, storage and cache, e.g. for the work and
+  /// the personal accounts of a user. Use it with [`WebViewBuilder::with_profile`].
+  ///
+  /// The data of a profile is found by its name, so creating a profile with the same name, in the
+  /// same data directory, after a restart of the app reuses its data. Use
+  /// [`WebContext::delete_profile`] to remove it.
+  ///
+  /// Returns [`Error::InvalidProfileName`](crate::Error::InvalidProfileName) if the name isn't 1
+  /// to 64 lowercase ASCII letters, digits, `-` or `_`, and
+  /// [`Error::DuplicateProfile`](crate::Error::DuplicateProfile) if a profile with the same name
+  /// was already created with this context.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: Uses a WebView2 profile in the data directory of the context.
+  ///   Requires WebView2 Runtime version 101.0.1210.39 or higher, the profile is ignored on older
+  ///   versions.
+  /// - **macOS / iOS**: Uses the `WKWebsiteDataStore` of an identifier derived from the name.
+  ///   Requires macOS 14 or iOS 17, the profile is ignored on older versions.
+  /// - **Linux**: Uses a webkit2gtk context with the `profiles/<name>` data directory, in the
+  ///   data directory of the context or in the user data directory of the app.
+  /// - **Android**: Unsupported.
+  ///
+  /// [`WebViewBuilder::with_profile`]: crate::WebViewBuilder::with_profile
+  pub fn create_profile(&mut self, name: &str, options: ProfileOptions) -> crate::Result<Profile> {
+    validate_profile_name(name)?;
+    if self.profiles.iter().any(|profile| profile.name == name) {
+      return Err(crate::Error::DuplicateProfile(name.to_string()));
+    }
+
+    let profile = Profile {
+      name: name.to_string(),
+      identifier: profile_identifier(name),
+      data_directory: match options.ephemeral {
+        true => None,
+        false => profile_data_directory(self.data_directory.as_deref(), name),
+      },
+      options,
+    };
+    self.profiles.push(profile.clone());
+    Ok(profile)
+  }
+
+  /// The profiles created with this context, see [`WebContext::create_profile`].
+  pub fn profiles(&self) -> &[Profile] {
+    &self.profiles
+  }
+
+  /// Delete the profile `name` and its data, whether it was created in this run of the app or not.
+  ///
+  /// It must be called once the webviews using the profile are dropped.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows**: When a webview used the profile in this run of the app, the profile is
+  ///   deleted with `ICoreWebView2Profile8::Delete`, which closes the webviews still using it,
+  ///   and its data is removed when the WebView2 browser process exits. Requires WebView2 Runtime
+  ///   version 1.0.2210.55 or higher, the data directory is removed right away otherwise.
+  /// - **macOS / iOS**: The data store is removed asynchronously, and not removed if a webview
+  ///   still uses it. Requires macOS 14 or iOS 17.
+  /// - **Android**: Unsupported.
+  pub fn delete_profile(&mut self, name: &str) -> crate::Result<()> {
+    validate_profile_name(name)?;
+    self.profiles.retain(|profile| profile.name != name);
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    crate::wkwebview::remove_data_store(profile_identifier(name));
+
+    if let Some(directory) = profile_data_directory(self.data_directory.as_deref(), name) {
+      #[cfg(windows)]
+      if crate::webview2::delete_profile(&directory)? {
+        return Ok(());
+      }
+
+      match fs::remove_dir_all(directory) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+      }
+    }
+
+    Ok(())
+  }
+
   /// Set if this context allows automation.
   ///
   /// **Note:** This is currently only enforced on Linux, and has the stipulation that
@@ -87,6 +417,153 @@ impl Default for WebContext {
   }
 }
 
+/// The options of a [`Profile`], see [`WebContext::create_profile`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProfileOptions {
+  /// Keep the data of the profile in memory, it's lost when the webviews are dropped.
+  ///
+  /// See [`WebViewBuilder::with_ephemeral_storage`](crate::WebViewBuilder::with_ephemeral_storage).
+  pub ephemeral: bool,
+}
+
+/// A set of webview data isolated from the other profiles, see [`WebContext::create_profile`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Profile {
+  name: String,
+  options: ProfileOptions,
+  identifier: [u8; 16],
+  data_directory: Option<PathBuf>,
+}
+
+impl Profile {
+  /// The name of the profile.
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// The options the profile was created with.
+  pub fn options(&self) -> ProfileOptions {
+    self.options
+  }
+
+  /// The identifier of the `WKWebsiteDataStore` of the profile on macOS and iOS, derived from
+  /// its name.
+  pub fn identifier(&self) -> [u8; 16] {
+    self.identifier
+  }
+
+  /// The directory of the data of the profile on Windows and Linux, `None` if it's ephemeral.
+  pub fn data_directory(&self) -> Option<&Path> {
+    self.data_directory.as_deref()
+  }
+}
+
+fn validate_profile_name(name: &str) -> crate::Result<()> {
+  let valid = (1..=64).contains(&name.len())
+    && name
+      .bytes()
+      .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_');
+  match valid {
+    true => Ok(()),
+    false => Err(crate::Error::InvalidProfileName(name.to_string())),
+  }
+}
+
+/// A UUID derived from the name of a profile, the FNV-1a hash of the name, so the profile finds
+/// its data store again after a restart.
+fn profile_identifier(name: &str) -> [u8; 16] {
+  const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+  const PRIME: u128 = 0x0000000001000000000000000000013b;
+  let hash = name.bytes().fold(OFFSET, |hash, byte| {
+    (hash ^ byte as u128).wrapping_mul(PRIME)
+  });
+
+  let mut identifier = hash.to_be_bytes();
+  // version 8 (custom) and the RFC 4122 variant
+  identifier[6] = (identifier[6] & 0x0f) | 0x80;
+  identifier[8] = (identifier[8] & 0x3f) | 0x80;
+  identifier
+}
+
+/// The data directory of the profile `name` of a context, the profile directory in its WebView2
+/// user data folder.
+#[cfg(windows)]
+fn profile_data_directory(data_directory: Option<&Path>, name: &str) -> Option<PathBuf> {
+  let user_data_folder = match data_directory {
+    Some(data_directory) => data_directory.to_path_buf(),
+    // the default user data folder of WebView2, next to the executable
+    None => {
+      let exe = std::env::current_exe().ok()?;
+      let mut folder = exe.file_name()?.to_os_string();
+      folder.push(".WebView2");
+      exe.parent()?.join(folder)
+    }
+  };
+  Some(user_data_folder.join("EBWebView").join(name))
+}
+
+/// The data directory of the profile `name` of a context.
+#[cfg(gtk)]
+fn profile_data_directory(data_directory: Option<&Path>, name: &str) -> Option<PathBuf> {
+  let profiles = match data_directory {
+    Some(data_directory) => data_directory.join("profiles"),
+    None => crate::webkitgtk::default_profiles_directory(),
+  };
+  Some(profiles.join(name))
+}
+
+#[cfg(not(any(windows, gtk)))]
+fn profile_data_directory(_: Option<&Path>, _: &str) -> Option<PathBuf> {
+  None
+}
+
+/// The result of [`WebContext::migrate_data_directory`].
+#[non_exhaustive]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DataMigration {
+  /// The names of the entries of the old data directory moved to the new one.
+  pub migrated: Vec<PathBuf>,
+  /// The names of the entries kept in the old data directory, with the reason they couldn't be
+  /// moved.
+  pub failed: Vec<(PathBuf, String)>,
+}
+
+/// Move the file or directory `from` to `to`, copying it when they are on different volumes.
+fn move_entry(from: &Path, to: &Path) -> io::Result<()> {
+  if fs::rename(from, to).is_ok() {
+    return Ok(());
+  }
+
+  let is_dir = fs::symlink_metadata(from)?.is_dir();
+  if let Err(e) = copy_entry(from, to) {
+    // don't leave a partial copy
+    let _ = if is_dir {
+      fs::remove_dir_all(to)
+    } else {
+      fs::remove_file(to)
+    };
+    return Err(e);
+  }
+  if is_dir {
+    fs::remove_dir_all(from)
+  } else {
+    fs::remove_file(from)
+  }
+}
+
+fn copy_entry(from: &Path, to: &Path) -> io::Result<()> {
+  if fs::symlink_metadata(from)?.is_dir() {
+    fs::create_dir(to)?;
+    for entry in fs::read_dir(from)? {
+      let entry = entry?;
+      copy_entry(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+  } else {
+    fs::copy(from, to).map(|_| ())
+  }
+}
+
 #[cfg(not(gtk))]
 #[derive(Debug)]
 pub(crate) struct WebContextImpl;
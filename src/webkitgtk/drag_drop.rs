@@ -71,6 +71,7 @@ pub(crate) fn connect_drag_event(webview: &WebView, handler: Box<dyn Fn(DragDrop
         controller.enter();
         controller.call(DragDropEvent::Enter {
           paths: paths.clone(),
+          raw_paths: paths.clone(),
           position: controller.position.get(),
         });
         controller.store_paths(paths);
@@ -97,7 +98,8 @@ pub(crate) fn connect_drag_event(webview: &WebView, handler: Box<dyn Fn(DragDrop
         if let Some(paths) = controller.take_paths() {
           controller.leave();
           return controller.call(DragDropEvent::Drop {
-            paths,
+            paths: paths.clone(),
+            raw_paths: paths,
             position: (x, y),
           });
         }
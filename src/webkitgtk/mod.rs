@@ -9,6 +9,7 @@ use gdkx11::{
   X11Display,
 };
 use gtk::{
+  cairo,
   gdk::{self},
   gio::Cancellable,
   glib::{self, translate::FromGlibPtrFull},
@@ -20,50 +21,112 @@ use raw_window_handle::{HasWindowHandle, RawWindowHandle};
 #[cfg(any(debug_assertions, feature = "devtools"))]
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::{
+  cell::{Cell, OnceCell, RefCell},
+  collections::HashMap,
   ffi::c_ulong,
+  path::{Path, PathBuf},
+  rc::Rc,
   sync::{Arc, Mutex},
+  time::Duration,
 };
 #[cfg(any(debug_assertions, feature = "devtools"))]
 use webkit2gtk::WebInspectorExt;
 use webkit2gtk::{
-  AutoplayPolicy, CookieManagerExt, InputMethodContextExt, LoadEvent, NavigationPolicyDecision,
-  NavigationPolicyDecisionExt, NetworkProxyMode, NetworkProxySettings, PolicyDecisionType,
-  PrintOperationExt, SettingsExt, URIRequest, URIRequestExt, UserContentInjectedFrames,
-  UserContentManager, UserContentManagerExt, UserScript, UserScriptInjectionTime,
-  WebContextExt as Webkit2gtkWeContextExt, WebView, WebViewExt, WebsiteDataManagerExt,
-  WebsiteDataManagerExtManual, WebsitePolicies,
+  AutoplayPolicy, BackForwardListExt, CacheModel, CookieManagerExt, DownloadExt,
+  InputMethodContextExt, LoadEvent, NavigationPolicyDecision, NavigationPolicyDecisionExt,
+  NetworkProxyMode, NetworkProxySettings, PermissionRequestExt, PolicyDecisionType,
+  PrintOperationExt, ScriptDialogType, SettingsExt, URIRequest, URIRequestExt,
+  UserContentInjectedFrames, UserContentManager, UserContentManagerExt, UserMediaPermissionRequest,
+  UserMessage, UserMessageExt, UserScript, UserScriptInjectionTime, UserStyleLevel, UserStyleSheet,
+  WebContextExt as Webkit2gtkWeContextExt, WebProcessTerminationReason, WebView, WebViewExt,
+  WebViewSessionState, WebsiteDataManagerExt, WebsiteDataManagerExtManual, WebsiteDataTypes,
+  WebsitePolicies,
 };
 use webkit2gtk_sys::{
   webkit_get_major_version, webkit_get_micro_version, webkit_get_minor_version,
   webkit_policy_decision_ignore, webkit_policy_decision_use,
+  webkit_user_media_permission_is_for_display_device,
 };
 use x11_dl::xlib::*;
 
+pub(crate) use web_context::default_profiles_directory;
 pub use web_context::WebContextImpl;
 
 use crate::{
-  proxy::ProxyConfig, web_context::WebContext, Error, PageLoadEvent, Rect, Result,
-  WebViewAttributes, RGBA,
+  blob_download::{save_blob_download, BLOB_DOWNLOAD_MESSAGE_HANDLER_NAME, BLOB_DOWNLOAD_SCRIPT},
+  ipc_length_check_script, origin_matches_domain, page_world_ipc_script,
+  proxy::ProxyConfig,
+  util::random_token,
+  web_context::WebContext,
+  ApiCapabilities, CacheMode, DragRegion, Engine, EngineInfo, Error, FileAccessPolicy,
+  NavigationState, OriginStorageUsage, PageLoadEvent, PermissionKind, PermissionRequest,
+  PermissionResponse, PersistentHeaders, PlatformWebViewHandle, PrintCapabilities, PrintOptions,
+  ProcessFailure, ProcessFailureReason, Rect, ResizeEdge, Result, VisibilityState,
+  WebViewAttributes, FETCH_WORLD_NAME, ISOLATED_WORLD_IPC_LISTENER_SCRIPT, ISOLATED_WORLD_NAME,
+  RGBA,
 };
 
-use self::web_context::WebContextExt;
+pub(crate) use self::web_context::WebContextExt;
 
 const WEBVIEW_ID: &str = "webview_id";
 
+/// The message handler of the [`FETCH_WORLD_NAME`] world, see [`InnerWebView::eval_fetch`].
+const FETCH_MESSAGE_HANDLER_NAME: &str = "wryFetch";
+
+/// The callback of a script of [`InnerWebView::eval_fetch`].
+type FetchCallback = Box<dyn FnOnce(std::result::Result<String, String>)>;
+
 mod drag_drop;
 mod synthetic_mouse_events;
 mod web_context;
 
+/// A message exchanged with the WebKit web process extensions, wrapping a `WebKitUserMessage`.
+#[derive(Debug, Clone)]
+pub struct WebExtensionMessage {
+  /// The name of the message.
+  pub name: String,
+  /// The parameters of the message.
+  pub parameters: Option<glib::Variant>,
+}
+
+impl WebExtensionMessage {
+  /// Create a new message.
+  pub fn new(name: impl Into<String>, parameters: Option<glib::Variant>) -> Self {
+    Self {
+      name: name.into(),
+      parameters,
+    }
+  }
+
+  fn from_user_message(message: &UserMessage) -> Self {
+    Self {
+      name: message
+        .name()
+        .map(|name| name.to_string())
+        .unwrap_or_default(),
+      parameters: message.parameters(),
+    }
+  }
+
+  fn to_user_message(&self) -> UserMessage {
+    UserMessage::new(&self.name, self.parameters.as_ref())
+  }
+}
+
 struct X11Data {
   is_child: bool,
   xlib: Xlib,
   x11_display: *mut std::ffi::c_void,
   x11_window: c_ulong,
   gtk_window: gtk::Window,
+  auto_resize: Option<glib::SourceId>,
 }
 
 impl Drop for X11Data {
   fn drop(&mut self) {
+    if let Some(auto_resize) = self.auto_resize.take() {
+      auto_resize.remove();
+    }
     unsafe { (self.xlib.XDestroyWindow)(self.x11_display as _, self.x11_window) };
     self.gtk_window.close();
   }
@@ -75,13 +138,41 @@ pub(crate) struct InnerWebView {
   #[cfg(any(debug_assertions, feature = "devtools"))]
   is_inspector_open: Arc<AtomicBool>,
   pending_scripts: Arc<Mutex<Option<Vec<String>>>>,
+  user_style_sheets: RefCell<Vec<(String, UserStyleSheet)>>,
+  persistent_headers: Option<Rc<PersistentHeaders>>,
+  navigation_state: Rc<NavigationState>,
   is_in_fixed_parent: bool,
+  /// Whether the initialization scripts run in the [`ISOLATED_WORLD_NAME`] world.
+  isolated_world: bool,
+  /// The custom protocol handlers of the context, to remove the handlers of the webview.
+  uri_scheme_handlers: Rc<web_context::UriSchemeHandlers>,
+  /// The callbacks of the scripts of [`Self::eval_fetch`] by their id, once the message handler of
+  /// the [`FETCH_WORLD_NAME`] world is registered.
+  fetch_callbacks: OnceCell<Rc<RefCell<HashMap<String, FetchCallback>>>>,
 
   x11: Option<X11Data>,
 }
 
+/// The session of a webview, shared with its duplicates, see [`crate::WebView::duplicate`].
+pub(crate) struct Session {
+  context: webkit2gtk::WebContext,
+  /// The history of the webview.
+  state: Option<WebViewSessionState>,
+}
+
+/// A [`Send`] handle to run closures on the thread of a webview.
+#[derive(Clone)]
+pub(crate) struct MainThreadDispatcher;
+
+impl MainThreadDispatcher {
+  pub fn dispatch(&self, f: Box<dyn FnOnce() + Send>) {
+    glib::MainContext::default().invoke(f);
+  }
+}
+
 impl Drop for InnerWebView {
   fn drop(&mut self) {
+    self.uri_scheme_handlers.remove_webview(&self.id);
     unsafe { self.webview.destroy() }
   }
 }
@@ -130,6 +221,9 @@ impl InnerWebView {
     let (gtk_window, vbox) = Self::create_gtk_window(raw, x11_window);
 
     let visible = attributes.visible;
+    let auto_resize = pl_attrs.auto_resize.then(|| {
+      Self::attach_auto_resize(x11_display as _, parent, x11_window, is_child, &gtk_window)
+    });
 
     Self::new_gtk(&vbox, attributes, pl_attrs).map(|mut w| {
       // for some reason, if the webview starts as hidden,
@@ -148,12 +242,44 @@ impl InnerWebView {
         x11_display: x11_display as _,
         x11_window,
         gtk_window,
+        auto_resize: auto_resize.flatten(),
       });
 
       w
     })
   }
 
+  /// Resize the webview to fill the `parent` X11 window, polling its size as the foreign windows
+  /// don't receive the configure events, see [`crate::WebViewBuilderExtUnix::with_auto_resize`].
+  fn attach_auto_resize(
+    display: *mut _XDisplay,
+    parent: c_ulong,
+    x11_window: c_ulong,
+    is_child: bool,
+    gtk_window: &gtk::Window,
+  ) -> Option<glib::SourceId> {
+    const AUTO_RESIZE_INTERVAL: Duration = Duration::from_millis(100);
+
+    let xlib = Xlib::open().ok()?;
+    let gtk_window = gtk_window.clone();
+    let mut last_size = None;
+    Some(glib::timeout_add_local(AUTO_RESIZE_INTERVAL, move || {
+      let mut attributes = unsafe { std::mem::zeroed::<XWindowAttributes>() };
+      let ok = unsafe { (xlib.XGetWindowAttributes)(display, parent, &mut attributes) };
+
+      let size = (attributes.width, attributes.height);
+      if ok != 0 && last_size.replace(size) != Some(size) {
+        let (width, height) = size;
+        if is_child {
+          unsafe { (xlib.XMoveResizeWindow)(display, x11_window, 0, 0, width as _, height as _) };
+        }
+        gtk_window.size_allocate(&gtk::Allocation::new(0, 0, width, height));
+      }
+
+      glib::ControlFlow::Continue
+    }))
+  }
+
   fn create_container_x11_window(
     xlib: &Xlib,
     display: *mut _XDisplay,
@@ -209,16 +335,27 @@ impl InnerWebView {
   pub fn new_gtk<W>(
     container: &W,
     mut attributes: WebViewAttributes,
-    _pl_attrs: super::PlatformSpecificWebViewAttributes,
+    mut pl_attrs: super::PlatformSpecificWebViewAttributes,
   ) -> Result<Self>
   where
     W: IsA<gtk::Container>,
   {
     // default_context allows us to create a scoped context on-demand
     let mut default_context;
-    let web_context = if attributes.incognito {
+    let session = pl_attrs.session.take();
+    let web_context = if let Some(session) = &session {
+      // the duplicates of a webview use its context
+      default_context = WebContext::with_os(WebContextImpl::shared(session.context.clone()));
+      &mut default_context
+    } else if attributes.incognito || attributes.ephemeral_storage {
       default_context = WebContext::new_ephemeral();
       &mut default_context
+    } else if let Some(data_directory) = attributes.data_directory.take() {
+      default_context = WebContext::new(Some(data_directory));
+      &mut default_context
+    } else if let Some(profile) = &attributes.profile {
+      default_context = WebContext::with_os(WebContextImpl::profile(profile));
+      &mut default_context
     } else {
       match attributes.context.take() {
         Some(w) => w,
@@ -242,6 +379,12 @@ impl InnerWebView {
       }
     }
 
+    if attributes.cache_mode == CacheMode::NoCache {
+      web_context
+        .context()
+        .set_cache_model(CacheModel::DocumentViewer);
+    }
+
     let webview = Self::create_webview(web_context, &attributes);
 
     // Transparent
@@ -262,12 +405,42 @@ impl InnerWebView {
     // Webview Settings
     Self::set_webview_settings(&webview, &attributes);
 
+    // Persistent headers, connected before the navigation handler to see the navigations first
+    let persistent_headers = attributes.persistent_headers.then(|| {
+      Rc::new(PersistentHeaders::new(
+        attributes.url.as_deref(),
+        attributes.headers.clone(),
+      ))
+    });
+    if let Some(persistent_headers) = &persistent_headers {
+      Self::attach_persistent_headers_handler(&webview, persistent_headers.clone());
+    }
+
+    // Navigation state
+    let navigation_state = Rc::new(NavigationState::new());
+    let navigation_state_ = navigation_state.clone();
+    webview.connect_load_changed(move |_, event| match event {
+      LoadEvent::Started => navigation_state_.start(None),
+      LoadEvent::Finished => navigation_state_.finish(None),
+      _ => (),
+    });
+
     // Webview handlers
     Self::attach_handlers(&webview, web_context, &mut attributes);
 
     // IPC handler
     Self::attach_ipc_handler(webview.clone(), &mut attributes);
 
+    // Web process extension messages
+    if let Some(handler) = pl_attrs.web_extension_message_handler {
+      webview.connect_user_message_received(move |_, message| {
+        if let Some(reply) = handler(WebExtensionMessage::from_user_message(message)) {
+          message.send_reply(&reply.to_user_message());
+        }
+        true
+      });
+    }
+
     // Drag drop handler
     if let Some(drag_drop_handler) = attributes.drag_drop_handler.take() {
       drag_drop::connect_drag_event(&webview, drag_drop_handler);
@@ -290,8 +463,14 @@ impl InnerWebView {
       id,
       webview,
       pending_scripts: Arc::new(Mutex::new(Some(Vec::new()))),
+      user_style_sheets: Default::default(),
+      persistent_headers,
+      navigation_state,
 
       is_in_fixed_parent,
+      isolated_world: attributes.isolated_world,
+      uri_scheme_handlers: web_context.os.uri_scheme_handlers().clone(),
+      fetch_callbacks: OnceCell::new(),
       x11: None,
 
       #[cfg(any(debug_assertions, feature = "devtools"))]
@@ -299,12 +478,29 @@ impl InnerWebView {
     };
 
     // Initialize message handler
-    w.init("Object.defineProperty(window, 'ipc', { value: Object.freeze({ postMessage: function(x) { window.webkit.messageHandlers['ipc'].postMessage(x) } }) })")?;
+    if w.isolated_world {
+      w.add_user_script(&page_world_ipc_script(attributes.ipc_limits), None)?;
+    }
+    w.init(&format!(
+      "Object.defineProperty(window, 'ipc', {{ value: Object.freeze({{ postMessage: function(message) {{ {} window.webkit.messageHandlers['ipc'].postMessage(message) }} }}) }})",
+      ipc_length_check_script(attributes.ipc_limits)
+    ))?;
+    if w.isolated_world {
+      w.init(ISOLATED_WORLD_IPC_LISTENER_SCRIPT)?;
+    }
 
     // Initialize scripts
     for js in attributes.initialization_scripts {
       w.init(&js)?;
     }
+    for js in &pl_attrs.page_world_scripts {
+      w.add_user_script(js, None)?;
+    }
+
+    // User style sheets
+    for (css, main_frame_only) in attributes.user_style_sheets {
+      w.add_css(&css, main_frame_only)?;
+    }
 
     // Run pending webview.eval() scripts once webview loads.
     let pending_scripts = w.pending_scripts.clone();
@@ -322,11 +518,17 @@ impl InnerWebView {
 
     // Custom protocols handler
     for (name, handler) in attributes.custom_protocols {
-      web_context.register_uri_scheme(&name, handler)?;
+      web_context.register_webview_uri_scheme(&w.id, &name, handler)?;
     }
 
-    // Navigation
-    if let Some(url) = attributes.url {
+    // Navigation, to the current page of the history of the duplicated webview if any
+    let history_item = session.and_then(|session| {
+      w.webview.restore_session_state(&session.state?);
+      w.webview.back_forward_list()?.current_item()
+    });
+    if let Some(item) = history_item {
+      w.webview.go_to_back_forward_list_item(&item);
+    } else if let Some(url) = attributes.url {
       web_context.queue_load_uri(w.webview.clone(), url, attributes.headers);
       web_context.flush_queue_loader();
     } else if let Some(html) = attributes.html {
@@ -376,6 +578,10 @@ impl InnerWebView {
       // Enable webgl, webaudio, canvas features as default.
       settings.set_enable_webgl(true);
       settings.set_enable_webaudio(true);
+      // Needed by `getDisplayMedia`, only exposed to the pages when the app decides on the captures
+      if attributes.permission_handler.is_some() {
+        settings.set_enable_media_stream(true);
+      }
       settings
         .set_enable_back_forward_navigation_gestures(attributes.back_forward_navigation_gestures);
 
@@ -390,6 +596,21 @@ impl InnerWebView {
       // Set user agent
       settings.set_user_agent(attributes.user_agent.as_deref());
 
+      // Disabling markup JavaScript keeps the initialization scripts and `evaluate_script` working
+      settings.set_enable_javascript_markup(attributes.javascript_enabled);
+      settings.set_auto_load_images(attributes.images_enabled);
+
+      // The navigations to file URLs are blocked by the navigation handler when they are denied
+      if attributes.file_access != FileAccessPolicy::Default {
+        settings.set_allow_file_access_from_file_urls(matches!(
+          attributes.file_access,
+          FileAccessPolicy::AllowFiles | FileAccessPolicy::Universal
+        ));
+        settings.set_allow_universal_access_from_file_urls(
+          attributes.file_access == FileAccessPolicy::Universal,
+        );
+      }
+
       // Devtools
       if attributes.devtools {
         settings.set_enable_developer_extras(true);
@@ -397,6 +618,66 @@ impl InnerWebView {
     }
   }
 
+  /// Start the `GET` navigations of the main frame again with the [`PersistentHeaders`] when they
+  /// don't have them, as WebKit doesn't send them on redirections and page navigations.
+  fn attach_persistent_headers_handler(
+    webview: &WebView,
+    persistent_headers: Rc<PersistentHeaders>,
+  ) {
+    // the navigation started again, let it go even if WebKit doesn't report its headers
+    let restarted_uri = RefCell::new(None);
+    webview.connect_decide_policy(move |webview, policy_decision, policy_type| {
+      if policy_type != PolicyDecisionType::NavigationAction {
+        return false;
+      }
+
+      let Some(request) = policy_decision
+        .dynamic_cast_ref::<NavigationPolicyDecision>()
+        .and_then(|policy| policy.navigation_action())
+        .and_then(|action| action.request())
+      else {
+        return false;
+      };
+      let Some(uri) = request.uri() else {
+        return false;
+      };
+      let restarted = restarted_uri
+        .take()
+        .is_some_and(|restarted| restarted == uri);
+      if restarted
+        || request
+          .http_method()
+          .map_or(true, |method| method.as_str() != "GET")
+      {
+        return false;
+      }
+      let Some(headers) = persistent_headers.get(&uri) else {
+        return false;
+      };
+      let has_headers = request.http_headers().is_some_and(|request_headers| {
+        headers
+          .keys()
+          .all(|name| request_headers.one(name.as_str()).is_some())
+      });
+      if has_headers {
+        return false;
+      }
+
+      unsafe { webkit_policy_decision_ignore(policy_decision.as_ptr()) };
+
+      let request = URIRequest::builder().uri(uri.as_str()).build();
+      if let Some(request_headers) = request.http_headers() {
+        for (name, value) in headers.iter() {
+          request_headers.append(name.as_str(), value.to_str().unwrap_or_default());
+        }
+      }
+      webview.load_request(&request);
+      restarted_uri.replace(Some(uri));
+
+      true
+    });
+  }
+
   fn attach_handlers(
     webview: &WebView,
     web_context: &mut WebContext,
@@ -408,6 +689,62 @@ impl InnerWebView {
     // Synthetic mouse events
     synthetic_mouse_events::setup(webview);
 
+    // Visibility changed handler
+    if let Some(handler) = attributes.visibility_changed_handler.take() {
+      let handler = Rc::new(handler);
+      let handler_ = handler.clone();
+      webview.connect_map(move |_| handler_(VisibilityState::Visible));
+      webview.connect_unmap(move |_| handler(VisibilityState::Hidden));
+    }
+
+    // Bounds changed handler
+    if let Some(handler) = attributes.bounds_changed_handler.take() {
+      let last_allocation = Cell::new(None);
+      webview.connect_size_allocate(move |_, allocation| {
+        let allocation = (
+          allocation.x(),
+          allocation.y(),
+          allocation.width(),
+          allocation.height(),
+        );
+        if last_allocation.replace(Some(allocation)) != Some(allocation) {
+          let (x, y, width, height) = allocation;
+          handler(Rect {
+            position: LogicalPosition::new(x, y).into(),
+            size: LogicalSize::new(width, height).into(),
+          });
+        }
+      });
+    }
+
+    // Keep the display awake while media is playing
+    if attributes.media_keeps_display_awake {
+      let cookie = Cell::new(None);
+      webview.connect_is_playing_audio_notify(move |webview| {
+        let Some(window) = webview
+          .toplevel()
+          .and_then(|w| w.downcast::<gtk::Window>().ok())
+        else {
+          return;
+        };
+        let Some(application) = window.application() else {
+          return;
+        };
+
+        if webview.is_playing_audio() {
+          if cookie.get().is_none() {
+            cookie.set(Some(application.inhibit(
+              Some(&window),
+              gtk::ApplicationInhibitFlags::IDLE,
+              Some("Playing media"),
+            )));
+          }
+        } else if let Some(cookie) = cookie.take() {
+          application.uninhibit(cookie);
+        }
+      });
+    }
+
     // Document title changed handler
     if let Some(document_title_changed_handler) = attributes.document_title_changed_handler.take() {
       webview.connect_title_notify(move |webview| {
@@ -416,6 +753,70 @@ impl InnerWebView {
       });
     }
 
+    // Before unload handler
+    if let Some(before_unload_handler) = attributes.before_unload_handler.take() {
+      webview.connect_script_dialog(move |webview, dialog| {
+        if dialog.dialog_type() != ScriptDialogType::BeforeUnloadConfirm {
+          return false;
+        }
+        let url = webview.uri().map(|uri| uri.to_string()).unwrap_or_default();
+        let message = dialog.message().map(|m| m.to_string()).unwrap_or_default();
+        dialog.confirm_set_confirmed(before_unload_handler(url, message));
+        true
+      });
+    }
+
+    // Display capture permission, the other requests keep the default behavior of WebKit
+    if let Some(permission_handler) = attributes.permission_handler.take() {
+      webview.connect_permission_request(move |webview, request| {
+        let Some(media_request) = request.dynamic_cast_ref::<UserMediaPermissionRequest>() else {
+          return false;
+        };
+        let is_for_display = unsafe {
+          webkit_user_media_permission_is_for_display_device(media_request.as_ptr()) != 0
+        };
+        if !is_for_display {
+          return false;
+        }
+
+        let response = permission_handler(PermissionRequest {
+          url: webview.uri().map(|uri| uri.to_string()).unwrap_or_default(),
+          kind: PermissionKind::DisplayCapture,
+        });
+        // the XDG desktop portal asks the user what to share
+        match response {
+          PermissionResponse::Allow | PermissionResponse::AllowDisplayCapture(_) => request.allow(),
+          _ => request.deny(),
+        }
+        true
+      });
+    }
+
+    // Process failed handler
+    if let Some(process_failed_handler) = attributes.process_failed_handler.take() {
+      webview.connect_web_process_terminated(move |_, reason| {
+        let reason = match reason {
+          WebProcessTerminationReason::Crashed => ProcessFailureReason::Crashed,
+          WebProcessTerminationReason::ExceededMemoryLimit => ProcessFailureReason::OutOfMemory,
+          WebProcessTerminationReason::TerminatedByApi => ProcessFailureReason::Terminated,
+          _ => ProcessFailureReason::Unknown,
+        };
+        process_failed_handler(ProcessFailure {
+          reason,
+          exit_code: None,
+          description: None,
+          crash_dumps_dir: None,
+        });
+      });
+    }
+
+    // Close requested handler
+    if let Some(close_requested_handler) = attributes.close_requested_handler.take() {
+      webview.connect_close(move |_| {
+        close_requested_handler();
+      });
+    }
+
     // Page load handler
     if let Some(on_page_load_handler) = attributes.on_page_load_handler.take() {
       webview.connect_load_changed(move |webview, load_event| match load_event {
@@ -471,13 +872,69 @@ impl InnerWebView {
     if attributes.download_started_handler.is_some()
       || attributes.download_completed_handler.is_some()
     {
+      let download_started_handler = attributes
+        .download_started_handler
+        .take()
+        .map(|handler| Rc::new(RefCell::new(handler)));
+      let download_completed_handler = attributes.download_completed_handler.take();
+
+      if let Some(download_started_handler) = &download_started_handler {
+        Self::attach_blob_download_handler(
+          webview,
+          download_started_handler.clone(),
+          download_completed_handler.clone(),
+        );
+      }
+
       web_context.register_download_handler(
-        attributes.download_started_handler.take(),
-        attributes.download_completed_handler.take(),
+        download_started_handler.map(|handler| {
+          Box::new(move |url: String, path: &mut PathBuf| (handler.borrow_mut())(url, path))
+            as Box<dyn FnMut(String, &mut PathBuf) -> bool>
+        }),
+        download_completed_handler,
       )
     }
   }
 
+  #[allow(clippy::type_complexity)]
+  fn attach_blob_download_handler(
+    webview: &WebView,
+    download_started_handler: Rc<RefCell<Box<dyn FnMut(String, &mut PathBuf) -> bool>>>,
+    download_completed_handler: Option<Rc<dyn Fn(String, Option<PathBuf>, bool) + 'static>>,
+  ) {
+    let Some(manager) = webview.user_content_manager() else {
+      return;
+    };
+
+    // Connect before registering as recommended by the docs
+    manager.connect_script_message_received(
+      Some(BLOB_DOWNLOAD_MESSAGE_HANDLER_NAME),
+      move |_m, msg| {
+        if let Some(message) = msg.js_value() {
+          let download_dir = glib::user_special_dir(glib::UserDirectory::Downloads);
+          save_blob_download(
+            &message.to_string(),
+            download_dir.as_deref(),
+            &mut **download_started_handler.borrow_mut(),
+            download_completed_handler.as_deref(),
+          );
+        }
+      },
+    );
+
+    // Register the handler we just connected
+    manager.register_script_message_handler(BLOB_DOWNLOAD_MESSAGE_HANDLER_NAME);
+
+    let script = UserScript::new(
+      BLOB_DOWNLOAD_SCRIPT,
+      UserContentInjectedFrames::TopFrame,
+      UserScriptInjectionTime::Start,
+      &[],
+      &[],
+    );
+    manager.add_script(&script);
+  }
+
   fn add_to_container<W>(webview: &WebView, container: &W, attributes: &WebViewAttributes) -> bool
   where
     W: IsA<gtk::Container>,
@@ -521,12 +978,13 @@ impl InnerWebView {
   fn attach_ipc_handler(webview: WebView, attributes: &mut WebViewAttributes) {
     // Message handler
     let ipc_handler = attributes.ipc_handler.take();
+
     let manager = webview
       .user_content_manager()
       .expect("WebView does not have UserContentManager");
 
     // Connect before registering as recommended by the docs
-    manager.connect_script_message_received(None, move |_m, msg| {
+    manager.connect_script_message_received(Some("ipc"), move |_m, msg| {
       #[cfg(feature = "tracing")]
       let _span = tracing::info_span!(parent: None, "wry::ipc::handle").entered();
 
@@ -543,7 +1001,49 @@ impl InnerWebView {
     });
 
     // Register the handler we just connected
-    manager.register_script_message_handler("ipc");
+    if attributes.isolated_world {
+      manager.register_script_message_handler_in_world("ipc", ISOLATED_WORLD_NAME);
+    } else {
+      manager.register_script_message_handler("ipc");
+    }
+  }
+
+  /// Move or resize the top-level window of the webview with the mouse, for the drag regions.
+  pub fn drag_window(&self, region: DragRegion) {
+    let webview = &self.webview;
+    let Some(window) = webview
+      .toplevel()
+      .and_then(|toplevel| toplevel.downcast::<gtk::Window>().ok())
+    else {
+      return;
+    };
+    let Some(pointer) = webview
+      .display()
+      .default_seat()
+      .and_then(|seat| seat.pointer())
+    else {
+      return;
+    };
+
+    let (_, x, y) = pointer.position();
+    let button = 1;
+    let timestamp = gtk::current_event_time();
+    match region {
+      DragRegion::Move => window.begin_move_drag(button, x, y, timestamp),
+      DragRegion::Resize(edge) => {
+        let edge = match edge {
+          ResizeEdge::North => gdk::WindowEdge::North,
+          ResizeEdge::NorthEast => gdk::WindowEdge::NorthEast,
+          ResizeEdge::East => gdk::WindowEdge::East,
+          ResizeEdge::SouthEast => gdk::WindowEdge::SouthEast,
+          ResizeEdge::South => gdk::WindowEdge::South,
+          ResizeEdge::SouthWest => gdk::WindowEdge::SouthWest,
+          ResizeEdge::West => gdk::WindowEdge::West,
+          ResizeEdge::NorthWest => gdk::WindowEdge::NorthWest,
+        };
+        window.begin_resize_drag(edge, button, x, y, timestamp);
+      }
+    }
   }
 
   #[cfg(any(debug_assertions, feature = "devtools"))]
@@ -573,10 +1073,75 @@ impl InnerWebView {
     Ok(())
   }
 
+  pub fn print_with_options(&self, options: &PrintOptions) -> Result<()> {
+    let print = webkit2gtk::PrintOperation::new(&self.webview);
+
+    // the setting of the webview is restored once the job is done
+    if let Some(print_backgrounds) = options.print_backgrounds {
+      if let Some(settings) = WebViewExt::settings(&self.webview) {
+        let previous = settings.is_print_backgrounds();
+        settings.set_print_backgrounds(print_backgrounds);
+        print.connect_finished(move |_| settings.set_print_backgrounds(previous));
+      }
+    }
+
+    let page_setup = gtk::PageSetup::new();
+    page_setup.set_top_margin(options.margins.top.into(), gtk::Unit::Points);
+    page_setup.set_right_margin(options.margins.right.into(), gtk::Unit::Points);
+    page_setup.set_bottom_margin(options.margins.bottom.into(), gtk::Unit::Points);
+    page_setup.set_left_margin(options.margins.left.into(), gtk::Unit::Points);
+
+    print.set_page_setup(&page_setup);
+    if let Some(scale) = options.scale {
+      let print_settings = gtk::PrintSettings::new();
+      // the scale of the print settings is a percentage
+      print_settings.set_scale(scale * 100.0);
+      print.set_print_settings(&print_settings);
+    }
+    match options.silent {
+      true => print.print(),
+      false => {
+        print.run_dialog(None::<&gtk::Window>);
+      }
+    }
+    Ok(())
+  }
+
+  pub fn print_capabilities(&self) -> PrintCapabilities {
+    PrintCapabilities {
+      margins: true,
+      scale: true,
+      print_backgrounds: true,
+      ..Default::default()
+    }
+  }
+
+  pub fn api_capabilities(&self) -> ApiCapabilities {
+    ApiCapabilities {
+      gamepad: true,
+      ..Default::default()
+    }
+  }
+
   pub fn url(&self) -> Result<String> {
     Ok(self.webview.uri().unwrap_or_default().to_string())
   }
 
+  pub fn session(&self) -> Result<Session> {
+    Ok(Session {
+      context: self.webview.context().ok_or(Error::MissingManager)?,
+      state: self.webview.session_state(),
+    })
+  }
+
+  pub fn platform_handle(&self) -> PlatformWebViewHandle {
+    PlatformWebViewHandle::WebKitGtk(self.webview.clone())
+  }
+
+  pub fn navigation_state(&self) -> &NavigationState {
+    &self.navigation_state
+  }
+
   pub fn eval(
     &self,
     js: &str,
@@ -609,16 +1174,118 @@ impl InnerWebView {
     Ok(())
   }
 
+  /// Evaluate `js` in the isolated world `world`, passing its JSON result to `callback`.
+  #[cfg(feature = "reader")]
+  pub fn eval_in_world(
+    &self,
+    js: &str,
+    world: &str,
+    callback: impl FnOnce(String) + Send + 'static,
+  ) -> Result<()> {
+    let cancellable: Option<&Cancellable> = None;
+    self
+      .webview
+      .run_javascript_in_world(js, world, cancellable, |result| {
+        let result = result
+          .map(|r| r.js_value().and_then(|js| js.to_json(0)))
+          .unwrap_or_default()
+          .unwrap_or_default()
+          .to_string();
+        callback(result);
+      });
+
+    Ok(())
+  }
+
+  /// Run the async function `body` in the [`FETCH_WORLD_NAME`] world, passing the string it
+  /// returns, or the error it throws, to `callback`.
+  ///
+  /// The result is sent with a message handler only registered in this world, so the page scripts
+  /// can't send or read it.
+  pub fn eval_fetch(
+    &self,
+    body: &str,
+    callback: impl FnOnce(std::result::Result<String, String>) + 'static,
+  ) -> Result<()> {
+    let manager = self
+      .webview
+      .user_content_manager()
+      .ok_or(Error::InitScriptError)?;
+    let callbacks = self.fetch_callbacks.get_or_init(|| {
+      let callbacks: Rc<RefCell<HashMap<String, FetchCallback>>> = Default::default();
+
+      // Connect before registering as recommended by the docs
+      let callbacks_ = callbacks.clone();
+      manager.connect_script_message_received(Some(FETCH_MESSAGE_HANDLER_NAME), move |_m, msg| {
+        let Some(message) = msg.js_value().map(|value| value.to_string()) else {
+          return;
+        };
+        let mut parts = message.splitn(3, '\n');
+        let (Some(id), Some(status), Some(result)) = (parts.next(), parts.next(), parts.next())
+        else {
+          return;
+        };
+        let callback = callbacks_.borrow_mut().remove(id);
+        if let Some(callback) = callback {
+          callback(match status {
+            "ok" => Ok(result.to_string()),
+            _ => Err(result.to_string()),
+          });
+        }
+      });
+
+      // Register the handler we just connected
+      manager
+        .register_script_message_handler_in_world(FETCH_MESSAGE_HANDLER_NAME, FETCH_WORLD_NAME);
+      callbacks
+    });
+
+    let id = random_token();
+    callbacks
+      .borrow_mut()
+      .insert(id.clone(), Box::new(callback));
+
+    let js = format!(
+      r#"
+(async function () {{
+{body}
+}})().then(
+  (result) => window.webkit.messageHandlers.{FETCH_MESSAGE_HANDLER_NAME}.postMessage('{id}\nok\n' + result),
+  (error) => window.webkit.messageHandlers.{FETCH_MESSAGE_HANDLER_NAME}.postMessage('{id}\nerror\n' + String(error)),
+);"#
+    );
+    let callbacks = callbacks.clone();
+    let cancellable: Option<&Cancellable> = None;
+    self
+      .webview
+      .run_javascript_in_world(&js, FETCH_WORLD_NAME, cancellable, move |result| {
+        if let Err(e) = result {
+          let callback = callbacks.borrow_mut().remove(&id);
+          if let Some(callback) = callback {
+            callback(Err(e.to_string()));
+          }
+        }
+      });
+
+    Ok(())
+  }
+
   fn init(&self, js: &str) -> Result<()> {
+    let world = self.isolated_world.then_some(ISOLATED_WORLD_NAME);
+    self.add_user_script(js, world)
+  }
+
+  /// Add a user script running in `world`, or in the page world if `None`.
+  fn add_user_script(&self, js: &str, world: Option<&str>) -> Result<()> {
     if let Some(manager) = self.webview.user_content_manager() {
-      let script = UserScript::new(
-        js,
-        // TODO: feature to allow injecting into subframes
-        UserContentInjectedFrames::TopFrame,
-        UserScriptInjectionTime::Start,
-        &[],
-        &[],
-      );
+      // TODO: feature to allow injecting into subframes
+      let frames = UserContentInjectedFrames::TopFrame;
+      let script = match world {
+        Some(world) => {
+          UserScript::for_world(js, frames, UserScriptInjectionTime::Start, world, &[], &[])
+        }
+        None => UserScript::new(js, frames, UserScriptInjectionTime::Start, &[], &[]),
+      };
       manager.add_script(&script);
     } else {
       return Err(Error::InitScriptError);
@@ -626,6 +1293,43 @@ impl InnerWebView {
     Ok(())
   }
 
+  pub fn add_css(&self, css: &str, main_frame_only: bool) -> Result<()> {
+    let Some(manager) = self.webview.user_content_manager() else {
+      return Err(Error::InitScriptError);
+    };
+
+    let style_sheet = UserStyleSheet::new(
+      css,
+      if main_frame_only {
+        UserContentInjectedFrames::TopFrame
+      } else {
+        UserContentInjectedFrames::AllFrames
+      },
+      UserStyleLevel::User,
+      &[],
+      &[],
+    );
+    manager.add_style_sheet(&style_sheet);
+    self
+      .user_style_sheets
+      .borrow_mut()
+      .push((css.to_string(), style_sheet));
+
+    Ok(())
+  }
+
+  pub fn remove_css(&self, css: &str) -> Result<()> {
+    let mut user_style_sheets = self.user_style_sheets.borrow_mut();
+    if let Some(index) = user_style_sheets.iter().position(|(c, _)| c == css) {
+      let (_, style_sheet) = user_style_sheets.remove(index);
+      if let Some(manager) = self.webview.user_content_manager() {
+        manager.remove_style_sheet(&style_sheet);
+      }
+    }
+
+    Ok(())
+  }
+
   #[cfg(any(debug_assertions, feature = "devtools"))]
   pub fn open_devtools(&self) {
     if let Some(inspector) = self.webview.inspector() {
@@ -663,11 +1367,27 @@ impl InnerWebView {
   }
 
   pub fn load_url(&self, url: &str) -> Result<()> {
+    if let Some(persistent_headers) = &self.persistent_headers {
+      persistent_headers.set(url, None);
+    }
     self.webview.load_uri(url);
     Ok(())
   }
 
+  pub fn download(&self, url: &str, destination: &Path) -> Result<()> {
+    // The `download-started` signal is emitted asynchronously, so the destination set here is
+    // the one passed to the download started handler.
+    if let Some(download) = self.webview.download_uri(url) {
+      download.set_destination(&destination.to_string_lossy());
+    }
+    Ok(())
+  }
+
   pub fn load_url_with_headers(&self, url: &str, headers: http::HeaderMap) -> Result<()> {
+    if let Some(persistent_headers) = &self.persistent_headers {
+      persistent_headers.set(url, Some(headers.clone()));
+    }
+
     let req = URIRequest::builder().uri(url).build();
 
     if let Some(ref mut req_headers) = req.http_headers() {
@@ -704,6 +1424,126 @@ impl InnerWebView {
     Ok(())
   }
 
+  pub fn clear_http_cache(&self) -> Result<()> {
+    if let Some(context) = self.webview.context() {
+      if let Some(data_manger) = context.website_data_manager() {
+        data_manger.clear(
+          WebsiteDataTypes::DISK_CACHE | WebsiteDataTypes::MEMORY_CACHE,
+          gtk::glib::TimeSpan::from_seconds(0),
+          None::<&Cancellable>,
+          |_| {},
+        );
+      }
+    }
+
+    Ok(())
+  }
+
+  pub fn send_web_extension_message<F>(&self, message: WebExtensionMessage, reply_handler: F)
+  where
+    F: FnOnce(Result<WebExtensionMessage>) + 'static,
+  {
+    self.webview.send_message_to_page(
+      &message.to_user_message(),
+      None::<&Cancellable>,
+      move |reply| {
+        reply_handler(
+          reply
+            .map(|reply| WebExtensionMessage::from_user_message(&reply))
+            .map_err(Into::into),
+        )
+      },
+    );
+  }
+
+  pub fn renderer_pid(&self) -> Result<Option<u32>> {
+    // webkit2gtk doesn't expose the web process of a webview
+    Ok(None)
+  }
+
+  pub fn memory_usage(&self, handler: impl FnOnce(Option<u64>) + 'static) -> Result<()> {
+    handler(None);
+    Ok(())
+  }
+
+  pub fn engine_info(&self) -> Result<EngineInfo> {
+    let mut info = platform_engine_info()?;
+    info.user_data_dir = self
+      .webview
+      .website_data_manager()
+      .and_then(|manager| manager.base_data_directory())
+      .map(|dir| PathBuf::from(dir.as_str()));
+    Ok(info)
+  }
+
+  pub fn history_length(&self) -> Result<Option<usize>> {
+    Ok(
+      self
+        .webview
+        .back_forward_list()
+        .map(|list| list.length() as usize),
+    )
+  }
+
+  pub fn storage_usage(&self) -> Result<Vec<OriginStorageUsage>> {
+    let Some(manager) = self.webview.website_data_manager() else {
+      return Ok(Vec::new());
+    };
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    manager.fetch(WebsiteDataTypes::ALL, None::<&Cancellable>, move |data| {
+      let usage = data.map(|data| {
+        data
+          .into_iter()
+          .filter_map(|data| {
+            Some(OriginStorageUsage {
+              origin: data.name()?.to_string(),
+              // webkit2gtk only knows the size of the disk cache
+              cache: Some(data.size(WebsiteDataTypes::DISK_CACHE)),
+              indexed_db: None,
+              local_storage: None,
+            })
+          })
+          .collect()
+      });
+      let _ = tx.send(usage);
+    });
+
+    loop {
+      gtk::main_iteration();
+
+      if let Ok(response) = rx.try_recv() {
+        return response.map_err(Into::into);
+      }
+    }
+  }
+
+  pub fn remove_origin_data(&self, origin: &str) -> Result<()> {
+    if let Some(manager) = self.webview.website_data_manager() {
+      let origin = origin.to_string();
+      let manager_ = manager.clone();
+      manager.fetch(WebsiteDataTypes::ALL, None::<&Cancellable>, move |data| {
+        let Ok(data) = data else {
+          return;
+        };
+
+        let data: Vec<_> = data
+          .iter()
+          .filter(|data| {
+            data
+              .name()
+              .map_or(false, |domain| origin_matches_domain(&origin, &domain))
+          })
+          .collect();
+        if !data.is_empty() {
+          manager_.remove(WebsiteDataTypes::ALL, &data, None::<&Cancellable>, |_| {});
+        }
+      });
+    }
+
+    Ok(())
+  }
+
   pub fn bounds(&self) -> Result<Rect> {
     let mut bounds = Rect::default();
 
@@ -754,6 +1594,37 @@ impl InnerWebView {
     Ok(())
   }
 
+  pub fn set_hit_test_ignore_regions(&self, regions: Vec<Rect>) -> Result<()> {
+    let region = if regions.is_empty() {
+      None
+    } else {
+      let scale_factor = self.webview.scale_factor() as f64;
+      // A region larger than any webview, the ignored regions are cut out of it
+      let region = cairo::Region::create_rectangle(&cairo::RectangleInt::new(
+        0,
+        0,
+        i16::MAX as i32,
+        i16::MAX as i32,
+      ));
+      for rect in regions {
+        let (x, y) = rect.position.to_logical::<i32>(scale_factor).into();
+        let (width, height) = rect.size.to_logical::<i32>(scale_factor).into();
+        let _ = region.subtract_rectangle(&cairo::RectangleInt::new(x, y, width, height));
+      }
+      Some(region)
+    };
+
+    self.webview.input_shape_combine_region(region.as_ref());
+    // The child X11 window must let the events through as well
+    if let Some(x11_data) = &self.x11 {
+      x11_data
+        .gtk_window
+        .input_shape_combine_region(region.as_ref());
+    }
+
+    Ok(())
+  }
+
   fn set_visible_x11(&self, visible: bool) {
     if let Some(x11_data) = &self.x11 {
       if x11_data.is_child {
@@ -778,6 +1649,20 @@ impl InnerWebView {
     }
   }
 
+  pub fn set_javascript_enabled(&self, enabled: bool) -> Result<()> {
+    if let Some(settings) = WebViewExt::settings(&self.webview) {
+      settings.set_enable_javascript_markup(enabled);
+    }
+    Ok(())
+  }
+
+  pub fn set_images_enabled(&self, enabled: bool) -> Result<()> {
+    if let Some(settings) = WebViewExt::settings(&self.webview) {
+      settings.set_auto_load_images(enabled);
+    }
+    Ok(())
+  }
+
   pub fn set_visible(&self, visible: bool) -> Result<()> {
     self.set_visible_x11(visible);
 
@@ -792,6 +1677,13 @@ impl InnerWebView {
     Ok(())
   }
 
+  /// Show or hide the content of the webview, which keeps painting unlike with
+  /// [`Self::set_visible`].
+  pub fn set_content_visible(&self, visible: bool) -> Result<()> {
+    self.webview.set_opacity(if visible { 1.0 } else { 0.0 });
+    Ok(())
+  }
+
   pub fn focus(&self) -> Result<()> {
     self.webview.grab_focus();
     Ok(())
@@ -805,6 +1697,10 @@ impl InnerWebView {
     Ok(())
   }
 
+  pub fn dispatcher(&self) -> MainThreadDispatcher {
+    MainThreadDispatcher
+  }
+
   fn cookie_from_soup_cookie(mut cookie: soup::Cookie) -> cookie::Cookie<'static> {
     let name = cookie.name().map(|n| n.to_string()).unwrap_or_default();
     let value = cookie.value().map(|n| n.to_string()).unwrap_or_default();
@@ -944,6 +1840,16 @@ pub fn platform_webview_version() -> Result<String> {
   Ok(format!("{major}.{minor}.{patch}"))
 }
 
+pub fn platform_engine_info() -> Result<EngineInfo> {
+  Ok(EngineInfo {
+    engine: Engine::WebKitGTK,
+    version: platform_webview_version()?,
+    runtime_channel: None,
+    runtime_path: None,
+    user_data_dir: None,
+  })
+}
+
 // SAFETY: only use this when you are sure the span will be dropped on the same thread it was entered
 #[cfg(feature = "tracing")]
 struct SendEnteredSpan(tracing::span::EnteredSpan);
@@ -4,14 +4,14 @@
 
 //! Unix platform extensions for [`WebContext`](super::WebContext).
 
-use crate::{Error, RequestAsyncResponder};
+use crate::{Error, Profile, RawHeaders, RequestAsyncResponder, ResponderFn};
 use gtk::glib::{self, MainContext, ObjectExt};
-use http::{header::CONTENT_TYPE, HeaderName, HeaderValue, Request, Response as HttpResponse};
+use http::{header::CONTENT_TYPE, HeaderName, HeaderValue, Request};
 use soup::{MessageHeaders, MessageHeadersType};
 use std::{
-  borrow::Cow,
   cell::RefCell,
-  collections::VecDeque,
+  collections::{HashMap, VecDeque},
+  fmt,
   path::{Path, PathBuf},
   rc::Rc,
   sync::{
@@ -26,10 +26,22 @@ use webkit2gtk::{
   WebView, WebViewExt,
 };
 
+/// The directory of the profiles of the contexts without a data directory, in the user data
+/// directory of the app.
+pub(crate) fn default_profiles_directory() -> PathBuf {
+  let app = glib::prgname().map_or_else(|| "wry".to_string(), |name| name.to_string());
+  glib::user_data_dir().join(app).join("profiles")
+}
+
+/// The key of the [`UriSchemeHandlers`] of a GTK context, shared by the [`WebContextImpl`]s using
+/// the context.
+const URI_SCHEME_HANDLERS: &str = "wry-uri-scheme-handlers";
+
 #[derive(Debug)]
 pub struct WebContextImpl {
   context: WebContext,
   webview_uri_loader: Rc<WebViewUriLoader>,
+  uri_scheme_handlers: Rc<UriSchemeHandlers>,
   automation: bool,
   app_info: Option<ApplicationInfo>,
 }
@@ -61,7 +73,41 @@ impl WebContextImpl {
     Self::create_context(context)
   }
 
+  /// The GTK context of `profile`, shared by its webviews while one of them is alive.
+  pub fn profile(profile: &Profile) -> Self {
+    thread_local! {
+      static PROFILES: RefCell<HashMap<Profile, glib::WeakRef<WebContext>>> =
+        RefCell::new(HashMap::new());
+    }
+
+    PROFILES.with(|profiles| {
+      let mut profiles = profiles.borrow_mut();
+      profiles.retain(|_, context| context.upgrade().is_some());
+      if let Some(context) = profiles.get(profile).and_then(|context| context.upgrade()) {
+        return Self::shared(context);
+      }
+
+      let context = match profile.data_directory() {
+        Some(data_directory) => Self::new(Some(data_directory)),
+        None => Self::new_ephemeral(),
+      };
+      profiles.insert(profile.clone(), context.context.downgrade());
+      context
+    })
+  }
+
   pub fn create_context(context: WebContext) -> Self {
+    // the schemes are registered once per GTK context
+    let uri_scheme_handlers =
+      match unsafe { context.data::<Rc<UriSchemeHandlers>>(URI_SCHEME_HANDLERS) } {
+        Some(handlers) => unsafe { handlers.as_ref() }.clone(),
+        None => {
+          let handlers = Rc::<UriSchemeHandlers>::default();
+          unsafe { context.set_data(URI_SCHEME_HANDLERS, handlers.clone()) };
+          handlers
+        }
+      };
+
     let automation = false;
     context.set_automation_allowed(automation);
 
@@ -84,14 +130,104 @@ impl WebContextImpl {
       context,
       automation,
       webview_uri_loader: Rc::default(),
+      uri_scheme_handlers,
       app_info: Some(app_info),
     }
   }
 
+  /// Use the GTK context of another webview, keeping whether it allows automation.
+  pub fn shared(context: WebContext) -> Self {
+    let automation = context.is_automation_allowed();
+    let mut shared = Self::create_context(context);
+    shared.set_allows_automation(automation);
+    shared
+  }
+
   pub fn set_allows_automation(&mut self, flag: bool) {
     self.automation = flag;
     self.context.set_automation_allowed(flag);
   }
+
+  /// The custom protocol handlers of the webviews in the context.
+  pub(crate) fn uri_scheme_handlers(&self) -> &Rc<UriSchemeHandlers> {
+    &self.uri_scheme_handlers
+  }
+
+  /// Register `name` on the GTK context the first time it is used, routing its requests with the
+  /// [`UriSchemeHandlers`].
+  fn register_scheme(&self, name: &str) -> crate::Result<()> {
+    if self.uri_scheme_handlers.contains(name) {
+      return Ok(());
+    }
+
+    // Enable secure context
+    self
+      .context
+      .security_manager()
+      .ok_or(Error::MissingManager)?
+      .register_uri_scheme_as_secure(name);
+
+    let handlers = self.uri_scheme_handlers.clone();
+    self
+      .context
+      .register_uri_scheme(name, move |request| handle_request(request, &handlers));
+
+    Ok(())
+  }
+}
+
+type UriSchemeHandler = dyn Fn(crate::WebViewId, Request<Vec<u8>>, RequestAsyncResponder);
+
+/// The handlers of a custom protocol.
+#[derive(Default)]
+struct SchemeHandlers {
+  /// The handler registered on the [`WebContext`](super::WebContext), used by all its webviews.
+  context: Option<Rc<UriSchemeHandler>>,
+  /// The handlers registered by the webviews, by webview id.
+  webviews: HashMap<String, Rc<UriSchemeHandler>>,
+}
+
+/// The custom protocol handlers of a context.
+///
+/// The schemes are registered once on the GTK context, which only accepts a handler per scheme,
+/// so the requests are routed to the handler of the webview making them by its id, falling back
+/// to the handler of the context. This lets several webviews of a context register the same
+/// scheme with their own handler, like on the other platforms.
+#[derive(Default)]
+pub(crate) struct UriSchemeHandlers {
+  schemes: RefCell<HashMap<String, SchemeHandlers>>,
+}
+
+impl fmt::Debug for UriSchemeHandlers {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_list()
+      .entries(self.schemes.borrow().keys())
+      .finish()
+  }
+}
+
+impl UriSchemeHandlers {
+  fn contains(&self, name: &str) -> bool {
+    self.schemes.borrow().contains_key(name)
+  }
+
+  /// The handler of the `name` requests made by the `webview_id` webview.
+  fn get(&self, name: &str, webview_id: &str) -> Option<Rc<UriSchemeHandler>> {
+    let schemes = self.schemes.borrow();
+    let handlers = schemes.get(name)?;
+    handlers
+      .webviews
+      .get(webview_id)
+      .or(handlers.context.as_ref())
+      .cloned()
+  }
+
+  /// Remove the handlers registered by the `webview_id` webview.
+  pub(crate) fn remove_webview(&self, webview_id: &str) {
+    for handlers in self.schemes.borrow_mut().values_mut() {
+      handlers.webviews.remove(webview_id);
+    }
+  }
 }
 
 /// [`WebContext`](super::WebContext) items that only matter on unix.
@@ -99,11 +235,22 @@ pub trait WebContextExt {
   /// The GTK [`WebContext`] of all webviews in the context.
   fn context(&self) -> &WebContext;
 
-  /// Register a custom protocol to the web context.
+  /// Register a custom protocol to the web context, used by all its webviews which don't register
+  /// their own handler for it.
   fn register_uri_scheme<F>(&mut self, name: &str, handler: F) -> crate::Result<()>
   where
     F: Fn(crate::WebViewId, Request<Vec<u8>>, RequestAsyncResponder) + 'static;
 
+  /// Register a custom protocol handling the requests of the `webview_id` webview only.
+  fn register_webview_uri_scheme<F>(
+    &mut self,
+    webview_id: &str,
+    name: &str,
+    handler: F,
+  ) -> crate::Result<()>
+  where
+    F: Fn(crate::WebViewId, Request<Vec<u8>>, RequestAsyncResponder) + 'static;
+
   /// Add a [`WebView`] to the queue waiting to be opened.
   ///
   /// See the [`WebViewUriLoader`] for more information.
@@ -137,132 +284,36 @@ impl WebContextExt for super::WebContext {
   where
     F: Fn(crate::WebViewId, Request<Vec<u8>>, RequestAsyncResponder) + 'static,
   {
-    // Enable secure context
+    self.os.register_scheme(name)?;
     self
       .os
-      .context
-      .security_manager()
-      .ok_or(Error::MissingManager)?
-      .register_uri_scheme_as_secure(name);
-
-    self.os.context.register_uri_scheme(name, move |request| {
-      #[cfg(feature = "tracing")]
-      let span = tracing::info_span!(parent: None, "wry::custom_protocol::handle", uri = tracing::field::Empty).entered();
-
-      if let Some(uri) = request.uri() {
-        let uri = uri.as_str();
-
-        #[cfg(feature = "tracing")]
-        span.record("uri", uri);
-
-        #[allow(unused_mut)]
-        let mut http_request = Request::builder().uri(uri).method("GET");
-
-        // Set request http headers
-        if let Some(headers) = request.http_headers() {
-          if let Some(map) = http_request.headers_mut() {
-            headers.foreach(move |k, v| {
-              if let Ok(name) = HeaderName::from_bytes(k.as_bytes()) {
-                if let Ok(value) = HeaderValue::from_bytes(v.as_bytes()) {
-                  map.insert(name, value);
-                }
-              }
-            });
-          }
-        }
-
-        // Set request http method
-        if let Some(method) = request.http_method() {
-          http_request = http_request.method(method.as_str());
-        }
-
-        let body;
-        #[cfg(feature = "linux-body")]
-        {
-          use gtk::{gdk::prelude::InputStreamExtManual, gio::Cancellable};
-
-          // Set request http body
-          let cancellable: Option<&Cancellable> = None;
-          body = request
-            .http_body()
-            .map(|s| {
-              const BUFFER_LEN: usize = 1024;
-              let mut result = Vec::new();
-              let mut buffer = vec![0; BUFFER_LEN];
-              while let Ok(count) = s.read(&mut buffer[..], cancellable) {
-                if count == BUFFER_LEN {
-                  result.append(&mut buffer);
-                  buffer.resize(BUFFER_LEN, 0);
-                } else {
-                  buffer.truncate(count);
-                  result.append(&mut buffer);
-                  break;
-                }
-              }
-              result
-            })
-            .unwrap_or_default();
-        }
-        #[cfg(not(feature = "linux-body"))]
-        {
-          body = Vec::new();
-        }
+      .uri_scheme_handlers
+      .schemes
+      .borrow_mut()
+      .entry(name.to_string())
+      .or_default()
+      .context = Some(Rc::new(handler));
 
-        let http_request = match http_request.body(body) {
-          Ok(req) => req,
-          Err(_) => {
-            request.finish_error(&mut gtk::glib::Error::new(
-              glib::UriError::Failed,
-              "Internal server error: could not create request.",
-            ));
-            return;
-          }
-        };
-
-        let request_ = MainThreadRequest(request.clone());
-        let responder: Box<dyn FnOnce(HttpResponse<Cow<'static, [u8]>>)> =
-          Box::new(move |http_response| {
-            MainContext::default().invoke(move || {
-              let buffer = http_response.body();
-              let input = gtk::gio::MemoryInputStream::from_bytes(&gtk::glib::Bytes::from(buffer));
-              let content_type = http_response
-                .headers()
-                .get(CONTENT_TYPE)
-                .and_then(|h| h.to_str().ok());
-
-              let response = URISchemeResponse::new(&input, buffer.len() as i64);
-              response.set_status(http_response.status().as_u16() as u32, None);
-              if let Some(content_type) = content_type {
-                response.set_content_type(content_type);
-              }
-
-              let headers = MessageHeaders::new(MessageHeadersType::Response);
-              for (name, value) in http_response.headers().into_iter() {
-                headers.append(name.as_str(), value.to_str().unwrap_or(""));
-              }
-              response.set_http_headers(headers);
-              request_.finish_with_response(&response);
-            });
-
-          });
-
-        #[cfg(feature = "tracing")]
-        let _span = tracing::info_span!("wry::custom_protocol::call_handler").entered();
-
-        let webview_id = request
-          .web_view()
-          .and_then(|w| unsafe { w.data::<String>(super::WEBVIEW_ID) })
-          .map(|id| unsafe { id.as_ref().clone() })
-          .unwrap_or_default();
+    Ok(())
+  }
 
-        handler(&webview_id, http_request, RequestAsyncResponder { responder });
-      } else {
-        request.finish_error(&mut glib::Error::new(
-          glib::FileError::Exist,
-          "Could not get uri.",
-        ));
-      }
-    });
+  fn register_webview_uri_scheme<F>(
+    &mut self,
+    webview_id: &str,
+    name: &str,
+    handler: F,
+  ) -> crate::Result<()>
+  where
+    F: Fn(crate::WebViewId, Request<Vec<u8>>, RequestAsyncResponder) + 'static,
+  {
+    self.os.register_scheme(name)?;
+    let mut schemes = self.os.uri_scheme_handlers.schemes.borrow_mut();
+    let webviews = &mut schemes.entry(name.to_string()).or_default().webviews;
+    // another webview of the context has the same id
+    if webviews.contains_key(webview_id) {
+      return Err(Error::DuplicateCustomProtocol(name.to_string()));
+    }
+    webviews.insert(webview_id.to_string(), Rc::new(handler));
 
     Ok(())
   }
@@ -355,6 +406,146 @@ impl WebContextExt for super::WebContext {
   }
 }
 
+/// Build the [`Request`] of a custom protocol request and pass it to its handler.
+fn handle_request(request: &URISchemeRequest, handlers: &UriSchemeHandlers) {
+  #[cfg(feature = "tracing")]
+  let span =
+    tracing::info_span!(parent: None, "wry::custom_protocol::handle", uri = tracing::field::Empty)
+      .entered();
+
+  let webview_id = request
+    .web_view()
+    .and_then(|w| unsafe { w.data::<String>(super::WEBVIEW_ID) })
+    .map(|id| unsafe { id.as_ref().clone() })
+    .unwrap_or_default();
+  let scheme = request.scheme().unwrap_or_default();
+  let Some(handler) = handlers.get(&scheme, &webview_id) else {
+    request.finish_error(&mut glib::Error::new(
+      glib::FileError::Noent,
+      "No handler for this custom protocol.",
+    ));
+    return;
+  };
+
+  if let Some(uri) = request.uri() {
+    let uri = uri.as_str();
+
+    #[cfg(feature = "tracing")]
+    span.record("uri", uri);
+
+    #[allow(unused_mut)]
+    let mut http_request = Request::builder().uri(uri).method("GET");
+
+    // Set request http headers, keeping the duplicates
+    let mut raw_headers = Vec::new();
+    if let Some(headers) = request.http_headers() {
+      headers.foreach(|k, v| raw_headers.push((k.to_string(), v.to_string())));
+    }
+    if let Some(map) = http_request.headers_mut() {
+      for (k, v) in &raw_headers {
+        if let (Ok(name), Ok(value)) = (
+          HeaderName::from_bytes(k.as_bytes()),
+          HeaderValue::from_bytes(v.as_bytes()),
+        ) {
+          map.append(name, value);
+        }
+      }
+    }
+    http_request = http_request.extension(RawHeaders(raw_headers));
+
+    // Set request http method
+    if let Some(method) = request.http_method() {
+      http_request = http_request.method(method.as_str());
+    }
+
+    let body;
+    #[cfg(feature = "linux-body")]
+    {
+      use gtk::{gdk::prelude::InputStreamExtManual, gio::Cancellable};
+
+      // Set request http body
+      let cancellable: Option<&Cancellable> = None;
+      body = request
+        .http_body()
+        .map(|s| {
+          const BUFFER_LEN: usize = 1024;
+          let mut result = Vec::new();
+          let mut buffer = vec![0; BUFFER_LEN];
+          while let Ok(count) = s.read(&mut buffer[..], cancellable) {
+            if count == BUFFER_LEN {
+              result.append(&mut buffer);
+              buffer.resize(BUFFER_LEN, 0);
+            } else {
+              buffer.truncate(count);
+              result.append(&mut buffer);
+              break;
+            }
+          }
+          result
+        })
+        .unwrap_or_default();
+    }
+    #[cfg(not(feature = "linux-body"))]
+    {
+      body = Vec::new();
+    }
+
+    let http_request = match http_request.body(body) {
+      Ok(req) => req,
+      Err(_) => {
+        request.finish_error(&mut gtk::glib::Error::new(
+          glib::UriError::Failed,
+          "Internal server error: could not create request.",
+        ));
+        return;
+      }
+    };
+
+    let request_ = MainThreadRequest(request.clone());
+    let responder: ResponderFn = Box::new(move |http_response| {
+      MainContext::default().invoke(move || {
+        let buffer = http_response.body();
+        let input = gtk::gio::MemoryInputStream::from_bytes(&gtk::glib::Bytes::from(buffer));
+        let content_type = http_response
+          .headers()
+          .get(CONTENT_TYPE)
+          .and_then(|h| h.to_str().ok());
+
+        let response = URISchemeResponse::new(&input, buffer.len() as i64);
+        response.set_status(http_response.status().as_u16() as u32, None);
+        if let Some(content_type) = content_type {
+          response.set_content_type(content_type);
+        }
+
+        let headers = MessageHeaders::new(MessageHeadersType::Response);
+        for (name, value) in http_response.headers().into_iter() {
+          headers.append(name.as_str(), value.to_str().unwrap_or(""));
+        }
+        response.set_http_headers(headers);
+        request_.finish_with_response(&response);
+      });
+      Ok(())
+    });
+
+    #[cfg(feature = "tracing")]
+    let _span = tracing::info_span!("wry::custom_protocol::call_handler").entered();
+
+    handler(
+      &webview_id,
+      http_request,
+      RequestAsyncResponder {
+        responder,
+        cancellation: Default::default(),
+      },
+    );
+  } else {
+    request.finish_error(&mut glib::Error::new(
+      glib::FileError::Exist,
+      "Could not get uri.",
+    ));
+  }
+}
+
 struct MainThreadRequest(URISchemeRequest);
 
 impl MainThreadRequest {
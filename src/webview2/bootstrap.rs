@@ -0,0 +1,206 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Installation of the WebView2 Runtime, see [`ensure_webview2_runtime`].
+
+use std::{
+  path::{Path, PathBuf},
+  process::Command,
+};
+
+use windows::{
+  core::{IUnknown, HSTRING, PCWSTR},
+  Win32::{
+    Foundation::{BOOL, HANDLE, HWND},
+    Security::{
+      Cryptography::{CertGetNameStringW, CERT_NAME_SIMPLE_DISPLAY_TYPE},
+      WinTrust::{
+        WTHelperGetProvSignerFromChain, WTHelperProvDataFromStateData, WinVerifyTrust,
+        WINTRUST_ACTION_GENERIC_VERIFY_V2, WINTRUST_DATA, WINTRUST_DATA_0, WINTRUST_FILE_INFO,
+        WTD_CHOICE_FILE, WTD_REVOKE_WHOLECHAIN, WTD_STATEACTION_CLOSE, WTD_STATEACTION_VERIFY,
+        WTD_UI_NONE,
+      },
+    },
+    System::Com::{IBindStatusCallback, Urlmon::URLDownloadToFileW},
+  },
+};
+
+use super::platform_webview_version;
+use crate::{util::random_token, Error, Result};
+
+/// The link to the Evergreen Bootstrapper, which downloads and installs the latest runtime.
+const BOOTSTRAPPER_URL: &str = "https://go.microsoft.com/fwlink/p/?LinkId=2124703";
+
+/// The signer of the installers of the WebView2 Runtime.
+const MICROSOFT_SIGNER: &str = "Microsoft Corporation";
+
+/// How [`ensure_webview2_runtime`] installs the WebView2 Runtime when it is missing.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstallMode {
+  /// Don't install the runtime, only fail with [`Error::WebView2RuntimeMissing`].
+  DetectOnly,
+  /// Download the Evergreen Bootstrapper from Microsoft and run it silently.
+  DownloadBootstrapper,
+  /// Run the Evergreen Bootstrapper shipped with the app, at this path, silently.
+  EmbeddedBootstrapper(PathBuf),
+  /// Run the Evergreen Standalone Installer shipped with the app, at this path, silently. It
+  /// doesn't need an internet connection.
+  OfflineInstaller(PathBuf),
+}
+
+/// Make sure the Evergreen WebView2 Runtime is installed, installing it with `mode` when it is
+/// missing, and return its version.
+///
+/// The installation blocks until the installer exits, which can take a few minutes. The runtime
+/// is installed for all the users when the app runs elevated, for the current user otherwise.
+///
+/// The installer only runs when its Authenticode signature is valid and made by Microsoft.
+///
+/// ```no_run
+/// use wry::windows::{ensure_webview2_runtime, InstallMode};
+///
+/// let version = ensure_webview2_runtime(InstallMode::DownloadBootstrapper).unwrap();
+/// println!("WebView2 Runtime {version}");
+/// ```
+pub fn ensure_webview2_runtime(mode: InstallMode) -> Result<String> {
+  if let Some(version) = installed_version() {
+    return Ok(version);
+  }
+
+  match mode {
+    InstallMode::DetectOnly => return Err(Error::WebView2RuntimeMissing),
+    InstallMode::DownloadBootstrapper => {
+      let dir = TempDir::new()?;
+      let path = dir.0.join("MicrosoftEdgeWebview2Setup.exe");
+      unsafe {
+        URLDownloadToFileW(
+          None::<&IUnknown>,
+          &HSTRING::from(BOOTSTRAPPER_URL),
+          &HSTRING::from(path.as_path()),
+          0,
+          None::<&IBindStatusCallback>,
+        )
+      }
+      .map_err(|e| Error::WebView2RuntimeInstallFailed(e.to_string()))?;
+      install(&path)?;
+    }
+    InstallMode::EmbeddedBootstrapper(path) | InstallMode::OfflineInstaller(path) => {
+      install(&path)?
+    }
+  }
+
+  installed_version()
+    .ok_or_else(|| Error::WebView2RuntimeInstallFailed("the runtime is still missing".to_string()))
+}
+
+/// Run `installer` silently once its signature is verified.
+fn install(installer: &Path) -> Result<()> {
+  verify_signature(installer)?;
+
+  let status = Command::new(installer)
+    .args(["/silent", "/install"])
+    .status()
+    .map_err(|e| Error::WebView2RuntimeInstallFailed(e.to_string()))?;
+  if !status.success() {
+    return Err(Error::WebView2RuntimeInstallFailed(format!(
+      "the installer exited with {status}"
+    )));
+  }
+
+  Ok(())
+}
+
+/// Check the Authenticode signature of `installer` is valid and made by [`MICROSOFT_SIGNER`].
+fn verify_signature(installer: &Path) -> Result<()> {
+  let path = HSTRING::from(installer);
+  let mut file = WINTRUST_FILE_INFO {
+    cbStruct: std::mem::size_of::<WINTRUST_FILE_INFO>() as u32,
+    pcwszFilePath: PCWSTR(path.as_ptr()),
+    ..Default::default()
+  };
+  let mut data = WINTRUST_DATA {
+    cbStruct: std::mem::size_of::<WINTRUST_DATA>() as u32,
+    dwUIChoice: WTD_UI_NONE,
+    fdwRevocationChecks: WTD_REVOKE_WHOLECHAIN,
+    dwUnionChoice: WTD_CHOICE_FILE,
+    Anonymous: WINTRUST_DATA_0 { pFile: &mut file },
+    dwStateAction: WTD_STATEACTION_VERIFY,
+    ..Default::default()
+  };
+  let mut action = WINTRUST_ACTION_GENERIC_VERIFY_V2;
+
+  let status = unsafe { WinVerifyTrust(HWND::default(), &mut action, &mut data as *mut _ as _) };
+  let signer = match status {
+    0 => unsafe { signer_name(data.hWVTStateData) },
+    _ => None,
+  };
+
+  // release the state kept by the verification
+  data.dwStateAction = WTD_STATEACTION_CLOSE;
+  unsafe { WinVerifyTrust(HWND::default(), &mut action, &mut data as *mut _ as _) };
+
+  if status != 0 {
+    return Err(Error::WebView2RuntimeInstallFailed(format!(
+      "the signature of the installer is invalid: {status:#x}"
+    )));
+  }
+  match signer {
+    Some(signer) if signer == MICROSOFT_SIGNER => Ok(()),
+    _ => Err(Error::WebView2RuntimeInstallFailed(
+      "the installer is not signed by Microsoft".to_string(),
+    )),
+  }
+}
+
+/// The name of the signer of the file verified with `state`.
+unsafe fn signer_name(state: HANDLE) -> Option<String> {
+  let provider = WTHelperProvDataFromStateData(state);
+  if provider.is_null() {
+    return None;
+  }
+  let signer = WTHelperGetProvSignerFromChain(provider, 0, BOOL::from(false), 0);
+  if signer.is_null() || (*signer).csCertChain == 0 {
+    return None;
+  }
+
+  let cert = (*(*signer).pasCertChain).pCert;
+  let mut name = [0; 256];
+  let len = CertGetNameStringW(
+    cert,
+    CERT_NAME_SIMPLE_DISPLAY_TYPE,
+    0,
+    None,
+    Some(&mut name),
+  );
+  // the length includes the null terminator
+  Some(String::from_utf16_lossy(
+    &name[..(len as usize).saturating_sub(1)],
+  ))
+}
+
+/// A new directory for the downloaded installer, removed with its content when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+  fn new() -> Result<Self> {
+    // a random name, so the directory can't be created by someone else beforehand
+    let path = std::env::temp_dir().join(format!("wry-webview2-{}", random_token()));
+    std::fs::create_dir(&path).map_err(|e| Error::WebView2RuntimeInstallFailed(e.to_string()))?;
+    Ok(Self(path))
+  }
+}
+
+impl Drop for TempDir {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_dir_all(&self.0);
+  }
+}
+
+/// The version of the installed runtime, if any.
+fn installed_version() -> Option<String> {
+  platform_webview_version()
+    .ok()
+    .filter(|version| !version.is_empty())
+}
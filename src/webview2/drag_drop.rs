@@ -168,7 +168,8 @@ impl IDropTarget_Impl for DragDropTarget_Impl {
     let mut paths = Vec::new();
     let hdrop = unsafe { DragDropTarget::iterate_filenames(pDataObj, |path| paths.push(path)) };
     (self.listener)(DragDropEvent::Enter {
-      paths,
+      paths: paths.clone(),
+      raw_paths: paths,
       position: (pt.x as _, pt.y as _),
     });
 
@@ -227,7 +228,8 @@ impl IDropTarget_Impl for DragDropTarget_Impl {
       let mut paths = Vec::new();
       let hdrop = unsafe { DragDropTarget::iterate_filenames(pDataObj, |path| paths.push(path)) };
       (self.listener)(DragDropEvent::Drop {
-        paths,
+        paths: paths.clone(),
+        raw_paths: paths,
         position: (pt.x as _, pt.y as _),
       });
 
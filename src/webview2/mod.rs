@@ -2,15 +2,26 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+#[cfg(feature = "webview2-bootstrap")]
+pub(crate) mod bootstrap;
 mod drag_drop;
 mod util;
 
 use std::{
-  borrow::Cow, cell::RefCell, collections::HashSet, fmt::Write, path::PathBuf, rc::Rc, sync::mpsc,
+  borrow::Cow,
+  cell::{Cell, RefCell},
+  collections::{HashMap, HashSet},
+  fmt::Write,
+  path::{Path, PathBuf},
+  rc::Rc,
+  sync::{
+    atomic::{AtomicU32, Ordering},
+    mpsc,
+  },
 };
 
 use dpi::{PhysicalPosition, PhysicalSize};
-use http::{Request, Response as HttpResponse, StatusCode};
+use http::{header::CONTENT_TYPE, Request, Response as HttpResponse, StatusCode};
 use once_cell::sync::Lazy;
 use raw_window_handle::{HasWindowHandle, RawWindowHandle};
 use webview2_com::{Microsoft::Web::WebView2::Win32::*, *};
@@ -20,21 +31,94 @@ use windows::{
     Foundation::*,
     Globalization::*,
     Graphics::Gdi::*,
-    System::{Com::*, LibraryLoader::GetModuleHandleW, WinRT::EventRegistrationToken},
-    UI::{Input::KeyboardAndMouse::SetFocus, Shell::*, WindowsAndMessaging::*},
+    System::{
+      Com::*,
+      LibraryLoader::GetModuleHandleW,
+      Power::{SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED},
+      ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS},
+      Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+      },
+      WinRT::EventRegistrationToken,
+    },
+    UI::{
+      Input::KeyboardAndMouse::{ReleaseCapture, SetFocus},
+      Shell::*,
+      WindowsAndMessaging::*,
+    },
   },
 };
 
 use self::drag_drop::DragDropController;
 use super::Theme;
 use crate::{
-  proxy::ProxyConfig, Error, MemoryUsageLevel, PageLoadEvent, Rect, RequestAsyncResponder, Result,
-  WebViewAttributes, RGBA,
+  escape_js_string, ipc_length_check_script, proxy::ProxyConfig, remove_user_style_sheet_script,
+  user_style_sheet_script, ApiCapabilities, CacheMode, DragRegion, Engine, EngineInfo, Error,
+  FileAccessPolicy, MemoryUsageLevel, NavigationState, PageLoadEvent, PermissionKind,
+  PermissionRequest, PermissionResponse, PersistentHeaders, PlatformWebViewHandle,
+  PrintCapabilities, PrintOptions, ProcessFailure, ProcessFailureReason, Rect,
+  RequestAsyncResponder, ResizeEdge, Result, VisibilityTracker, WebViewAttributes,
+  FETCH_WORLD_NAME, RGBA,
 };
 
 const PARENT_SUBCLASS_ID: u32 = WM_USER + 0x64;
 const PARENT_DESTROY_MESSAGE: u32 = WM_USER + 0x65;
 const MAIN_THREAD_DISPATCHER_SUBCLASS_ID: u32 = WM_USER + 0x66;
+const BOUNDS_SUBCLASS_ID: u32 = WM_USER + 0x67;
+
+thread_local! {
+  /// The reason phrases of the custom protocol responses, converted to wide strings once per status.
+  static REASON_PHRASES: RefCell<HashMap<u16, HSTRING>> = RefCell::new(HashMap::new());
+  /// The profiles of the webviews created with a [`crate::Profile`] on this thread, by lowercase
+  /// directory, see [`delete_profile`].
+  static PROFILES: RefCell<HashMap<String, ICoreWebView2Profile8>> = RefCell::new(HashMap::new());
+}
+
+/// The reason phrase of `status` as a wide string, reused across the responses.
+fn reason_phrase(status: StatusCode) -> HSTRING {
+  REASON_PHRASES.with(|phrases| {
+    phrases
+      .borrow_mut()
+      .entry(status.as_u16())
+      .or_insert_with(|| HSTRING::from(status.canonical_reason().unwrap_or("OK")))
+      .clone()
+  })
+}
+
+/// The minimum space read at once from the body streams of the custom protocol requests.
+const REQUEST_BODY_CHUNK_SIZE: usize = 16 * 1024;
+/// The maximum size of the scripts added with `AddScriptToExecuteOnDocumentCreated`, in bytes.
+const MAX_SCRIPT_SIZE: usize = 2 * 1024 * 1024;
+/// The number of characters of each part of a script larger than [`MAX_SCRIPT_SIZE`], so a part
+/// stays smaller than it even when every character is escaped.
+const SCRIPT_PART_LEN: usize = 128 * 1024;
+/// The global collecting the parts of a large initialization script until it is evaluated.
+const SCRIPT_PARTS_GLOBAL: &str = "__WRY_SCRIPT_PARTS__";
+/// The maximum size of the documents loaded with `NavigateToString`, in bytes.
+const MAX_HTML_SIZE: usize = 2 * 1024 * 1024;
+/// The URL prefix of the documents larger than [`MAX_HTML_SIZE`], served by the webview itself.
+const LARGE_HTML_URL: &str = "https://wry-html.localhost/";
+
+/// The URL and the content of the last document larger than [`MAX_HTML_SIZE`] loaded in a
+/// webview, see [`navigate_to_string`].
+type LargeHtml = Rc<RefCell<Option<(String, Vec<u8>)>>>;
+
+/// What a new webview does once its handlers are attached: register its scripts, then load its
+/// first page.
+struct PendingInit {
+  /// The initialization scripts, with the CSS of the ones injecting a user style sheet.
+  scripts: Vec<(String, Option<String>)>,
+  page: InitialPage,
+}
+
+/// The first page of a webview.
+enum InitialPage {
+  Url(String, Option<http::HeaderMap>),
+  Html(String),
+  None,
+}
+
 static EXEC_MSG_ID: Lazy<u32> = Lazy::new(|| unsafe { RegisterWindowMessageA(s!("Wry::ExecMsg")) });
 
 impl From<webview2_com::Error> for Error {
@@ -61,6 +145,60 @@ pub(crate) struct InnerWebView {
   // the webview gets dropped, otherwise we'll have a memory leak
   #[allow(dead_code)]
   drag_drop_controller: Option<DragDropController>,
+  /// Destinations of the downloads started with [`InnerWebView::download`], keyed by URL.
+  pending_downloads: Rc<RefCell<HashMap<String, PathBuf>>>,
+  /// Whether images are loaded, see [`InnerWebView::attach_images_blocker`].
+  images_enabled: Rc<Cell<bool>>,
+  /// The user style sheets and the id of the script injecting them.
+  user_style_sheets: RefCell<Vec<(String, String)>>,
+  visibility_tracker: Option<Rc<VisibilityTracker>>,
+  persistent_headers: Option<Rc<PersistentHeaders>>,
+  navigation_state: Rc<NavigationState>,
+  /// The top-level window subclassed to track the visibility of the webview.
+  visibility_root: Cell<HWND>,
+  large_html: LargeHtml,
+}
+
+/// The session of a webview, shared with its duplicates, see [`crate::WebView::duplicate`].
+#[derive(Clone)]
+pub(crate) struct Session {
+  env: ICoreWebView2Environment,
+  in_private: bool,
+  profile: Option<HSTRING>,
+}
+
+/// A [`Send`] handle to run closures on the thread of a webview.
+#[derive(Clone)]
+pub(crate) struct MainThreadDispatcher {
+  hwnd: isize,
+}
+
+impl MainThreadDispatcher {
+  pub fn dispatch(&self, f: Box<dyn FnOnce() + Send>) {
+    let function: Box<dyn FnMut()> = {
+      let mut f = Some(f);
+      Box::new(move || {
+        if let Some(f) = f.take() {
+          f();
+        }
+      })
+    };
+    // We double-box because the first box is a fat pointer.
+    let raw = Box::into_raw(Box::new(function));
+
+    let res = unsafe {
+      PostMessageW(
+        HWND(self.hwnd as _),
+        *EXEC_MSG_ID,
+        WPARAM(raw as _),
+        LPARAM(0),
+      )
+    };
+    // The webview window was destroyed, drop the closure.
+    if res.is_err() {
+      drop(unsafe { Box::from_raw(raw) });
+    }
+  }
 }
 
 impl Drop for InnerWebView {
@@ -70,6 +208,7 @@ impl Drop for InnerWebView {
       let _ = unsafe { DestroyWindow(self.hwnd) };
     }
     unsafe { Self::dettach_parent_subclass(*self.parent.borrow()) }
+    unsafe { self.dettach_visibility_subclass() };
   }
 }
 
@@ -101,32 +240,276 @@ impl InnerWebView {
     Self::new_in_hwnd(parent, attributes, pl_attrs, true)
   }
 
+  /// Create the webview without waiting for the WebView2 environment and controller, calling
+  /// `callback` from the event loop once they are created.
+  pub fn new_async(
+    window: &impl HasWindowHandle,
+    attributes: WebViewAttributes<'static>,
+    pl_attrs: super::PlatformSpecificWebViewAttributes,
+    callback: impl FnOnce(Result<Self>) + 'static,
+  ) -> Result<()> {
+    let parent = match window.window_handle()?.as_raw() {
+      RawWindowHandle::Win32(window) => HWND(window.hwnd.get() as _),
+      _ => return Err(Error::UnsupportedWindowHandle),
+    };
+
+    let _ = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) };
+
+    let hwnd = Self::create_container_hwnd(parent, &attributes, false)?;
+    unsafe { Self::attach_main_thread_dispatcher(hwnd) };
+
+    // The callback is called once, by the first handler that fails or by the controller handler
+    let callback: Rc<Cell<Option<Box<dyn FnOnce(Result<Self>)>>>> =
+      Rc::new(Cell::new(Some(Box::new(callback))));
+    let finish = move |result: Result<Self>| {
+      if result.is_err() {
+        let _ = unsafe { DestroyWindow(hwnd) };
+      }
+      if let Some(callback) = callback.take() {
+        callback(result);
+      }
+    };
+
+    let incognito = attributes.incognito || attributes.ephemeral_storage;
+    let profile = attributes.profile.as_ref().map(|p| HSTRING::from(p.name()));
+    let options = Self::environment_options(&attributes, pl_attrs.clone());
+    let finish_ = finish.clone();
+    let handler = CreateCoreWebView2EnvironmentCompletedHandler::create(Box::new(
+      move |error_code, environment| {
+        let env = match error_code.and_then(|_| environment.ok_or_else(|| E_POINTER.into())) {
+          Ok(env) => env,
+          Err(e) => {
+            finish_(Err(e.into()));
+            return Ok(());
+          }
+        };
+
+        if let Some(timer) = &pl_attrs.creation_timer {
+          timer.environment_created();
+        }
+
+        let env_ = env.clone();
+        let finish__ = finish_.clone();
+        let handler = CreateCoreWebView2ControllerCompletedHandler::create(Box::new(
+          move |error_code, controller| {
+            let result = error_code
+              .and_then(|_| controller.ok_or_else(|| E_POINTER.into()))
+              .map_err(Into::into)
+              .and_then(|controller| {
+                if let Some(timer) = &pl_attrs.creation_timer {
+                  timer.controller_created();
+                }
+                Self::new_with_controller(
+                  parent, hwnd, attributes, pl_attrs, false, env_, controller,
+                )
+              });
+            // the scripts are registered without pumping the messages in this callback
+            match result {
+              Ok((webview, init)) => webview.finish_init_async(init, finish__),
+              Err(e) => finish__(Err(e)),
+            }
+            Ok(())
+          },
+        ));
+
+        if let Err(e) =
+          Self::start_controller_creation(hwnd, &env, incognito, profile.as_ref(), &handler)
+        {
+          finish_(Err(e));
+        }
+        Ok(())
+      },
+    ));
+
+    let (data_directory, options) = options;
+    if let Err(e) = unsafe {
+      CreateCoreWebView2EnvironmentWithOptions(
+        PCWSTR::null(),
+        &data_directory.unwrap_or_default(),
+        &options,
+        &handler,
+      )
+    } {
+      finish(Err(e.into()));
+    }
+
+    Ok(())
+  }
+
   #[inline]
   fn new_in_hwnd(
     parent: HWND,
-    mut attributes: WebViewAttributes,
+    attributes: WebViewAttributes,
     pl_attrs: super::PlatformSpecificWebViewAttributes,
     is_child: bool,
   ) -> Result<Self> {
     let _ = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) };
 
     let hwnd = Self::create_container_hwnd(parent, &attributes, is_child)?;
+    unsafe { Self::attach_main_thread_dispatcher(hwnd) };
+
+    let result = Self::new_in_container(parent, hwnd, attributes, pl_attrs, is_child);
+    if result.is_err() {
+      let _ = unsafe { DestroyWindow(hwnd) };
+    }
+    result
+  }
+
+  fn new_in_container(
+    parent: HWND,
+    hwnd: HWND,
+    attributes: WebViewAttributes,
+    pl_attrs: super::PlatformSpecificWebViewAttributes,
+    is_child: bool,
+  ) -> Result<Self> {
+    // the duplicates of a webview use its environment and profile
+    let (env, incognito, profile) = match pl_attrs.session.clone() {
+      Some(session) => (session.env, session.in_private, session.profile),
+      None => (
+        Self::create_environment(&attributes, pl_attrs.clone())?,
+        attributes.incognito || attributes.ephemeral_storage,
+        attributes.profile.as_ref().map(|p| HSTRING::from(p.name())),
+      ),
+    };
+    if let Some(timer) = &pl_attrs.creation_timer {
+      timer.environment_created();
+    }
+    let controller = Self::create_controller(hwnd, &env, incognito, profile.as_ref())?;
+    if let Some(timer) = &pl_attrs.creation_timer {
+      timer.controller_created();
+    }
+
+    let (w, init) = Self::new_with_controller(
+      parent, hwnd, attributes, pl_attrs, is_child, env, controller,
+    )?;
+    for (js, css) in init.scripts {
+      let id = Self::add_script_to_execute_on_document_created(&w.webview, js)?;
+      if let Some(css) = css {
+        w.user_style_sheets.borrow_mut().push((css, id));
+      }
+    }
+    w.load_initial_page(init.page)?;
+    Ok(w)
+  }
+
+  /// Register the scripts of `init` without waiting for them, then load the first page and pass
+  /// the webview to `callback`.
+  fn finish_init_async(self, init: PendingInit, callback: impl FnOnce(Result<Self>) + 'static) {
+    let webview = self.webview.clone();
+    Self::add_scripts_async(
+      webview,
+      init.scripts.into_iter(),
+      Vec::new(),
+      Box::new(move |result| {
+        callback(result.and_then(|style_sheets| {
+          self.user_style_sheets.borrow_mut().extend(style_sheets);
+          self.load_initial_page(init.page)?;
+          Ok(self)
+        }))
+      }),
+    );
+  }
+
+  /// Register `scripts` one after the other, then call `done` with the CSS and the id of the
+  /// scripts injecting user style sheets.
+  fn add_scripts_async(
+    webview: ICoreWebView2,
+    mut scripts: std::vec::IntoIter<(String, Option<String>)>,
+    mut style_sheets: Vec<(String, String)>,
+    done: Box<dyn FnOnce(Result<Vec<(String, String)>>)>,
+  ) {
+    let Some((js, css)) = scripts.next() else {
+      done(Ok(style_sheets));
+      return;
+    };
+
+    // `done` is called by the handler, or here if the script can't be added
+    let done = Rc::new(Cell::new(Some(done)));
+    let done_ = done.clone();
+    let webview_ = webview.clone();
+    let handler = AddScriptToExecuteOnDocumentCreatedCompletedHandler::create(Box::new(
+      move |error_code, id| {
+        let Some(done) = done_.take() else {
+          return Ok(());
+        };
+        match error_code {
+          Ok(()) => {
+            if let Some(css) = css {
+              style_sheets.push((css, id));
+            }
+            Self::add_scripts_async(webview_, scripts, style_sheets, done);
+          }
+          Err(e) => done(Err(e.into())),
+        }
+        Ok(())
+      },
+    ));
+
+    let js = HSTRING::from(js);
+    if let Err(e) = unsafe { webview.AddScriptToExecuteOnDocumentCreated(&js, &handler) } {
+      if let Some(done) = done.take() {
+        done(Err(e.into()));
+      }
+    }
+  }
+
+  /// Load the first page of the webview, once its initialization scripts are registered.
+  fn load_initial_page(&self, page: InitialPage) -> Result<()> {
+    match page {
+      InitialPage::Url(url, Some(headers)) => {
+        load_url_with_headers(&self.webview, &self.env, &url, headers)
+      }
+      InitialPage::Url(url, None) => {
+        let url = HSTRING::from(url);
+        unsafe { self.webview.Navigate(&url) }.map_err(Into::into)
+      }
+      InitialPage::Html(html) => navigate_to_string(&self.webview, &self.large_html, &html),
+      InitialPage::None => Ok(()),
+    }
+  }
 
+  /// Initialize the webview once its environment and controller are created, returning the
+  /// scripts to register and the page to load.
+  fn new_with_controller(
+    parent: HWND,
+    hwnd: HWND,
+    mut attributes: WebViewAttributes,
+    pl_attrs: super::PlatformSpecificWebViewAttributes,
+    is_child: bool,
+    env: ICoreWebView2Environment,
+    controller: ICoreWebView2Controller,
+  ) -> Result<(Self, PendingInit)> {
     let drop_handler = attributes.drag_drop_handler.take();
+    let bounds_changed_handler = attributes.bounds_changed_handler.take();
     let bounds = attributes.bounds;
+    let visibility_tracker = attributes
+      .visibility_changed_handler
+      .take()
+      .map(|handler| Rc::new(VisibilityTracker::new(attributes.visible, handler)));
 
     let id = attributes
       .id
       .map(|id| id.to_string())
       .unwrap_or_else(|| (hwnd.0 as isize).to_string());
 
-    let env = Self::create_environment(&attributes, pl_attrs.clone())?;
-    let controller = Self::create_controller(hwnd, &env, attributes.incognito)?;
-    let webview = Self::init_webview(
+    let pending_downloads = Rc::new(RefCell::new(HashMap::new()));
+    let images_enabled = Rc::new(Cell::new(attributes.images_enabled));
+    let user_style_sheets = RefCell::new(Vec::new());
+    let persistent_headers = attributes
+      .persistent_headers
+      .then(|| Rc::new(PersistentHeaders::default()));
+    let navigation_state = Rc::new(NavigationState::new());
+    let large_html = LargeHtml::default();
+    let (webview, init) = Self::init_webview(
       parent,
       hwnd,
       id.clone(),
       attributes,
+      pending_downloads.clone(),
+      images_enabled.clone(),
+      persistent_headers.clone(),
+      navigation_state.clone(),
+      large_html.clone(),
       &env,
       &controller,
       pl_attrs,
@@ -144,15 +527,32 @@ impl InnerWebView {
       webview,
       env,
       drag_drop_controller,
+      pending_downloads,
+      images_enabled,
+      user_style_sheets,
+      visibility_tracker,
+      persistent_headers,
+      navigation_state,
+      visibility_root: Cell::new(HWND::default()),
+      large_html,
     };
 
+    if let Some(visibility_tracker) = &w.visibility_tracker {
+      unsafe { w.attach_visibility_subclass(parent, visibility_tracker.clone()) };
+    }
+
     if is_child {
       w.set_bounds(bounds.unwrap_or_default())?;
     } else {
       w.resize_to_parent()?;
     }
 
-    Ok(w)
+    // Attached after the initial bounds are set so the handler only sees the later changes
+    if let Some(handler) = bounds_changed_handler {
+      unsafe { Self::attach_bounds_subclass(hwnd, handler) };
+    }
+
+    Ok((w, init))
   }
 
   #[inline]
@@ -255,10 +655,44 @@ impl InnerWebView {
     attributes: &WebViewAttributes,
     pl_attrs: super::PlatformSpecificWebViewAttributes,
   ) -> Result<ICoreWebView2Environment> {
+    let (data_directory, options) = Self::environment_options(attributes, pl_attrs);
+
+    let (tx, rx) = mpsc::channel();
+    unsafe {
+      CreateCoreWebView2EnvironmentWithOptions(
+        PCWSTR::null(),
+        &data_directory.unwrap_or_default(),
+        &options,
+        // we don't use CreateCoreWebView2EnvironmentCompletedHandler::wait_for_async
+        // as it uses an mspc::channel under the hood, so we can avoid using two channels
+        // by manually creating the callback handler and use webview2_com::with_with_bump
+        &CreateCoreWebView2EnvironmentCompletedHandler::create(Box::new(
+          move |error_code, environment| {
+            error_code?;
+            tx.send(environment.ok_or_else(|| windows::core::Error::from(E_POINTER)))
+              .map_err(|_| windows::core::Error::from(E_UNEXPECTED))
+          },
+        )),
+      )?;
+    }
+
+    webview2_com::wait_with_pump(rx)?.map_err(Into::into)
+  }
+
+  /// The data directory and the options of the environment of a webview.
+  fn environment_options(
+    attributes: &WebViewAttributes,
+    pl_attrs: super::PlatformSpecificWebViewAttributes,
+  ) -> (Option<HSTRING>, ICoreWebView2EnvironmentOptions) {
     let data_directory = attributes
-      .context
+      .data_directory
       .as_deref()
-      .and_then(|context| context.data_directory())
+      .or_else(|| {
+        attributes
+          .context
+          .as_deref()
+          .and_then(|context| context.data_directory())
+      })
       .map(HSTRING::from);
 
     // additional browser args
@@ -272,6 +706,13 @@ impl InnerWebView {
         arguments.push_str(" --autoplay-policy=no-user-gesture-required");
       }
 
+      if matches!(
+        attributes.file_access,
+        FileAccessPolicy::AllowFiles | FileAccessPolicy::Universal
+      ) {
+        arguments.push_str(" --allow-file-access-from-files");
+      }
+
       if let Some(proxy_setting) = &attributes.proxy_config {
         match proxy_setting {
           ProxyConfig::Http(endpoint) => {
@@ -292,7 +733,6 @@ impl InnerWebView {
       arguments
     });
 
-    let (tx, rx) = mpsc::channel();
     let options = CoreWebView2EnvironmentOptions::default();
     unsafe {
       options.set_additional_browser_arguments(additional_browser_args);
@@ -310,25 +750,12 @@ impl InnerWebView {
       };
 
       options.set_scroll_bar_style(scroll_bar_style);
-
-      CreateCoreWebView2EnvironmentWithOptions(
-        PCWSTR::null(),
-        &data_directory.unwrap_or_default(),
-        &ICoreWebView2EnvironmentOptions::from(options),
-        // we don't use CreateCoreWebView2EnvironmentCompletedHandler::wait_for_async
-        // as it uses an mspc::channel under the hood, so we can avoid using two channels
-        // by manually creating the callback handler and use webview2_com::with_with_bump
-        &CreateCoreWebView2EnvironmentCompletedHandler::create(Box::new(
-          move |error_code, environment| {
-            error_code?;
-            tx.send(environment.ok_or_else(|| windows::core::Error::from(E_POINTER)))
-              .map_err(|_| windows::core::Error::from(E_UNEXPECTED))
-          },
-        )),
-      )?;
     }
 
-    webview2_com::wait_with_pump(rx)?.map_err(Into::into)
+    (
+      data_directory,
+      ICoreWebView2EnvironmentOptions::from(options),
+    )
   }
 
   #[inline]
@@ -336,10 +763,9 @@ impl InnerWebView {
     hwnd: HWND,
     env: &ICoreWebView2Environment,
     incognito: bool,
+    profile: Option<&HSTRING>,
   ) -> Result<ICoreWebView2Controller> {
     let (tx, rx) = mpsc::channel();
-    let env = env.clone();
-    let env10 = env.cast::<ICoreWebView2Environment10>();
 
     // we don't use CreateCoreWebView2ControllerCompletedHandler::wait_for_async
     // as it uses an mspc::channel under the hood, so we can avoid using two channels
@@ -351,31 +777,52 @@ impl InnerWebView {
           .map_err(|_| windows::core::Error::from(E_UNEXPECTED))
       },
     ));
+    Self::start_controller_creation(hwnd, env, incognito, profile, &handler)?;
+
+    webview2_com::wait_with_pump(rx)?.map_err(Into::into)
+  }
 
+  /// Start creating the controller of the webview, passed to `handler` once created.
+  #[inline]
+  fn start_controller_creation(
+    hwnd: HWND,
+    env: &ICoreWebView2Environment,
+    incognito: bool,
+    profile: Option<&HSTRING>,
+    handler: &ICoreWebView2CreateCoreWebView2ControllerCompletedHandler,
+  ) -> Result<()> {
     unsafe {
-      if let Ok(env10) = env10 {
+      if let Ok(env10) = env.cast::<ICoreWebView2Environment10>() {
         let controller_opts = env10.CreateCoreWebView2ControllerOptions()?;
         controller_opts.SetIsInPrivateModeEnabled(incognito)?;
-        env10.CreateCoreWebView2ControllerWithOptions(hwnd, &controller_opts, &handler)?;
+        if let Some(profile) = profile {
+          controller_opts.SetProfileName(profile)?;
+        }
+        env10.CreateCoreWebView2ControllerWithOptions(hwnd, &controller_opts, handler)?;
       } else {
-        env.CreateCoreWebView2Controller(hwnd, &handler)?
+        env.CreateCoreWebView2Controller(hwnd, handler)?
       }
     }
-
-    webview2_com::wait_with_pump(rx)?.map_err(Into::into)
+    Ok(())
   }
 
   #[inline]
+  #[allow(clippy::too_many_arguments)]
   fn init_webview(
     parent: HWND,
     hwnd: HWND,
     webview_id: String,
     mut attributes: WebViewAttributes,
+    pending_downloads: Rc<RefCell<HashMap<String, PathBuf>>>,
+    images_enabled: Rc<Cell<bool>>,
+    persistent_headers: Option<Rc<PersistentHeaders>>,
+    navigation_state: Rc<NavigationState>,
+    large_html: LargeHtml,
     env: &ICoreWebView2Environment,
     controller: &ICoreWebView2Controller,
     pl_attrs: super::PlatformSpecificWebViewAttributes,
     is_child: bool,
-  ) -> Result<ICoreWebView2> {
+  ) -> Result<(ICoreWebView2, PendingInit)> {
     let webview = unsafe { controller.CoreWebView2()? };
 
     // Theme
@@ -391,6 +838,11 @@ impl InnerWebView {
       }
     }
 
+    // Profile
+    if attributes.profile.is_some() {
+      unsafe { register_profile(&webview) };
+    }
+
     // Background color
     if let Some(background_color) = attributes.background_color {
       if !attributes.transparent {
@@ -412,12 +864,97 @@ impl InnerWebView {
     // Webview Settings
     unsafe { Self::set_webview_settings(&webview, &attributes, &pl_attrs)? };
 
+    // Keep the display awake while media is playing
+    if attributes.media_keeps_display_awake {
+      unsafe { Self::attach_display_awake_handler(&webview, &mut token)? };
+    }
+
+    // Forced dark content
+    if attributes.forced_dark_content {
+      unsafe {
+        webview.CallDevToolsProtocolMethod(
+          w!("Emulation.setAutoDarkModeOverride"),
+          w!(r#"{"enabled":true}"#),
+          &CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_, _| Ok(()))),
+        )?
+      };
+    }
+
+    // Cache mode
+    if attributes.cache_mode == CacheMode::NoCache {
+      // the cache is only bypassed while the network domain is enabled
+      unsafe {
+        webview.CallDevToolsProtocolMethod(
+          w!("Network.enable"),
+          w!("{}"),
+          &CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_, _| Ok(()))),
+        )?;
+        webview.CallDevToolsProtocolMethod(
+          w!("Network.setCacheDisabled"),
+          w!(r#"{"cacheDisabled":true}"#),
+          &CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_, _| Ok(()))),
+        )?;
+      };
+    }
+
+    // Virtual authenticator
+    if attributes.virtual_authenticator {
+      unsafe {
+        webview.CallDevToolsProtocolMethod(
+          w!("WebAuthn.enable"),
+          w!("{}"),
+          &CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_, _| Ok(()))),
+        )?;
+        webview.CallDevToolsProtocolMethod(
+          w!("WebAuthn.addVirtualAuthenticator"),
+          w!(
+            r#"{"options":{"protocol":"ctap2","transport":"internal","hasResidentKey":true,"hasUserVerification":true,"isUserVerified":true,"automaticPresenceSimulation":true}}"#
+          ),
+          &CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_, _| Ok(()))),
+        )?;
+      };
+    }
+
     // Webview handlers
-    unsafe { Self::attach_handlers(hwnd, &webview, &mut attributes, &mut token)? };
+    unsafe {
+      Self::attach_handlers(
+        hwnd,
+        &webview,
+        &mut attributes,
+        pending_downloads,
+        large_html.clone(),
+        &mut token,
+      )?
+    };
+
+    // The scripts are registered once the handlers are attached
+    let mut scripts = vec![(
+      format!(
+        r#"Object.defineProperty(window, 'ipc', {{ value: Object.freeze({{ postMessage: message => {{ {} window.chrome.webview.postMessage(message) }} }}) }});"#,
+        ipc_length_check_script(attributes.ipc_limits)
+      ),
+      None,
+    )];
 
     // IPC handler
     unsafe { Self::attach_ipc_handler(&webview, &mut attributes, &mut token)? };
 
+    // Images
+    unsafe { Self::attach_images_blocker(&webview, env, images_enabled.clone(), &mut token)? };
+
+    // Persistent headers
+    if let Some(persistent_headers) = &persistent_headers {
+      unsafe {
+        Self::attach_persistent_headers_handler(&webview, persistent_headers.clone(), &mut token)?
+      };
+    }
+
+    // Navigation state
+    unsafe { Self::attach_navigation_state_handler(&webview, navigation_state, &mut token)? };
+
+    // Documents too large for NavigateToString
+    unsafe { Self::attach_large_html_handler(&webview, env, large_html.clone(), &mut token)? };
+
     // Custom protocols handler
     let scheme = if pl_attrs.use_https { "https" } else { "http" };
     let custom_protocols: HashSet<String> = attributes
@@ -433,6 +970,7 @@ impl InnerWebView {
           hwnd,
           webview_id,
           scheme,
+          images_enabled,
           &mut attributes,
           &mut token,
         )?
@@ -440,8 +978,21 @@ impl InnerWebView {
     }
 
     // Initialize scripts
+    let allows_eval = match &attributes.content_security_policy {
+      Some(policy) => csp_allows_eval(policy),
+      None => true,
+    };
     for js in attributes.initialization_scripts {
-      Self::add_script_to_execute_on_document_created(&webview, js)?;
+      let split = split_initialization_script(js);
+      if split.len() > 1 && !allows_eval {
+        return Err(Error::InitScriptTooLarge);
+      }
+      scripts.extend(split.into_iter().map(|js| (js, None)));
+    }
+
+    // User style sheets
+    for (css, main_frame_only) in attributes.user_style_sheets {
+      scripts.push((user_style_sheet_script(&css, main_frame_only), Some(css)));
     }
 
     // Enable clipboard
@@ -464,8 +1015,43 @@ impl InnerWebView {
       }
     }
 
-    // Navigation
-    if let Some(mut url) = attributes.url {
+    // Display capture permission, WebView2 shows its picker unless the capture is cancelled.
+    // Without a handler, WebView2 keeps its own behavior.
+    if let Some(permission_handler) = attributes.permission_handler.take() {
+      if let Ok(webview) = webview.cast::<ICoreWebView2_27>() {
+        unsafe {
+          webview.add_ScreenCaptureStarting(
+            &ScreenCaptureStartingEventHandler::create(Box::new(move |webview, args| {
+              let (Some(webview), Some(args)) = (webview, args) else {
+                return Ok(());
+              };
+
+              let url = {
+                let mut uri = PWSTR::null();
+                webview.Source(&mut uri)?;
+                take_pwstr(uri)
+              };
+              let response = permission_handler(PermissionRequest {
+                url,
+                kind: PermissionKind::DisplayCapture,
+              });
+              if !matches!(
+                response,
+                PermissionResponse::Allow | PermissionResponse::AllowDisplayCapture(_)
+              ) {
+                args.SetCancel(true)?;
+              }
+
+              Ok(())
+            })),
+            &mut token,
+          )?;
+        }
+      }
+    }
+
+    // Navigation, once the scripts are registered
+    let page = if let Some(mut url) = attributes.url {
       if let Some(pos) = url.find("://") {
         let name = &url[..pos];
         if custom_protocols.contains(name) {
@@ -475,16 +1061,16 @@ impl InnerWebView {
         }
       }
 
-      if let Some(headers) = attributes.headers {
-        load_url_with_headers(&webview, env, &url, headers)?;
-      } else {
-        let url = HSTRING::from(url);
-        unsafe { webview.Navigate(&url)? };
+      if let Some(persistent_headers) = &persistent_headers {
+        persistent_headers.set(&url, attributes.headers.clone());
       }
+
+      InitialPage::Url(url, attributes.headers)
     } else if let Some(html) = attributes.html {
-      let html = HSTRING::from(html);
-      unsafe { webview.NavigateToString(&html)? };
-    }
+      InitialPage::Html(html)
+    } else {
+      InitialPage::None
+    };
 
     // Subclass parent for resizing and focus
     if !is_child {
@@ -499,7 +1085,7 @@ impl InnerWebView {
       }
     }
 
-    Ok(webview)
+    Ok((webview, PendingInit { scripts, page }))
   }
 
   #[inline]
@@ -508,33 +1094,49 @@ impl InnerWebView {
     attributes: &WebViewAttributes,
     pl_attrs: &super::PlatformSpecificWebViewAttributes,
   ) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::info_span!("wry::webview2::settings").entered();
+
     let settings = webview.Settings()?;
     settings.SetIsStatusBarEnabled(false)?;
     settings.SetAreDefaultContextMenusEnabled(true)?;
     settings.SetIsZoomControlEnabled(attributes.zoom_hotkeys_enabled)?;
     settings.SetAreDevToolsEnabled(attributes.devtools)?;
+    settings.SetIsScriptEnabled(attributes.javascript_enabled)?;
+
+    // Each settings interface extends the previous versions, so on the runtimes supporting the
+    // newest one, a single cast gives the methods of all of them instead of a cast per version.
+    let settings9 = settings.cast::<ICoreWebView2Settings9>().ok();
+    macro_rules! settings_version {
+      ($interface:ty) => {
+        match &settings9 {
+          Some(settings9) => Some(<$interface>::clone(settings9)),
+          None => settings.cast::<$interface>().ok(),
+        }
+      };
+    }
 
     if let Some(user_agent) = &attributes.user_agent {
-      if let Ok(settings2) = settings.cast::<ICoreWebView2Settings2>() {
+      if let Some(settings2) = settings_version!(ICoreWebView2Settings2) {
         settings2.SetUserAgent(&HSTRING::from(user_agent))?;
       }
     }
 
     if !pl_attrs.browser_accelerator_keys {
-      if let Ok(settings3) = settings.cast::<ICoreWebView2Settings3>() {
+      if let Some(settings3) = settings_version!(ICoreWebView2Settings3) {
         settings3.SetAreBrowserAcceleratorKeysEnabled(false)?;
       }
     }
 
-    if let Ok(settings5) = settings.cast::<ICoreWebView2Settings5>() {
+    if let Some(settings5) = settings_version!(ICoreWebView2Settings5) {
       settings5.SetIsPinchZoomEnabled(attributes.zoom_hotkeys_enabled)?;
     }
 
-    if let Ok(settings6) = settings.cast::<ICoreWebView2Settings6>() {
+    if let Some(settings6) = settings_version!(ICoreWebView2Settings6) {
       settings6.SetIsSwipeNavigationEnabled(attributes.back_forward_navigation_gestures)?;
     }
 
-    if let Ok(settings9) = settings.cast::<ICoreWebView2Settings9>() {
+    if let Some(settings9) = &settings9 {
       settings9.SetIsNonClientRegionSupportEnabled(true)?;
     }
 
@@ -546,29 +1148,100 @@ impl InnerWebView {
     hwnd: HWND,
     webview: &ICoreWebView2,
     attributes: &mut WebViewAttributes,
+    pending_downloads: Rc<RefCell<HashMap<String, PathBuf>>>,
+    large_html: LargeHtml,
     token: &mut EventRegistrationToken,
   ) -> Result<()> {
-    // Close container HWND when `window.close` is called in JS
+    // Close container HWND when `window.close` is called in JS, unless the handler keeps it
+    let close_requested_handler = attributes.close_requested_handler.take();
     webview.add_WindowCloseRequested(
-      &WindowCloseRequestedEventHandler::create(Box::new(move |_, _| DestroyWindow(hwnd))),
+      &WindowCloseRequestedEventHandler::create(Box::new(move |_, _| {
+        if close_requested_handler
+          .as_ref()
+          .map_or(true, |handler| handler())
+        {
+          DestroyWindow(hwnd)?;
+        }
+        Ok(())
+      })),
       token,
     )?;
 
-    // Document title changed handler
-    if let Some(document_title_changed_handler) = attributes.document_title_changed_handler.take() {
-      webview.add_DocumentTitleChanged(
-        &DocumentTitleChangedEventHandler::create(Box::new(move |webview, _| {
-          let Some(webview) = webview else {
+    // Process failed handler
+    if let Some(process_failed_handler) = attributes.process_failed_handler.take() {
+      let crash_dumps_dir = webview
+        .cast::<ICoreWebView2_2>()
+        .and_then(|webview| webview.Environment())
+        .and_then(|env| env.cast::<ICoreWebView2Environment11>())
+        .and_then(|env| {
+          let mut path = PWSTR::null();
+          env.FailureReportFolderPath(&mut path)?;
+          Ok(PathBuf::from(take_pwstr(path)))
+        })
+        .ok();
+
+      webview.add_ProcessFailed(
+        &ProcessFailedEventHandler::create(Box::new(move |_, args| {
+          let Some(args) = args else {
             return Ok(());
           };
 
-          let title = {
-            let mut title = PWSTR::null();
-            webview.DocumentTitle(&mut title)?;
-            take_pwstr(title)
+          let mut kind = COREWEBVIEW2_PROCESS_FAILED_KIND::default();
+          args.ProcessFailedKind(&mut kind)?;
+
+          let mut failure = ProcessFailure {
+            reason: if kind == COREWEBVIEW2_PROCESS_FAILED_KIND_RENDER_PROCESS_UNRESPONSIVE {
+              ProcessFailureReason::Unresponsive
+            } else {
+              ProcessFailureReason::Unknown
+            },
+            exit_code: None,
+            description: None,
+            crash_dumps_dir: crash_dumps_dir.clone(),
           };
 
-          document_title_changed_handler(title);
+          if let Ok(args) = args.cast::<ICoreWebView2ProcessFailedEventArgs2>() {
+            let mut reason = COREWEBVIEW2_PROCESS_FAILED_REASON::default();
+            args.Reason(&mut reason)?;
+            failure.reason = match reason {
+              COREWEBVIEW2_PROCESS_FAILED_REASON_CRASHED => ProcessFailureReason::Crashed,
+              COREWEBVIEW2_PROCESS_FAILED_REASON_OUT_OF_MEMORY => ProcessFailureReason::OutOfMemory,
+              COREWEBVIEW2_PROCESS_FAILED_REASON_UNRESPONSIVE => ProcessFailureReason::Unresponsive,
+              COREWEBVIEW2_PROCESS_FAILED_REASON_TERMINATED => ProcessFailureReason::Terminated,
+              _ => ProcessFailureReason::Unknown,
+            };
+
+            let mut exit_code = 0;
+            args.ExitCode(&mut exit_code)?;
+            failure.exit_code = Some(exit_code);
+
+            let mut description = PWSTR::null();
+            args.ProcessDescription(&mut description)?;
+            failure.description = Some(take_pwstr(description)).filter(|d| !d.is_empty());
+          }
+
+          process_failed_handler(failure);
+          Ok(())
+        })),
+        token,
+      )?;
+    }
+
+    // Document title changed handler
+    if let Some(document_title_changed_handler) = attributes.document_title_changed_handler.take() {
+      webview.add_DocumentTitleChanged(
+        &DocumentTitleChangedEventHandler::create(Box::new(move |webview, _| {
+          let Some(webview) = webview else {
+            return Ok(());
+          };
+
+          let title = {
+            let mut title = PWSTR::null();
+            webview.DocumentTitle(&mut title)?;
+            take_pwstr(title)
+          };
+
+          document_title_changed_handler(title);
           Ok(())
         })),
         token,
@@ -619,6 +1292,12 @@ impl InnerWebView {
             take_pwstr(uri)
           };
 
+          // the document loaded with `load_html`
+          let is_large_html = matches!(&*large_html.borrow(), Some((url, _)) if *url == uri);
+          if is_large_html {
+            return Ok(());
+          }
+
           let allow = nav_callback(uri);
           args.SetCancel(!allow)?;
 
@@ -651,9 +1330,7 @@ impl InnerWebView {
       )?;
     }
 
-    // Download handler
-    if attributes.download_started_handler.is_some()
-      || attributes.download_completed_handler.is_some()
+    // Download handler, always registered as it is also used by `download`
     {
       let mut download_started_handler = attributes.download_started_handler.take();
       let download_completed_handler = attributes.download_completed_handler.take();
@@ -709,12 +1386,17 @@ impl InnerWebView {
             )?;
           }
 
+          let destination = pending_downloads.borrow_mut().remove(&uri);
+
           if let Some(download_started_handler) = &mut download_started_handler {
-            let mut path = {
-              let mut path = PWSTR::null();
-              args.ResultFilePath(&mut path)?;
-              let path = take_pwstr(path);
-              PathBuf::from(&path)
+            let mut path = match destination {
+              Some(destination) => destination,
+              None => {
+                let mut path = PWSTR::null();
+                args.ResultFilePath(&mut path)?;
+                let path = take_pwstr(path);
+                PathBuf::from(&path)
+              }
             };
 
             if download_started_handler(uri, &mut path) {
@@ -725,6 +1407,11 @@ impl InnerWebView {
             } else {
               args.SetCancel(true)?;
             }
+          } else if let Some(destination) = destination {
+            let simplified = dunce::simplified(&destination);
+            let path = HSTRING::from(simplified);
+            args.SetResultFilePath(&path)?;
+            args.SetHandled(true)?;
           }
 
           Ok(())
@@ -742,14 +1429,8 @@ impl InnerWebView {
     attributes: &mut WebViewAttributes,
     token: &mut EventRegistrationToken,
   ) -> Result<()> {
-    Self::add_script_to_execute_on_document_created(
-      webview,
-      String::from(
-        r#"Object.defineProperty(window, 'ipc', { value: Object.freeze({ postMessage: s=> window.chrome.webview.postMessage(s) }) });"#,
-      ),
-    )?;
-
     let ipc_handler = attributes.ipc_handler.take();
+
     webview.add_WebMessageReceived(
       &WebMessageReceivedEventHandler::create(Box::new(move |_, args| {
         let (Some(args), Some(ipc_handler)) = (args, &ipc_handler) else {
@@ -780,6 +1461,39 @@ impl InnerWebView {
     Ok(())
   }
 
+  /// Move or resize the top-level window of the webview with the mouse, for the drag regions.
+  pub fn drag_window(&self, region: DragRegion) {
+    let hit_test = match region {
+      DragRegion::Move => HTCAPTION,
+      DragRegion::Resize(edge) => match edge {
+        ResizeEdge::North => HTTOP,
+        ResizeEdge::NorthEast => HTTOPRIGHT,
+        ResizeEdge::East => HTRIGHT,
+        ResizeEdge::SouthEast => HTBOTTOMRIGHT,
+        ResizeEdge::South => HTBOTTOM,
+        ResizeEdge::SouthWest => HTBOTTOMLEFT,
+        ResizeEdge::West => HTLEFT,
+        ResizeEdge::NorthWest => HTTOPLEFT,
+      },
+    };
+
+    unsafe {
+      let root = GetAncestor(self.hwnd, GA_ROOT);
+      let mut point = POINT::default();
+      let _ = GetCursorPos(&mut point);
+      let lparam = ((point.y & 0xFFFF) << 16) | (point.x & 0xFFFF);
+
+      // The mouse is captured by the webview, release it so the window handles the drag
+      let _ = ReleaseCapture();
+      let _ = PostMessageW(
+        root,
+        WM_NCLBUTTONDOWN,
+        WPARAM(hit_test as usize),
+        LPARAM(lparam as isize),
+      );
+    }
+  }
+
   #[inline]
   unsafe fn attach_custom_protocol_handler(
     webview: &ICoreWebView2,
@@ -787,6 +1501,7 @@ impl InnerWebView {
     hwnd: HWND,
     webview_id: String,
     scheme: &'static str,
+    images_enabled: Rc<Cell<bool>>,
     attributes: &mut WebViewAttributes,
     token: &mut EventRegistrationToken,
   ) -> Result<()> {
@@ -807,6 +1522,11 @@ impl InnerWebView {
           return Ok(());
         };
 
+        // Responded by the images blocker
+        if Self::is_blocked_image(&args, &images_enabled)? {
+          return Ok(());
+        }
+
         #[cfg(feature = "tracing")]
         let span = tracing::info_span!(parent: None, "wry::custom_protocol::handle", uri = tracing::field::Empty)
           .entered();
@@ -823,11 +1543,14 @@ impl InnerWebView {
         #[cfg(feature = "tracing")]
         span.record("uri", &uri);
 
-        if let Some((custom_protocol, custom_protocol_handler)) = custom_protocols
-          .iter()
-          .find(|(protocol, _)| is_custom_protocol_uri(&uri, scheme, protocol))
+        if let Some((custom_protocol, custom_protocol_handler, path)) =
+          custom_protocols.iter().find_map(|(protocol, handler)| {
+            custom_protocol_path(&uri, scheme, protocol).map(|path| (protocol, handler, path))
+          })
         {
-          let request = match Self::prepare_request(scheme, custom_protocol, &webview_request, &uri)
+          #[cfg(feature = "tracing")]
+          let prepare_span = tracing::info_span!("wry::custom_protocol::prepare_request").entered();
+          let request = match Self::prepare_request(custom_protocol, &webview_request, path)
           {
             Ok(req) => req,
             Err(e) => {
@@ -836,11 +1559,13 @@ impl InnerWebView {
               return Ok(());
             }
           };
+          #[cfg(feature = "tracing")]
+          drop(prepare_span);
 
           let env = env.clone();
           let deferral = args.GetDeferral();
 
-          let async_responder = Box::new(move |sent_response| {
+          let async_responder = Box::new(move |sent_response| -> Result<()> {
             let handler = move || {
               match Self::prepare_web_request_response(&env, &sent_response) {
                 Ok(response) => {
@@ -863,6 +1588,7 @@ impl InnerWebView {
             } else {
               Self::dispatch_handler(hwnd, handler);
             }
+            Ok(())
           });
 
           #[cfg(feature = "tracing")]
@@ -872,6 +1598,7 @@ impl InnerWebView {
             request,
             RequestAsyncResponder {
               responder: async_responder,
+              cancellation: Default::default(),
             },
           );
         }
@@ -881,17 +1608,224 @@ impl InnerWebView {
       token,
     )?;
 
-    Self::attach_main_thread_dispatcher(hwnd);
+    Ok(())
+  }
+
+  /// Prevent the display from sleeping while the document is playing audio.
+  #[inline]
+  unsafe fn attach_display_awake_handler(
+    webview: &ICoreWebView2,
+    token: &mut EventRegistrationToken,
+  ) -> Result<()> {
+    let Ok(webview) = webview.cast::<ICoreWebView2_8>() else {
+      return Ok(());
+    };
+
+    webview.add_IsDocumentPlayingAudioChanged(
+      &IsDocumentPlayingAudioChangedEventHandler::create(Box::new(|webview, _| {
+        let Some(webview) = webview else {
+          return Ok(());
+        };
+
+        let mut playing = BOOL::default();
+        webview
+          .cast::<ICoreWebView2_8>()?
+          .IsDocumentPlayingAudio(&mut playing)?;
+
+        let state = if playing.as_bool() {
+          ES_CONTINUOUS | ES_DISPLAY_REQUIRED
+        } else {
+          ES_CONTINUOUS
+        };
+        SetThreadExecutionState(state);
+
+        Ok(())
+      })),
+      token,
+    )?;
+
+    Ok(())
+  }
+
+  /// Respond to the image requests with a `403 Forbidden` response when images are disabled.
+  ///
+  /// WebView2 doesn't have a setting to disable images, so the image requests are intercepted
+  /// with a filter that is only added while images are disabled.
+  #[inline]
+  unsafe fn attach_images_blocker(
+    webview: &ICoreWebView2,
+    env: &ICoreWebView2Environment,
+    images_enabled: Rc<Cell<bool>>,
+    token: &mut EventRegistrationToken,
+  ) -> Result<()> {
+    if !images_enabled.get() {
+      webview.AddWebResourceRequestedFilter(w!("*"), COREWEBVIEW2_WEB_RESOURCE_CONTEXT_IMAGE)?;
+    }
+
+    let env = env.clone();
+    webview.add_WebResourceRequested(
+      &WebResourceRequestedEventHandler::create(Box::new(move |_, args| {
+        let Some(args) = args else {
+          return Ok(());
+        };
+
+        if Self::is_blocked_image(&args, &images_enabled)? {
+          let status = StatusCode::FORBIDDEN;
+          let reason = HSTRING::from(status.canonical_reason().unwrap_or("Forbidden"));
+          let response =
+            env.CreateWebResourceResponse(None, status.as_u16() as i32, &reason, w!(""))?;
+          args.SetResponse(&response)?;
+        }
+
+        Ok(())
+      })),
+      token,
+    )?;
+
+    Ok(())
+  }
+
+  /// Add the [`PersistentHeaders`] to the document requests.
+  unsafe fn attach_persistent_headers_handler(
+    webview: &ICoreWebView2,
+    persistent_headers: Rc<PersistentHeaders>,
+    token: &mut EventRegistrationToken,
+  ) -> Result<()> {
+    webview.AddWebResourceRequestedFilter(w!("*"), COREWEBVIEW2_WEB_RESOURCE_CONTEXT_DOCUMENT)?;
+
+    webview.add_WebResourceRequested(
+      &WebResourceRequestedEventHandler::create(Box::new(move |_, args| {
+        let Some(args) = args else {
+          return Ok(());
+        };
+
+        let request = args.Request()?;
+        let uri = {
+          let mut uri = PWSTR::null();
+          request.Uri(&mut uri)?;
+          take_pwstr(uri)
+        };
+
+        if let Some(headers) = persistent_headers.get(&uri) {
+          let request_headers = request.Headers()?;
+          for (name, value) in headers.iter() {
+            if let Ok(value) = value.to_str() {
+              request_headers.SetHeader(&HSTRING::from(name.as_str()), &HSTRING::from(value))?;
+            }
+          }
+        }
+
+        Ok(())
+      })),
+      token,
+    )?;
+
+    Ok(())
+  }
+
+  /// Serve the documents loaded by [`navigate_to_string`] from [`LARGE_HTML_URL`], the other URLs
+  /// of its origin are not found.
+  unsafe fn attach_large_html_handler(
+    webview: &ICoreWebView2,
+    env: &ICoreWebView2Environment,
+    large_html: LargeHtml,
+    token: &mut EventRegistrationToken,
+  ) -> Result<()> {
+    let filter = HSTRING::from(format!("{LARGE_HTML_URL}*"));
+    webview.AddWebResourceRequestedFilter(&filter, COREWEBVIEW2_WEB_RESOURCE_CONTEXT_DOCUMENT)?;
+
+    let env = env.clone();
+    webview.add_WebResourceRequested(
+      &WebResourceRequestedEventHandler::create(Box::new(move |_, args| {
+        let Some(args) = args else {
+          return Ok(());
+        };
+
+        let uri = {
+          let mut uri = PWSTR::null();
+          args.Request()?.Uri(&mut uri)?;
+          take_pwstr(uri)
+        };
+
+        let response = match &*large_html.borrow() {
+          Some((url, html)) if *url == uri => HttpResponse::builder()
+            .header(CONTENT_TYPE, "text/html; charset=utf-8")
+            .body(Cow::Owned(html.clone()))
+            .unwrap(),
+          _ => HttpResponse::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Cow::Borrowed(&[][..]))
+            .unwrap(),
+        };
+        args.SetResponse(&Self::prepare_web_request_response(&env, &response)?)?;
+
+        Ok(())
+      })),
+      token,
+    )?;
+
+    Ok(())
+  }
+
+  /// Keep the [`NavigationState`] in sync with the navigation events.
+  unsafe fn attach_navigation_state_handler(
+    webview: &ICoreWebView2,
+    navigation_state: Rc<NavigationState>,
+    token: &mut EventRegistrationToken,
+  ) -> Result<()> {
+    let navigation_state_ = navigation_state.clone();
+    webview.add_NavigationStarting(
+      &NavigationStartingEventHandler::create(Box::new(move |_, args| {
+        let Some(args) = args else {
+          return Ok(());
+        };
+
+        let mut navigation_id = 0;
+        args.NavigationId(&mut navigation_id)?;
+        navigation_state_.start(Some(navigation_id));
+
+        Ok(())
+      })),
+      token,
+    )?;
+
+    webview.add_NavigationCompleted(
+      &NavigationCompletedEventHandler::create(Box::new(move |_, args| {
+        let Some(args) = args else {
+          return Ok(());
+        };
+
+        let mut navigation_id = 0;
+        args.NavigationId(&mut navigation_id)?;
+        navigation_state.finish(Some(navigation_id));
+
+        Ok(())
+      })),
+      token,
+    )?;
 
     Ok(())
   }
 
+  #[inline]
+  unsafe fn is_blocked_image(
+    args: &ICoreWebView2WebResourceRequestedEventArgs,
+    images_enabled: &Cell<bool>,
+  ) -> windows::core::Result<bool> {
+    if images_enabled.get() {
+      return Ok(false);
+    }
+
+    let mut context = COREWEBVIEW2_WEB_RESOURCE_CONTEXT::default();
+    args.ResourceContext(&mut context)?;
+    Ok(context == COREWEBVIEW2_WEB_RESOURCE_CONTEXT_IMAGE)
+  }
+
   #[inline]
   unsafe fn prepare_request(
-    scheme: &'static str,
     custom_protocol: &str,
     webview_request: &ICoreWebView2WebResourceRequest,
-    webview_request_uri: &str,
+    path: &str,
   ) -> Result<http::Request<Vec<u8>>> {
     let mut request = Request::builder();
 
@@ -903,6 +1837,7 @@ impl InnerWebView {
 
     // Get all headers from the request
     let headers = webview_request.Headers()?.GetIterator()?;
+    let mut raw_headers = Vec::new();
     let mut has_current = BOOL::default();
     headers.HasCurrentHeader(&mut has_current)?;
     while has_current.as_bool() {
@@ -912,21 +1847,29 @@ impl InnerWebView {
 
       let (key, value) = (take_pwstr(key), take_pwstr(value));
       request = request.header(&key, &value);
+      raw_headers.push((key, value));
 
       headers.MoveNext(&mut has_current)?;
     }
+    request = request.extension(crate::RawHeaders(raw_headers));
 
     // Get the body if available
     let mut body_sent = Vec::new();
     if let Ok(content) = webview_request.Content() {
-      let mut buffer: [u8; 1024] = [0; 1024];
+      // The stream is read into the spare capacity of the body, growing it as needed, instead of
+      // copying each chunk from an intermediate buffer.
+      let content: IStream = content.cast()?;
       loop {
+        if body_sent.capacity() - body_sent.len() < REQUEST_BODY_CHUNK_SIZE {
+          body_sent.reserve(REQUEST_BODY_CHUNK_SIZE);
+        }
+        let spare = body_sent.spare_capacity_mut();
+
         let mut cb_read = 0;
-        let content: IStream = content.cast()?;
         content
           .Read(
-            buffer.as_mut_ptr() as *mut _,
-            buffer.len() as u32,
+            spare.as_mut_ptr() as *mut _,
+            spare.len() as u32,
             Some(&mut cb_read),
           )
           .ok()?;
@@ -935,17 +1878,17 @@ impl InnerWebView {
           break;
         }
 
-        body_sent.extend_from_slice(&buffer[..(cb_read as usize)]);
+        body_sent.set_len(body_sent.len() + cb_read as usize);
       }
     }
 
     // Undo the protocol workaround when giving path to resolver
-    let path = webview_request_uri.replace(
-      &format!("{scheme}://{}.", custom_protocol),
-      &format!("{}://", custom_protocol),
-    );
+    let mut uri = String::with_capacity(custom_protocol.len() + 3 + path.len());
+    uri.push_str(custom_protocol);
+    uri.push_str("://");
+    uri.push_str(path);
 
-    let request = request.uri(&path).body(body_sent)?;
+    let request = request.uri(uri).body(body_sent)?;
 
     Ok(request)
   }
@@ -955,17 +1898,20 @@ impl InnerWebView {
     env: &ICoreWebView2Environment,
     sent_response: &HttpResponse<Cow<'static, [u8]>>,
   ) -> windows::core::Result<ICoreWebView2WebResourceResponse> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::info_span!("wry::custom_protocol::prepare_response").entered();
+
     let content = sent_response.body();
 
     let status = sent_response.status();
     let status_code = status.as_u16();
-    let status = HSTRING::from(status.canonical_reason().unwrap_or("OK"));
+    let status = reason_phrase(status);
 
-    let mut headers_map = String::new();
-    for (name, value) in sent_response.headers().iter() {
-      let header_key = name.to_string();
+    let headers = sent_response.headers();
+    let mut headers_map = String::with_capacity(headers.len() * 32);
+    for (name, value) in headers.iter() {
       if let Ok(value) = value.to_str() {
-        let _ = writeln!(headers_map, "{}: {}", header_key, value);
+        let _ = writeln!(headers_map, "{}: {}", name.as_str(), value);
       }
     }
     let headers_map = HSTRING::from(headers_map);
@@ -1130,10 +2076,127 @@ impl InnerWebView {
     );
   }
 
+  /// Call `handler` with the bounds of the container window, relative to its parent, when they
+  /// change.
+  #[inline]
+  unsafe fn attach_bounds_subclass(hwnd: HWND, handler: Box<dyn Fn(Rect)>) {
+    let _ = SetWindowSubclass(
+      hwnd,
+      Some(Self::bounds_subclass_proc),
+      BOUNDS_SUBCLASS_ID as _,
+      Box::into_raw(Box::new(handler)) as _,
+    );
+  }
+
+  unsafe extern "system" fn bounds_subclass_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    uidsubclass: usize,
+    dwrefdata: usize,
+  ) -> LRESULT {
+    match msg {
+      WM_WINDOWPOSCHANGED => {
+        let window_pos = &*(lparam.0 as *const WINDOWPOS);
+        if window_pos.flags.0 & (SWP_NOMOVE.0 | SWP_NOSIZE.0) != (SWP_NOMOVE.0 | SWP_NOSIZE.0) {
+          let handler = &*(dwrefdata as *const Box<dyn Fn(Rect)>);
+
+          let mut rect = RECT::default();
+          let _ = GetClientRect(hwnd, &mut rect);
+          let position = &mut [POINT {
+            x: rect.left,
+            y: rect.top,
+          }];
+          MapWindowPoints(hwnd, GetParent(hwnd).unwrap_or_default(), position);
+
+          handler(Rect {
+            position: PhysicalPosition::new(position[0].x, position[0].y).into(),
+            size: PhysicalSize::new(rect.right - rect.left, rect.bottom - rect.top).into(),
+          });
+        }
+      }
+
+      WM_NCDESTROY => {
+        let _ = RemoveWindowSubclass(hwnd, Some(Self::bounds_subclass_proc), uidsubclass);
+        drop(Box::from_raw(dwrefdata as *mut Box<dyn Fn(Rect)>));
+      }
+
+      _ => (),
+    }
+
+    DefSubclassProc(hwnd, msg, wparam, lparam)
+  }
+
+  /// Track the visibility of the top-level window of `parent`, using the container window as the
+  /// subclass id so each webview has its own subclass.
+  #[inline]
+  unsafe fn attach_visibility_subclass(&self, parent: HWND, tracker: Rc<VisibilityTracker>) {
+    let root = GetAncestor(parent, GA_ROOT);
+    self.visibility_root.set(root);
+    let _ = SetWindowSubclass(
+      root,
+      Some(Self::visibility_subclass_proc),
+      self.hwnd.0 as _,
+      Box::into_raw(Box::new(tracker)) as _,
+    );
+  }
+
+  #[inline]
+  unsafe fn dettach_visibility_subclass(&self) {
+    let root = self.visibility_root.take();
+    let mut data = 0;
+    // the subclass is already removed if the window was destroyed
+    if GetWindowSubclass(
+      root,
+      Some(Self::visibility_subclass_proc),
+      self.hwnd.0 as _,
+      Some(&mut data as *mut _),
+    )
+    .as_bool()
+    {
+      let _ = RemoveWindowSubclass(root, Some(Self::visibility_subclass_proc), self.hwnd.0 as _);
+      drop(Box::from_raw(data as *mut Rc<VisibilityTracker>));
+    }
+  }
+
+  unsafe extern "system" fn visibility_subclass_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    uidsubclass: usize,
+    dwrefdata: usize,
+  ) -> LRESULT {
+    let tracker = &*(dwrefdata as *const Rc<VisibilityTracker>);
+    match msg {
+      WM_SIZE => match wparam.0 as u32 {
+        SIZE_MINIMIZED => tracker.set_window_visible(false),
+        SIZE_RESTORED | SIZE_MAXIMIZED => tracker.set_window_visible(true),
+        _ => (),
+      },
+
+      WM_SHOWWINDOW => tracker.set_window_visible(wparam.0 != 0),
+
+      WM_NCDESTROY => {
+        let _ = RemoveWindowSubclass(hwnd, Some(Self::visibility_subclass_proc), uidsubclass);
+        drop(Box::from_raw(dwrefdata as *mut Rc<VisibilityTracker>));
+      }
+
+      _ => (),
+    }
+
+    DefSubclassProc(hwnd, msg, wparam, lparam)
+  }
+
   // TODO: feature to allow injecting into (specific) subframes
   #[inline]
-  fn add_script_to_execute_on_document_created(webview: &ICoreWebView2, js: String) -> Result<()> {
+  fn add_script_to_execute_on_document_created(
+    webview: &ICoreWebView2,
+    js: String,
+  ) -> Result<String> {
     let webview = webview.clone();
+    let (tx, rx) = mpsc::channel();
     AddScriptToExecuteOnDocumentCreatedCompletedHandler::wait_for_async_operation(
       Box::new(move |handler| unsafe {
         let js = HSTRING::from(js);
@@ -1141,9 +2204,14 @@ impl InnerWebView {
           .AddScriptToExecuteOnDocumentCreated(&js, &handler)
           .map_err(Into::into)
       }),
-      Box::new(|e, _| e),
-    )
-    .map_err(Into::into)
+      Box::new(move |e, id| {
+        let _ = tx.send(id);
+        e
+      }),
+    )?;
+
+    // The id of the script, used to remove it
+    Ok(rx.try_recv().unwrap_or_default())
   }
 
   #[inline]
@@ -1199,22 +2267,74 @@ impl InnerWebView {
     Self::url_from_webview(&self.webview).map_err(Into::into)
   }
 
+  pub fn session(&self) -> Result<Session> {
+    let mut session = Session {
+      env: self.env.clone(),
+      in_private: false,
+      profile: None,
+    };
+    // the profile is the default one on older runtimes
+    if let Ok(webview) = self.webview.cast::<ICoreWebView2_13>() {
+      unsafe {
+        let profile = webview.Profile()?;
+        let mut in_private = BOOL::default();
+        profile.IsInPrivateModeEnabled(&mut in_private)?;
+        let mut name = PWSTR::null();
+        profile.ProfileName(&mut name)?;
+        session.in_private = in_private.as_bool();
+        session.profile = Some(HSTRING::from(take_pwstr(name)));
+      }
+    }
+    Ok(session)
+  }
+
+  pub fn platform_handle(&self) -> PlatformWebViewHandle {
+    PlatformWebViewHandle::WebView2 {
+      hwnd: self.hwnd.0 as isize,
+      controller: self.controller.clone(),
+    }
+  }
+
+  pub fn navigation_state(&self) -> &NavigationState {
+    &self.navigation_state
+  }
+
   pub fn zoom(&self, scale_factor: f64) -> Result<()> {
     unsafe { self.controller.SetZoomFactor(scale_factor) }.map_err(Into::into)
   }
 
   pub fn load_url(&self, url: &str) -> Result<()> {
+    if let Some(persistent_headers) = &self.persistent_headers {
+      persistent_headers.set(url, None);
+    }
     let url = HSTRING::from(url);
     unsafe { self.webview.Navigate(&url) }.map_err(Into::into)
   }
 
+  pub fn download(&self, url: &str, destination: &Path) -> Result<()> {
+    self
+      .pending_downloads
+      .borrow_mut()
+      .insert(url.to_string(), destination.to_path_buf());
+
+    // WebView2 has no API to start a download, so click a link with the `download` attribute
+    // which makes the request with the cookies of the webview.
+    let url = url.replace('\\', "\\\\").replace('\'', "\\'");
+    let js = format!(
+      "(function () {{ const a = document.createElement('a'); a.href = '{url}'; a.download = ''; a.style.display = 'none'; document.body.appendChild(a); a.click(); a.remove(); }})()"
+    );
+    Self::execute_script(&self.webview, js, |_| ()).map_err(Into::into)
+  }
+
   pub fn load_url_with_headers(&self, url: &str, headers: http::HeaderMap) -> Result<()> {
+    if let Some(persistent_headers) = &self.persistent_headers {
+      persistent_headers.set(url, Some(headers.clone()));
+    }
     load_url_with_headers(&self.webview, &self.env, url, headers)
   }
 
   pub fn load_html(&self, html: &str) -> Result<()> {
-    let html = HSTRING::from(html);
-    unsafe { self.webview.NavigateToString(&html) }.map_err(Into::into)
+    navigate_to_string(&self.webview, &self.large_html, html)
   }
 
   pub fn bounds(&self) -> Result<Rect> {
@@ -1275,6 +2395,38 @@ impl InnerWebView {
     Ok(())
   }
 
+  pub fn set_hit_test_ignore_regions(&self, regions: Vec<Rect>) -> Result<()> {
+    unsafe {
+      if regions.is_empty() {
+        SetWindowRgn(self.hwnd, HRGN::default(), true);
+        return Ok(());
+      }
+
+      let dpi = util::hwnd_dpi(self.hwnd);
+      let scale_factor = util::dpi_to_scale_factor(dpi);
+
+      // A region larger than any webview, the ignored regions are cut out of it
+      let region = CreateRectRgn(0, 0, i16::MAX as i32, i16::MAX as i32);
+      for rect in regions {
+        let size = rect.size.to_physical::<i32>(scale_factor);
+        let position = rect.position.to_physical::<i32>(scale_factor);
+        let ignored = CreateRectRgn(
+          position.x,
+          position.y,
+          position.x + size.width,
+          position.y + size.height,
+        );
+        CombineRgn(region, region, ignored, RGN_DIFF);
+        let _ = DeleteObject(ignored);
+      }
+
+      // The system owns the region after this call
+      SetWindowRgn(self.hwnd, region, true);
+    }
+
+    Ok(())
+  }
+
   fn resize_to_parent(&self) -> crate::Result<()> {
     let mut rect = RECT::default();
     unsafe { GetClientRect(*self.parent.borrow(), &mut rect)? };
@@ -1284,6 +2436,58 @@ impl InnerWebView {
     self.set_bounds_inner((width, height).into(), (0, 0).into())
   }
 
+  pub fn add_css(&self, css: &str, main_frame_only: bool) -> Result<()> {
+    let js = user_style_sheet_script(css, main_frame_only);
+    let id = Self::add_script_to_execute_on_document_created(&self.webview, js.clone())?;
+    self
+      .user_style_sheets
+      .borrow_mut()
+      .push((css.to_string(), id));
+    self.eval(&js, None::<Box<dyn FnOnce(String) + Send + 'static>>)
+  }
+
+  pub fn remove_css(&self, css: &str) -> Result<()> {
+    let mut user_style_sheets = self.user_style_sheets.borrow_mut();
+    let Some(index) = user_style_sheets.iter().position(|(c, _)| c == css) else {
+      return Ok(());
+    };
+    let (_, id) = user_style_sheets.remove(index);
+
+    unsafe {
+      self
+        .webview
+        .RemoveScriptToExecuteOnDocumentCreated(&HSTRING::from(id))?
+    };
+    self.eval(
+      &remove_user_style_sheet_script(css),
+      None::<Box<dyn FnOnce(String) + Send + 'static>>,
+    )
+  }
+
+  pub fn set_javascript_enabled(&self, enabled: bool) -> Result<()> {
+    unsafe { self.webview.Settings()?.SetIsScriptEnabled(enabled)? };
+    Ok(())
+  }
+
+  pub fn set_images_enabled(&self, enabled: bool) -> Result<()> {
+    if self.images_enabled.replace(enabled) == enabled {
+      return Ok(());
+    }
+
+    unsafe {
+      if enabled {
+        self
+          .webview
+          .RemoveWebResourceRequestedFilter(w!("*"), COREWEBVIEW2_WEB_RESOURCE_CONTEXT_IMAGE)
+      } else {
+        self
+          .webview
+          .AddWebResourceRequestedFilter(w!("*"), COREWEBVIEW2_WEB_RESOURCE_CONTEXT_IMAGE)
+      }
+    }
+    .map_err(Into::into)
+  }
+
   pub fn set_visible(&self, visible: bool) -> Result<()> {
     unsafe {
       let _ = ShowWindow(
@@ -1297,6 +2501,10 @@ impl InnerWebView {
       self.controller.SetIsVisible(visible)?;
     }
 
+    if let Some(visibility_tracker) = &self.visibility_tracker {
+      visibility_tracker.set_webview_visible(visible);
+    }
+
     Ok(())
   }
 
@@ -1320,6 +2528,12 @@ impl InnerWebView {
     Ok(())
   }
 
+  pub fn dispatcher(&self) -> MainThreadDispatcher {
+    MainThreadDispatcher {
+      hwnd: self.hwnd.0 as isize,
+    }
+  }
+
   unsafe fn cookie_from_win32(cookie: ICoreWebView2Cookie) -> Result<cookie::Cookie<'static>> {
     let mut name = PWSTR::null();
     cookie.Name(&mut name)?;
@@ -1432,6 +2646,11 @@ impl InnerWebView {
     unsafe {
       SetParent(self.hwnd, parent)?;
 
+      if let Some(visibility_tracker) = &self.visibility_tracker {
+        self.dettach_visibility_subclass();
+        self.attach_visibility_subclass(parent, visibility_tracker.clone());
+      }
+
       if !self.is_child {
         Self::dettach_parent_subclass(*self.parent.borrow());
         Self::attach_parent_subclass(parent, &self.controller);
@@ -1458,6 +2677,68 @@ impl InnerWebView {
     )
   }
 
+  pub fn print_with_options(&self, options: &PrintOptions) -> Result<()> {
+    unsafe {
+      let webview = self.webview.cast::<ICoreWebView2_16>()?;
+      if !options.silent {
+        return webview
+          .ShowPrintUI(COREWEBVIEW2_PRINT_DIALOG_KIND_BROWSER)
+          .map_err(Into::into);
+      }
+
+      let settings = self
+        .env
+        .cast::<ICoreWebView2Environment6>()?
+        .CreatePrintSettings()?;
+
+      // the margins of the print settings are in inches
+      settings.SetMarginTop(options.margins.top as f64 / 72.0)?;
+      settings.SetMarginRight(options.margins.right as f64 / 72.0)?;
+      settings.SetMarginBottom(options.margins.bottom as f64 / 72.0)?;
+      settings.SetMarginLeft(options.margins.left as f64 / 72.0)?;
+
+      // the footer of WebView2 is the URL of the page, it can't be replaced by a text
+      if let Some(header) = &options.header {
+        settings.SetShouldPrintHeaderAndFooter(true)?;
+        settings.SetHeaderTitle(&HSTRING::from(header))?;
+      }
+      if let Some(scale) = options.scale {
+        settings.SetScaleFactor(scale)?;
+      }
+      if let Some(print_backgrounds) = options.print_backgrounds {
+        settings.SetShouldPrintBackgrounds(print_backgrounds)?;
+      }
+
+      webview
+        .Print(
+          &settings,
+          &PrintCompletedHandler::create(Box::new(|_, _| Ok(()))),
+        )
+        .map_err(Into::into)
+    }
+  }
+
+  pub fn print_capabilities(&self) -> PrintCapabilities {
+    let supported = self.webview.cast::<ICoreWebView2_16>().is_ok()
+      && self.env.cast::<ICoreWebView2Environment6>().is_ok();
+    PrintCapabilities {
+      margins: supported,
+      header: supported,
+      scale: supported,
+      print_backgrounds: supported,
+      ..Default::default()
+    }
+  }
+
+  pub fn api_capabilities(&self) -> ApiCapabilities {
+    // WebView2 exposes the WebHID, WebUSB and Web Serial APIs, but without a device chooser
+    ApiCapabilities {
+      gamepad: true,
+      webauthn: true,
+      ..Default::default()
+    }
+  }
+
   pub fn clear_all_browsing_data(&self) -> Result<()> {
     unsafe {
       self
@@ -1472,6 +2753,249 @@ impl InnerWebView {
     }
   }
 
+  pub fn clear_http_cache(&self) -> Result<()> {
+    unsafe {
+      self
+        .webview
+        .cast::<ICoreWebView2_13>()?
+        .Profile()?
+        .cast::<ICoreWebView2Profile2>()?
+        .ClearBrowsingData(
+          COREWEBVIEW2_BROWSING_DATA_KINDS_DISK_CACHE,
+          &ClearBrowsingDataCompletedHandler::create(Box::new(move |_| Ok(()))),
+        )
+        .map_err(Into::into)
+    }
+  }
+
+  pub fn storage_usage(&self) -> Result<Vec<crate::OriginStorageUsage>> {
+    Ok(Vec::new())
+  }
+
+  pub fn renderer_pid(&self) -> Result<Option<u32>> {
+    let (tx, rx) = mpsc::channel();
+    self.find_renderer_pid(move |pid| {
+      let _ = tx.send(pid);
+    })?;
+    webview2_com::wait_with_pump(rx).map_err(Into::into)
+  }
+
+  /// Find the renderer process of the main frame of the webview among the processes of its
+  /// environment, WebView2 only exposes the browser process of the webviews.
+  fn find_renderer_pid(&self, f: impl FnOnce(Option<u32>) + 'static) -> Result<()> {
+    unsafe {
+      let mut frame_id = 0;
+      self
+        .webview
+        .cast::<ICoreWebView2_20>()?
+        .FrameId(&mut frame_id)?;
+
+      let handler =
+        GetProcessExtendedInfosCompletedHandler::create(Box::new(move |error_code, infos| {
+          let pid = error_code
+            .and_then(|_| infos.ok_or_else(|| E_POINTER.into()))
+            .and_then(|infos| Self::renderer_of_frame(&infos, frame_id));
+          f(pid.ok().flatten());
+          Ok(())
+        }));
+      self
+        .env
+        .cast::<ICoreWebView2Environment13>()?
+        .GetProcessExtendedInfos(&handler)
+        .map_err(Into::into)
+    }
+  }
+
+  unsafe fn renderer_of_frame(
+    infos: &ICoreWebView2ProcessExtendedInfoCollection,
+    frame_id: u32,
+  ) -> windows::core::Result<Option<u32>> {
+    let mut count = 0;
+    infos.Count(&mut count)?;
+
+    for index in 0..count {
+      let info = infos.GetValueAtIndex(index)?;
+      let process = info.ProcessInfo()?;
+      let mut kind = COREWEBVIEW2_PROCESS_KIND::default();
+      process.Kind(&mut kind)?;
+      if kind != COREWEBVIEW2_PROCESS_KIND_RENDERER {
+        continue;
+      }
+
+      let frames = info.AssociatedFrameInfos()?.GetIterator()?;
+      let mut has_current = BOOL::default();
+      frames.HasCurrent(&mut has_current)?;
+      while has_current.as_bool() {
+        let mut id = 0;
+        frames
+          .GetCurrent()?
+          .cast::<ICoreWebView2FrameInfo2>()?
+          .FrameId(&mut id)?;
+        if id == frame_id {
+          let mut pid = 0;
+          process.ProcessId(&mut pid)?;
+          return Ok(Some(pid as u32));
+        }
+        frames.MoveNext(&mut has_current)?;
+      }
+    }
+
+    Ok(None)
+  }
+
+  pub fn engine_info(&self) -> Result<EngineInfo> {
+    let mut info = platform_engine_info()?;
+
+    info.user_data_dir = unsafe {
+      self
+        .env
+        .cast::<ICoreWebView2Environment7>()
+        .and_then(|env| {
+          let mut path = PWSTR::null();
+          env.UserDataFolder(&mut path)?;
+          Ok(PathBuf::from(take_pwstr(path)))
+        })
+        .ok()
+    };
+
+    // the runtime is the directory of the executable of the browser process
+    let mut pid = 0;
+    unsafe { self.webview.BrowserProcessId(&mut pid)? };
+    info.runtime_path = unsafe {
+      let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid)?;
+      let mut buf = vec![0u16; MAX_PATH as usize];
+      let mut len = buf.len() as u32;
+      let result = QueryFullProcessImageNameW(
+        process,
+        PROCESS_NAME_WIN32,
+        PWSTR(buf.as_mut_ptr()),
+        &mut len,
+      );
+      let _ = CloseHandle(process);
+      result
+        .ok()
+        .map(|_| PathBuf::from(String::from_utf16_lossy(&buf[..len as usize])))
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    };
+
+    Ok(info)
+  }
+
+  pub fn history_length(&self) -> Result<Option<usize>> {
+    // WebView2 doesn't expose its back/forward list
+    Ok(None)
+  }
+
+  pub fn memory_usage(&self, handler: impl FnOnce(Option<u64>) + 'static) -> Result<()> {
+    self.find_renderer_pid(move |pid| handler(pid.and_then(|pid| working_set_size(pid).ok())))
+  }
+
+  pub fn remove_origin_data(&self, origin: &str) -> Result<()> {
+    let origin = origin.replace('\\', "\\\\").replace('"', "\\\"");
+    let params = HSTRING::from(format!(r#"{{"origin":"{origin}","storageTypes":"all"}}"#));
+    unsafe {
+      self
+        .webview
+        .CallDevToolsProtocolMethod(
+          w!("Storage.clearDataForOrigin"),
+          &params,
+          &CallDevToolsProtocolMethodCompletedHandler::create(Box::new(|_, _| Ok(()))),
+        )
+        .map_err(Into::into)
+    }
+  }
+
+  /// Run the async function `body` in a new [`FETCH_WORLD_NAME`] isolated world of the main frame,
+  /// passing the string it returns, or the error it throws, to `callback`.
+  pub fn eval_fetch(
+    &self,
+    body: &str,
+    callback: impl FnOnce(std::result::Result<String, String>) + 'static,
+  ) -> Result<()> {
+    let webview = self.webview.clone();
+    let expression = format!("(async function () {{\n{body}\n}})()");
+    unsafe {
+      call_devtools_method(&self.webview, "Page.getFrameTree", "{}", move |tree| {
+        // the main frame is the first of the tree
+        let Some(frame_id) = tree.ok().and_then(|tree| json_string_field(&tree, "id")) else {
+          callback(Err("failed to get the main frame".to_string()));
+          return;
+        };
+        let params = format!(
+          r#"{{"frameId":{},"worldName":{}}}"#,
+          json_string(&frame_id),
+          json_string(FETCH_WORLD_NAME)
+        );
+        let webview_ = webview.clone();
+        call_devtools_method(
+          &webview,
+          "Page.createIsolatedWorld",
+          &params,
+          move |world| {
+            let Some(context_id) = world
+              .ok()
+              .and_then(|world| json_number_field(&world, "executionContextId"))
+            else {
+              callback(Err("failed to create the isolated world".to_string()));
+              return;
+            };
+            let params = format!(
+              r#"{{"expression":{},"contextId":{context_id},"awaitPromise":true,"returnByValue":true}}"#,
+              json_string(&expression)
+            );
+            call_devtools_method(&webview_, "Runtime.evaluate", &params, move |result| {
+              callback(result.and_then(|result| {
+                // the result is the thrown error when the script failed
+                if result.contains(r#""exceptionDetails":"#) {
+                  Err(
+                    json_string_field(&result, "description")
+                      .unwrap_or_else(|| "the request failed".to_string()),
+                  )
+                } else {
+                  json_string_field(&result, "value")
+                    .ok_or_else(|| "the request returned no response".to_string())
+                }
+              }));
+            });
+          },
+        );
+      });
+    }
+
+    Ok(())
+  }
+
+  pub fn add_extension(&self, path: &Path) -> Result<String> {
+    let (tx, rx) = mpsc::channel();
+
+    unsafe {
+      let path = HSTRING::from(dunce::simplified(path));
+      self
+        .webview
+        .cast::<ICoreWebView2_13>()?
+        .Profile()?
+        .cast::<ICoreWebView2Profile7>()?
+        .AddBrowserExtension(
+          &path,
+          &ProfileAddBrowserExtensionCompletedHandler::create(Box::new(
+            move |error_code, extension| {
+              let id = error_code.and_then(|_| {
+                let mut id = PWSTR::null();
+                if let Some(extension) = extension {
+                  extension.Id(&mut id)?;
+                }
+                Ok(take_pwstr(id))
+              });
+              tx.send(id)
+                .map_err(|_| windows::core::Error::from(E_UNEXPECTED))
+            },
+          )),
+        )?;
+    }
+
+    webview2_com::wait_with_pump(rx)?.map_err(Into::into)
+  }
+
   pub fn set_theme(&self, theme: Theme) -> Result<()> {
     unsafe { set_theme(&self.webview, theme) }
   }
@@ -1569,6 +3093,36 @@ unsafe fn set_background_color(
     .map_err(Into::into)
 }
 
+/// Keep the profile of `webview` to delete it with [`delete_profile`], on runtimes supporting it.
+unsafe fn register_profile(webview: &ICoreWebView2) {
+  let Ok(profile) = webview
+    .cast::<ICoreWebView2_13>()
+    .and_then(|webview| webview.Profile())
+    .and_then(|profile| profile.cast::<ICoreWebView2Profile8>())
+  else {
+    return;
+  };
+
+  let mut path = PWSTR::null();
+  if profile.ProfilePath(&mut path).is_ok() {
+    let path = take_pwstr(path).to_lowercase();
+    PROFILES.with(|profiles| profiles.borrow_mut().insert(path, profile));
+  }
+}
+
+/// Delete the profile in `directory` with `ICoreWebView2Profile8::Delete` when a webview used it
+/// on this thread, returning whether it did.
+pub(crate) fn delete_profile(directory: &Path) -> Result<bool> {
+  let directory = directory.to_string_lossy().to_lowercase();
+  match PROFILES.with(|profiles| profiles.borrow_mut().remove(&directory)) {
+    Some(profile) => {
+      unsafe { profile.Delete()? };
+      Ok(true)
+    }
+    None => Ok(false),
+  }
+}
+
 #[inline]
 unsafe fn set_theme(webview: &ICoreWebView2, theme: Theme) -> Result<()> {
   let webview = webview.cast::<ICoreWebView2_13>()?;
@@ -1582,20 +3136,189 @@ unsafe fn set_theme(webview: &ICoreWebView2, theme: Theme) -> Result<()> {
     .map_err(Into::into)
 }
 
+/// The part of `uri` after `{scheme}://{protocol}.` when it is a request of the `protocol` custom
+/// protocol, e.g. `localhost/path/to/page` for `http://wry.localhost/path/to/page`.
 #[inline]
-fn is_custom_protocol_uri(uri: &str, scheme: &'static str, protocol: &str) -> bool {
-  let uri_len = uri.len();
-  let scheme_len = scheme.len();
-  let protocol_len = protocol.len();
-
-  // starts with `http` or `https``
-  &uri[..scheme_len] == scheme
-  // followed by `://`
-  && &uri[scheme_len..scheme_len + 3] == "://"
-  // followed by custom protocol name
-  && scheme_len + 3 + protocol_len < uri_len && &uri[scheme_len + 3.. scheme_len + 3 + protocol_len] == protocol
-  // and a dot
-  && scheme_len + 3 + protocol_len < uri_len && uri.as_bytes()[scheme_len + 3 + protocol_len] == b'.'
+fn custom_protocol_path<'a>(uri: &'a str, scheme: &'static str, protocol: &str) -> Option<&'a str> {
+  uri
+    // starts with `http` or `https`
+    .strip_prefix(scheme)?
+    // followed by `://`
+    .strip_prefix("://")?
+    // followed by custom protocol name
+    .strip_prefix(protocol)?
+    // and a dot
+    .strip_prefix('.')
+    .filter(|path| !path.is_empty())
+}
+
+/// Load `html` with `NavigateToString`, or from a URL of [`LARGE_HTML_URL`] when it is larger than
+/// the [`MAX_HTML_SIZE`] it supports.
+fn navigate_to_string(webview: &ICoreWebView2, large_html: &LargeHtml, html: &str) -> Result<()> {
+  if html.encode_utf16().count() * 2 <= MAX_HTML_SIZE {
+    let html = HSTRING::from(html);
+    return unsafe { webview.NavigateToString(&html) }.map_err(Into::into);
+  }
+
+  // a new URL for each document, so the webview doesn't serve the previous one from its cache
+  static DOCUMENT_ID: AtomicU32 = AtomicU32::new(0);
+  let url = format!(
+    "{LARGE_HTML_URL}{}",
+    DOCUMENT_ID.fetch_add(1, Ordering::Relaxed)
+  );
+  *large_html.borrow_mut() = Some((url.clone(), html.as_bytes().to_vec()));
+
+  let url = HSTRING::from(url);
+  unsafe { webview.Navigate(&url) }.map_err(Into::into)
+}
+
+/// Split `js` in scripts small enough for `AddScriptToExecuteOnDocumentCreated` when it is
+/// larger than [`MAX_SCRIPT_SIZE`]: the first scripts collect its source in a global, and the last
+/// one evaluates it, so it needs `unsafe-eval` in the Content-Security-Policy of the pages.
+fn split_initialization_script(js: String) -> Vec<String> {
+  if js.encode_utf16().count() * 2 <= MAX_SCRIPT_SIZE {
+    return vec![js];
+  }
+
+  let mut scripts = Vec::new();
+  let mut rest = js.as_str();
+  while !rest.is_empty() {
+    let end = rest
+      .char_indices()
+      .nth(SCRIPT_PART_LEN)
+      .map_or(rest.len(), |(i, _)| i);
+    let (part, tail) = rest.split_at(end);
+    scripts.push(format!(
+      "(window.{SCRIPT_PARTS_GLOBAL} = window.{SCRIPT_PARTS_GLOBAL} || []).push('{}');",
+      escape_js_string(part)
+    ));
+    rest = tail;
+  }
+  scripts.push(format!(
+    r#"(function () {{
+  const source = window.{SCRIPT_PARTS_GLOBAL}.join('');
+  delete window.{SCRIPT_PARTS_GLOBAL};
+  try {{
+    (0, eval)(source);
+  }} catch (e) {{
+    if (e instanceof EvalError) {{
+      console.error('wry: the initialization script is larger than the 2 MB supported by WebView2, evaluating it requires `unsafe-eval` in the Content-Security-Policy');
+    }}
+    throw e;
+  }}
+}})();"#
+  ));
+  scripts
+}
+
+/// Call the DevTools protocol `method` of `webview` with the JSON `params`, passing its JSON result,
+/// or its error, to `callback`.
+unsafe fn call_devtools_method(
+  webview: &ICoreWebView2,
+  method: &str,
+  params: &str,
+  callback: impl FnOnce(std::result::Result<String, String>) + 'static,
+) {
+  // the callback is called by the handler, or here if the method can't be called
+  let callback = Rc::new(Cell::new(Some(callback)));
+  let callback_ = callback.clone();
+  let handler =
+    CallDevToolsProtocolMethodCompletedHandler::create(Box::new(move |error, result| {
+      if let Some(callback) = callback_.take() {
+        callback(error.map(|_| result).map_err(|e| e.to_string()));
+      }
+      Ok(())
+    }));
+  if let Err(e) =
+    webview.CallDevToolsProtocolMethod(&HSTRING::from(method), &HSTRING::from(params), &handler)
+  {
+    if let Some(callback) = callback.take() {
+      callback(Err(e.to_string()));
+    }
+  }
+}
+
+/// The JSON string of `value`.
+fn json_string(value: &str) -> String {
+  let mut json = String::with_capacity(value.len() + 2);
+  json.push('"');
+  for c in value.chars() {
+    match c {
+      '"' => json.push_str("\\\""),
+      '\\' => json.push_str("\\\\"),
+      '\n' => json.push_str("\\n"),
+      '\r' => json.push_str("\\r"),
+      '\t' => json.push_str("\\t"),
+      c if c < ' ' => json.push_str(&format!("\\u{:04x}", c as u32)),
+      c => json.push(c),
+    }
+  }
+  json.push('"');
+  json
+}
+
+/// The first string field `name` of the JSON object `json`, e.g. in a DevTools protocol result.
+fn json_string_field(json: &str, name: &str) -> Option<String> {
+  let start = json.find(&format!(r#""{name}":""#))? + name.len() + 4;
+  let mut chars = json[start..].chars();
+  let mut units = Vec::new();
+  loop {
+    match chars.next()? {
+      '"' => return Some(String::from_utf16_lossy(&units)),
+      '\\' => match chars.next()? {
+        'b' => units.push(0x08),
+        'f' => units.push(0x0c),
+        'n' => units.push(u16::from(b'\n')),
+        'r' => units.push(u16::from(b'\r')),
+        't' => units.push(u16::from(b'\t')),
+        'u' => {
+          let hex: String = chars.by_ref().take(4).collect();
+          units.push(u16::from_str_radix(&hex, 16).ok()?);
+        }
+        c => units.extend(c.encode_utf16(&mut [0; 2]).iter()),
+      },
+      c => units.extend(c.encode_utf16(&mut [0; 2]).iter()),
+    }
+  }
+}
+
+/// The first integer field `name` of the JSON object `json`, e.g. in a DevTools protocol result.
+fn json_number_field(json: &str, name: &str) -> Option<i64> {
+  let start = json.find(&format!(r#""{name}":"#))? + name.len() + 3;
+  let number: String = json[start..]
+    .chars()
+    .take_while(|c| c.is_ascii_digit() || *c == '-')
+    .collect();
+  number.parse().ok()
+}
+
+/// Whether the Content-Security-Policy `policy` allows the pages to call `eval`.
+fn csp_allows_eval(policy: &str) -> bool {
+  let directive = |name: &str| {
+    policy
+      .split(';')
+      .find(|directive| directive.split_whitespace().next() == Some(name))
+  };
+  match directive("script-src").or_else(|| directive("default-src")) {
+    Some(directive) => directive.contains("'unsafe-eval'"),
+    None => true,
+  }
+}
+
+/// The resident memory of the process `pid` in bytes.
+fn working_set_size(pid: u32) -> Result<u64> {
+  unsafe {
+    let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid)?;
+    let mut counters = PROCESS_MEMORY_COUNTERS::default();
+    let result = GetProcessMemoryInfo(
+      process,
+      &mut counters,
+      std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32,
+    );
+    let _ = CloseHandle(process);
+    result?;
+    Ok(counters.WorkingSetSize as u64)
+  }
 }
 
 pub fn platform_webview_version() -> Result<String> {
@@ -1604,6 +3327,23 @@ pub fn platform_webview_version() -> Result<String> {
   Ok(take_pwstr(versioninfo))
 }
 
+pub fn platform_engine_info() -> Result<EngineInfo> {
+  // the preview runtimes append their channel to the version, e.g. `121.0.2277.4 beta`
+  let version = platform_webview_version()?;
+  let (version, channel) = match version.split_once(' ') {
+    Some((version, channel)) => (version.to_string(), channel.to_string()),
+    None => (version, "stable".to_string()),
+  };
+
+  Ok(EngineInfo {
+    engine: Engine::WebView2,
+    version,
+    runtime_channel: Some(channel),
+    runtime_path: None,
+    user_data_dir: None,
+  })
+}
+
 #[inline]
 fn is_windows_7() -> bool {
   let v = windows_version::OsVersion::current();
@@ -1613,13 +3353,68 @@ fn is_windows_7() -> bool {
 
 #[cfg(test)]
 mod tests {
-  use super::is_custom_protocol_uri;
+  use super::{
+    csp_allows_eval, custom_protocol_path, json_number_field, json_string, json_string_field,
+    split_initialization_script, MAX_SCRIPT_SIZE,
+  };
 
   #[test]
   fn checks_if_custom_protocol_uri() {
     let scheme = "http";
     let uri = "http://wry.localhost/path/to/page";
-    assert!(is_custom_protocol_uri(uri, scheme, "wry"));
-    assert!(!is_custom_protocol_uri(uri, scheme, "asset"));
+    assert_eq!(
+      custom_protocol_path(uri, scheme, "wry"),
+      Some("localhost/path/to/page")
+    );
+    assert_eq!(custom_protocol_path(uri, scheme, "asset"), None);
+    assert_eq!(custom_protocol_path("http", scheme, "wry"), None);
+    assert_eq!(custom_protocol_path("http://wry.", scheme, "wry"), None);
+  }
+
+  #[test]
+  fn splits_large_initialization_scripts() {
+    let js = "console.log('small')".to_string();
+    assert_eq!(split_initialization_script(js.clone()), [js]);
+
+    let js = format!("window.large = '{}';", "<\\n".repeat(MAX_SCRIPT_SIZE));
+    let scripts = split_initialization_script(js);
+    assert!(scripts.len() > 2);
+    assert!(scripts
+      .iter()
+      .all(|script| script.encode_utf16().count() * 2 <= MAX_SCRIPT_SIZE));
+  }
+
+  #[test]
+  fn checks_if_csp_allows_eval() {
+    assert!(csp_allows_eval("img-src 'self'"));
+    assert!(csp_allows_eval("script-src 'self' 'unsafe-eval'"));
+    assert!(!csp_allows_eval("default-src 'self'"));
+    assert!(!csp_allows_eval("default-src *; script-src 'self'"));
+  }
+
+  #[test]
+  fn reads_devtools_protocol_results() {
+    let value = "200\ncontent-type: text/plain\n\n\"é\\ 😀";
+    let result = format!(
+      r#"{{"result":{{"type":"string","value":{}}}}}"#,
+      json_string(value)
+    );
+    assert_eq!(json_string_field(&result, "value").as_deref(), Some(value));
+    assert_eq!(
+      json_string_field(r#"{"value":"\u00e9\ud83d\ude00"}"#, "value").as_deref(),
+      Some("é😀")
+    );
+    assert_eq!(json_string_field(&result, "id"), None);
+
+    let tree = r#"{"frameTree":{"frame":{"id":"F1","loaderId":"L1"},"childFrames":[{"frame":{"id":"F2"}}]}}"#;
+    assert_eq!(json_string_field(tree, "id").as_deref(), Some("F1"));
+    assert_eq!(
+      json_number_field(r#"{"executionContextId":42}"#, "executionContextId"),
+      Some(42)
+    );
+    assert_eq!(
+      json_number_field(r#"{"id":"1"}"#, "executionContextId"),
+      None
+    );
   }
 }
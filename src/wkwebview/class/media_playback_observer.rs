@@ -0,0 +1,162 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{cell::Cell, ffi::c_void, ptr::null_mut};
+
+use objc2::{
+  declare_class, msg_send, msg_send_id,
+  mutability::InteriorMutable,
+  rc::Retained,
+  runtime::{AnyObject, Bool, NSObject},
+  ClassType, DeclaredClass,
+};
+use objc2_foundation::{
+  NSDictionary, NSKeyValueChangeKey, NSKeyValueObservingOptions,
+  NSObjectNSKeyValueObserverRegistration, NSObjectProtocol, NSString,
+};
+
+use crate::WryWebView;
+
+// WebKit only exposes whether media is playing through this private, but KVO compliant, property.
+const IS_PLAYING_AUDIO_KEY_PATH: &str = "_isPlayingAudio";
+
+#[cfg(target_os = "macos")]
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+  fn IOPMAssertionCreateWithName(
+    assertion_type: *const c_void,
+    assertion_level: u32,
+    assertion_name: *const c_void,
+    assertion_id: *mut u32,
+  ) -> i32;
+  fn IOPMAssertionRelease(assertion_id: u32) -> i32;
+}
+
+#[cfg(target_os = "macos")]
+const IOPM_ASSERTION_LEVEL_ON: u32 = 255;
+
+pub struct MediaPlaybackObserverIvars {
+  pub object: Retained<WryWebView>,
+  /// The IOKit assertion preventing the display sleep while media is playing.
+  #[cfg(target_os = "macos")]
+  assertion_id: Cell<Option<u32>>,
+  /// Whether the idle timer of the app was disabled by the observer.
+  #[cfg(target_os = "ios")]
+  idle_timer_disabled: Cell<bool>,
+}
+
+declare_class!(
+  pub struct MediaPlaybackObserver;
+
+  unsafe impl ClassType for MediaPlaybackObserver {
+    type Super = NSObject;
+    type Mutability = InteriorMutable;
+    const NAME: &'static str = "WryMediaPlaybackObserver";
+  }
+
+  impl DeclaredClass for MediaPlaybackObserver {
+    type Ivars = MediaPlaybackObserverIvars;
+  }
+
+  unsafe impl MediaPlaybackObserver {
+    #[method(observeValueForKeyPath:ofObject:change:context:)]
+    fn observe_value_for_key_path(
+      &self,
+      key_path: Option<&NSString>,
+      of_object: Option<&AnyObject>,
+      _change: Option<&NSDictionary<NSKeyValueChangeKey, AnyObject>>,
+      _context: *mut c_void,
+    ) {
+      if let (Some(key_path), Some(object)) = (key_path, of_object) {
+        if key_path.to_string() == IS_PLAYING_AUDIO_KEY_PATH {
+          let playing: Bool = unsafe { msg_send![object, _isPlayingAudio] };
+          self.set_keep_display_awake(playing.as_bool());
+        }
+      }
+    }
+  }
+
+  unsafe impl NSObjectProtocol for MediaPlaybackObserver {}
+);
+
+impl MediaPlaybackObserver {
+  pub fn new(webview: Retained<WryWebView>) -> Retained<Self> {
+    let observer = Self::alloc().set_ivars(MediaPlaybackObserverIvars {
+      object: webview,
+      #[cfg(target_os = "macos")]
+      assertion_id: Cell::new(None),
+      #[cfg(target_os = "ios")]
+      idle_timer_disabled: Cell::new(false),
+    });
+
+    let observer: Retained<Self> = unsafe { msg_send_id![super(observer), init] };
+
+    unsafe {
+      observer
+        .ivars()
+        .object
+        .addObserver_forKeyPath_options_context(
+          &observer,
+          &NSString::from_str(IS_PLAYING_AUDIO_KEY_PATH),
+          NSKeyValueObservingOptions::NSKeyValueObservingOptionNew,
+          null_mut(),
+        );
+    }
+
+    observer
+  }
+
+  #[cfg(target_os = "macos")]
+  fn set_keep_display_awake(&self, keep_awake: bool) {
+    let assertion_id = &self.ivars().assertion_id;
+    match (keep_awake, assertion_id.get()) {
+      (true, None) => {
+        let assertion_type = NSString::from_str("PreventUserIdleDisplaySleep");
+        let assertion_name = NSString::from_str("Playing media");
+        let mut id = 0;
+        let result = unsafe {
+          IOPMAssertionCreateWithName(
+            Retained::as_ptr(&assertion_type).cast(),
+            IOPM_ASSERTION_LEVEL_ON,
+            Retained::as_ptr(&assertion_name).cast(),
+            &mut id,
+          )
+        };
+        if result == 0 {
+          assertion_id.set(Some(id));
+        }
+      }
+      (false, Some(id)) => {
+        unsafe { IOPMAssertionRelease(id) };
+        assertion_id.set(None);
+      }
+      _ => {}
+    }
+  }
+
+  #[cfg(target_os = "ios")]
+  fn set_keep_display_awake(&self, keep_awake: bool) {
+    use objc2_foundation::MainThreadMarker;
+    use objc2_ui_kit::UIApplication;
+
+    let idle_timer_disabled = &self.ivars().idle_timer_disabled;
+    if idle_timer_disabled.get() != keep_awake {
+      let mtm = MainThreadMarker::new().unwrap();
+      unsafe { UIApplication::sharedApplication(mtm).setIdleTimerDisabled(keep_awake) };
+      idle_timer_disabled.set(keep_awake);
+    }
+  }
+}
+
+impl Drop for MediaPlaybackObserver {
+  fn drop(&mut self) {
+    self.set_keep_display_awake(false);
+    unsafe {
+      self
+        .ivars()
+        .object
+        .removeObserver_forKeyPath(self, &NSString::from_str(IS_PLAYING_AUDIO_KEY_PATH));
+    }
+  }
+}
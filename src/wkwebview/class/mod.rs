@@ -3,9 +3,12 @@
 // SPDX-License-Identifier: MIT
 
 pub mod document_title_changed_observer;
+pub mod media_playback_observer;
 pub mod url_scheme_handler;
 pub mod wry_download_delegate;
 pub mod wry_navigation_delegate;
+#[cfg(target_os = "macos")]
+pub mod wry_print_delegate;
 pub mod wry_web_view;
 pub mod wry_web_view_delegate;
 pub mod wry_web_view_parent;
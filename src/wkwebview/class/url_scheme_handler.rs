@@ -25,7 +25,9 @@ use objc2_foundation::{
 };
 use objc2_web_kit::{WKURLSchemeHandler, WKURLSchemeTask};
 
-use crate::{wkwebview::WEBVIEW_IDS, RequestAsyncResponder, WryWebView};
+use crate::{
+  wkwebview::WEBVIEW_IDS, CancellationToken, RawHeaders, RequestAsyncResponder, WryWebView,
+};
 
 pub fn create(name: &str) -> &AnyClass {
   unsafe {
@@ -63,7 +65,8 @@ extern "C" fn start_task(
             .entered();
 
     let task_key = task.hash(); // hash by task object address
-    let task_uuid = webview.add_custom_task_key(task_key);
+    let cancellation = CancellationToken::default();
+    let task_uuid = webview.add_custom_task_key(task_key, cancellation.clone());
 
     let ivar = this.class().instance_variable("webview_id").unwrap();
     let webview_id_ptr: *mut c_char = *ivar.load(this);
@@ -119,14 +122,17 @@ extern "C" fn start_task(
       let all_headers = request.allHTTPHeaderFields();
 
       // get all our headers values and inject them in our request
+      let mut raw_headers = Vec::new();
       if let Some(all_headers) = all_headers {
         for current_header in all_headers.allKeys().to_vec() {
           let header_value = all_headers.valueForKey(current_header).unwrap();
 
           // inject the header into the request
           http_request = http_request.header(current_header.to_string(), header_value.to_string());
+          raw_headers.push((current_header.to_string(), header_value.to_string()));
         }
       }
+      http_request = http_request.extension(RawHeaders(raw_headers));
 
       let respond_with_404 = || {
         let urlresponse = NSHTTPURLResponse::alloc();
@@ -148,7 +154,7 @@ extern "C" fn start_task(
       // send response
       match http_request.body(sent_form_body) {
         Ok(final_request) => {
-          let responder: Box<dyn FnOnce(HttpResponse<Cow<'static, [u8]>>)> =
+          let responder: Box<dyn FnOnce(HttpResponse<Cow<'static, [u8]>>) -> crate::Result<()>> =
             Box::new(move |sent_response| {
               fn check_webview_id_valid(webview_id: &str) -> crate::Result<()> {
                 if !WEBVIEW_IDS.lock().unwrap().contains(webview_id) {
@@ -238,7 +244,7 @@ extern "C" fn start_task(
                 objc2::exception::catch(AssertUnwindSafe(|| {
                   task.didReceiveResponse(&response);
                 }))
-                .unwrap();
+                .map_err(|_| crate::Error::CustomProtocolTaskInvalid)?;
 
                 // Send data
                 let bytes = content.as_ptr() as *mut c_void;
@@ -251,7 +257,7 @@ extern "C" fn start_task(
                 objc2::exception::catch(AssertUnwindSafe(|| {
                   task.didReceiveData(&data);
                 }))
-                .unwrap();
+                .map_err(|_| crate::Error::CustomProtocolTaskInvalid)?;
 
                 // Finish
                 check_webview_id_valid(webview_id)?;
@@ -259,13 +265,13 @@ extern "C" fn start_task(
                 objc2::exception::catch(AssertUnwindSafe(|| {
                   task.didFinish();
                 }))
-                .unwrap();
+                .map_err(|_| crate::Error::CustomProtocolTaskInvalid)?;
 
                 webview.remove_custom_task_key(task_key);
                 Ok(())
               }
 
-              let _ = response(
+              response(
                 task,
                 webview,
                 task_key,
@@ -273,7 +279,7 @@ extern "C" fn start_task(
                 webview_id,
                 url.clone(),
                 sent_response,
-              );
+              )
             });
 
           #[cfg(feature = "tracing")]
@@ -281,7 +287,10 @@ extern "C" fn start_task(
           function(
             webview_id,
             final_request,
-            RequestAsyncResponder { responder },
+            RequestAsyncResponder {
+              responder,
+              cancellation,
+            },
           );
         }
         Err(_) => respond_with_404(),
@@ -300,5 +309,7 @@ extern "C" fn stop_task(
   webview: &mut WryWebView,
   task: &ProtocolObject<dyn WKURLSchemeTask>,
 ) {
-  webview.remove_custom_task_key(task.hash());
+  if let Some(cancellation) = webview.remove_custom_task_key(task.hash()) {
+    cancellation.cancel();
+  }
 }
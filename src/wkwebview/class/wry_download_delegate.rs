@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use std::{cell::RefCell, path::PathBuf, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, path::PathBuf, rc::Rc};
 
 use objc2::{
   declare_class, msg_send_id, mutability::MainThreadOnly, rc::Retained, runtime::NSObject,
@@ -11,13 +11,19 @@ use objc2::{
 use objc2_foundation::{
   MainThreadMarker, NSData, NSError, NSObjectProtocol, NSString, NSURLResponse, NSURL,
 };
-use objc2_web_kit::{WKDownload, WKDownloadDelegate};
+use objc2_web_kit::{
+  WKDownload, WKDownloadDelegate, WKScriptMessage, WKScriptMessageHandler, WKUserContentController,
+};
 
-use crate::wkwebview::download::{download_did_fail, download_did_finish, download_policy};
+use crate::wkwebview::download::{
+  blob_download_message, download_did_fail, download_did_finish, download_policy,
+};
 
 pub struct WryDownloadDelegateIvars {
   pub started: Option<RefCell<Box<dyn FnMut(String, &mut PathBuf) -> bool + 'static>>>,
   pub completed: Option<Rc<dyn Fn(String, Option<PathBuf>, bool) + 'static>>,
+  /// Destinations of the downloads started with [`crate::WebView::download`], keyed by URL.
+  pub destinations: RefCell<HashMap<String, PathBuf>>,
 }
 
 declare_class!(
@@ -62,6 +68,14 @@ declare_class!(
       download_did_fail(self, download, error, resume_data);
     }
   }
+
+  unsafe impl WKScriptMessageHandler for WryDownloadDelegate {
+    // Function for blob downloads
+    #[method(userContentController:didReceiveScriptMessage:)]
+    fn did_receive(&self, _controller: &WKUserContentController, msg: &WKScriptMessage) {
+      blob_download_message(self, msg);
+    }
+  }
 );
 
 impl WryDownloadDelegate {
@@ -75,6 +89,7 @@ impl WryDownloadDelegate {
       .set_ivars(WryDownloadDelegateIvars {
         started: download_started_handler.map(|handler| RefCell::new(handler)),
         completed: download_completed_handler,
+        destinations: Default::default(),
       });
 
     unsafe { msg_send_id![super(delegate), init] }
@@ -2,13 +2,18 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use std::sync::{Arc, Mutex};
+use std::{
+  cell::{Cell, RefCell},
+  ptr::null,
+  rc::Rc,
+  sync::{Arc, Mutex},
+};
 
 use objc2::{
   declare_class, msg_send_id, mutability::MainThreadOnly, rc::Retained, runtime::NSObject,
   ClassType, DeclaredClass,
 };
-use objc2_foundation::{MainThreadMarker, NSObjectProtocol};
+use objc2_foundation::{MainThreadMarker, NSError, NSObjectProtocol};
 use objc2_web_kit::{
   WKDownload, WKNavigation, WKNavigationAction, WKNavigationActionPolicy, WKNavigationDelegate,
   WKNavigationResponse, WKNavigationResponsePolicy,
@@ -24,10 +29,12 @@ use crate::{
   wkwebview::{
     download::{navigation_download_action, navigation_download_response},
     navigation::{
-      did_commit_navigation, did_finish_navigation, navigation_policy, navigation_policy_response,
+      did_commit_navigation, did_fail_navigation, did_finish_navigation,
+      did_start_provisional_navigation, navigation_policy, navigation_policy_response,
     },
   },
-  PageLoadEvent, WryWebView,
+  NavigationState, PageLoadEvent, PersistentHeaders, ProcessFailure, ProcessFailureReason,
+  WryWebView,
 };
 
 use super::wry_download_delegate::WryDownloadDelegate;
@@ -38,8 +45,21 @@ pub struct WryNavigationDelegateIvars {
   pub navigation_policy_function: Box<dyn Fn(String, bool) -> bool>,
   pub download_delegate: Option<Retained<WryDownloadDelegate>>,
   pub on_page_load_handler: Option<Box<dyn Fn(PageLoadEvent)>>,
+  pub persistent_headers: Option<Rc<PersistentHeaders>>,
+  /// The URL of the navigation started again with the persistent headers.
+  pub restarted_url: RefCell<Option<String>>,
+  pub navigation_state: Rc<NavigationState>,
+  /// The navigation reported to `webView:didStartProvisionalNavigation:` last, the end of the
+  /// navigations it replaced doesn't end the loading.
+  pub current_navigation: Cell<*const WKNavigation>,
+  pub process_failed_handler: Option<Box<dyn Fn(ProcessFailure)>>,
+  pub first_paint_handler: Option<Box<dyn Fn()>>,
 }
 
+/// The `_WKRenderingProgressEventFirstVisuallyNonEmptyLayout` and `_WKRenderingProgressEventFirstPaint`
+/// events reported to `_webView:renderingProgressDidChange:`.
+const FIRST_PAINT_RENDERING_PROGRESS_EVENTS: usize = 1 << 1 | 1 << 6;
+
 declare_class!(
   pub struct WryNavigationDelegate;
 
@@ -55,6 +75,19 @@ declare_class!(
 
   unsafe impl NSObjectProtocol for WryNavigationDelegate {}
 
+  unsafe impl WryNavigationDelegate {
+    // The private `WKNavigationDelegatePrivate` method reporting the events observed with
+    // `_setObservedRenderingProgressEvents:`
+    #[method(_webView:renderingProgressDidChange:)]
+    fn rendering_progress_did_change(&self, _webview: &WKWebView, events: usize) {
+      if events & FIRST_PAINT_RENDERING_PROGRESS_EVENTS != 0 {
+        if let Some(handler) = &self.ivars().first_paint_handler {
+          handler();
+        }
+      }
+    }
+  }
+
   unsafe impl WKNavigationDelegate for WryNavigationDelegate {
     #[method(webView:decidePolicyForNavigationAction:decisionHandler:)]
     fn navigation_policy(
@@ -76,6 +109,35 @@ declare_class!(
       navigation_policy_response(self, webview, response, handler);
     }
 
+    #[method(webView:didStartProvisionalNavigation:)]
+    fn did_start_provisional_navigation(
+      &self,
+      webview: &WKWebView,
+      navigation: &WKNavigation,
+    ) {
+      did_start_provisional_navigation(self, webview, navigation);
+    }
+
+    #[method(webView:didFailProvisionalNavigation:withError:)]
+    fn did_fail_provisional_navigation(
+      &self,
+      webview: &WKWebView,
+      navigation: &WKNavigation,
+      error: &NSError,
+    ) {
+      did_fail_navigation(self, webview, navigation, error);
+    }
+
+    #[method(webView:didFailNavigation:withError:)]
+    fn did_fail_navigation(
+      &self,
+      webview: &WKWebView,
+      navigation: &WKNavigation,
+      error: &NSError,
+    ) {
+      did_fail_navigation(self, webview, navigation, error);
+    }
+
     #[method(webView:didFinishNavigation:)]
     fn did_finish_navigation(
       &self,
@@ -113,6 +175,19 @@ declare_class!(
     ) {
       navigation_download_response(self, webview, response, download);
     }
+
+    #[method(webViewWebContentProcessDidTerminate:)]
+    fn web_content_process_did_terminate(&self, _webview: &WKWebView) {
+      if let Some(handler) = &self.ivars().process_failed_handler {
+        // WebKit doesn't tell why the process terminated
+        handler(ProcessFailure {
+          reason: ProcessFailureReason::Unknown,
+          exit_code: None,
+          description: None,
+          crash_dumps_dir: None,
+        });
+      }
+    }
   }
 );
 
@@ -126,8 +201,23 @@ impl WryNavigationDelegate {
     new_window_req_handler: Option<Box<dyn Fn(String) -> bool>>,
     download_delegate: Option<Retained<WryDownloadDelegate>>,
     on_page_load_handler: Option<Box<dyn Fn(PageLoadEvent, String)>>,
+    persistent_headers: Option<Rc<PersistentHeaders>>,
+    navigation_state: Rc<NavigationState>,
+    process_failed_handler: Option<Box<dyn Fn(ProcessFailure)>>,
+    first_paint_handler: Option<Box<dyn Fn()>>,
     mtm: MainThreadMarker,
   ) -> Retained<Self> {
+    if first_paint_handler.is_some() {
+      // Calls the private `_setObservedRenderingProgressEvents:`, the first paint is reported when
+      // the first page finished loading without it
+      let observe = objc2::sel!(_setObservedRenderingProgressEvents:);
+      if webview.respondsToSelector(observe) {
+        let () = unsafe {
+          objc2::msg_send![&webview, _setObservedRenderingProgressEvents: FIRST_PAINT_RENDERING_PROGRESS_EVENTS]
+        };
+      }
+    }
+
     let navigation_policy_function = Box::new(move |url: String, is_main_frame: bool| -> bool {
       if is_main_frame {
         navigation_handler
@@ -157,6 +247,12 @@ impl WryNavigationDelegate {
         has_download_handler,
         download_delegate,
         on_page_load_handler,
+        persistent_headers,
+        restarted_url: RefCell::new(None),
+        navigation_state,
+        current_navigation: Cell::new(null()),
+        process_failed_handler,
+        first_paint_handler,
       });
 
     unsafe { msg_send_id![super(delegate), init] }
@@ -0,0 +1,94 @@
+// Copyright 2020-2024 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::ffi::c_void;
+
+use objc2::{
+  declare_class, msg_send, msg_send_id,
+  mutability::MainThreadOnly,
+  rc::Retained,
+  runtime::{AnyObject, Bool, NSObject},
+  sel, ClassType, DeclaredClass,
+};
+use objc2_app_kit::{NSPrintOperation, NSWindow};
+use objc2_foundation::{
+  ns_string, MainThreadMarker, NSNumber, NSObjectNSKeyValueCoding, NSObjectProtocol,
+};
+use objc2_web_kit::WKPreferences;
+
+pub struct WryPrintDelegateIvars {
+  preferences: Retained<WKPreferences>,
+  /// The `shouldPrintBackgrounds` preference before the print job, restored once it is done.
+  print_backgrounds: bool,
+}
+
+declare_class!(
+  pub struct WryPrintDelegate;
+
+  unsafe impl ClassType for WryPrintDelegate {
+    type Super = NSObject;
+    type Mutability = MainThreadOnly;
+    const NAME: &'static str = "WryPrintDelegate";
+  }
+
+  impl DeclaredClass for WryPrintDelegate {
+    type Ivars = WryPrintDelegateIvars;
+  }
+
+  unsafe impl WryPrintDelegate {
+    #[method(printOperationDidRun:success:contextInfo:)]
+    fn print_operation_did_run(
+      &self,
+      _operation: &AnyObject,
+      _success: Bool,
+      context_info: *mut c_void,
+    ) {
+      unsafe {
+        self.ivars().preferences.setValue_forKey(
+          Some(&NSNumber::new_bool(self.ivars().print_backgrounds)),
+          ns_string!("shouldPrintBackgrounds"),
+        );
+        // the delegate was kept alive for the print job by `WryPrintDelegate::run`
+        drop(Retained::from_raw(context_info.cast::<Self>()));
+      }
+    }
+  }
+
+  unsafe impl NSObjectProtocol for WryPrintDelegate {}
+);
+
+impl WryPrintDelegate {
+  /// Run `operation` modally for `window`, printing the backgrounds according to
+  /// `print_backgrounds` for this job only.
+  ///
+  /// The `shouldPrintBackgrounds` preference must be supported, it's available on macOS 13.3+.
+  pub fn run(
+    operation: &NSPrintOperation,
+    window: &NSWindow,
+    preferences: Retained<WKPreferences>,
+    print_backgrounds: bool,
+    mtm: MainThreadMarker,
+  ) {
+    unsafe {
+      let previous: Bool = msg_send![&preferences, shouldPrintBackgrounds];
+      preferences.setValue_forKey(
+        Some(&NSNumber::new_bool(print_backgrounds)),
+        ns_string!("shouldPrintBackgrounds"),
+      );
+
+      let delegate = mtm.alloc::<Self>().set_ivars(WryPrintDelegateIvars {
+        preferences,
+        print_backgrounds: previous.as_bool(),
+      });
+      let delegate: Retained<Self> = msg_send_id![super(delegate), init];
+
+      operation.runOperationModalForWindow_delegate_didRunSelector_contextInfo(
+        window,
+        Some(&delegate),
+        Some(sel!(printOperationDidRun:success:contextInfo:)),
+        Retained::into_raw(delegate.clone()).cast(),
+      );
+    }
+  }
+}
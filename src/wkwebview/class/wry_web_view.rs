@@ -2,6 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+#[cfg(target_os = "macos")]
+use std::cell::RefCell;
 use std::collections::HashMap;
 
 #[cfg(target_os = "macos")]
@@ -10,16 +12,21 @@ use objc2::{
   declare_class, mutability::MainThreadOnly, rc::Retained, runtime::Bool, ClassType, DeclaredClass,
 };
 #[cfg(target_os = "macos")]
-use objc2_app_kit::{NSDraggingDestination, NSEvent};
+use objc2_app_kit::{NSDraggingDestination, NSEvent, NSView};
 use objc2_foundation::{NSObjectProtocol, NSUUID};
+#[cfg(target_os = "macos")]
+use objc2_foundation::{NSPoint, NSRect, NSSize};
 
 #[cfg(target_os = "ios")]
 use crate::wkwebview::ios::WKWebView::WKWebView;
+use crate::CancellationToken;
 #[cfg(target_os = "macos")]
 use crate::{
   wkwebview::{drag_drop, synthetic_mouse_events},
-  DragDropEvent,
+  DragDropEvent, Rect,
 };
+#[cfg(target_os = "macos")]
+use dpi::{LogicalPosition, LogicalSize};
 #[cfg(target_os = "ios")]
 use objc2_ui_kit::UIEvent as NSEvent;
 #[cfg(target_os = "macos")]
@@ -31,7 +38,16 @@ pub struct WryWebViewIvars {
   pub(crate) drag_drop_handler: Box<dyn Fn(DragDropEvent) -> bool>,
   #[cfg(target_os = "macos")]
   pub(crate) accept_first_mouse: objc2::runtime::Bool,
-  pub(crate) custom_protocol_task_ids: HashMap<usize, Retained<NSUUID>>,
+  /// Whether the back and forward mouse buttons are dispatched to the page by wry.
+  #[cfg(target_os = "macos")]
+  pub(crate) synthetic_mouse_events: bool,
+  /// The regions, in the webview coordinates, where the mouse events fall through.
+  #[cfg(target_os = "macos")]
+  pub(crate) hit_test_ignore_regions: RefCell<Vec<NSRect>>,
+  #[cfg(target_os = "macos")]
+  pub(crate) bounds_changed_handler: Option<Box<dyn Fn(Rect)>>,
+  /// The custom protocol tasks in progress, with the token cancelled when WebKit stops them.
+  pub(crate) custom_protocol_task_ids: HashMap<usize, (Retained<NSUUID>, CancellationToken)>,
 }
 
 declare_class!(
@@ -133,22 +149,131 @@ declare_class!(
       synthetic_mouse_events::other_mouse_up(self, event)
     }
   }
+
+  // Hit-test pass-through regions
+  #[cfg(target_os = "macos")]
+  unsafe impl WryWebView {
+    #[method(hitTest:)]
+    fn hit_test(
+      &self,
+      point: NSPoint,
+    ) -> *mut NSView {
+      if self.is_hit_test_ignored(point) {
+        std::ptr::null_mut()
+      } else {
+        unsafe {
+          objc2::msg_send![super(self), hitTest: point]
+        }
+      }
+    }
+  }
+
+  // Bounds changed handler
+  #[cfg(target_os = "macos")]
+  unsafe impl WryWebView {
+    #[method(setFrameOrigin:)]
+    fn set_frame_origin(
+      &self,
+      origin: NSPoint,
+    ) {
+      let changed = unsafe { self.frame().origin } != origin;
+      unsafe {
+        let _: () = objc2::msg_send![super(self), setFrameOrigin: origin];
+      }
+      if changed {
+        self.notify_bounds_changed();
+      }
+    }
+
+    #[method(setFrameSize:)]
+    fn set_frame_size(
+      &self,
+      size: NSSize,
+    ) {
+      let changed = unsafe { self.frame().size } != size;
+      unsafe {
+        let _: () = objc2::msg_send![super(self), setFrameSize: size];
+      }
+      if changed {
+        self.notify_bounds_changed();
+      }
+    }
+  }
 );
 
+#[cfg(target_os = "macos")]
+impl WryWebView {
+  /// Whether the point, in the superview coordinates, is in a hit-test pass-through region.
+  fn is_hit_test_ignored(&self, point: NSPoint) -> bool {
+    let regions = self.ivars().hit_test_ignore_regions.borrow();
+    if regions.is_empty() {
+      return false;
+    }
+
+    let point = unsafe {
+      let mut point = self.convertPoint_fromView(point, self.superview().as_deref());
+      if !self.isFlipped() {
+        point.y = self.bounds().size.height - point.y;
+      }
+      point
+    };
+
+    regions.iter().any(|region| {
+      point.x >= region.origin.x
+        && point.x < region.origin.x + region.size.width
+        && point.y >= region.origin.y
+        && point.y < region.origin.y + region.size.height
+    })
+  }
+}
+
+#[cfg(target_os = "macos")]
+impl WryWebView {
+  /// Call the bounds changed handler with the frame of the webview, in the same coordinates as
+  /// [`crate::WebView::bounds`].
+  fn notify_bounds_changed(&self) {
+    let Some(handler) = &self.ivars().bounds_changed_handler else {
+      return;
+    };
+    let Some(parent) = (unsafe { self.superview() }) else {
+      return;
+    };
+
+    let (frame, parent_frame) = unsafe { (self.frame(), parent.frame()) };
+    let y = parent_frame.size.height - frame.origin.y - frame.size.height;
+    handler(Rect {
+      position: LogicalPosition::new(frame.origin.x, y).into(),
+      size: LogicalSize::new(frame.size.width, frame.size.height).into(),
+    });
+  }
+}
+
 // Custom Protocol Task Checker
 impl WryWebView {
-  pub(crate) fn add_custom_task_key(&mut self, task_id: usize) -> Retained<NSUUID> {
+  pub(crate) fn add_custom_task_key(
+    &mut self,
+    task_id: usize,
+    cancellation: CancellationToken,
+  ) -> Retained<NSUUID> {
     let task_uuid = NSUUID::new();
     self
       .ivars_mut()
       .custom_protocol_task_ids
-      .insert(task_id, task_uuid.clone());
+      .insert(task_id, (task_uuid.clone(), cancellation));
     task_uuid
   }
-  pub(crate) fn remove_custom_task_key(&mut self, task_id: usize) {
-    self.ivars_mut().custom_protocol_task_ids.remove(&task_id);
+  pub(crate) fn remove_custom_task_key(&mut self, task_id: usize) -> Option<CancellationToken> {
+    self
+      .ivars_mut()
+      .custom_protocol_task_ids
+      .remove(&task_id)
+      .map(|(_, cancellation)| cancellation)
   }
   pub(crate) fn get_custom_task_uuid(&self, task_id: usize) -> Option<Retained<NSUUID>> {
-    self.ivars().custom_protocol_task_ids.get(&task_id).cloned()
+    self
+      .ivars()
+      .custom_protocol_task_ids
+      .get(&task_id)
+      .map(|(task_uuid, _)| task_uuid.clone())
   }
 }
@@ -13,7 +13,9 @@ use objc2::{
   ClassType, DeclaredClass,
 };
 use objc2_foundation::{MainThreadMarker, NSObjectProtocol, NSString};
-use objc2_web_kit::{WKScriptMessage, WKScriptMessageHandler, WKUserContentController};
+use objc2_web_kit::{
+  WKContentWorld, WKScriptMessage, WKScriptMessageHandler, WKUserContentController,
+};
 
 pub const IPC_MESSAGE_HANDLER_NAME: &str = "ipc";
 
@@ -83,6 +85,7 @@ impl WryWebViewDelegate {
   pub fn new(
     controller: Retained<WKUserContentController>,
     ipc_handler: Box<dyn Fn(Request<String>)>,
+    content_world: Option<&WKContentWorld>,
     mtm: MainThreadMarker,
   ) -> Retained<Self> {
     let delegate = mtm
@@ -95,12 +98,16 @@ impl WryWebViewDelegate {
     let delegate: Retained<Self> = unsafe { msg_send_id![super(delegate), init] };
 
     let proto_delegate = ProtocolObject::from_ref(delegate.as_ref());
+    let name = NSString::from_str(IPC_MESSAGE_HANDLER_NAME);
+    let controller = &delegate.ivars().controller;
     unsafe {
       // this will increate the retain count of the delegate
-      delegate.ivars().controller.addScriptMessageHandler_name(
-        proto_delegate,
-        &NSString::from_str(IPC_MESSAGE_HANDLER_NAME),
-      );
+      match content_world {
+        Some(world) => {
+          controller.addScriptMessageHandler_contentWorld_name(proto_delegate, world, &name)
+        }
+        None => controller.addScriptMessageHandler_name(proto_delegate, &name),
+      }
     }
 
     delegate
@@ -2,19 +2,25 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+use std::collections::HashMap;
 #[cfg(target_os = "macos")]
 use std::ptr::null_mut;
 
 use block2::Block;
 use objc2::{
-  declare_class, msg_send_id, mutability::MainThreadOnly, rc::Retained, runtime::NSObject,
+  declare_class, msg_send_id,
+  mutability::MainThreadOnly,
+  rc::Retained,
+  runtime::{Bool, NSObject, Sel},
   ClassType, DeclaredClass,
 };
 #[cfg(target_os = "macos")]
-use objc2_app_kit::{NSModalResponse, NSModalResponseOK, NSOpenPanel};
-use objc2_foundation::{MainThreadMarker, NSObjectProtocol};
+use objc2_app_kit::{
+  NSModalResponse, NSModalResponseOK, NSOpenPanel, NSUserInterfaceLayoutDirection,
+};
+use objc2_foundation::{MainThreadMarker, NSObjectProtocol, NSString};
 #[cfg(target_os = "macos")]
-use objc2_foundation::{NSArray, NSURL};
+use objc2_foundation::{NSArray, NSInteger, NSURL};
 
 #[cfg(target_os = "macos")]
 use objc2_web_kit::WKOpenPanelParameters;
@@ -22,9 +28,23 @@ use objc2_web_kit::{
   WKFrameInfo, WKMediaCaptureType, WKPermissionDecision, WKSecurityOrigin, WKUIDelegate,
 };
 
-use crate::WryWebView;
+use crate::{
+  wkwebview::url_from_webview, PermissionRequest, PermissionResponse, UiLayoutDirection, UiString,
+  WryWebView,
+};
+#[cfg(target_os = "macos")]
+use crate::{DisplaySurface, PermissionKind};
 
-pub struct WryWebViewUIDelegateIvars {}
+pub struct WryWebViewUIDelegateIvars {
+  before_unload_handler: Option<Box<dyn Fn(String, String) -> bool>>,
+  close_requested_handler: Option<Box<dyn Fn() -> bool>>,
+  #[cfg_attr(target_os = "ios", allow(dead_code))]
+  permission_handler: Option<Box<dyn Fn(PermissionRequest) -> PermissionResponse>>,
+  #[cfg_attr(target_os = "ios", allow(dead_code))]
+  ui_strings: HashMap<UiString, String>,
+  #[cfg_attr(target_os = "ios", allow(dead_code))]
+  ui_layout_direction: UiLayoutDirection,
+}
 
 declare_class!(
   pub struct WryWebViewUIDelegate;
@@ -59,6 +79,23 @@ declare_class!(
           open_panel.setAllowsMultipleSelection(allow_multi);
           let allow_dir = open_panel_params.allowsDirectories();
           open_panel.setCanChooseDirectories(allow_dir);
+          let ui_strings = &self.ivars().ui_strings;
+          if let Some(prompt) = ui_strings.get(&UiString::OpenPanelPrompt) {
+            open_panel.setPrompt(Some(&NSString::from_str(prompt)));
+          }
+          if let Some(message) = ui_strings.get(&UiString::OpenPanelMessage) {
+            open_panel.setMessage(Some(&NSString::from_str(message)));
+          }
+          let layout_direction = match self.ivars().ui_layout_direction {
+            UiLayoutDirection::Auto => None,
+            UiLayoutDirection::LeftToRight => Some(NSUserInterfaceLayoutDirection::LeftToRight),
+            UiLayoutDirection::RightToLeft => Some(NSUserInterfaceLayoutDirection::RightToLeft),
+          };
+          if let (Some(direction), Some(content_view)) =
+            (layout_direction, open_panel.contentView())
+          {
+            content_view.setUserInterfaceLayoutDirection(direction);
+          }
           let ok: NSModalResponse = open_panel.runModal();
           if ok == NSModalResponseOK {
             let url = open_panel.URLs();
@@ -82,14 +119,94 @@ declare_class!(
       //https://developer.apple.com/documentation/webkit/wkpermissiondecision?language=objc
       (*decision_handler).call((WKPermissionDecision::Grant,));
     }
+
+    #[method(webViewDidClose:)]
+    fn did_close(&self, _webview: &WryWebView) {
+      if let Some(handler) = &self.ivars().close_requested_handler {
+        handler();
+      }
+    }
+  }
+
+  // Private WKUIDelegate methods
+  unsafe impl WryWebViewUIDelegate {
+    // WebKit checks which methods the delegate implements, and keeps its default behavior for
+    // the private ones without a handler
+    #[method(respondsToSelector:)]
+    fn responds_to_selector(&self, selector: Sel) -> Bool {
+      if selector
+        == objc2::sel!(_webView:runBeforeUnloadConfirmPanelWithMessage:initiatedByFrame:completionHandler:)
+        && self.ivars().before_unload_handler.is_none()
+      {
+        return Bool::NO;
+      }
+      unsafe { objc2::msg_send![super(self), respondsToSelector: selector] }
+    }
+
+    #[method(_webView:runBeforeUnloadConfirmPanelWithMessage:initiatedByFrame:completionHandler:)]
+    fn run_before_unload_confirm_panel(
+      &self,
+      webview: &WryWebView,
+      message: &NSString,
+      _frame: &WKFrameInfo,
+      completion_handler: &Block<dyn Fn(Bool)>
+    ) {
+      let allow = match &self.ivars().before_unload_handler {
+        Some(handler) => {
+          let url = url_from_webview(webview).unwrap_or_default();
+          handler(url, message.to_string())
+        }
+        None => true,
+      };
+      (*completion_handler).call((Bool::new(allow),));
+    }
+
+    #[cfg(target_os = "macos")]
+    #[method(_webView:requestDisplayCapturePermissionForOrigin:initiatedByFrame:withSystemAudio:decisionHandler:)]
+    fn request_display_capture_permission(
+      &self,
+      webview: &WryWebView,
+      _origin: &WKSecurityOrigin,
+      _frame: &WKFrameInfo,
+      _with_system_audio: Bool,
+      decision_handler: &Block<dyn Fn(NSInteger)>
+    ) {
+      let response = match &self.ivars().permission_handler {
+        Some(handler) => handler(PermissionRequest {
+          url: url_from_webview(webview).unwrap_or_default(),
+          kind: PermissionKind::DisplayCapture,
+        }),
+        None => PermissionResponse::Deny,
+      };
+      // WKDisplayCapturePermissionDecision: Deny, ScreenPrompt and WindowPrompt
+      let decision = match response {
+        PermissionResponse::AllowDisplayCapture(DisplaySurface::Window) => 2,
+        PermissionResponse::Allow | PermissionResponse::AllowDisplayCapture(_) => 1,
+        _ => 0,
+      };
+      (*decision_handler).call((decision,));
+    }
   }
 );
 
 impl WryWebViewUIDelegate {
-  pub fn new(mtm: MainThreadMarker) -> Retained<Self> {
+  pub fn new(
+    mtm: MainThreadMarker,
+    before_unload_handler: Option<Box<dyn Fn(String, String) -> bool>>,
+    close_requested_handler: Option<Box<dyn Fn() -> bool>>,
+    permission_handler: Option<Box<dyn Fn(PermissionRequest) -> PermissionResponse>>,
+    ui_strings: HashMap<UiString, String>,
+    ui_layout_direction: UiLayoutDirection,
+  ) -> Retained<Self> {
     let delegate = mtm
       .alloc::<WryWebViewUIDelegate>()
-      .set_ivars(WryWebViewUIDelegateIvars {});
+      .set_ivars(WryWebViewUIDelegateIvars {
+        before_unload_handler,
+        close_requested_handler,
+        permission_handler,
+        ui_strings,
+        ui_layout_direction,
+      });
     unsafe { msg_send_id![super(delegate), init] }
   }
 }
@@ -1,14 +1,23 @@
 use std::{path::PathBuf, ptr::null_mut};
 
-use objc2::{rc::Retained, runtime::ProtocolObject, DeclaredClass};
-use objc2_foundation::{NSData, NSError, NSString, NSURLResponse, NSURL};
-use objc2_web_kit::{WKDownload, WKNavigationAction, WKNavigationResponse};
+use objc2::{
+  rc::Retained,
+  runtime::{NSObject, ProtocolObject},
+  ClassType, DeclaredClass,
+};
+use objc2_foundation::{
+  NSData, NSError, NSFileManager, NSSearchPathDirectory, NSSearchPathDomainMask, NSString,
+  NSURLResponse, NSURL,
+};
+use objc2_web_kit::{WKDownload, WKNavigationAction, WKNavigationResponse, WKScriptMessage};
 
 #[cfg(target_os = "ios")]
 use crate::wkwebview::ios::WKWebView::WKWebView;
 #[cfg(target_os = "macos")]
 use objc2_web_kit::WKWebView;
 
+use crate::blob_download::save_blob_download;
+
 use super::class::{
   wry_download_delegate::WryDownloadDelegate, wry_navigation_delegate::WryNavigationDelegate,
 };
@@ -53,7 +62,14 @@ pub(crate) fn download_policy(
   unsafe {
     let request = download.originalRequest().unwrap();
     let url = request.URL().unwrap().absoluteString().unwrap();
-    let mut path = PathBuf::from(suggested_path.to_string());
+    let destination = this
+      .ivars()
+      .destinations
+      .borrow_mut()
+      .remove(&url.to_string());
+    let mut path = destination
+      .clone()
+      .unwrap_or_else(|| PathBuf::from(suggested_path.to_string()));
 
     let started_fn = &this.ivars().started;
     if let Some(started_fn) = started_fn {
@@ -66,6 +82,10 @@ pub(crate) fn download_policy(
         }
         false => (*completion_handler).call((null_mut(),)),
       };
+    } else if destination.is_some() {
+      let path = NSString::from_str(&path.display().to_string());
+      let ns_url = NSURL::fileURLWithPath_isDirectory(&path, false);
+      (*completion_handler).call((Retained::as_ptr(&ns_url),))
     } else {
       #[cfg(feature = "tracing")]
       tracing::warn!("WebView instance is dropped! This navigation handler shouldn't be called.");
@@ -104,3 +124,32 @@ pub(crate) fn download_did_fail(
     }
   }
 }
+
+// Blob download handler, receives the content of the blob from the script
+pub(crate) fn blob_download_message(this: &WryDownloadDelegate, msg: &WKScriptMessage) {
+  unsafe {
+    let body = msg.body();
+    let is_string = Retained::cast::<NSObject>(body.clone()).isKindOfClass(NSString::class());
+    if !is_string {
+      return;
+    }
+
+    let body = Retained::cast::<NSString>(body);
+    if let Some(started_fn) = &this.ivars().started {
+      let download_dir = NSFileManager::defaultManager()
+        .URLsForDirectory_inDomains(
+          NSSearchPathDirectory::NSDownloadsDirectory,
+          NSSearchPathDomainMask::NSUserDomainMask,
+        )
+        .firstObject()
+        .and_then(|url| url.path())
+        .map(|path| PathBuf::from(path.to_string()));
+      save_blob_download(
+        &body.to_string(),
+        download_dir.as_deref(),
+        &mut **started_fn.borrow_mut(),
+        this.ivars().completed.as_deref(),
+      );
+    }
+  }
+}
@@ -42,7 +42,11 @@ pub(crate) fn dragging_entered(
   let position = (dl.x as i32, (frame.size.height - dl.y) as i32);
 
   let listener = &this.ivars().drag_drop_handler;
-  if !listener(DragDropEvent::Enter { paths, position }) {
+  if !listener(DragDropEvent::Enter {
+    paths: paths.clone(),
+    raw_paths: paths,
+    position,
+  }) {
     // Reject the Wry file drop (invoke the OS default behaviour)
     unsafe { objc2::msg_send![super(this), draggingEntered: drag_info] }
   } else {
@@ -87,7 +91,11 @@ pub(crate) fn perform_drag_operation(
   let position = (dl.x as i32, (frame.size.height - dl.y) as i32);
 
   let listener = &this.ivars().drag_drop_handler;
-  if !listener(DragDropEvent::Drop { paths, position }) {
+  if !listener(DragDropEvent::Drop {
+    paths: paths.clone(),
+    raw_paths: paths,
+    position,
+  }) {
     // Reject the Wry drop (invoke the OS default behaviour)
     unsafe { objc2::msg_send![super(this), performDragOperation: drag_info] }
   } else {
@@ -157,6 +157,14 @@ extern_methods!(
     #[method_id(@__retain_semantics Other loadRequest:)]
     pub unsafe fn loadRequest(&self, request: &NSURLRequest) -> Option<Retained<WKNavigation>>;
 
+    // #[cfg(all(feature = "WKDownload", feature = "block2"))]
+    #[method(startDownloadUsingRequest:completionHandler:)]
+    pub unsafe fn startDownloadUsingRequest_completionHandler(
+      &self,
+      request: &NSURLRequest,
+      completion_handler: &block2::Block<dyn Fn(NonNull<WKDownload>)>,
+    );
+
     // #[cfg(feature = "WKNavigation")]
     #[method_id(@__retain_semantics Other loadFileURL:allowingReadAccessToURL:)]
     pub unsafe fn loadFileURL_allowingReadAccessToURL(
@@ -239,7 +247,6 @@ extern_methods!(
       completion_handler: Option<&block2::Block<dyn Fn(*mut AnyObject, *mut NSError)>>,
     );
 
-    #[cfg(target_os = "macos")]
     // #[cfg(all(
     //   feature = "WKContentWorld",
     //   feature = "WKFrameInfo",
@@ -254,7 +261,6 @@ extern_methods!(
       completion_handler: Option<&block2::Block<dyn Fn(*mut AnyObject, *mut NSError)>>,
     );
 
-    #[cfg(target_os = "macos")]
     // #[cfg(all(
     //   feature = "WKContentWorld",
     //   feature = "WKFrameInfo",
@@ -17,10 +17,9 @@ mod ios;
 
 mod class;
 pub use class::wry_web_view::WryWebView;
-#[cfg(target_os = "macos")]
-use class::wry_web_view_parent::WryWebViewParent;
 use class::{
   document_title_changed_observer::*,
+  media_playback_observer::MediaPlaybackObserver,
   url_scheme_handler,
   wry_download_delegate::WryDownloadDelegate,
   wry_navigation_delegate::WryNavigationDelegate,
@@ -28,6 +27,8 @@ use class::{
   wry_web_view_delegate::{WryWebViewDelegate, IPC_MESSAGE_HANDLER_NAME},
   wry_web_view_ui_delegate::WryWebViewUIDelegate,
 };
+#[cfg(target_os = "macos")]
+use class::{wry_print_delegate::WryPrintDelegate, wry_web_view_parent::WryWebViewParent};
 
 use dpi::{LogicalPosition, LogicalSize};
 #[cfg(target_os = "macos")]
@@ -39,19 +40,18 @@ use objc2::{
 };
 #[cfg(target_os = "macos")]
 use objc2_app_kit::{NSApplication, NSAutoresizingMaskOptions, NSTitlebarSeparatorStyle, NSView};
-#[cfg(target_os = "macos")]
-use objc2_foundation::CGSize;
 use objc2_foundation::{
-  ns_string, CGPoint, CGRect, MainThreadMarker, NSArray, NSBundle, NSDate, NSError, NSHTTPCookie,
-  NSHTTPCookieSameSiteLax, NSHTTPCookieSameSiteStrict, NSJSONSerialization, NSMutableURLRequest,
-  NSNumber, NSObjectNSKeyValueCoding, NSObjectProtocol, NSString, NSUTF8StringEncoding, NSURL,
-  NSUUID,
+  ns_string, CGPoint, CGRect, CGSize, MainThreadMarker, NSArray, NSBundle, NSDate, NSError,
+  NSHTTPCookie, NSHTTPCookieSameSiteLax, NSHTTPCookieSameSiteStrict, NSJSONSerialization,
+  NSMutableURLRequest, NSNumber, NSObjectNSKeyValueCoding, NSObjectProtocol, NSSet, NSString,
+  NSURLRequest, NSURLRequestCachePolicy, NSUTF8StringEncoding, NSURL, NSUUID,
 };
 #[cfg(target_os = "ios")]
 use objc2_ui_kit::{UIScrollView, UIViewAutoresizing};
 
 #[cfg(target_os = "macos")]
-use objc2_app_kit::NSWindow;
+use objc2_app_kit::{NSWindow, NSWindowOcclusionState};
+use objc2_foundation::{NSNotification, NSNotificationCenter};
 #[cfg(target_os = "ios")]
 use objc2_ui_kit::UIView as NSView;
 // #[cfg(target_os = "ios")]
@@ -63,19 +63,23 @@ use crate::wkwebview::ios::WKWebView::WKWebView;
 use objc2_web_kit::WKWebView;
 
 use objc2_web_kit::{
-  WKAudiovisualMediaTypes, WKURLSchemeHandler, WKUserContentController, WKUserScript,
-  WKUserScriptInjectionTime, WKWebViewConfiguration, WKWebsiteDataStore,
+  WKAudiovisualMediaTypes, WKContentWorld, WKDownload, WKURLSchemeHandler, WKUserContentController,
+  WKUserScript, WKUserScriptInjectionTime, WKWebViewConfiguration, WKWebsiteDataRecord,
+  WKWebsiteDataStore, WKWebsiteDataTypeDiskCache, WKWebsiteDataTypeMemoryCache,
 };
 use once_cell::sync::Lazy;
 use raw_window_handle::{HasWindowHandle, RawWindowHandle};
 
 use std::{
+  cell::RefCell,
   collections::{HashMap, HashSet},
   ffi::{c_void, CString},
   net::Ipv4Addr,
   os::raw::c_char,
   panic::AssertUnwindSafe,
+  path::{Path, PathBuf},
   ptr::{null_mut, NonNull},
+  rc::Rc,
   str::{self, FromStr},
   sync::{Arc, Mutex},
 };
@@ -88,7 +92,16 @@ use crate::{
   },
 };
 
-use crate::{Error, Rect, RequestAsyncResponder, Result, WebViewAttributes, RGBA};
+#[cfg(target_os = "macos")]
+use crate::DragRegion;
+use crate::{
+  blob_download::{BLOB_DOWNLOAD_MESSAGE_HANDLER_NAME, BLOB_DOWNLOAD_SCRIPT},
+  ipc_length_check_script, page_world_ipc_script, remove_user_style_sheet_script,
+  user_style_sheet_script, ApiCapabilities, CacheMode, Engine, EngineInfo, Error, FileAccessPolicy,
+  NavigationState, PersistentHeaders, PlatformWebViewHandle, PrintCapabilities, PrintOptions, Rect,
+  RequestAsyncResponder, Result, VisibilityTracker, WebViewAttributes, WindowActivation,
+  FETCH_WORLD_NAME, ISOLATED_WORLD_IPC_LISTENER_SCRIPT, ISOLATED_WORLD_NAME, RGBA,
+};
 
 use http::Request;
 
@@ -97,19 +110,6 @@ use crate::util::Counter;
 static COUNTER: Counter = Counter::new();
 static WEBVIEW_IDS: Lazy<Mutex<HashSet<String>>> = Lazy::new(Default::default);
 
-#[derive(Debug, Default, Copy, Clone)]
-pub struct PrintMargin {
-  pub top: f32,
-  pub right: f32,
-  pub bottom: f32,
-  pub left: f32,
-}
-
-#[derive(Debug, Default, Clone)]
-pub struct PrintOptions {
-  pub margins: PrintMargin,
-}
-
 pub(crate) struct InnerWebView {
   id: String,
   pub webview: Retained<WryWebView>,
@@ -119,6 +119,15 @@ pub(crate) struct InnerWebView {
   #[allow(dead_code)]
   is_child: bool,
   pending_scripts: Arc<Mutex<Option<Vec<String>>>>,
+  /// The user scripts added with [`InnerWebView::init`], kept to add them back when a style sheet is
+  /// removed as `WKUserContentController` can only remove all the user scripts at once.
+  user_scripts: RefCell<Vec<Retained<WKUserScript>>>,
+  user_style_sheets: RefCell<Vec<(String, Retained<WKUserScript>)>>,
+  /// The world of the IPC bridge and the initialization scripts, if isolated.
+  content_world: Option<Retained<WKContentWorld>>,
+  cache_mode: CacheMode,
+  persistent_headers: Option<Rc<PersistentHeaders>>,
+  navigation_state: Rc<NavigationState>,
   // Note that if following functions signatures are changed in the future,
   // all functions pointer declarations in objc callbacks below all need to get updated.
   ipc_handler_delegate: Option<Retained<WryWebViewDelegate>>,
@@ -127,10 +136,18 @@ pub(crate) struct InnerWebView {
   document_title_changed_observer: Option<Retained<DocumentTitleChangedObserver>>,
   #[allow(dead_code)]
   // We need this the keep the reference count
+  media_playback_observer: Option<Retained<MediaPlaybackObserver>>,
+  visibility_tracker: Option<Rc<VisibilityTracker>>,
+  #[cfg(target_os = "macos")]
+  window_occlusion_observer: Option<Retained<ProtocolObject<dyn NSObjectProtocol>>>,
+  #[cfg(target_os = "ios")]
+  virtual_keyboard_observers: Vec<Retained<ProtocolObject<dyn NSObjectProtocol>>>,
+  #[allow(dead_code)]
+  // We need this the keep the reference count
   navigation_policy_delegate: Retained<WryNavigationDelegate>,
   #[allow(dead_code)]
   // We need this the keep the reference count
-  download_delegate: Option<Retained<WryDownloadDelegate>>,
+  download_delegate: Retained<WryDownloadDelegate>,
   #[allow(dead_code)]
   // We need this the keep the reference count
   ui_delegate: Retained<WryWebViewUIDelegate>,
@@ -198,19 +215,23 @@ impl InnerWebView {
       #[cfg(target_os = "ios")]
       let custom_data_store_available = os_version.0 >= 17;
 
-      let data_store = match (
-        attributes.incognito,
-        custom_data_store_available,
-        pl_attrs.data_store_identifier,
-      ) {
-        (true, _, _) => WKWebsiteDataStore::nonPersistentDataStore(),
-        // if data_store_identifier is given and custom data stores are available, use custom store
-        (false, true, Some(data_store)) => {
-          let identifier = NSUUID::from_bytes(data_store);
-          WKWebsiteDataStore::dataStoreForIdentifier(&identifier)
-        }
-        // default data store
-        _ => WKWebsiteDataStore::defaultDataStore(),
+      let data_store = match &pl_attrs.session {
+        // the duplicates of a webview use its data store
+        Some(session) => session.data_store.clone(),
+        None => match (
+          attributes.incognito || attributes.ephemeral_storage,
+          custom_data_store_available,
+          pl_attrs.data_store_identifier,
+        ) {
+          (true, _, _) => WKWebsiteDataStore::nonPersistentDataStore(),
+          // if data_store_identifier is given and custom data stores are available, use custom store
+          (false, true, Some(data_store)) => {
+            let identifier = NSUUID::from_bytes(data_store);
+            WKWebsiteDataStore::dataStoreForIdentifier(&identifier)
+          }
+          // default data store
+          _ => WKWebsiteDataStore::defaultDataStore(),
+        },
       };
 
       // Register Custom Protocols
@@ -251,6 +272,12 @@ impl InnerWebView {
         },
         #[cfg(target_os = "macos")]
         accept_first_mouse: Bool::new(attributes.accept_first_mouse),
+        #[cfg(target_os = "macos")]
+        synthetic_mouse_events: pl_attrs.synthetic_mouse_events,
+        #[cfg(target_os = "macos")]
+        hit_test_ignore_regions: Default::default(),
+        #[cfg(target_os = "macos")]
+        bounds_changed_handler: attributes.bounds_changed_handler,
         custom_protocol_task_ids: HashMap::new(),
       });
 
@@ -283,6 +310,17 @@ impl InnerWebView {
       #[cfg(target_os = "ios")]
       config.setValue_forKey(Some(&_yes), ns_string!("allowsInlineMediaPlayback"));
 
+      // limitsNavigationsToAppBoundDomains is only available on iOS 14+
+      #[cfg(target_os = "ios")]
+      if !attributes.navigation_allowlist.is_empty()
+        && config.respondsToSelector(objc2::sel!(setLimitsNavigationsToAppBoundDomains:))
+      {
+        config.setValue_forKey(
+          Some(&_yes),
+          ns_string!("limitsNavigationsToAppBoundDomains"),
+        );
+      }
+
       if attributes.autoplay {
         config.setMediaTypesRequiringUserActionForPlayback(
           WKAudiovisualMediaTypes::WKAudiovisualMediaTypeNone,
@@ -300,6 +338,44 @@ impl InnerWebView {
       // Equivalent Obj-C:
       _preference.setValue_forKey(Some(&_yes), ns_string!("fullScreenEnabled"));
 
+      // Calls the private `_setScreenCaptureEnabled:`, without it `getDisplayMedia` is undefined
+      #[cfg(target_os = "macos")]
+      if attributes.permission_handler.is_some()
+        && _preference.respondsToSelector(objc2::sel!(_setScreenCaptureEnabled:))
+      {
+        _preference.setValue_forKey(Some(&_yes), ns_string!("screenCaptureEnabled"));
+      }
+
+      // Only disables the scripts of the pages, the initialization scripts and `evaluate_script` still run
+      #[allow(deprecated)]
+      _preference.setJavaScriptEnabled(attributes.javascript_enabled);
+
+      if !attributes.images_enabled {
+        let no = NSNumber::numberWithBool(false);
+        // Calls the private `_setLoadsImagesAutomatically:`
+        _preference.setValue_forKey(Some(&no), ns_string!("loadsImagesAutomatically"));
+      }
+
+      // The navigations to file URLs are blocked by the navigation handler when they are denied
+      if attributes.file_access != FileAccessPolicy::Default {
+        let from_file_urls = NSNumber::numberWithBool(matches!(
+          attributes.file_access,
+          FileAccessPolicy::AllowFiles | FileAccessPolicy::Universal
+        ));
+        let universal =
+          NSNumber::numberWithBool(attributes.file_access == FileAccessPolicy::Universal);
+        // Calls the private `_setAllowFileAccessFromFileURLs:` and
+        // `_setAllowUniversalAccessFromFileURLs:`
+        _preference.setValue_forKey(
+          Some(&from_file_urls),
+          ns_string!("allowFileAccessFromFileURLs"),
+        );
+        config.setValue_forKey(
+          Some(&universal),
+          ns_string!("allowUniversalAccessFromFileURLs"),
+        );
+      }
+
       #[cfg(target_os = "macos")]
       let webview = {
         let window = ns_view.window().unwrap();
@@ -341,7 +417,15 @@ impl InnerWebView {
       };
       #[cfg(target_os = "ios")]
       let webview = {
-        let frame = ns_view.frame();
+        let frame = if is_child {
+          let scale_factor = ns_view.contentScaleFactor();
+          attributes
+            .bounds
+            .map(|b| ios_frame(b, scale_factor))
+            .unwrap_or_else(|| ns_view.frame())
+        } else {
+          ns_view.frame()
+        };
         let webview: Retained<WryWebView> =
           objc2::msg_send_id![super(webview), initWithFrame:frame configuration:&**config];
         webview
@@ -368,8 +452,15 @@ impl InnerWebView {
       }
       #[cfg(target_os = "ios")]
       {
-        // set all autoresizingmasks
-        webview.setAutoresizingMask(UIViewAutoresizing::from_bits(31).unwrap());
+        if is_child {
+          // fixed element, anchored to the top left corner of the parent view
+          webview.setAutoresizingMask(
+            UIViewAutoresizing::FlexibleRightMargin | UIViewAutoresizing::FlexibleBottomMargin,
+          );
+        } else {
+          // set all autoresizingmasks
+          webview.setAutoresizingMask(UIViewAutoresizing::from_bits(31).unwrap());
+        }
         // let () = msg_send![webview, setAutoresizingMask: 31];
 
         // disable scroll bounce by default
@@ -377,9 +468,28 @@ impl InnerWebView {
         // But not exist in objc2-web-kit
         let scroll_view: Retained<UIScrollView> = objc2::msg_send_id![&webview, scrollView];
         // let scroll_view: Retained<UIScrollView> = webview.ivars().scrollView; // FIXME: not test yet
-        scroll_view.setBounces(false)
+        scroll_view.setBounces(false);
+
+        if attributes.virtual_keyboard_policy == crate::VirtualKeyboardPolicy::Overlay {
+          // WKWebView scrolls its content above the keyboard from these notifications
+          let center = NSNotificationCenter::defaultCenter();
+          for name in [
+            ns_string!("UIKeyboardWillShowNotification"),
+            ns_string!("UIKeyboardWillHideNotification"),
+            ns_string!("UIKeyboardWillChangeFrameNotification"),
+            ns_string!("UIKeyboardDidChangeFrameNotification"),
+          ] {
+            center.removeObserver_name_object(&webview, Some(name), None);
+          }
+        }
       }
 
+      #[cfg(target_os = "ios")]
+      let virtual_keyboard_observers = attributes
+        .text_input_handler
+        .map(|handler| observe_virtual_keyboard(Rc::new(handler)))
+        .unwrap_or_default();
+
       if !attributes.visible {
         webview.setHidden(true);
       }
@@ -397,8 +507,15 @@ impl InnerWebView {
       }
 
       // Message handler
-      let ipc_handler_delegate = if let Some(ipc_handler) = attributes.ipc_handler {
-        let delegate = WryWebViewDelegate::new(manager.clone(), ipc_handler, mtm);
+      let ipc_handler = attributes.ipc_handler;
+      // Isolated world, requires macOS 11+ and iOS 14+
+      let content_world = (attributes.isolated_world
+        && objc2::runtime::AnyClass::get("WKContentWorld").is_some())
+      .then(|| WKContentWorld::worldWithName(&NSString::from_str(ISOLATED_WORLD_NAME)));
+
+      let ipc_handler_delegate = if let Some(ipc_handler) = ipc_handler {
+        let delegate =
+          WryWebViewDelegate::new(manager.clone(), ipc_handler, content_world.as_deref(), mtm);
         Some(delegate)
       } else {
         None
@@ -413,30 +530,74 @@ impl InnerWebView {
           None
         };
 
+      let media_playback_observer = attributes
+        .media_keeps_display_awake
+        .then(|| MediaPlaybackObserver::new(webview.clone()));
+
+      let visibility_tracker = attributes
+        .visibility_changed_handler
+        .map(|handler| Rc::new(VisibilityTracker::new(attributes.visible, handler)));
+
+      #[cfg(target_os = "macos")]
+      let window_occlusion_observer = match (&visibility_tracker, ns_view.window()) {
+        (Some(tracker), Some(window)) => {
+          let tracker = tracker.clone();
+          let window_ = window.clone();
+          let block = block2::RcBlock::new(move |_notification: NonNull<NSNotification>| {
+            tracker.set_window_visible(
+              window_
+                .occlusionState()
+                .contains(NSWindowOcclusionState::NSWindowOcclusionStateVisible),
+            );
+          });
+          Some(
+            NSNotificationCenter::defaultCenter().addObserverForName_object_queue_usingBlock(
+              Some(ns_string!("NSWindowDidChangeOcclusionStateNotification")),
+              Some(window.as_ref()),
+              None,
+              &block,
+            ),
+          )
+        }
+        _ => None,
+      };
+
       let pending_scripts = Arc::new(Mutex::new(Some(Vec::new())));
       let has_download_handler = attributes.download_started_handler.is_some();
-      // Download handler
-      let download_delegate = if attributes.download_started_handler.is_some()
-        || attributes.download_completed_handler.is_some()
-      {
-        let delegate = WryDownloadDelegate::new(
-          attributes.download_started_handler,
-          attributes.download_completed_handler,
-          mtm,
+      // Download handler, always created as it is also used by `download`
+      let download_delegate = WryDownloadDelegate::new(
+        attributes.download_started_handler,
+        attributes.download_completed_handler,
+        mtm,
+      );
+      if has_download_handler {
+        let proto_download_delegate = ProtocolObject::from_ref(download_delegate.as_ref());
+        // this will increase the retain count of the download delegate
+        manager.addScriptMessageHandler_name(
+          proto_download_delegate,
+          &NSString::from_str(BLOB_DOWNLOAD_MESSAGE_HANDLER_NAME),
         );
-        Some(delegate)
-      } else {
-        None
-      };
+      }
 
+      let persistent_headers = attributes.persistent_headers.then(|| {
+        Rc::new(PersistentHeaders::new(
+          attributes.url.as_deref(),
+          attributes.headers.clone(),
+        ))
+      });
+      let navigation_state = Rc::new(NavigationState::new());
       let navigation_policy_delegate = WryNavigationDelegate::new(
         webview.clone(),
         pending_scripts.clone(),
         has_download_handler,
         attributes.navigation_handler,
         attributes.new_window_req_handler,
-        download_delegate.clone(),
+        Some(download_delegate.clone()),
         attributes.on_page_load_handler,
+        persistent_headers.clone(),
+        navigation_state.clone(),
+        attributes.process_failed_handler,
+        attributes.first_paint_handler,
         mtm,
       );
 
@@ -444,18 +605,25 @@ impl InnerWebView {
         ProtocolObject::from_ref(navigation_policy_delegate.as_ref());
       webview.setNavigationDelegate(Some(proto_navigation_policy_delegate));
 
-      let ui_delegate: Retained<WryWebViewUIDelegate> = WryWebViewUIDelegate::new(mtm);
+      let ui_delegate: Retained<WryWebViewUIDelegate> = WryWebViewUIDelegate::new(
+        mtm,
+        attributes.before_unload_handler,
+        attributes.close_requested_handler,
+        attributes.permission_handler,
+        attributes.ui_strings,
+        attributes.ui_layout_direction,
+      );
       let proto_ui_delegate = ProtocolObject::from_ref(ui_delegate.as_ref());
       webview.setUIDelegate(Some(proto_ui_delegate));
 
       // ns window is required for the print operation
       #[cfg(target_os = "macos")]
-      {
+      if let Some(style) = pl_attrs.titlebar_separator {
         let ns_window = ns_view.window().unwrap();
         let can_set_titlebar_style =
           ns_window.respondsToSelector(objc2::sel!(setTitlebarSeparatorStyle:));
         if can_set_titlebar_style {
-          ns_window.setTitlebarSeparatorStyle(NSTitlebarSeparatorStyle::None);
+          ns_window.setTitlebarSeparatorStyle(style.into());
         }
       }
 
@@ -466,8 +634,20 @@ impl InnerWebView {
         ns_view: ns_view.retain(),
         data_store,
         pending_scripts,
+        user_scripts: Default::default(),
+        user_style_sheets: Default::default(),
+        content_world,
+        cache_mode: attributes.cache_mode,
+        persistent_headers,
+        navigation_state,
         ipc_handler_delegate,
         document_title_changed_observer,
+        media_playback_observer,
+        visibility_tracker,
+        #[cfg(target_os = "macos")]
+        window_occlusion_observer,
+        #[cfg(target_os = "ios")]
+        virtual_keyboard_observers,
         navigation_policy_delegate,
         download_delegate,
         ui_delegate,
@@ -476,14 +656,31 @@ impl InnerWebView {
       };
 
       // Initialize scripts
-      w.init(
-r#"Object.defineProperty(window, 'ipc', {
-  value: Object.freeze({postMessage: function(s) {window.webkit.messageHandlers.ipc.postMessage(s);}})
-});"#,
-      );
+      if w.content_world.is_some() {
+        w.init_in_world(&page_world_ipc_script(attributes.ipc_limits), None);
+      }
+      w.init(&format!(
+        r#"Object.defineProperty(window, 'ipc', {{
+  value: Object.freeze({{postMessage: function(message) {{ {} window.webkit.messageHandlers.ipc.postMessage(message);}}}})
+}});"#,
+        ipc_length_check_script(attributes.ipc_limits)
+      ));
+      if w.content_world.is_some() {
+        w.init(ISOLATED_WORLD_IPC_LISTENER_SCRIPT);
+      }
+      if has_download_handler {
+        // The blob download handler is registered in the page world
+        w.init_in_world(BLOB_DOWNLOAD_SCRIPT, None);
+      }
       for js in attributes.initialization_scripts {
         w.init(&js);
       }
+      for js in &pl_attrs.page_world_scripts {
+        w.init_in_world(js, None);
+      }
+      for (css, main_frame_only) in attributes.user_style_sheets {
+        w.add_user_style_sheet(&css, main_frame_only);
+      }
 
       // Set user agent
       if let Some(user_agent) = attributes.user_agent {
@@ -491,7 +688,13 @@ r#"Object.defineProperty(window, 'ipc', {
       }
 
       // Navigation
-      if let Some(url) = attributes.url {
+      let interaction_state = pl_attrs
+        .session
+        .and_then(|session| session.interaction_state);
+      if let Some(interaction_state) = interaction_state {
+        // the history of the duplicated webview, its current page is loaded again
+        let () = objc2::msg_send![&w.webview, setInteractionState: &*interaction_state];
+      } else if let Some(url) = attributes.url {
         w.navigate_to_url(url.as_str(), attributes.headers)?;
       } else if let Some(html) = attributes.html {
         w.navigate_to_string(&html);
@@ -502,6 +705,11 @@ r#"Object.defineProperty(window, 'ipc', {
       {
         if is_child {
           ns_view.addSubview(&webview);
+          if attributes.focused {
+            if let Some(ns_window) = ns_view.window() {
+              ns_window.makeFirstResponder(Some(&webview));
+            }
+          }
         } else {
           let parent_view = WryWebViewParent::new(mtm);
           parent_view.setAutoresizingMask(
@@ -512,19 +720,35 @@ r#"Object.defineProperty(window, 'ipc', {
 
           // inject the webview into the window
           let ns_window = ns_view.window().unwrap();
-          // Tell the webview receive keyboard events in the window.
-          // See https://github.com/tauri-apps/wry/issues/739
-          ns_window.setContentView(Some(&parent_view));
-          ns_window.makeFirstResponder(Some(&webview));
+          if pl_attrs.replace_content_view {
+            // Tell the webview receive keyboard events in the window.
+            // See https://github.com/tauri-apps/wry/issues/739
+            ns_window.setContentView(Some(&parent_view));
+          } else {
+            // on top of the views of the app, resized with the content view
+            ns_view.addSubview(&parent_view);
+            parent_view.setFrame(ns_view.bounds());
+          }
+          if attributes.focused {
+            ns_window.makeFirstResponder(Some(&webview));
+          }
         }
 
-        // make sure the window is always on top when we create a new webview
-        let app = NSApplication::sharedApplication(mtm);
-        if os_version.0 >= 14 {
-          NSApplication::activate(&app);
-        } else {
-          #[allow(deprecated)]
-          NSApplication::activateIgnoringOtherApps(&app, true);
+        // make sure the window is always on top when we create a new webview, unless the app
+        // opted out
+        let activate = pl_attrs.activate_app
+          && match &attributes.window_activation_handler {
+            Some(handler) => handler(WindowActivation::WebViewCreated),
+            None => true,
+          };
+        if activate {
+          let app = NSApplication::sharedApplication(mtm);
+          if os_version.0 >= 14 {
+            NSApplication::activate(&app);
+          } else {
+            #[allow(deprecated)]
+            NSApplication::activateIgnoringOtherApps(&app, true);
+          }
         }
       }
 
@@ -545,6 +769,33 @@ r#"Object.defineProperty(window, 'ipc', {
     url_from_webview(&self.webview)
   }
 
+  pub fn session(&self) -> crate::Result<Session> {
+    let os_version = util::operating_system_version();
+    #[cfg(target_os = "macos")]
+    let interaction_state_available = os_version.0 >= 12;
+    #[cfg(target_os = "ios")]
+    let interaction_state_available = os_version.0 >= 15;
+
+    Ok(Session {
+      data_store: self.data_store.clone(),
+      interaction_state: match interaction_state_available {
+        true => unsafe { objc2::msg_send_id![&self.webview, interactionState] },
+        false => None,
+      },
+    })
+  }
+
+  pub fn platform_handle(&self) -> PlatformWebViewHandle {
+    PlatformWebViewHandle::WkWebView {
+      webview: self.webview.clone(),
+      manager: self.manager.clone(),
+    }
+  }
+
+  pub fn navigation_state(&self) -> &NavigationState {
+    &self.navigation_state
+  }
+
   pub fn eval(&self, js: &str, callback: Option<impl Fn(String) + Send + 'static>) -> Result<()> {
     if let Some(scripts) = &mut *self.pending_scripts.lock().unwrap() {
       scripts.push(js.into());
@@ -560,23 +811,7 @@ r#"Object.defineProperty(window, 'ipc', {
             #[cfg(feature = "tracing")]
             span.lock().unwrap().take();
 
-            let mut result = String::new();
-
-            if !val.is_null() {
-              let json_ns_data = NSJSONSerialization::dataWithJSONObject_options_error(
-                &*val,
-                objc2_foundation::NSJSONWritingOptions::NSJSONWritingFragmentsAllowed,
-              )
-              .unwrap();
-              let json_string = NSString::alloc();
-              let json_string =
-                NSString::initWithData_encoding(json_string, &json_ns_data, NSUTF8StringEncoding)
-                  .unwrap();
-
-              result = json_string.to_string();
-            }
-
-            callback(result);
+            callback(json_string(val));
           });
 
           self
@@ -602,26 +837,178 @@ r#"Object.defineProperty(window, 'ipc', {
     Ok(())
   }
 
+  /// Evaluate `js` in the content world `world`, passing its JSON result to `callback`.
+  /// Content worlds require macOS 11+ and iOS 14+, the page world is used on older versions.
+  #[cfg(feature = "reader")]
+  pub fn eval_in_world(
+    &self,
+    js: &str,
+    world: &str,
+    callback: impl Fn(String) + Send + 'static,
+  ) -> Result<()> {
+    if objc2::runtime::AnyClass::get("WKContentWorld").is_none() {
+      return self.eval(js, Some(callback));
+    }
+
+    unsafe {
+      let world = WKContentWorld::worldWithName(&NSString::from_str(world));
+      let handler = block2::RcBlock::new(move |val: *mut AnyObject, _err: *mut NSError| {
+        callback(json_string(val));
+      });
+      self
+        .webview
+        .evaluateJavaScript_inFrame_inContentWorld_completionHandler(
+          &NSString::from_str(js),
+          None,
+          &world,
+          Some(&handler),
+        );
+    }
+
+    Ok(())
+  }
+
+  /// Run the async function `body` in the [`FETCH_WORLD_NAME`] content world, passing the string
+  /// it returns, or the error it throws, to `callback`.
+  ///
+  /// Content worlds require macOS 11+ and iOS 14+, an error is returned on older versions.
+  pub fn eval_fetch(
+    &self,
+    body: &str,
+    callback: impl FnOnce(std::result::Result<String, String>) + 'static,
+  ) -> Result<()> {
+    if objc2::runtime::AnyClass::get("WKContentWorld").is_none() {
+      return Err(Error::FetchFailed(
+        "isolated worlds require macOS 11+ and iOS 14+".to_string(),
+      ));
+    }
+
+    // the completion handler is called once
+    let callback = RefCell::new(Some(callback));
+    unsafe {
+      let world = WKContentWorld::worldWithName(&NSString::from_str(FETCH_WORLD_NAME));
+      let handler = block2::RcBlock::new(move |val: *mut AnyObject, err: *mut NSError| {
+        let Some(callback) = callback.borrow_mut().take() else {
+          return;
+        };
+        let result = if !err.is_null() {
+          Err((*err).localizedDescription().to_string())
+        } else if !val.is_null() && (*(val as *mut NSObject)).isKindOfClass(NSString::class()) {
+          Ok((*(val as *mut NSString)).to_string())
+        } else {
+          Err("the request returned no response".to_string())
+        };
+        callback(result);
+      });
+      self
+        .webview
+        .callAsyncJavaScript_arguments_inFrame_inContentWorld_completionHandler(
+          &NSString::from_str(body),
+          None,
+          None,
+          &world,
+          Some(&handler),
+        );
+    }
+
+    Ok(())
+  }
+
   fn init(&self, js: &str) {
+    self.init_in_world(js, self.content_world.as_deref());
+  }
+
+  /// Add an initialization script running in `world`, or in the page world if `None`.
+  fn init_in_world(&self, js: &str, world: Option<&WKContentWorld>) {
+    // TODO: feature to allow injecting into subframes
+    let script = self.add_user_script(js, true, world);
+    self.user_scripts.borrow_mut().push(script);
+  }
+
+  fn add_user_script(
+    &self,
+    js: &str,
+    main_frame_only: bool,
+    world: Option<&WKContentWorld>,
+  ) -> Retained<WKUserScript> {
     // Safety: objc runtime calls are unsafe
     unsafe {
       let userscript = WKUserScript::alloc();
-      // TODO: feature to allow injecting into subframes
-      let script = WKUserScript::initWithSource_injectionTime_forMainFrameOnly(
-        userscript,
-        &NSString::from_str(js),
-        WKUserScriptInjectionTime::AtDocumentStart,
-        true,
-      );
+      let script = match world {
+        Some(world) => WKUserScript::initWithSource_injectionTime_forMainFrameOnly_inContentWorld(
+          userscript,
+          &NSString::from_str(js),
+          WKUserScriptInjectionTime::AtDocumentStart,
+          main_frame_only,
+          world,
+        ),
+        None => WKUserScript::initWithSource_injectionTime_forMainFrameOnly(
+          userscript,
+          &NSString::from_str(js),
+          WKUserScriptInjectionTime::AtDocumentStart,
+          main_frame_only,
+        ),
+      };
       self.manager.addUserScript(&script);
+      script
     }
   }
 
+  fn add_user_style_sheet(&self, css: &str, main_frame_only: bool) {
+    let script = self.add_user_script(
+      &user_style_sheet_script(css, main_frame_only),
+      main_frame_only,
+      None,
+    );
+    self
+      .user_style_sheets
+      .borrow_mut()
+      .push((css.to_string(), script));
+  }
+
+  pub fn add_css(&self, css: &str, main_frame_only: bool) -> Result<()> {
+    self.add_user_style_sheet(css, main_frame_only);
+    self.eval(
+      &user_style_sheet_script(css, main_frame_only),
+      None::<Box<dyn Fn(String) + Send + 'static>>,
+    )
+  }
+
+  pub fn remove_css(&self, css: &str) -> Result<()> {
+    let mut user_style_sheets = self.user_style_sheets.borrow_mut();
+    let Some(index) = user_style_sheets.iter().position(|(c, _)| c == css) else {
+      return Ok(());
+    };
+    user_style_sheets.remove(index);
+
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+      self.manager.removeAllUserScripts();
+      for script in self.user_scripts.borrow().iter() {
+        self.manager.addUserScript(script);
+      }
+      for (_, script) in user_style_sheets.iter() {
+        self.manager.addUserScript(script);
+      }
+    }
+
+    self.eval(
+      &remove_user_style_sheet_script(css),
+      None::<Box<dyn Fn(String) + Send + 'static>>,
+    )
+  }
+
   pub fn load_url(&self, url: &str) -> crate::Result<()> {
+    if let Some(persistent_headers) = &self.persistent_headers {
+      persistent_headers.set(url, None);
+    }
     self.navigate_to_url(url, None)
   }
 
   pub fn load_url_with_headers(&self, url: &str, headers: http::HeaderMap) -> crate::Result<()> {
+    if let Some(persistent_headers) = &self.persistent_headers {
+      persistent_headers.set(url, Some(headers.clone()));
+    }
     self.navigate_to_url(url, Some(headers))
   }
 
@@ -642,11 +1029,71 @@ r#"Object.defineProperty(window, 'ipc', {
     Ok(())
   }
 
+  pub fn clear_http_cache(&self) -> Result<()> {
+    unsafe {
+      let cache_data_types =
+        NSSet::from_slice(&[WKWebsiteDataTypeDiskCache, WKWebsiteDataTypeMemoryCache]);
+      let date = NSDate::dateWithTimeIntervalSince1970(0.0);
+      let handler = block2::RcBlock::new(|| {});
+      self
+        .data_store
+        .removeDataOfTypes_modifiedSince_completionHandler(&cache_data_types, &date, &handler);
+    }
+    Ok(())
+  }
+
+  pub fn download(&self, url: &str, destination: &Path) -> crate::Result<()> {
+    // Safety: objc runtime calls are unsafe
+    unsafe {
+      // startDownloadUsingRequest is only available on macOS 11.3+ and iOS 14.5+
+      if !self
+        .webview
+        .respondsToSelector(objc2::sel!(startDownloadUsingRequest:completionHandler:))
+      {
+        #[cfg(feature = "tracing")]
+        tracing::warn!("Programmatic downloads require macOS 11.3+ or iOS 14.5+");
+        return Ok(());
+      }
+
+      url::Url::parse(url)?;
+      let url = NSURL::URLWithString(&NSString::from_str(url)).unwrap();
+      if let Some(key) = url.absoluteString() {
+        self
+          .download_delegate
+          .ivars()
+          .destinations
+          .borrow_mut()
+          .insert(key.to_string(), destination.to_path_buf());
+      }
+
+      let request = NSURLRequest::requestWithURL(&url);
+      let delegate = self.download_delegate.clone();
+      let handler = block2::RcBlock::new(move |download: NonNull<WKDownload>| {
+        let proto_delegate = ProtocolObject::from_ref(delegate.as_ref());
+        download.as_ref().setDelegate(Some(proto_delegate));
+      });
+      self
+        .webview
+        .startDownloadUsingRequest_completionHandler(&request, &handler);
+    }
+
+    Ok(())
+  }
+
   fn navigate_to_url(&self, url: &str, headers: Option<http::HeaderMap>) -> crate::Result<()> {
     // Safety: objc runtime calls are unsafe
     unsafe {
       let url = NSURL::URLWithString(&NSString::from_str(url)).unwrap();
       let mut request = NSMutableURLRequest::requestWithURL(&url);
+      match self.cache_mode {
+        CacheMode::NoCache => {
+          request.setCachePolicy(NSURLRequestCachePolicy::NSURLRequestReloadIgnoringLocalCacheData)
+        }
+        CacheMode::CacheOnly => {
+          request.setCachePolicy(NSURLRequestCachePolicy::NSURLRequestReturnCacheDataDontLoad)
+        }
+        CacheMode::Default => {}
+      }
       if let Some(headers) = headers {
         for (name, value) in headers.iter() {
           let key = NSString::from_str(name.as_str());
@@ -681,6 +1128,32 @@ r#"Object.defineProperty(window, 'ipc', {
     self.print_with_options(&PrintOptions::default())
   }
 
+  #[cfg(target_os = "macos")]
+  pub fn print_capabilities(&self) -> PrintCapabilities {
+    unsafe {
+      let preferences = self.webview.configuration().preferences();
+      PrintCapabilities {
+        margins: true,
+        scale: true,
+        print_backgrounds: preferences.respondsToSelector(objc2::sel!(setShouldPrintBackgrounds:)),
+        ..Default::default()
+      }
+    }
+  }
+
+  #[cfg(target_os = "ios")]
+  pub fn print_capabilities(&self) -> PrintCapabilities {
+    PrintCapabilities::default()
+  }
+
+  pub fn api_capabilities(&self) -> ApiCapabilities {
+    ApiCapabilities {
+      gamepad: true,
+      webauthn: true,
+      ..Default::default()
+    }
+  }
+
   pub fn print_with_options(&self, _options: &PrintOptions) -> crate::Result<()> {
     // Safety: objc runtime calls are unsafe
     #[cfg(target_os = "macos")]
@@ -689,28 +1162,53 @@ r#"Object.defineProperty(window, 'ipc', {
         .webview
         .respondsToSelector(objc2::sel!(printOperationWithPrintInfo:));
       if can_print {
-        // Create a shared print info
-        let print_info = objc2_app_kit::NSPrintInfo::sharedPrintInfo();
-        // let print_info: id = msg_send![print_info, init];
+        use objc2_foundation::NSCopying;
+
+        let mtm = MainThreadMarker::new().ok_or(Error::NotMainThread)?;
+        // A copy of the shared print info, so the options only apply to this print job
+        let print_info = objc2_app_kit::NSPrintInfo::sharedPrintInfo().copy();
         print_info.setTopMargin(_options.margins.top.into());
         print_info.setRightMargin(_options.margins.right.into());
         print_info.setBottomMargin(_options.margins.bottom.into());
         print_info.setLeftMargin(_options.margins.left.into());
+        if let Some(scale) = _options.scale {
+          print_info.dictionary().setValue_forKey(
+            Some(&NSNumber::new_f64(scale)),
+            objc2_app_kit::NSPrintScalingFactor,
+          );
+        }
 
         // Create new print operation from the webview content
         let print_operation = self.webview.printOperationWithPrintInfo(&print_info);
+        print_operation.setShowsPrintPanel(!_options.silent);
+        print_operation.setShowsProgressPanel(!_options.silent);
 
         // Allow the modal to detach from the current thread and be non-blocker
         print_operation.setCanSpawnSeparateThread(true);
 
         // Launch the modal
         let window = self.webview.window().unwrap();
-        print_operation.runOperationModalForWindow_delegate_didRunSelector_contextInfo(
-          &window,
-          None,
-          None,
-          null_mut(),
-        )
+        // available on macOS 13.3+
+        let preferences = self.webview.configuration().preferences();
+        match _options.print_backgrounds {
+          Some(print_backgrounds)
+            if preferences.respondsToSelector(objc2::sel!(setShouldPrintBackgrounds:)) =>
+          {
+            WryPrintDelegate::run(
+              &print_operation,
+              &window,
+              preferences,
+              print_backgrounds,
+              mtm,
+            )
+          }
+          _ => print_operation.runOperationModalForWindow_delegate_didRunSelector_contextInfo(
+            &window,
+            None,
+            None,
+            null_mut(),
+          ),
+        }
       }
     }
 
@@ -765,16 +1263,20 @@ r#"Object.defineProperty(window, 'ipc', {
   pub fn bounds(&self) -> crate::Result<Rect> {
     #[allow(unused_unsafe)]
     unsafe {
-      let parent = self.webview.superview().unwrap();
-      let parent_frame = parent.frame();
       let webview_frame = self.webview.frame();
 
+      #[cfg(target_os = "macos")]
+      let y = {
+        let parent = self.webview.superview().unwrap();
+        let parent_frame = parent.frame();
+        parent_frame.size.height - webview_frame.origin.y - webview_frame.size.height
+      };
+      // UIKit uses a top-left origin so there's no need to flip the y axis
+      #[cfg(target_os = "ios")]
+      let y = webview_frame.origin.y;
+
       Ok(Rect {
-        position: LogicalPosition::new(
-          webview_frame.origin.x,
-          parent_frame.size.height - webview_frame.origin.y - webview_frame.size.height,
-        )
-        .into(),
+        position: LogicalPosition::new(webview_frame.origin.x, y).into(),
         size: LogicalSize::new(webview_frame.size.width, webview_frame.size.height).into(),
       })
     }
@@ -798,14 +1300,98 @@ r#"Object.defineProperty(window, 'ipc', {
       }
     }
 
+    #[cfg(target_os = "ios")]
+    if self.is_child {
+      let scale_factor = self.webview.contentScaleFactor();
+      self.webview.setFrame(ios_frame(bounds, scale_factor));
+    }
+
+    Ok(())
+  }
+
+  pub fn set_hit_test_ignore_regions(&self, #[allow(unused)] regions: Vec<Rect>) -> Result<()> {
+    #[cfg(target_os = "macos")]
+    {
+      let scale_factor = self
+        .webview
+        .window()
+        .map_or(1.0, |window| window.backingScaleFactor());
+      *self.webview.ivars().hit_test_ignore_regions.borrow_mut() = regions
+        .into_iter()
+        .map(|rect| {
+          let (x, y) = rect.position.to_logical::<f64>(scale_factor).into();
+          let (width, height) = rect.size.to_logical::<f64>(scale_factor).into();
+          CGRect {
+            origin: CGPoint::new(x, y),
+            size: CGSize::new(width, height),
+          }
+        })
+        .collect();
+    }
+
+    Ok(())
+  }
+
+  pub fn set_javascript_enabled(&self, enabled: bool) -> Result<()> {
+    unsafe {
+      #[allow(deprecated)]
+      self
+        .webview
+        .configuration()
+        .preferences()
+        .setJavaScriptEnabled(enabled);
+    }
+    Ok(())
+  }
+
+  pub fn set_images_enabled(&self, enabled: bool) -> Result<()> {
+    unsafe {
+      let enabled = NSNumber::numberWithBool(enabled);
+      // Calls the private `_setLoadsImagesAutomatically:`
+      self
+        .webview
+        .configuration()
+        .preferences()
+        .setValue_forKey(Some(&enabled), ns_string!("loadsImagesAutomatically"));
+    }
     Ok(())
   }
 
   pub fn set_visible(&self, visible: bool) -> Result<()> {
     self.webview.setHidden(!visible);
+    if let Some(visibility_tracker) = &self.visibility_tracker {
+      visibility_tracker.set_webview_visible(visible);
+    }
+    Ok(())
+  }
+
+  /// Show or hide the content of the webview, which keeps painting unlike with
+  /// [`Self::set_visible`].
+  #[cfg(target_os = "macos")]
+  pub fn set_content_visible(&self, visible: bool) -> Result<()> {
+    self.webview.setAlphaValue(if visible { 1.0 } else { 0.0 });
     Ok(())
   }
 
+  /// Move the window of the webview with the mouse, for the drag regions.
+  #[cfg(target_os = "macos")]
+  pub fn drag_window(&self, region: DragRegion) {
+    // AppKit doesn't expose resizing the window from an edge
+    let (DragRegion::Move, Some(mtm)) = (region, MainThreadMarker::new()) else {
+      return;
+    };
+
+    unsafe {
+      let (Some(window), Some(event)) = (
+        self.webview.window(),
+        NSApplication::sharedApplication(mtm).currentEvent(),
+      ) else {
+        return;
+      };
+      window.performWindowDragWithEvent(&event);
+    }
+  }
+
   pub fn focus(&self) -> Result<()> {
     #[cfg(target_os = "macos")]
     {
@@ -828,6 +1414,10 @@ r#"Object.defineProperty(window, 'ipc', {
     Ok(())
   }
 
+  pub fn dispatcher(&self) -> MainThreadDispatcher {
+    MainThreadDispatcher
+  }
+
   unsafe fn cookie_from_wkwebview(cookie: &NSHTTPCookie) -> cookie::Cookie<'static> {
     let name = cookie.name().to_string();
     let value = cookie.value().to_string();
@@ -917,6 +1507,117 @@ r#"Object.defineProperty(window, 'ipc', {
     }
   }
 
+  pub fn renderer_pid(&self) -> Result<Option<u32>> {
+    // private API, 0 until the web content process is launched
+    if !NSObject::respondsToSelector(&self.webview, objc2::sel!(_webProcessIdentifier)) {
+      return Ok(None);
+    }
+    let pid: libc::pid_t = unsafe { objc2::msg_send![&self.webview, _webProcessIdentifier] };
+    Ok((pid > 0).then_some(pid as u32))
+  }
+
+  pub fn engine_info(&self) -> Result<EngineInfo> {
+    // WebKit doesn't expose the directory of its website data stores
+    platform_engine_info()
+  }
+
+  pub fn history_length(&self) -> Result<Option<usize>> {
+    #[cfg(target_os = "macos")]
+    let length = unsafe {
+      let list = self.webview.backForwardList();
+      let current = list.currentItem().is_some() as usize;
+      Some(list.backList().count() + list.forwardList().count() + current)
+    };
+    // the iOS bindings of `WKWebView` don't expose its back/forward list
+    #[cfg(target_os = "ios")]
+    let length = None;
+    Ok(length)
+  }
+
+  pub fn memory_usage(&self, handler: impl FnOnce(Option<u64>) + 'static) -> Result<()> {
+    handler(self.resident_size()?);
+    Ok(())
+  }
+
+  fn resident_size(&self) -> Result<Option<u64>> {
+    #[cfg(target_os = "macos")]
+    if let Some(pid) = self.renderer_pid()? {
+      let mut info = std::mem::MaybeUninit::<libc::proc_taskinfo>::uninit();
+      let size = std::mem::size_of::<libc::proc_taskinfo>() as libc::c_int;
+      let written = unsafe {
+        libc::proc_pidinfo(
+          pid as libc::c_int,
+          libc::PROC_PIDTASKINFO,
+          0,
+          info.as_mut_ptr().cast(),
+          size,
+        )
+      };
+      if written == size {
+        return Ok(Some(unsafe { info.assume_init() }.pti_resident_size));
+      }
+    }
+
+    Ok(None)
+  }
+
+  pub fn storage_usage(&self) -> Result<Vec<crate::OriginStorageUsage>> {
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    unsafe {
+      self.data_store.fetchDataRecordsOfTypes_completionHandler(
+        &WKWebsiteDataStore::allWebsiteDataTypes(),
+        &block2::RcBlock::new(move |records: NonNull<NSArray<WKWebsiteDataRecord>>| {
+          let usage = records
+            .as_ref()
+            .to_vec()
+            .into_iter()
+            .map(|record| crate::OriginStorageUsage {
+              origin: record.displayName().to_string(),
+              // WebKit doesn't expose the size of the data
+              cache: None,
+              indexed_db: None,
+              local_storage: None,
+            })
+            .collect();
+          let _ = tx.send(usage);
+        }),
+      );
+
+      wait_for_blocking_operation(rx)
+    }
+  }
+
+  pub fn remove_origin_data(&self, origin: &str) -> Result<()> {
+    let origin = origin.to_string();
+    let data_store = self.data_store.clone();
+
+    unsafe {
+      self.data_store.fetchDataRecordsOfTypes_completionHandler(
+        &WKWebsiteDataStore::allWebsiteDataTypes(),
+        &block2::RcBlock::new(move |records: NonNull<NSArray<WKWebsiteDataRecord>>| {
+          let records: Vec<_> = records
+            .as_ref()
+            .to_vec_retained()
+            .into_iter()
+            .filter(|record| {
+              crate::origin_matches_domain(&origin, &record.displayName().to_string())
+            })
+            .collect();
+          if !records.is_empty() {
+            data_store.removeDataOfTypes_forDataRecords_completionHandler(
+              &WKWebsiteDataStore::allWebsiteDataTypes(),
+              &NSArray::from_vec(records),
+              &block2::RcBlock::new(|| {}),
+            );
+          }
+        }),
+      );
+    }
+
+    Ok(())
+  }
+
   #[cfg(target_os = "macos")]
   pub(crate) fn reparent(&self, window: *mut NSWindow) -> crate::Result<()> {
     unsafe {
@@ -928,6 +1629,23 @@ r#"Object.defineProperty(window, 'ipc', {
   }
 }
 
+/// The JSON of the result of a script, or an empty string if it has none.
+unsafe fn json_string(val: *mut AnyObject) -> String {
+  if val.is_null() {
+    return String::new();
+  }
+
+  let json_ns_data = NSJSONSerialization::dataWithJSONObject_options_error(
+    &*val,
+    objc2_foundation::NSJSONWritingOptions::NSJSONWritingFragmentsAllowed,
+  )
+  .unwrap();
+  let json_string = NSString::alloc();
+  let json_string =
+    NSString::initWithData_encoding(json_string, &json_ns_data, NSUTF8StringEncoding).unwrap();
+  json_string.to_string()
+}
+
 pub fn url_from_webview(webview: &WKWebView) -> Result<String> {
   let url_obj = unsafe { webview.URL().unwrap() };
   let absolute_url = unsafe { url_obj.absoluteString().unwrap() };
@@ -960,6 +1678,62 @@ pub fn platform_webview_version() -> Result<String> {
   }
 }
 
+pub fn platform_engine_info() -> Result<EngineInfo> {
+  let runtime_path = unsafe {
+    NSBundle::bundleWithIdentifier(&NSString::from_str("com.apple.WebKit"))
+      .map(|bundle| PathBuf::from(bundle.bundlePath().to_string()))
+  };
+
+  Ok(EngineInfo {
+    engine: Engine::WebKit,
+    version: platform_webview_version()?,
+    runtime_channel: None,
+    runtime_path,
+    user_data_dir: None,
+  })
+}
+
+/// The session of a webview, shared with its duplicates, see [`crate::WebView::duplicate`].
+#[derive(Clone)]
+pub(crate) struct Session {
+  data_store: Retained<WKWebsiteDataStore>,
+  /// The history of the webview, `interactionState` is available on macOS 12 and iOS 15.
+  interaction_state: Option<Retained<AnyObject>>,
+}
+
+/// Remove the `WKWebsiteDataStore` of a profile, see [`crate::WebContext::delete_profile`].
+pub(crate) fn remove_data_store(identifier: [u8; 16]) {
+  let os_version = util::operating_system_version();
+  #[cfg(target_os = "macos")]
+  let custom_data_store_available = os_version.0 >= 14;
+  #[cfg(target_os = "ios")]
+  let custom_data_store_available = os_version.0 >= 17;
+  if !custom_data_store_available {
+    return;
+  }
+
+  unsafe {
+    let identifier = NSUUID::from_bytes(identifier);
+    let handler = block2::RcBlock::new(|_error: *mut NSError| {
+      #[cfg(feature = "tracing")]
+      if !_error.is_null() {
+        tracing::warn!("failed to remove the data store of a profile");
+      }
+    });
+    WKWebsiteDataStore::removeDataStoreForIdentifier_completionHandler(&identifier, &handler);
+  }
+}
+
+/// A [`Send`] handle to run closures on the thread of a webview.
+#[derive(Clone)]
+pub(crate) struct MainThreadDispatcher;
+
+impl MainThreadDispatcher {
+  pub fn dispatch(&self, f: Box<dyn FnOnce() + Send>) {
+    util::dispatch_main(f);
+  }
+}
+
 impl Drop for InnerWebView {
   fn drop(&mut self) {
     WEBVIEW_IDS.lock().unwrap().remove(&self.id);
@@ -968,13 +1742,29 @@ impl Drop for InnerWebView {
     unsafe {
       if let Some(ipc_handler) = self.ipc_handler_delegate.take() {
         let ipc = NSString::from_str(IPC_MESSAGE_HANDLER_NAME);
+        let controller = &ipc_handler.ivars().controller;
         // this will decrease the retain count of the ipc handler and trigger the drop
-        ipc_handler
-          .ivars()
-          .controller
-          .removeScriptMessageHandlerForName(&ipc);
+        match &self.content_world {
+          Some(world) => controller.removeScriptMessageHandlerForName_contentWorld(&ipc, world),
+          None => controller.removeScriptMessageHandlerForName(&ipc),
+        }
+      }
+
+      #[cfg(target_os = "macos")]
+      if let Some(observer) = self.window_occlusion_observer.take() {
+        NSNotificationCenter::defaultCenter().removeObserver(observer.as_ref());
       }
 
+      #[cfg(target_os = "ios")]
+      for observer in self.virtual_keyboard_observers.drain(..) {
+        NSNotificationCenter::defaultCenter().removeObserver(observer.as_ref());
+      }
+
+      // no-op if the blob download handler was not registered
+      self
+        .manager
+        .removeScriptMessageHandlerForName(&NSString::from_str(BLOB_DOWNLOAD_MESSAGE_HANDLER_NAME));
+
       for ptr in self.protocol_ptrs.iter() {
         if !ptr.is_null() {
           drop(Box::from_raw(*ptr));
@@ -993,6 +1783,58 @@ impl Drop for InnerWebView {
 /// wry: top-left is (0, 0) and y increasing downwards
 /// macOS: bottom-left is (0, 0) and y increasing upwards
 #[allow(dead_code)]
+#[cfg(target_os = "ios")]
+fn ios_frame(bounds: Rect, scale_factor: f64) -> CGRect {
+  let (x, y) = bounds.position.to_logical::<f64>(scale_factor).into();
+  let (width, height) = bounds.size.to_logical::<f64>(scale_factor).into();
+  CGRect {
+    origin: CGPoint::new(x, y),
+    size: CGSize::new(width, height),
+  }
+}
+
+/// Report the virtual keyboard being shown and hidden to the text input handler.
+#[cfg(target_os = "ios")]
+unsafe fn observe_virtual_keyboard(
+  handler: Rc<Box<dyn Fn(crate::TextInputEvent)>>,
+) -> Vec<Retained<ProtocolObject<dyn NSObjectProtocol>>> {
+  let center = NSNotificationCenter::defaultCenter();
+
+  let handler_ = handler.clone();
+  let shown = block2::RcBlock::new(move |notification: NonNull<NSNotification>| {
+    let key: &AnyObject = ns_string!("UIKeyboardFrameEndUserInfoKey").as_ref();
+    let Some(frame) = notification
+      .as_ref()
+      .userInfo()
+      .and_then(|user_info| user_info.objectForKey(key))
+    else {
+      return;
+    };
+    let frame: CGRect = objc2::msg_send![&frame, CGRectValue];
+    handler_(crate::TextInputEvent::VirtualKeyboardShown {
+      height: frame.size.height,
+    });
+  });
+  let hidden = block2::RcBlock::new(move |_notification: NonNull<NSNotification>| {
+    handler(crate::TextInputEvent::VirtualKeyboardHidden);
+  });
+
+  vec![
+    center.addObserverForName_object_queue_usingBlock(
+      Some(ns_string!("UIKeyboardWillShowNotification")),
+      None,
+      None,
+      &shown,
+    ),
+    center.addObserverForName_object_queue_usingBlock(
+      Some(ns_string!("UIKeyboardWillHideNotification")),
+      None,
+      None,
+      &hidden,
+    ),
+  ]
+}
+
 unsafe fn window_position(view: &NSView, x: i32, y: i32, height: f64) -> CGPoint {
   let frame: CGRect = view.frame();
   CGPoint::new(x as f64, frame.size.height - y as f64 - height)
@@ -1019,3 +1861,29 @@ unsafe fn wait_for_blocking_operation<T>(rx: std::sync::mpsc::Receiver<T>) -> Re
     }
   }
 }
+
+/// The style of the separator between the titlebar and the content of a window,
+/// see `WebViewBuilderExtDarwin::with_titlebar_separator`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitlebarSeparatorStyle {
+  /// AppKit picks the style from the views of the window.
+  Automatic,
+  /// No separator.
+  None,
+  /// A line.
+  Line,
+  /// A shadow.
+  Shadow,
+}
+
+#[cfg(target_os = "macos")]
+impl From<TitlebarSeparatorStyle> for NSTitlebarSeparatorStyle {
+  fn from(style: TitlebarSeparatorStyle) -> Self {
+    match style {
+      TitlebarSeparatorStyle::Automatic => Self::Automatic,
+      TitlebarSeparatorStyle::None => Self::None,
+      TitlebarSeparatorStyle::Line => Self::Line,
+      TitlebarSeparatorStyle::Shadow => Self::Shadow,
+    }
+  }
+}
@@ -1,5 +1,7 @@
+use std::ptr;
+
 use objc2::DeclaredClass;
-use objc2_foundation::{NSObjectProtocol, NSString};
+use objc2_foundation::{NSError, NSMutableURLRequest, NSObjectProtocol, NSString, NSURLRequest};
 use objc2_web_kit::{
   WKNavigation, WKNavigationAction, WKNavigationActionPolicy, WKNavigationResponse,
   WKNavigationResponsePolicy,
@@ -14,6 +16,41 @@ use crate::PageLoadEvent;
 
 use super::class::wry_navigation_delegate::WryNavigationDelegate;
 
+/// The `NSURLErrorCancelled` code of the navigations cancelled by another one.
+const NSURL_ERROR_CANCELLED: isize = -999;
+
+pub(crate) fn did_start_provisional_navigation(
+  this: &WryNavigationDelegate,
+  _webview: &WKWebView,
+  navigation: &WKNavigation,
+) {
+  this.ivars().current_navigation.set(navigation);
+  this.ivars().navigation_state.start(None);
+}
+
+/// End the loading if `navigation` is the current one.
+fn finish_navigation(this: &WryNavigationDelegate, navigation: &WKNavigation) {
+  if ptr::eq(this.ivars().current_navigation.get(), navigation) {
+    this.ivars().navigation_state.finish(None);
+  }
+}
+
+pub(crate) fn did_fail_navigation(
+  this: &WryNavigationDelegate,
+  _webview: &WKWebView,
+  navigation: &WKNavigation,
+  error: &NSError,
+) {
+  finish_navigation(this, navigation);
+
+  // The failed page isn't painted, unless it was cancelled by another navigation
+  if error.code() != NSURL_ERROR_CANCELLED {
+    if let Some(handler) = &this.ivars().first_paint_handler {
+      handler();
+    }
+  }
+}
+
 pub(crate) fn did_commit_navigation(
   this: &WryNavigationDelegate,
   webview: &WKWebView,
@@ -39,17 +76,74 @@ pub(crate) fn did_commit_navigation(
 pub(crate) fn did_finish_navigation(
   this: &WryNavigationDelegate,
   _webview: &WKWebView,
-  _navigation: &WKNavigation,
+  navigation: &WKNavigation,
 ) {
+  finish_navigation(this, navigation);
+
   if let Some(on_page_load) = &this.ivars().on_page_load_handler {
     on_page_load(PageLoadEvent::Finished);
   }
 }
 
+/// Start the `GET` navigations of the main frame again with the persistent headers when they don't
+/// have them, as WebKit doesn't send them on redirections and page navigations.
+///
+/// Returns whether the navigation was started again.
+unsafe fn restart_with_persistent_headers(
+  this: &WryNavigationDelegate,
+  webview: &WKWebView,
+  request: &NSURLRequest,
+  url: &str,
+) -> bool {
+  let Some(persistent_headers) = &this.ivars().persistent_headers else {
+    return false;
+  };
+
+  // the navigation started again, let it go even if WebKit doesn't report its headers
+  let restarted = this
+    .ivars()
+    .restarted_url
+    .take()
+    .is_some_and(|restarted| restarted == url);
+  let is_get = request
+    .HTTPMethod()
+    .is_some_and(|method| method.to_string() == "GET");
+  if restarted || !is_get {
+    return false;
+  }
+
+  let Some(headers) = persistent_headers.get(url) else {
+    return false;
+  };
+  let has_headers = headers.keys().all(|name| {
+    request
+      .valueForHTTPHeaderField(&NSString::from_str(name.as_str()))
+      .is_some()
+  });
+  let Some(request_url) = request.URL() else {
+    return false;
+  };
+  if has_headers {
+    return false;
+  }
+
+  let new_request = NSMutableURLRequest::requestWithURL(&request_url);
+  for (name, value) in headers.iter() {
+    new_request.addValue_forHTTPHeaderField(
+      &NSString::from_str(value.to_str().unwrap_or_default()),
+      &NSString::from_str(name.as_str()),
+    );
+  }
+  webview.loadRequest(&new_request);
+  this.ivars().restarted_url.replace(Some(url.to_string()));
+
+  true
+}
+
 // Navigation handler
 pub(crate) fn navigation_policy(
   this: &WryNavigationDelegate,
-  _webview: &WKWebView,
+  webview: &WKWebView,
   action: &WKNavigationAction,
   handler: &block2::Block<dyn Fn(WKNavigationActionPolicy)>,
 ) {
@@ -66,6 +160,14 @@ pub(crate) fn navigation_policy(
     let target_frame = action.targetFrame();
     let is_main_frame = target_frame.map_or(false, |frame| frame.isMainFrame());
 
+    if is_main_frame
+      && !should_download
+      && restart_with_persistent_headers(this, webview, &request, &url.to_string())
+    {
+      (*handler).call((WKNavigationActionPolicy::Cancel,));
+      return;
+    }
+
     if should_download {
       let has_download_handler = this.ivars().has_download_handler;
       if has_download_handler {
@@ -1,3 +1,4 @@
+use objc2::{msg_send, DeclaredClass};
 use objc2_app_kit::{
   NSAlternateKeyMask, NSCommandKeyMask, NSControlKeyMask, NSEvent, NSEventType, NSShiftKeyMask,
   NSView,
@@ -8,6 +9,11 @@ use super::WryWebView;
 
 pub(crate) fn other_mouse_down(this: &WryWebView, event: &NSEvent) {
   unsafe {
+    if !this.ivars().synthetic_mouse_events {
+      let () = msg_send![super(this), otherMouseDown: event];
+      return;
+    }
+
     if event.r#type() == NSEventType::OtherMouseDown {
       let button_number = event.buttonNumber();
       match button_number {
@@ -32,6 +38,11 @@ pub(crate) fn other_mouse_down(this: &WryWebView, event: &NSEvent) {
 }
 pub(crate) fn other_mouse_up(this: &WryWebView, event: &NSEvent) {
   unsafe {
+    if !this.ivars().synthetic_mouse_events {
+      let () = msg_send![super(this), otherMouseUp: event];
+      return;
+    }
+
     if event.r#type() == NSEventType::OtherMouseUp {
       let button_number = event.buttonNumber();
       match button_number {
@@ -13,3 +13,29 @@ pub fn operating_system_version() -> (isize, isize, isize) {
     version.patchVersion,
   )
 }
+
+#[repr(C)]
+struct DispatchQueue {
+  _private: [u8; 0],
+}
+
+extern "C" {
+  static _dispatch_main_q: DispatchQueue;
+  fn dispatch_async_f(
+    queue: *const DispatchQueue,
+    context: *mut std::ffi::c_void,
+    work: extern "C" fn(*mut std::ffi::c_void),
+  );
+}
+
+/// Runs the closure asynchronously on the main dispatch queue.
+pub fn dispatch_main(f: Box<dyn FnOnce() + Send>) {
+  extern "C" fn work(context: *mut std::ffi::c_void) {
+    let f = unsafe { Box::from_raw(context as *mut Box<dyn FnOnce() + Send>) };
+    f();
+  }
+
+  // We double-box because the first box is a fat pointer.
+  let context = Box::into_raw(Box::new(f)) as *mut std::ffi::c_void;
+  unsafe { dispatch_async_f(&_dispatch_main_q, context, work) };
+}